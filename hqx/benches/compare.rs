@@ -0,0 +1,102 @@
+// Checks the fast scalers against the reference ones pixel for pixel, and times both.
+//
+//     cargo bench -p hqx
+
+extern crate hqx;
+
+use std::time::{Duration, Instant};
+
+const WIDTH: usize = 240;
+const HEIGHT: usize = 160;
+const ITERATIONS: usize = 600;
+
+// Blocky frames with a small palette, so the scalers see edges like they would in a game instead of noise.
+fn test_frame(seed: u32) -> Vec<u32> {
+    const PALETTE: [u32; 6] = [0xff000000, 0xffffffff, 0xff3050d0, 0xff20a040, 0xffe0c020, 0xff8a8a8a];
+    let mut state = seed.wrapping_mul(2654435761) | 1;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    let mut frame = vec![PALETTE[0]; WIDTH * HEIGHT];
+    for _ in 0..200 {
+        let (x, y) = (next() as usize % WIDTH, next() as usize % HEIGHT);
+        let (w, h) = (1 + next() as usize % 24, 1 + next() as usize % 24);
+        let color = PALETTE[next() as usize % PALETTE.len()];
+        for j in y..std::cmp::min(y + h, HEIGHT) {
+            for i in x..std::cmp::min(x + w, WIDTH) {
+                // Diagonal edges too, not just rectangles.
+                if (i - x) + (j - y) < std::cmp::max(w, h) {
+                    frame[j * WIDTH + i] = color;
+                }
+            }
+        }
+    }
+    frame
+}
+
+fn time(f: &mut dyn FnMut()) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort();
+    times[times.len() / 2]
+}
+
+fn compare(
+    name: &str,
+    factor: usize,
+    fast: fn(&[u32], &mut [u32], usize, usize),
+    reference: fn(&[u32], &mut [u32], usize, usize),
+) {
+    let frames = (0..8).map(test_frame).collect::<Vec<_>>();
+    let mut fast_output = vec![0u32; WIDTH * HEIGHT * factor * factor];
+    let mut reference_output = vec![0u32; WIDTH * HEIGHT * factor * factor];
+
+    for (i, frame) in frames.iter().enumerate() {
+        fast(frame, &mut fast_output, WIDTH, HEIGHT);
+        reference(frame, &mut reference_output, WIDTH, HEIGHT);
+        if let Some(index) = (0..fast_output.len()).find(|&i| fast_output[i] != reference_output[i]) {
+            panic!(
+                "{}: frame {} differs at ({}, {}): {:08x} != {:08x}",
+                name,
+                i,
+                index % (WIDTH * factor),
+                index / (WIDTH * factor),
+                fast_output[index],
+                reference_output[index]
+            );
+        }
+    }
+
+    // Alternating between the two means whatever else the machine is doing slows them both down alike.
+    let frame = &frames[0];
+    let (fast_times, reference_times): (Vec<_>, Vec<_>) = (0..ITERATIONS)
+        .map(|_| {
+            (
+                time(&mut || fast(frame, &mut fast_output, WIDTH, HEIGHT)),
+                time(&mut || reference(frame, &mut reference_output, WIDTH, HEIGHT)),
+            )
+        })
+        .unzip();
+    let (fast_time, reference_time) = (median(fast_times), median(reference_times));
+    println!(
+        "{}: {:?} per frame, reference {:?} per frame ({:.1}x)",
+        name,
+        fast_time,
+        reference_time,
+        reference_time.as_secs_f64() / fast_time.as_secs_f64()
+    );
+}
+
+fn main() {
+    compare("hq2x", 2, hqx::hq2x, hqx::reference::hq2x);
+    compare("hq3x", 3, hqx::hq3x, hqx::reference::hq3x);
+    compare("hq4x", 4, hqx::hq4x, hqx::reference::hq4x);
+}
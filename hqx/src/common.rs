@@ -1,3 +1,5 @@
+use wide::{i32x8, CmpEq, CmpGt};
+
 pub fn rgb_to_yuv(c: u32) -> u32 {
     lazy_static! {
         static ref RGB_TO_YUV: Vec<u32> = {
@@ -18,14 +20,14 @@ pub fn rgb_to_yuv(c: u32) -> u32 {
     RGB_TO_YUV[(c & MASK_RGB) as usize]
 }
 
-pub fn yuv_diff(yuv1: u32, yuv2: u32) -> bool {
-    const YMASK: u32 = 0x00FF0000;
-    const UMASK: u32 = 0x0000FF00;
-    const VMASK: u32 = 0x000000FF;
-    const TRY: i32 = 0x00300000;
-    const TRU: i32 = 0x00000700;
-    const TRV: i32 = 0x00000006;
+const YMASK: u32 = 0x00FF0000;
+const UMASK: u32 = 0x0000FF00;
+const VMASK: u32 = 0x000000FF;
+const TRY: i32 = 0x00300000;
+const TRU: i32 = 0x00000700;
+const TRV: i32 = 0x00000006;
 
+pub fn yuv_diff(yuv1: u32, yuv2: u32) -> bool {
     ((yuv1 & YMASK) as i32 - (yuv2 & YMASK) as i32).abs() > TRY
         || ((yuv1 & UMASK) as i32 - (yuv2 & UMASK) as i32).abs() > TRU
         || ((yuv1 & VMASK) as i32 - (yuv2 & VMASK) as i32).abs() > TRV
@@ -35,6 +37,61 @@ pub fn diff(c1: u32, c2: u32) -> bool {
     yuv_diff(rgb_to_yuv(c1), rgb_to_yuv(c2))
 }
 
+// Rows of the source image scaled together on one thread.
+pub const BAND_ROWS: usize = 16;
+
+// The neighbours of w[5], in the order their bits appear in a pattern.
+const NEIGHBOURS: [usize; 8] = [1, 2, 3, 4, 6, 7, 8, 9];
+
+fn neighbours(w: &[u32; 10]) -> i32x8 {
+    i32x8::new(NEIGHBOURS.map(|k| w[k] as i32))
+}
+
+// Whether all 8 neighbours of w[5] are exactly the same colour as it. Interpolating a colour with itself gives that
+// colour back, so the whole scaled block is then just w[5].
+pub fn is_flat(w: &[u32; 10]) -> bool {
+    neighbours(w).cmp_eq(i32x8::splat(w[5] as i32)).move_mask() == 0xff
+}
+
+// Keeps yuv holding the YUV values of w as the window slides along a row, so that only the column coming into view
+// needs looking up. first is whether w is at the start of a row.
+pub fn update_yuv(yuv: &mut [u32; 10], w: &[u32; 10], first: bool) {
+    if first {
+        for k in [1, 2, 4, 5, 7, 8] {
+            yuv[k] = rgb_to_yuv(w[k]);
+        }
+    } else {
+        for k in [1, 2, 4, 5, 7, 8] {
+            yuv[k] = yuv[k + 1];
+        }
+    }
+    for k in [3, 6, 9] {
+        yuv[k] = rgb_to_yuv(w[k]);
+    }
+}
+
+// Which of the 8 neighbours of yuv[5] look different from it, with yuv[1] in the lowest bit. All 8 are compared at
+// once.
+pub fn yuv_pattern(yuv: &[u32; 10]) -> u8 {
+    let (yuv1, yuv2) = (i32x8::splat(yuv[5] as i32), neighbours(yuv));
+    let exceeds = |mask: u32, threshold: i32| {
+        let (mask, threshold) = (i32x8::splat(mask as i32), i32x8::splat(threshold));
+        // Both ways round rather than abs, which needs more than SSE2.
+        let difference = (yuv1 & mask) - (yuv2 & mask);
+        difference.cmp_gt(threshold) | (-difference).cmp_gt(threshold)
+    };
+    (exceeds(YMASK, TRY) | exceeds(UMASK, TRU) | exceeds(VMASK, TRV)).move_mask() as u8
+}
+
+// Fills the factor by factor block of dst at dst_index with c.
+pub fn fill_block(dst: &mut UncheckedDst, dst_index: isize, dst_row_elements: isize, factor: isize, c: u32) {
+    for y in 0..factor {
+        for x in 0..factor {
+            dst[(dst_index + y * dst_row_elements + x) as usize] = c;
+        }
+    }
+}
+
 const MASK_2: u32 = 0x0000FF00;
 const MASK_13: u32 = 0x00FF00FF;
 const MASK_RGB: u32 = 0x00FFFFFF;
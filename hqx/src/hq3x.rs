@@ -1,4 +1,8 @@
-use common::{diff, interp1, interp2, interp3, interp4, interp5, rgb_to_yuv, yuv_diff, UncheckedDst};
+use common::{
+    diff, fill_block, interp1, interp2, interp3, interp4, interp5, is_flat, update_yuv, yuv_pattern, UncheckedDst,
+    BAND_ROWS,
+};
+use rayon::prelude::*;
 
 macro_rules! pixel00_1_m {
     ($dst:ident, $dst_index:ident, $dst_row_elements:ident, $w:ident) => {
@@ -235,21 +239,45 @@ macro_rules! pixel22_c {
 }
 
 pub fn calculate(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    if width == 0 || height == 0 {
+        return;
+    }
     let row_bytes_l = width * 4;
-    inner(src, row_bytes_l, dst, row_bytes_l * 3, width, height);
+    // Bands only pay for themselves when there are other threads to run them on.
+    if rayon::current_num_threads() == 1 {
+        inner(src, row_bytes_l, dst, row_bytes_l * 3, width, height, 0..height);
+        return;
+    }
+    dst.par_chunks_mut(width * 3 * 3 * BAND_ROWS)
+        .enumerate()
+        .for_each(|(i, dst)| {
+            let start = i * BAND_ROWS;
+            let end = std::cmp::min(start + BAND_ROWS, height);
+            inner(src, row_bytes_l, dst, row_bytes_l * 3, width, height, start..end);
+        });
 }
 
-pub fn inner(src: &[u32], src_row_bytes: usize, dst: &mut [u32], dst_row_bytes: usize, width: usize, height: usize) {
+// Scales the given rows of src into dst, which starts at the first of those rows.
+pub fn inner(
+    src: &[u32],
+    src_row_bytes: usize,
+    dst: &mut [u32],
+    dst_row_bytes: usize,
+    width: usize,
+    height: usize,
+    rows: std::ops::Range<usize>,
+) {
     let dst = unsafe { UncheckedDst::from_mut(dst) };
     let mut w = [0; 10];
+    let mut yuv = [0; 10];
     let src_row_elements = (src_row_bytes >> 2) as isize;
     let dst_row_elements = (dst_row_bytes >> 2) as isize;
-    let mut src_index = 0isize;
+    let mut src_index = rows.start as isize * src_row_elements;
     let mut dst_index = 0isize;
-    let mut src_row_offset = 0;
+    let mut src_row_offset = src_index;
     let mut dst_row_offset = 0;
 
-    for j in 0..height {
+    for j in rows {
         let prev_line = if j > 0 { -src_row_elements } else { 0 };
         let next_line = if j < height - 1 { src_row_elements } else { 0 };
 
@@ -278,24 +306,16 @@ pub fn inner(src: &[u32], src_row_bytes: usize, dst: &mut [u32], dst_row_bytes:
                 w[9] = w[8];
             }
 
-            let (mut pattern, mut flag) = (0, 1);
-
-            let yuv1 = rgb_to_yuv(w[5]);
-
-            for k in 1..10 {
-                if k == 5 {
-                    continue;
-                }
-
-                if w[k] != w[5] {
-                    let yuv2 = rgb_to_yuv(w[k]);
-                    if yuv_diff(yuv1, yuv2) {
-                        pattern |= flag;
-                    }
-                }
-                flag <<= 1;
+            update_yuv(&mut yuv, &w, i == 0);
+            if is_flat(&w) {
+                fill_block(dst, dst_index, dst_row_elements, 3, w[5]);
+                src_index += 1;
+                dst_index += 3;
+                continue;
             }
 
+            let pattern = yuv_pattern(&yuv);
+
             match pattern {
                 0 | 1 | 4 | 32 | 128 | 5 | 132 | 160 | 33 | 129 | 36 | 133 | 164 | 161 | 37 | 165 => {
                     pixel00_2!(dst, dst_index, dst_row_elements, w);
@@ -2964,7 +2984,6 @@ pub fn inner(src: &[u32], src_row_bytes: usize, dst: &mut [u32], dst_row_bytes:
                         pixel22_2!(dst, dst_index, dst_row_elements, w);
                     }
                 }
-                _ => {}
             }
             src_index += 1;
             dst_index += 3;
@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate lazy_static;
+extern crate rayon;
+extern crate wide;
 
 mod common;
 
@@ -11,3 +13,7 @@ pub use hq3x::calculate as hq3x;
 
 mod hq4x;
 pub use hq4x::calculate as hq4x;
+
+/// The original implementations, which the ones above must match pixel for pixel.
+#[doc(hidden)]
+pub mod reference;
@@ -0,0 +1,8 @@
+mod hq2x;
+pub use self::hq2x::calculate as hq2x;
+
+mod hq3x;
+pub use self::hq3x::calculate as hq3x;
+
+mod hq4x;
+pub use self::hq4x::calculate as hq4x;
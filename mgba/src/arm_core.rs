@@ -33,6 +33,7 @@ impl<'a> ARMCoreRef<'a> {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ExecutionMode {
     ARM,
     Thumb,
@@ -1,4 +1,5 @@
 use super::blip;
+use super::debugger;
 use super::gba;
 use super::state;
 use super::trapper;
@@ -9,6 +10,7 @@ pub struct Core {
     pub(super) ptr: *mut mgba_sys::mCore,
     video_buffer: Option<Vec<u8>>,
     trapper: Option<trapper::Trapper>,
+    debugger: Option<debugger::Debugger>,
 }
 
 unsafe impl Send for Core {}
@@ -38,6 +40,7 @@ impl Core {
             ptr,
             video_buffer: None,
             trapper: None,
+            debugger: None,
         })
     }
 
@@ -75,6 +78,10 @@ impl Core {
     pub fn set_traps(&mut self, traps: Vec<(u32, Box<dyn Fn(CoreMutRef)>)>) {
         self.trapper = Some(trapper::Trapper::new(self.as_mut(), traps));
     }
+
+    pub fn enable_debugger(&mut self) {
+        self.debugger = Some(debugger::Debugger::new(self.as_mut()));
+    }
 }
 
 impl Drop for Core {
@@ -192,6 +199,10 @@ impl<'a> CoreMutRef<'a> {
         self.raw_read_8(0x080000bc, -1)
     }
 
+    pub fn debugger(&mut self) -> Option<debugger::DebuggerMutRef<'a>> {
+        debugger::DebuggerMutRef::new(*self)
+    }
+
     pub fn gba_mut(&mut self) -> gba::GBAMutRef {
         gba::GBAMutRef {
             ptr: unsafe { (*self.ptr).board as *mut mgba_sys::GBA },
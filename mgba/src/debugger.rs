@@ -0,0 +1,558 @@
+use super::arm_core;
+use super::core;
+
+// The trapper uses 0xef, so breakpoints and traps can sit at the same address.
+const DEBUGGER_IMM: u32 = 0xee;
+
+const THUMB_BKPT: u16 = 0xbe00 | DEBUGGER_IMM as u16;
+const ARM_BKPT: u32 = 0xe1200070 | ((DEBUGGER_IMM & 0xfff0) << 4) | (DEBUGGER_IMM & 0xf);
+
+#[derive(Clone, Debug)]
+pub enum StopReason {
+    Breakpoint {
+        address: u32,
+    },
+    Watchpoint {
+        address: u32,
+        size: u32,
+        value: Option<u32>,
+        pc: u32,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct Breakpoint {
+    mode: arm_core::ExecutionMode,
+    original: u32,
+    // Set by step over, and removed as soon as anything stops the CPU.
+    temporary: bool,
+}
+
+struct State {
+    breakpoints: std::collections::HashMap<u32, Breakpoint>,
+    watchpoints: Vec<std::ops::Range<u32>>,
+    stop_reason: Option<StopReason>,
+    // While stepping, breakpoints and watchpoints are noted but don't pause the thread.
+    stepping: bool,
+    // Set when a step only ran into a breakpoint, and so didn't run an instruction.
+    hit_breakpoint_while_stepping: bool,
+}
+
+type Bkpt = unsafe extern "C" fn(*mut mgba_sys::ARMCore, i32);
+type Store32 = unsafe extern "C" fn(*mut mgba_sys::ARMCore, u32, i32, *mut std::os::raw::c_int);
+type Store16 = unsafe extern "C" fn(*mut mgba_sys::ARMCore, u32, i16, *mut std::os::raw::c_int);
+type Store8 = unsafe extern "C" fn(*mut mgba_sys::ARMCore, u32, i8, *mut std::os::raw::c_int);
+type StoreMultiple = unsafe extern "C" fn(
+    *mut mgba_sys::ARMCore,
+    u32,
+    std::os::raw::c_int,
+    mgba_sys::LSMDirection,
+    *mut std::os::raw::c_int,
+) -> u32;
+
+#[repr(transparent)]
+pub struct Debugger(Box<DebuggerCStruct>);
+
+#[repr(C)]
+struct DebuggerCStruct {
+    cpu_component: mgba_sys::mCPUComponent,
+    real_bkpt16: Option<Bkpt>,
+    real_bkpt32: Option<Bkpt>,
+    // Only hooked while there are watchpoints, so writes don't pay for them otherwise.
+    real_stores: Option<(Store32, Store16, Store8, StoreMultiple)>,
+    state: parking_lot::Mutex<State>,
+}
+
+unsafe impl Send for DebuggerCStruct {}
+
+unsafe extern "C" fn c_debugger_init(_cpu: *mut std::os::raw::c_void, _cpu_component: *mut mgba_sys::mCPUComponent) {}
+
+unsafe extern "C" fn c_debugger_deinit(_cpu_component: *mut mgba_sys::mCPUComponent) {}
+
+unsafe fn debugger_from_cpu<'a>(cpu: *mut mgba_sys::ARMCore) -> &'a mut DebuggerCStruct {
+    let components = std::slice::from_raw_parts(
+        (*cpu).components,
+        mgba_sys::mCPUComponentType_CPU_COMPONENT_MAX as usize,
+    );
+    &mut *(components[mgba_sys::mCPUComponentType_CPU_COMPONENT_MISC_2 as usize] as *mut DebuggerCStruct)
+}
+
+fn word_size(mode: arm_core::ExecutionMode) -> u32 {
+    match mode {
+        arm_core::ExecutionMode::ARM => mgba_sys::WordSize_WORD_SIZE_ARM,
+        arm_core::ExecutionMode::Thumb => mgba_sys::WordSize_WORD_SIZE_THUMB,
+    }
+}
+
+unsafe fn execution_mode(cpu: *mut mgba_sys::ARMCore) -> arm_core::ExecutionMode {
+    arm_core::ARMCoreRef {
+        ptr: cpu,
+        _lifetime: std::marker::PhantomData,
+    }
+    .execution_mode()
+}
+
+// The address of the next instruction to run, between instructions: PC is one instruction ahead of it.
+unsafe fn next_pc(cpu: *mut mgba_sys::ARMCore) -> u32 {
+    let cpu_ref = arm_core::ARMCoreRef {
+        ptr: cpu,
+        _lifetime: std::marker::PhantomData,
+    };
+    cpu_ref.gpr(15) as u32 - word_size(execution_mode(cpu))
+}
+
+// The address of the instruction being run, from inside it: PC is two instructions ahead of it by then.
+unsafe fn executing_pc(cpu: *mut mgba_sys::ARMCore) -> u32 {
+    next_pc(cpu) - word_size(execution_mode(cpu))
+}
+
+unsafe fn stop(cpu: *mut mgba_sys::ARMCore) {
+    // Make the run loop return once this instruction is done, so the thread pauses before running any more.
+    (*cpu).nextEvent = (*cpu).cycles;
+    let thread = mgba_sys::mCoreThreadGet();
+    if !thread.is_null() {
+        mgba_sys::mCoreThreadPauseFromThread(thread);
+    }
+}
+
+unsafe fn write_opcode(cpu: *mut mgba_sys::ARMCore, address: u32, mode: arm_core::ExecutionMode, opcode: u32) -> u32 {
+    match mode {
+        arm_core::ExecutionMode::Thumb => {
+            let mut original = 0i16;
+            mgba_sys::GBAPatch16(cpu, address, opcode as u16 as i16, &mut original);
+            original as u16 as u32
+        }
+        arm_core::ExecutionMode::ARM => {
+            let mut original = 0i32;
+            mgba_sys::GBAPatch32(cpu, address, opcode as i32, &mut original);
+            original as u32
+        }
+    }
+}
+
+// Instructions are fetched two ahead, so patching the next one also has to patch what was already fetched for it.
+// The current one is left alone: a breakpoint set where the CPU is stopped takes effect the next time it gets there.
+unsafe fn write_prefetched_opcode(
+    cpu: *mut mgba_sys::ARMCore,
+    address: u32,
+    mode: arm_core::ExecutionMode,
+    opcode: u32,
+    including_current: bool,
+) {
+    if execution_mode(cpu) != mode {
+        return;
+    }
+    let pc = next_pc(cpu);
+    if including_current && address == pc {
+        (*cpu).prefetch[0] = opcode;
+    } else if address == pc + word_size(mode) {
+        (*cpu).prefetch[1] = opcode;
+    }
+}
+
+unsafe fn remove_breakpoint(cpu: *mut mgba_sys::ARMCore, address: u32, breakpoint: Breakpoint) {
+    write_opcode(cpu, address, breakpoint.mode, breakpoint.original);
+    write_prefetched_opcode(cpu, address, breakpoint.mode, breakpoint.original, true);
+}
+
+impl DebuggerCStruct {
+    unsafe fn remove_temporary_breakpoints(&self, cpu: *mut mgba_sys::ARMCore, state: &mut State) {
+        state.breakpoints.retain(|address, breakpoint| {
+            if breakpoint.temporary {
+                remove_breakpoint(cpu, *address, *breakpoint);
+            }
+            !breakpoint.temporary
+        });
+    }
+
+    unsafe fn handle_breakpoint(&self, cpu: *mut mgba_sys::ARMCore) -> bool {
+        let address = executing_pc(cpu);
+        let mut state = self.state.lock();
+        let breakpoint = if let Some(breakpoint) = state.breakpoints.get(&address) {
+            *breakpoint
+        } else {
+            return false;
+        };
+
+        // Run the original instruction next, as if the breakpoint was never there. This also rewinds PC to it.
+        mgba_sys::ARMRunFake(cpu, breakpoint.original);
+
+        if state.stepping {
+            state.hit_breakpoint_while_stepping = true;
+            return true;
+        }
+
+        self.remove_temporary_breakpoints(cpu, &mut state);
+        state.stop_reason = Some(StopReason::Breakpoint { address });
+        stop(cpu);
+        true
+    }
+
+    unsafe fn check_write(&self, cpu: *mut mgba_sys::ARMCore, address: u32, size: u32, value: Option<u32>) {
+        let mut state = self.state.lock();
+        let start = if let Some(watchpoint) = state
+            .watchpoints
+            .iter()
+            .find(|w| w.start < address.wrapping_add(size) && address < w.end)
+        {
+            std::cmp::max(watchpoint.start, address)
+        } else {
+            return;
+        };
+
+        state.stop_reason = Some(StopReason::Watchpoint {
+            address: start,
+            size,
+            value,
+            pc: executing_pc(cpu),
+        });
+        if !state.stepping {
+            self.remove_temporary_breakpoints(cpu, &mut state);
+            stop(cpu);
+        }
+    }
+}
+
+unsafe extern "C" fn c_debugger_bkpt16(cpu: *mut mgba_sys::ARMCore, imm: i32) {
+    let debugger = debugger_from_cpu(cpu);
+    if imm as u32 == DEBUGGER_IMM && debugger.handle_breakpoint(cpu) {
+        return;
+    }
+    debugger.real_bkpt16.unwrap()(cpu, imm);
+}
+
+unsafe extern "C" fn c_debugger_bkpt32(cpu: *mut mgba_sys::ARMCore, imm: i32) {
+    let debugger = debugger_from_cpu(cpu);
+    if imm as u32 == DEBUGGER_IMM && debugger.handle_breakpoint(cpu) {
+        return;
+    }
+    debugger.real_bkpt32.unwrap()(cpu, imm);
+}
+
+unsafe extern "C" fn c_debugger_store32(
+    cpu: *mut mgba_sys::ARMCore,
+    address: u32,
+    value: i32,
+    cycle_counter: *mut std::os::raw::c_int,
+) {
+    let debugger = debugger_from_cpu(cpu);
+    debugger.check_write(cpu, address & !0x3, 4, Some(value as u32));
+    (debugger.real_stores.unwrap().0)(cpu, address, value, cycle_counter);
+}
+
+unsafe extern "C" fn c_debugger_store16(
+    cpu: *mut mgba_sys::ARMCore,
+    address: u32,
+    value: i16,
+    cycle_counter: *mut std::os::raw::c_int,
+) {
+    let debugger = debugger_from_cpu(cpu);
+    debugger.check_write(cpu, address & !0x1, 2, Some(value as u16 as u32));
+    (debugger.real_stores.unwrap().1)(cpu, address, value, cycle_counter);
+}
+
+unsafe extern "C" fn c_debugger_store8(
+    cpu: *mut mgba_sys::ARMCore,
+    address: u32,
+    value: i8,
+    cycle_counter: *mut std::os::raw::c_int,
+) {
+    let debugger = debugger_from_cpu(cpu);
+    debugger.check_write(cpu, address, 1, Some(value as u8 as u32));
+    (debugger.real_stores.unwrap().2)(cpu, address, value, cycle_counter);
+}
+
+unsafe extern "C" fn c_debugger_store_multiple(
+    cpu: *mut mgba_sys::ARMCore,
+    base_address: u32,
+    mask: std::os::raw::c_int,
+    direction: mgba_sys::LSMDirection,
+    cycle_counter: *mut std::os::raw::c_int,
+) -> u32 {
+    let debugger = debugger_from_cpu(cpu);
+    let size = (mask as u32 & 0xffff).count_ones() * 4;
+    let decrement = direction & mgba_sys::LSMDirection_LSM_DA != 0;
+    let before = direction & mgba_sys::LSMDirection_LSM_IB != 0;
+    let mut start = base_address & !0x3;
+    if decrement {
+        start = start.wrapping_sub(size).wrapping_add(4);
+    }
+    if before {
+        start = if decrement {
+            start.wrapping_sub(4)
+        } else {
+            start.wrapping_add(4)
+        };
+    }
+    debugger.check_write(cpu, start, size, None);
+    (debugger.real_stores.unwrap().3)(cpu, base_address, mask, direction, cycle_counter)
+}
+
+impl Debugger {
+    pub fn new(mut core: core::CoreMutRef) -> Self {
+        let mut cpu_component = unsafe { std::mem::zeroed::<mgba_sys::mCPUComponent>() };
+        cpu_component.init = Some(c_debugger_init);
+        cpu_component.deinit = Some(c_debugger_deinit);
+        let mut debugger_c_struct = Box::new(DebuggerCStruct {
+            cpu_component,
+            real_bkpt16: None,
+            real_bkpt32: None,
+            real_stores: None,
+            state: parking_lot::Mutex::new(State {
+                breakpoints: std::collections::HashMap::new(),
+                watchpoints: vec![],
+                stop_reason: None,
+                stepping: false,
+                hit_breakpoint_while_stepping: false,
+            }),
+        });
+
+        unsafe {
+            let arm_core = &mut *core.gba_mut().cpu_mut().ptr;
+            debugger_c_struct.real_bkpt16 = arm_core.irqh.bkpt16;
+            debugger_c_struct.real_bkpt32 = arm_core.irqh.bkpt32;
+            let components = std::slice::from_raw_parts_mut(
+                arm_core.components,
+                mgba_sys::mCPUComponentType_CPU_COMPONENT_MAX as usize,
+            );
+            components[mgba_sys::mCPUComponentType_CPU_COMPONENT_MISC_2 as usize] =
+                &mut *debugger_c_struct as *mut _ as *mut mgba_sys::mCPUComponent;
+            mgba_sys::ARMHotplugAttach(
+                arm_core,
+                mgba_sys::mCPUComponentType_CPU_COMPONENT_MISC_2 as mgba_sys::size_t,
+            );
+            arm_core.irqh.bkpt16 = Some(c_debugger_bkpt16);
+            arm_core.irqh.bkpt32 = Some(c_debugger_bkpt32);
+        }
+
+        Debugger(debugger_c_struct)
+    }
+}
+
+/// Access to the debugger installed on a core. Anything that patches memory or runs the CPU must be called from the
+/// emulator thread, e.g. through `thread::Handle::run_on_core`.
+pub struct DebuggerMutRef<'a> {
+    core: core::CoreMutRef<'a>,
+    ptr: *mut DebuggerCStruct,
+}
+
+impl<'a> DebuggerMutRef<'a> {
+    pub(super) fn new(mut core: core::CoreMutRef<'a>) -> Option<Self> {
+        let component = unsafe {
+            core.gba_mut().cpu_mut().components_mut()[mgba_sys::mCPUComponentType_CPU_COMPONENT_MISC_2 as usize]
+        };
+        if component.is_null() {
+            return None;
+        }
+        Some(DebuggerMutRef {
+            core,
+            ptr: component as *mut DebuggerCStruct,
+        })
+    }
+
+    fn debugger(&self) -> &DebuggerCStruct {
+        unsafe { &*self.ptr }
+    }
+
+    fn cpu(&mut self) -> *mut mgba_sys::ARMCore {
+        self.core.gba_mut().cpu_mut().ptr
+    }
+
+    /// User breakpoints, by address.
+    pub fn breakpoints(&self) -> Vec<(u32, arm_core::ExecutionMode)> {
+        let state = self.debugger().state.lock();
+        let mut breakpoints = state
+            .breakpoints
+            .iter()
+            .filter(|(_, breakpoint)| !breakpoint.temporary)
+            .map(|(address, breakpoint)| (*address, breakpoint.mode))
+            .collect::<Vec<_>>();
+        breakpoints.sort_by_key(|(address, _)| *address);
+        breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, address: u32, mode: arm_core::ExecutionMode) {
+        let address = address & !(word_size(mode) - 1);
+        let cpu = self.cpu();
+        let mut state = self.debugger().state.lock();
+        if let Some(breakpoint) = state.breakpoints.get_mut(&address) {
+            breakpoint.temporary = false;
+            return;
+        }
+        let opcode = match mode {
+            arm_core::ExecutionMode::Thumb => THUMB_BKPT as u32,
+            arm_core::ExecutionMode::ARM => ARM_BKPT,
+        };
+        unsafe {
+            let original = write_opcode(cpu, address, mode, opcode);
+            write_prefetched_opcode(cpu, address, mode, opcode, false);
+            state.breakpoints.insert(
+                address,
+                Breakpoint {
+                    mode,
+                    original,
+                    temporary: false,
+                },
+            );
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, address: u32) {
+        let cpu = self.cpu();
+        let mut state = self.debugger().state.lock();
+        if let Some(breakpoint) = state.breakpoints.remove(&address) {
+            unsafe {
+                remove_breakpoint(cpu, address, breakpoint);
+            }
+        }
+    }
+
+    /// What was at an address before a breakpoint was put there, so disassembly can show the real instruction.
+    pub fn original_opcode(&self, address: u32) -> Option<u32> {
+        let state = self.debugger().state.lock();
+        state.breakpoints.get(&address).map(|breakpoint| breakpoint.original)
+    }
+
+    /// Write watchpoints, as address ranges.
+    pub fn watchpoints(&self) -> Vec<std::ops::Range<u32>> {
+        self.debugger().state.lock().watchpoints.clone()
+    }
+
+    pub fn add_watchpoint(&mut self, range: std::ops::Range<u32>) {
+        let cpu = self.cpu();
+        let mut state = self.debugger().state.lock();
+        if !state.watchpoints.contains(&range) {
+            state.watchpoints.push(range);
+        }
+        drop(state);
+        self.update_store_hooks(cpu);
+    }
+
+    pub fn remove_watchpoint(&mut self, range: std::ops::Range<u32>) {
+        let cpu = self.cpu();
+        self.debugger().state.lock().watchpoints.retain(|w| *w != range);
+        self.update_store_hooks(cpu);
+    }
+
+    fn update_store_hooks(&mut self, cpu: *mut mgba_sys::ARMCore) {
+        let wanted = !self.debugger().state.lock().watchpoints.is_empty();
+        let debugger = unsafe { &mut *self.ptr };
+        unsafe {
+            let memory = &mut (*cpu).memory;
+            match (wanted, debugger.real_stores) {
+                (true, None) => {
+                    debugger.real_stores = Some((
+                        memory.store32.unwrap(),
+                        memory.store16.unwrap(),
+                        memory.store8.unwrap(),
+                        memory.storeMultiple.unwrap(),
+                    ));
+                    memory.store32 = Some(c_debugger_store32);
+                    memory.store16 = Some(c_debugger_store16);
+                    memory.store8 = Some(c_debugger_store8);
+                    memory.storeMultiple = Some(c_debugger_store_multiple);
+                }
+                (false, Some((store32, store16, store8, store_multiple))) => {
+                    memory.store32 = Some(store32);
+                    memory.store16 = Some(store16);
+                    memory.store8 = Some(store8);
+                    memory.storeMultiple = Some(store_multiple);
+                    debugger.real_stores = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Why the CPU last stopped, if it was for a breakpoint or watchpoint.
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.debugger().state.lock().stop_reason.clone()
+    }
+
+    pub fn clear_stop_reason(&mut self) {
+        self.debugger().state.lock().stop_reason = None;
+    }
+
+    /// Runs a single instruction.
+    pub fn step_into(&mut self) {
+        let cpu = self.cpu();
+        {
+            let mut state = self.debugger().state.lock();
+            unsafe {
+                self.debugger().remove_temporary_breakpoints(cpu, &mut state);
+            }
+            state.stop_reason = None;
+            state.stepping = true;
+        }
+        loop {
+            self.core.step();
+            let mut state = self.debugger().state.lock();
+            if !std::mem::take(&mut state.hit_breakpoint_while_stepping) {
+                state.stepping = false;
+                break;
+            }
+        }
+    }
+
+    fn arm_opcode(&mut self, address: u32) -> u32 {
+        self.original_opcode(address)
+            .unwrap_or_else(|| self.core.raw_read_32(address, -1))
+    }
+
+    /// Like step_into, but runs calls through to the instruction after them. If the current instruction is a call,
+    /// this only sets a breakpoint after it and returns true: the thread must then be unpaused to run the call.
+    pub fn step_over(&mut self) -> bool {
+        let cpu = self.cpu();
+        let (mode, pc) = unsafe { (execution_mode(cpu), next_pc(cpu)) };
+        let next = match mode {
+            arm_core::ExecutionMode::Thumb => {
+                let opcode = self
+                    .original_opcode(pc)
+                    .unwrap_or_else(|| self.core.raw_read_16(pc, -1) as u32);
+                // Calls through a register are made with a BL to a BX veneer, so they are stepped over like any BL.
+                match opcode & 0xf800 {
+                    // The first half of a BL: the call happens in the second half.
+                    0xf000 => Some(pc + 4),
+                    0xf800 => Some(pc + 2),
+                    _ => None,
+                }
+            }
+            arm_core::ExecutionMode::ARM => {
+                let is_bl = |opcode: u32| opcode & 0x0f00_0000 == 0x0b00_0000 && opcode >> 28 != 0xf;
+                let is_mov_lr_pc = |opcode: u32| opcode & 0x0fff_ffff == 0x01a0_e00f;
+                // BX Rm, or LDR PC, [...].
+                let is_jump = |opcode: u32| opcode & 0x0fff_fff0 == 0x012f_ff10 || opcode & 0x0c50_f000 == 0x0410_f000;
+
+                // Calls through a register set LR to PC first: PC reads two instructions ahead, so LR ends up
+                // pointing just past the jump.
+                let current = self.arm_opcode(pc);
+                if is_bl(current) {
+                    Some(pc + 4)
+                } else if is_mov_lr_pc(current) && is_jump(self.arm_opcode(pc + 4)) {
+                    Some(pc + 8)
+                } else if is_jump(current) && is_mov_lr_pc(self.arm_opcode(pc - 4)) {
+                    Some(pc + 4)
+                } else {
+                    None
+                }
+            }
+        };
+
+        let next = if let Some(next) = next {
+            next
+        } else {
+            self.step_into();
+            return false;
+        };
+
+        self.clear_stop_reason();
+        let already_set = self.debugger().state.lock().breakpoints.contains_key(&next);
+        if !already_set {
+            self.add_breakpoint(next, mode);
+            if let Some(breakpoint) = self.debugger().state.lock().breakpoints.get_mut(&next) {
+                breakpoint.temporary = true;
+            }
+        }
+        true
+    }
+}
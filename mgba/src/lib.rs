@@ -4,6 +4,7 @@ extern crate lazy_static;
 pub mod arm_core;
 pub mod blip;
 pub mod core;
+pub mod debugger;
 pub mod gba;
 pub mod input;
 pub mod log;
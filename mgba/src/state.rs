@@ -5,6 +5,8 @@ pub struct State(pub(super) Box<mgba_sys::GBASerializedState>);
 unsafe impl Send for State {}
 
 impl State {
+    pub const SIZE: usize = std::mem::size_of::<mgba_sys::GBASerializedState>();
+
     pub fn rom_title(&self) -> String {
         let title = unsafe { &*(&self.0.title as *const [std::os::raw::c_char] as *const [u8]) };
         let cstr = match std::ffi::CString::new(title) {
@@ -9,6 +9,10 @@ impl<'a> SyncRef<'a> {
     pub fn fps_target(&self) -> f32 {
         unsafe { (*self.ptr).fpsTarget }
     }
+
+    pub fn audio_wait(&self) -> bool {
+        unsafe { (*self.ptr).audioWait }
+    }
 }
 
 #[repr(transparent)]
@@ -32,6 +36,12 @@ impl<'a> SyncMutRef<'a> {
         }
     }
 
+    pub fn set_audio_wait(&mut self, audio_wait: bool) {
+        unsafe {
+            (*self.ptr).audioWait = audio_wait;
+        }
+    }
+
     pub fn lock_audio(&mut self) {
         unsafe {
             mgba_sys::mCoreSyncLockAudio(self.ptr);
@@ -0,0 +1,90 @@
+// Checks magnify against the reference implementation pixel for pixel, and times both.
+//
+//     cargo bench -p mmpx
+
+use std::time::{Duration, Instant};
+
+const WIDTH: u32 = 240;
+const HEIGHT: u32 = 160;
+const ITERATIONS: u32 = 60;
+
+// Blocky frames with a small palette, so the rules see edges like they would in a game instead of noise.
+fn test_frame(seed: u32) -> image::RgbaImage {
+    const PALETTE: [[u8; 4]; 6] = [
+        [0x00, 0x00, 0x00, 0xff],
+        [0xff, 0xff, 0xff, 0xff],
+        [0xd0, 0x50, 0x30, 0xff],
+        [0x40, 0xa0, 0x20, 0xff],
+        [0x20, 0xc0, 0xe0, 0xff],
+        [0x8a, 0x8a, 0x8a, 0xff],
+    ];
+    let mut state = seed.wrapping_mul(2654435761) | 1;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    let mut frame = image::RgbaImage::from_pixel(WIDTH, HEIGHT, image::Rgba(PALETTE[0]));
+    for _ in 0..200 {
+        let (x, y) = (next() % WIDTH, next() % HEIGHT);
+        let (w, h) = (1 + next() % 24, 1 + next() % 24);
+        let color = image::Rgba(PALETTE[next() as usize % PALETTE.len()]);
+        for j in y..std::cmp::min(y + h, HEIGHT) {
+            for i in x..std::cmp::min(x + w, WIDTH) {
+                // Diagonal edges too, not just rectangles.
+                if (i - x) + (j - y) < std::cmp::max(w, h) {
+                    frame.put_pixel(i, j, color);
+                }
+            }
+        }
+    }
+    frame
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let frames = (0..8).map(test_frame).collect::<Vec<_>>();
+
+    for (i, frame) in frames.iter().enumerate() {
+        let output = mmpx::magnify(frame);
+        let reference_output = mmpx::reference::magnify(frame);
+        if let Some((x, y, pixel)) = output
+            .enumerate_pixels()
+            .find(|(x, y, pixel)| reference_output.get_pixel(*x, *y) != *pixel)
+        {
+            panic!(
+                "frame {} differs at ({}, {}): {:?} != {:?}",
+                i,
+                x,
+                y,
+                pixel,
+                reference_output.get_pixel(x, y)
+            );
+        }
+    }
+
+    let src = frames[0]
+        .pixels()
+        .map(|pixel| u32::from_ne_bytes(pixel.0))
+        .collect::<Vec<_>>();
+    let mut dst = vec![0u32; src.len() * 4];
+    let fast_time = time(|| mmpx::magnify_slice(&src, &mut dst, WIDTH as usize, HEIGHT as usize));
+    let reference_time = time(|| {
+        mmpx::reference::magnify(&frames[0]);
+    });
+    println!(
+        "mmpx: {:?} per frame, reference {:?} per frame ({:.1}x)",
+        fast_time,
+        reference_time,
+        reference_time.as_secs_f64() / fast_time.as_secs_f64()
+    );
+}
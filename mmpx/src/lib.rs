@@ -1,5 +1,9 @@
 mod magnify;
-pub use crate::magnify::magnify;
+pub use crate::magnify::{magnify, magnify_slice};
+
+/// The original implementation, which the one above must match pixel for pixel.
+#[doc(hidden)]
+pub mod reference;
 
 mod neighborhood;
 mod utils;
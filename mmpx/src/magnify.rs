@@ -1,280 +1,259 @@
-use image::{ImageBuffer, Pixel, Rgba, RgbaImage};
+use image::{Pixel, Rgba, RgbaImage};
 use rayon::prelude::*;
-
-use crate::neighborhood::GetMazzoleniNeighborhood;
-use crate::utils;
-use std::ops::Deref;
-
-fn get_pixel_checked<P: 'static + Pixel, C: Deref<Target = [P::Subpixel]>>(
-    image: &ImageBuffer<P, C>,
-    i: i32,
-    j: i32,
-) -> &P {
-    let x = utils::clamp(i, 0, image.width() as i32 - 1);
-    let y = utils::clamp(j, 0, image.height() as i32 - 1);
-
-    image.get_pixel(x as u32, y as u32)
+use wide::{i32x4, CmpEq};
+
+// Rows of the source image magnified together on one thread.
+const BAND_ROWS: usize = 16;
+
+// Pixels are compared as u32s instead of Rgba<u8>s, and lumas are worked out once per pixel instead of once per
+// neighbour.
+struct Source<'a> {
+    pixels: &'a [u32],
+    lumas: Vec<u8>,
+    width: usize,
+    height: usize,
 }
 
-impl GetMazzoleniNeighborhood<Rgba<u8>> for RgbaImage {
-    fn get_a(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 - 1, y as i32 - 1)
-    }
-    fn get_b(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32, y as i32 - 1)
-    }
-    fn get_c(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 + 1, y as i32 - 1)
+impl<'a> Source<'a> {
+    fn new(pixels: &'a [u32], width: usize, height: usize) -> Self {
+        Self {
+            pixels,
+            lumas: pixels
+                .par_iter()
+                .map(|pixel| Rgba(pixel.to_ne_bytes()).to_luma().0[0])
+                .collect(),
+            width,
+            height,
+        }
     }
 
-    fn get_d(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 - 1, y as i32)
-    }
-    fn get_e(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32, y as i32)
-    }
-    fn get_f(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 + 1, y as i32)
+    fn index(&self, x: isize, y: isize) -> usize {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        y * self.width + x
     }
 
-    fn get_g(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 - 1, y as i32 + 1)
-    }
-    fn get_h(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32, y as i32 + 1)
-    }
-    fn get_i(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 + 1, y as i32 + 1)
+    fn pixel(&self, x: isize, y: isize) -> u32 {
+        self.pixels[self.index(x, y)]
     }
 
-    fn get_p(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32, y as i32 - 2)
-    }
-    fn get_q(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 - 2, y as i32)
-    }
-    fn get_r(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32 + 2, y as i32)
-    }
-    fn get_s(&self, x: u32, y: u32) -> Rgba<u8> {
-        *get_pixel_checked(self, x as i32, y as i32 + 2)
+    fn luma(&self, x: isize, y: isize) -> u8 {
+        self.lumas[self.index(x, y)]
     }
 }
 
-pub fn magnify(image: &RgbaImage) -> RgbaImage {
-    let width = image.width();
-    let height = image.height();
-
-    let mut output_image: RgbaImage = RgbaImage::new(width * 2, height * 2);
-
-    let out_vec: Vec<((u32, u32), Rgba<u8>)> = (0..height)
-        .into_par_iter()
-        .fold(
-            || Vec::new(),
-            |mut vec: Vec<((u32, u32), Rgba<u8>)>, y: u32| {
-                let x = 0;
-
-                let mut a = image.get_a(x, y);
-                let mut b = image.get_b(x, y);
-
-                let mut d = image.get_d(x, y);
-                let mut e = image.get_e(x, y);
-                let mut f = image.get_f(x, y);
-
-                let mut g = image.get_g(x, y);
-                let mut h = image.get_h(x, y);
-
-                let p = image.get_p(x, y);
-                let mut q = image.get_q(x, y);
-                let s = image.get_s(x, y);
-
-                for x in 0..width {
-                    let c = image.get_c(x, y);
-                    let r = image.get_r(x, y);
-                    let i = image.get_i(x, y);
-
-                    let b_luma = b.to_luma().0[0];
-                    let d_luma = d.to_luma().0[0];
-                    let e_luma = e.to_luma().0[0];
-                    let f_luma = f.to_luma().0[0];
-                    let h_luma = h.to_luma().0[0];
-
-                    let mut j: Rgba<u8>;
-                    let mut k: Rgba<u8>;
-                    let mut l: Rgba<u8>;
-                    let mut m: Rgba<u8>;
-
-                    j = e;
-                    k = e;
-                    l = e;
-                    m = e;
-
-                    // 1:1 slope rules
-                    if (d == b && d != h && d != f)
-                        && (e_luma >= d_luma || e == a)
-                        && utils::any_eq3(e, a, c, g)
-                        && ((e_luma < d_luma) || a != d || e != p || e != q)
-                    {
-                        j = d;
-                    }
+fn lanes(a: [u32; 4]) -> i32x4 {
+    i32x4::new(a.map(|a| a as i32))
+}
 
-                    if (b == f && b != d && b != h)
-                        && (e_luma >= b_luma || e == c)
-                        && utils::any_eq3(e, a, c, i)
-                        && ((e_luma < b_luma) || c != b || e != p || e != r)
-                    {
-                        k = b;
-                    }
+fn eq_mask(b: u32, a: [u32; 4]) -> i32 {
+    lanes(a).cmp_eq(i32x4::splat(b as i32)).move_mask()
+}
 
-                    if (h == d && h != f && h != b)
-                        && (e_luma >= h_luma || e == g)
-                        && utils::any_eq3(e, a, g, i)
-                        && ((e_luma < h_luma) || g != h || e != s || e != q)
-                    {
-                        l = h;
-                    }
+fn any_eq3(b: u32, a0: u32, a1: u32, a2: u32) -> bool {
+    eq_mask(b, [a0, a1, a2, a2]) != 0
+}
 
-                    if (f == h && f != b && f != d)
-                        && (e_luma >= f_luma || e == i)
-                        && utils::any_eq3(e, c, g, i)
-                        && ((e_luma < f_luma) || i != h || e != r || e != s)
-                    {
-                        m = f;
-                    }
+fn all_eq2(b: u32, a0: u32, a1: u32) -> bool {
+    b == a0 && b == a1
+}
 
-                    // Intersection rules
-                    if (e != f && utils::all_eq4(e, c, i, d, q) && utils::all_eq2(f, b, h))
-                        && (f != *get_pixel_checked(image, x as i32 + 3, y as i32))
-                    {
-                        k = f;
-                        m = f;
+fn all_eq3(b: u32, a0: u32, a1: u32, a2: u32) -> bool {
+    eq_mask(b, [a0, a1, a2, a2]) == 0b1111
+}
+
+fn all_eq4(b: u32, a0: u32, a1: u32, a2: u32, a3: u32) -> bool {
+    eq_mask(b, [a0, a1, a2, a3]) == 0b1111
+}
+
+fn none_eq2(b: u32, a0: u32, a1: u32) -> bool {
+    b != a0 && b != a1
+}
+
+fn none_eq4(b: u32, a0: u32, a1: u32, a2: u32, a3: u32) -> bool {
+    eq_mask(b, [a0, a1, a2, a3]) == 0
+}
+
+// Magnifies the given rows of the source into dst, which starts at the first of those rows.
+fn magnify_rows(src: &Source, dst: &mut [u32], rows: std::ops::Range<usize>) {
+    let dst_width = src.width * 2;
+    for (row, y) in rows.enumerate() {
+        let y = y as isize;
+
+        // P and S are only read at the start of each row, same as in the reference implementation, so the output
+        // stays the same.
+        let p = src.pixel(0, y - 2);
+        let s = src.pixel(0, y + 2);
+
+        for x in 0..src.width as isize {
+            let a = src.pixel(x - 1, y - 1);
+            let b = src.pixel(x, y - 1);
+            let c = src.pixel(x + 1, y - 1);
+            let d = src.pixel(x - 1, y);
+            let e = src.pixel(x, y);
+            let f = src.pixel(x + 1, y);
+            let g = src.pixel(x - 1, y + 1);
+            let h = src.pixel(x, y + 1);
+            let i = src.pixel(x + 1, y + 1);
+            let q = src.pixel(x - 2, y);
+            let r = src.pixel(x + 2, y);
+
+            let b_luma = src.luma(x, y - 1);
+            let d_luma = src.luma(x - 1, y);
+            let e_luma = src.luma(x, y);
+            let f_luma = src.luma(x + 1, y);
+            let h_luma = src.luma(x, y + 1);
+
+            let mut j = e;
+            let mut k = e;
+            let mut l = e;
+            let mut m = e;
+
+            // 1:1 slope rules
+            if (d == b && d != h && d != f)
+                && (e_luma >= d_luma || e == a)
+                && any_eq3(e, a, c, g)
+                && ((e_luma < d_luma) || a != d || e != p || e != q)
+            {
+                j = d;
+            }
+
+            if (b == f && b != d && b != h)
+                && (e_luma >= b_luma || e == c)
+                && any_eq3(e, a, c, i)
+                && ((e_luma < b_luma) || c != b || e != p || e != r)
+            {
+                k = b;
+            }
+
+            if (h == d && h != f && h != b)
+                && (e_luma >= h_luma || e == g)
+                && any_eq3(e, a, g, i)
+                && ((e_luma < h_luma) || g != h || e != s || e != q)
+            {
+                l = h;
+            }
+
+            if (f == h && f != b && f != d)
+                && (e_luma >= f_luma || e == i)
+                && any_eq3(e, c, g, i)
+                && ((e_luma < f_luma) || i != h || e != r || e != s)
+            {
+                m = f;
+            }
+
+            // Intersection rules
+            if (e != f && all_eq4(e, c, i, d, q) && all_eq2(f, b, h)) && (f != src.pixel(x + 3, y)) {
+                k = f;
+                m = f;
+            }
+            if (e != d && all_eq4(e, a, g, f, r) && all_eq2(d, b, h)) && (d != src.pixel(x - 3, y)) {
+                j = d;
+                l = d;
+            }
+            if (e != h && all_eq4(e, g, i, b, p) && all_eq2(h, d, f)) && (h != src.pixel(x, y + 3)) {
+                l = h;
+                m = h;
+            }
+            if (e != b && all_eq4(e, a, c, h, s) && all_eq2(b, d, f)) && (b != src.pixel(x, y - 3)) {
+                j = b;
+                k = b;
+            }
+
+            if b_luma < e_luma && all_eq4(e, g, h, i, s) && none_eq4(e, a, d, c, f) {
+                j = b;
+                k = b;
+            }
+            if h_luma < e_luma && all_eq4(e, a, b, c, p) && none_eq4(e, d, g, i, f) {
+                l = h;
+                m = h;
+            }
+            if f_luma < e_luma && all_eq4(e, a, d, g, q) && none_eq4(e, b, c, i, h) {
+                k = f;
+                m = f;
+            }
+            if d_luma < e_luma && all_eq4(e, c, f, i, r) && none_eq4(e, b, a, g, h) {
+                j = d;
+                l = d;
+            }
+
+            // 2:1 slope rules
+            if h != b {
+                if h != a && h != e && h != c {
+                    if all_eq3(h, g, f, r) && none_eq2(h, d, src.pixel(x + 2, y - 1)) {
+                        l = m;
                     }
-                    if (e != d && utils::all_eq4(e, a, g, f, r) && utils::all_eq2(d, b, h))
-                        && (d != *get_pixel_checked(image, x as i32 - 3, y as i32))
-                    {
-                        j = d;
-                        l = d;
+                    if all_eq3(h, i, d, q) && none_eq2(h, f, src.pixel(x - 2, y - 1)) {
+                        m = l;
                     }
-                    if (e != h && utils::all_eq4(e, g, i, b, p) && utils::all_eq2(h, d, f))
-                        && (h != *get_pixel_checked(image, x as i32, y as i32 + 3))
-                    {
-                        l = h;
-                        m = h;
+                }
+
+                if b != i && b != g && b != e {
+                    if all_eq3(b, a, f, r) && none_eq2(b, d, src.pixel(x + 2, y + 1)) {
+                        j = k;
                     }
-                    if (e != b && utils::all_eq4(e, a, c, h, s) && utils::all_eq2(b, d, f))
-                        && (b != *get_pixel_checked(image, x as i32, y as i32 - 3))
-                    {
-                        j = b;
-                        k = b;
+                    if all_eq3(b, c, d, q) && none_eq2(b, f, src.pixel(x - 2, y + 1)) {
+                        k = j;
                     }
+                }
+            }
 
-                    if b_luma < e_luma && utils::all_eq4(e, g, h, i, s) && utils::none_eq4(e, a, d, c, f) {
-                        j = b;
-                        k = b;
+            if f != d {
+                if d != i && d != e && d != c {
+                    if all_eq3(d, a, h, s) && none_eq2(d, b, src.pixel(x + 1, y + 2)) {
+                        j = l;
                     }
-                    if h_luma < e_luma && utils::all_eq4(e, a, b, c, p) && utils::none_eq4(e, d, g, i, f) {
-                        l = h;
-                        m = h;
+                    if all_eq3(d, g, b, p) && none_eq2(d, h, src.pixel(x + 1, y - 2)) {
+                        l = j;
                     }
-                    if f_luma < e_luma && utils::all_eq4(e, a, d, g, q) && utils::none_eq4(e, b, c, i, h) {
-                        k = f;
-                        m = f;
+                }
+
+                if f != e && f != a && f != g {
+                    if all_eq3(f, c, h, s) && none_eq2(f, b, src.pixel(x - 1, y + 2)) {
+                        k = m;
                     }
-                    if d_luma < e_luma && utils::all_eq4(e, c, f, i, r) && utils::none_eq4(e, b, a, g, h) {
-                        j = d;
-                        l = d;
+                    if all_eq3(f, i, b, p) && none_eq2(f, h, src.pixel(x - 1, y - 2)) {
+                        m = k;
                     }
-
-                    // 2:1 slope rules
-                    if h != b {
-                        if h != a && h != e && h != c {
-                            if utils::all_eq3(h, g, f, r)
-                                && utils::none_eq2(h, d, *get_pixel_checked(image, x as i32 + 2, y as i32 - 1))
-                            {
-                                l = m;
-                            }
-                            if utils::all_eq3(h, i, d, q)
-                                && utils::none_eq2(h, f, *get_pixel_checked(image, x as i32 - 2, y as i32 - 1))
-                            {
-                                m = l
-                            };
-                        }
-
-                        if b != i && b != g && b != e {
-                            if utils::all_eq3(b, a, f, r)
-                                && utils::none_eq2(b, d, *get_pixel_checked(image, x as i32 + 2, y as i32 + 1))
-                            {
-                                j = k;
-                            }
-                            if utils::all_eq3(b, c, d, q)
-                                && utils::none_eq2(b, f, *get_pixel_checked(image, x as i32 - 2, y as i32 + 1))
-                            {
-                                k = j;
-                            }
-                        }
-                    } // H !== B
-
-                    if f != d {
-                        if d != i && d != e && d != c {
-                            if utils::all_eq3(d, a, h, s)
-                                && utils::none_eq2(d, b, *get_pixel_checked(image, x as i32 + 1, y as i32 + 2))
-                            {
-                                j = l;
-                            }
-                            if utils::all_eq3(d, g, b, p)
-                                && utils::none_eq2(d, h, *get_pixel_checked(image, x as i32 + 1, y as i32 - 2))
-                            {
-                                l = j;
-                            }
-                        }
-
-                        if f != e && f != a && f != g {
-                            if utils::all_eq3(f, c, h, s)
-                                && utils::none_eq2(f, b, *get_pixel_checked(image, x as i32 - 1, y as i32 + 2))
-                            {
-                                k = m;
-                            }
-                            if utils::all_eq3(f, i, b, p)
-                                && utils::none_eq2(f, h, *get_pixel_checked(image, x as i32 - 1, y as i32 - 2))
-                            {
-                                m = k;
-                            }
-                        }
-                    } // F !== D
-
-                    vec.append(&mut vec![
-                        ((x * 2, y * 2), j),
-                        ((x * 2 + 1, y * 2), k),
-                        ((x * 2, y * 2 + 1), l),
-                        ((x * 2 + 1, y * 2 + 1), m),
-                    ]);
-
-                    a = b;
-                    b = c;
-
-                    q = d;
-                    d = e;
-                    e = f;
-                    f = r;
-
-                    g = h;
-                    h = i;
                 }
+            }
+
+            let top = row * 2 * dst_width + x as usize * 2;
+            dst[top] = j;
+            dst[top + 1] = k;
+            dst[top + dst_width] = l;
+            dst[top + dst_width + 1] = m;
+        }
+    }
+}
 
-                vec
-            },
-        )
-        .reduce(
-            || Vec::new(),
-            |mut a, mut b| {
-                a.append(&mut b);
-                a
-            },
-        );
-
-    out_vec.iter().for_each(|((x, y), pixel)| {
-        output_image.put_pixel(*x, *y, *pixel);
-    });
-
-    output_image
+/// Magnifies `src`, which is `width` by `height` pixels in RGBA memory order, by 2x into `dst`.
+pub fn magnify_slice(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let src = Source::new(&src[..width * height], width, height);
+    dst[..width * height * 4]
+        .par_chunks_mut(width * 2 * 2 * BAND_ROWS)
+        .enumerate()
+        .for_each(|(i, dst)| {
+            let start = i * BAND_ROWS;
+            magnify_rows(&src, dst, start..std::cmp::min(start + BAND_ROWS, height));
+        });
+}
+
+pub fn magnify(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let src = image
+        .pixels()
+        .map(|pixel| u32::from_ne_bytes(pixel.0))
+        .collect::<Vec<_>>();
+    let mut dst = vec![0u32; width * height * 4];
+    magnify_slice(&src, &mut dst, width, height);
+    RgbaImage::from_raw(
+        width as u32 * 2,
+        height as u32 * 2,
+        dst.into_iter().flat_map(|pixel| pixel.to_ne_bytes()).collect(),
+    )
+    .unwrap()
 }
@@ -1,4 +1,3 @@
-use mmpx;
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -0,0 +1,96 @@
+//! The Scale2x/Scale3x/Scale4x family of pixel-art scalers, also known as EPX and AdvMAME2x/3x/4x.
+//!
+//! Pixels are compared for exact equality only, so any pixel format works as long as it fits in a `u32`.
+
+fn clamped(src: &[u32], width: usize, height: usize, x: isize, y: isize) -> u32 {
+    let x = x.clamp(0, width as isize - 1) as usize;
+    let y = y.clamp(0, height as isize - 1) as usize;
+    src[y * width + x]
+}
+
+/// Scales `src` by 2x into `dst`, which must be `width * 2` by `height * 2`.
+pub fn scale2x(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    assert!(src.len() >= width * height);
+    assert!(dst.len() >= width * height * 4);
+
+    let dst_width = width * 2;
+    for y in 0..height {
+        for x in 0..width {
+            let (xi, yi) = (x as isize, y as isize);
+            let b = clamped(src, width, height, xi, yi - 1);
+            let d = clamped(src, width, height, xi - 1, yi);
+            let e = src[y * width + x];
+            let f = clamped(src, width, height, xi + 1, yi);
+            let h = clamped(src, width, height, xi, yi + 1);
+
+            let out = if b != h && d != f {
+                [
+                    if d == b { d } else { e },
+                    if b == f { f } else { e },
+                    if d == h { d } else { e },
+                    if h == f { f } else { e },
+                ]
+            } else {
+                [e; 4]
+            };
+
+            let i = y * 2 * dst_width + x * 2;
+            dst[i] = out[0];
+            dst[i + 1] = out[1];
+            dst[i + dst_width] = out[2];
+            dst[i + dst_width + 1] = out[3];
+        }
+    }
+}
+
+/// Scales `src` by 3x into `dst`, which must be `width * 3` by `height * 3`.
+pub fn scale3x(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    assert!(src.len() >= width * height);
+    assert!(dst.len() >= width * height * 9);
+
+    let dst_width = width * 3;
+    for y in 0..height {
+        for x in 0..width {
+            let (xi, yi) = (x as isize, y as isize);
+            let a = clamped(src, width, height, xi - 1, yi - 1);
+            let b = clamped(src, width, height, xi, yi - 1);
+            let c = clamped(src, width, height, xi + 1, yi - 1);
+            let d = clamped(src, width, height, xi - 1, yi);
+            let e = src[y * width + x];
+            let f = clamped(src, width, height, xi + 1, yi);
+            let g = clamped(src, width, height, xi - 1, yi + 1);
+            let h = clamped(src, width, height, xi, yi + 1);
+            let i = clamped(src, width, height, xi + 1, yi + 1);
+
+            let out = if b != h && d != f {
+                [
+                    if d == b { d } else { e },
+                    if (d == b && e != c) || (b == f && e != a) { b } else { e },
+                    if b == f { f } else { e },
+                    if (d == b && e != g) || (d == h && e != a) { d } else { e },
+                    e,
+                    if (b == f && e != i) || (h == f && e != c) { f } else { e },
+                    if d == h { d } else { e },
+                    if (d == h && e != i) || (h == f && e != g) { h } else { e },
+                    if h == f { f } else { e },
+                ]
+            } else {
+                [e; 9]
+            };
+
+            for (j, row) in out.chunks_exact(3).enumerate() {
+                let k = (y * 3 + j) * dst_width + x * 3;
+                dst[k..k + 3].copy_from_slice(row);
+            }
+        }
+    }
+}
+
+/// Scales `src` by 4x into `dst`, which must be `width * 4` by `height * 4`.
+///
+/// This is Scale2x applied twice.
+pub fn scale4x(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    let mut tmp = vec![0u32; width * height * 4];
+    scale2x(src, &mut tmp, width, height);
+    scale2x(&tmp, dst, width * 2, height * 2);
+}
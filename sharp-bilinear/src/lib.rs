@@ -0,0 +1,47 @@
+//! Sharp bilinear scaling: the image is scaled up by the largest whole factor that fits with nearest neighbor, then
+//! bilinearly filtered the rest of the way. Pixels stay sharp, but unlike plain nearest neighbor, scaling by a
+//! non-integer factor doesn't make some rows and columns visibly thicker than others.
+//!
+//! Pixels are RGBA in memory order.
+
+// For each output coordinate along one axis: the two source coordinates to blend and the weight of the second, out of
+// 256.
+fn taps(src_len: usize, dst_len: usize, prescale: usize) -> Vec<(usize, usize, u32)> {
+    let prescaled_len = src_len * prescale;
+    (0..dst_len)
+        .map(|i| {
+            let pos = ((i as f32 + 0.5) * prescaled_len as f32 / dst_len as f32 - 0.5).max(0.0);
+            let lo = (pos.floor() as usize).min(prescaled_len - 1);
+            let hi = (lo + 1).min(prescaled_len - 1);
+            let weight = ((pos - lo as f32) * 256.0).round().clamp(0.0, 256.0) as u32;
+            (lo / prescale, hi / prescale, weight)
+        })
+        .collect()
+}
+
+fn lerp(a: [u8; 4], b: [u8; 4], weight: u32) -> [u8; 4] {
+    std::array::from_fn(|i| ((a[i] as u32 * (256 - weight) + b[i] as u32 * weight + 128) / 256) as u8)
+}
+
+/// Scales `src`, which is `width` by `height`, into `dst`, which is `dst_width` by `dst_height`.
+pub fn scale(src: &[u32], width: usize, height: usize, dst: &mut [u32], dst_width: usize, dst_height: usize) {
+    assert!(src.len() >= width * height);
+    assert!(dst.len() >= dst_width * dst_height);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let prescale = std::cmp::max(1, std::cmp::min(dst_width / width, dst_height / height));
+    let x_taps = taps(width, dst_width, prescale);
+    let y_taps = taps(height, dst_height, prescale);
+
+    for (y, &(y0, y1, y_weight)) in y_taps.iter().enumerate() {
+        let row0 = &src[y0 * width..(y0 + 1) * width];
+        let row1 = &src[y1 * width..(y1 + 1) * width];
+        for (x, &(x0, x1, x_weight)) in x_taps.iter().enumerate() {
+            let top = lerp(row0[x0].to_ne_bytes(), row0[x1].to_ne_bytes(), x_weight);
+            let bottom = lerp(row1[x0].to_ne_bytes(), row1[x1].to_ne_bytes(), x_weight);
+            dst[y * dst_width + x] = u32::from_ne_bytes(lerp(top, bottom, y_weight));
+        }
+    }
+}
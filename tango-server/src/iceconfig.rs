@@ -1,3 +1,5 @@
+pub mod coturn;
+pub mod file;
 pub mod opentok;
 pub mod twilio;
 
@@ -8,3 +10,27 @@ pub trait Backend {
         remote_ip: &std::net::IpAddr,
     ) -> anyhow::Result<Vec<tango_protos::matchmaking::packet::hello::IceServer>>;
 }
+
+pub struct Combined {
+    backends: Vec<Box<dyn Backend + Send + Sync + 'static>>,
+}
+
+impl Combined {
+    pub fn new(backends: Vec<Box<dyn Backend + Send + Sync + 'static>>) -> Self {
+        Self { backends }
+    }
+}
+
+#[async_trait::async_trait]
+impl Backend for Combined {
+    async fn get(
+        &self,
+        remote_ip: &std::net::IpAddr,
+    ) -> anyhow::Result<Vec<tango_protos::matchmaking::packet::hello::IceServer>> {
+        let mut ice_servers = vec![];
+        for backend in self.backends.iter() {
+            ice_servers.extend(backend.get(remote_ip).await?);
+        }
+        Ok(ice_servers)
+    }
+}
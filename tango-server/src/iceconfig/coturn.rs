@@ -0,0 +1,61 @@
+use hmac::Mac;
+
+// Implements the TURN REST API credential scheme (draft-uberti-behave-turn-rest), as used by coturn's
+// use-auth-secret mode.
+pub struct Backend {
+    shared_secret: String,
+    urls: Vec<String>,
+    ttl: std::time::Duration,
+}
+
+impl Backend {
+    pub fn new(shared_secret: String, urls: Vec<String>, ttl: std::time::Duration) -> Self {
+        Self {
+            shared_secret,
+            urls,
+            ttl,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl super::Backend for Backend {
+    async fn get(
+        &self,
+        remote_ip: &std::net::IpAddr,
+    ) -> anyhow::Result<Vec<tango_protos::matchmaking::packet::hello::IceServer>> {
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs()
+            + self.ttl.as_secs();
+
+        let username = format!("{}:{}", expiry, remote_ip);
+        let mut mac = hmac::Hmac::<sha1::Sha1>::new_from_slice(self.shared_secret.as_bytes())?;
+        mac.update(username.as_bytes());
+        let credential = base64::encode(mac.finalize().into_bytes());
+
+        // STUN servers don't need credentials, so hand them out separately.
+        let (turn_urls, stun_urls): (Vec<_>, Vec<_>) = self
+            .urls
+            .iter()
+            .cloned()
+            .partition(|url| url.starts_with("turn:") || url.starts_with("turns:"));
+
+        let mut ice_servers = vec![];
+        if !stun_urls.is_empty() {
+            ice_servers.push(tango_protos::matchmaking::packet::hello::IceServer {
+                credential: None,
+                username: None,
+                urls: stun_urls,
+            });
+        }
+        if !turn_urls.is_empty() {
+            ice_servers.push(tango_protos::matchmaking::packet::hello::IceServer {
+                credential: Some(credential),
+                username: Some(username),
+                urls: turn_urls,
+            });
+        }
+        Ok(ice_servers)
+    }
+}
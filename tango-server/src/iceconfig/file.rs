@@ -0,0 +1,40 @@
+#[derive(serde::Deserialize)]
+struct ICEServer {
+    #[serde(default)]
+    username: Option<String>,
+
+    #[serde(default)]
+    credential: Option<String>,
+
+    urls: Vec<String>,
+}
+
+pub struct Backend {
+    ice_servers: Vec<tango_protos::matchmaking::packet::hello::IceServer>,
+}
+
+impl Backend {
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        let ice_servers = serde_json::from_slice::<Vec<ICEServer>>(&std::fs::read(path)?)?;
+        Ok(Self {
+            ice_servers: ice_servers
+                .into_iter()
+                .map(|ice_server| tango_protos::matchmaking::packet::hello::IceServer {
+                    credential: ice_server.credential,
+                    username: ice_server.username,
+                    urls: ice_server.urls,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl super::Backend for Backend {
+    async fn get(
+        &self,
+        _remote_ip: &std::net::IpAddr,
+    ) -> anyhow::Result<Vec<tango_protos::matchmaking::packet::hello::IceServer>> {
+        Ok(self.ice_servers.clone())
+    }
+}
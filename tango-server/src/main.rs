@@ -27,6 +27,20 @@ struct Config {
 
     #[envconfig(from = "OPENTOK_API_SECRET", default = "")]
     opentok_api_secret: String,
+
+    #[envconfig(from = "COTURN_SHARED_SECRET", default = "")]
+    coturn_shared_secret: String,
+
+    // Comma-separated, e.g. "stun:turn.example.com:3478,turn:turn.example.com:3478?transport=udp".
+    #[envconfig(from = "COTURN_URLS", default = "")]
+    coturn_urls: String,
+
+    #[envconfig(from = "COTURN_CREDENTIAL_TTL_SECS", default = "86400")]
+    coturn_credential_ttl_secs: u64,
+
+    // Path to a JSON file containing a list of ICE servers, e.g. [{"urls": ["stun:stun.example.com:3478"]}].
+    #[envconfig(from = "ICE_SERVERS_PATH", default = "")]
+    ice_servers_path: String,
 }
 
 struct State {
@@ -60,19 +74,13 @@ async fn handle_matchmaking_request(
             .unwrap());
     };
 
-    let session_id = if let Some(session_id) = request.uri().query().and_then(|query| {
+    // Clients without a session ID may only list lobbies or join the quick-match queue.
+    let session_id = request.uri().query().and_then(|query| {
         url::form_urlencoded::parse(query.as_bytes())
             .into_owned()
             .find(|(k, _)| k == "session_id")
             .map(|(_, v)| v)
-    }) {
-        session_id
-    } else {
-        return Ok(hyper::Response::builder()
-            .status(hyper::StatusCode::BAD_REQUEST)
-            .body(hyper::Body::from("missing session_id"))
-            .unwrap());
-    };
+    });
 
     if !hyper_tungstenite::is_upgrade_request(&request) {
         return Ok(hyper::Response::builder()
@@ -99,10 +107,7 @@ async fn handle_matchmaking_request(
             }
         };
 
-        if let Err(e) = matchmaking_server
-            .handle_stream(websocket, remote_ip, &session_id)
-            .await
-        {
+        if let Err(e) = matchmaking_server.handle_stream(websocket, remote_ip, session_id).await {
             log::error!("error in websocket connection: {}", e);
         }
     });
@@ -152,11 +157,41 @@ async fn main() -> anyhow::Result<()> {
                 config.opentok_api_key.clone(),
                 config.opentok_api_secret.clone(),
             )))
+        } else if !config.coturn_shared_secret.is_empty() && !config.coturn_urls.is_empty() {
+            log::info!("using coturn iceconfig backend");
+            Some(Box::new(iceconfig::coturn::Backend::new(
+                config.coturn_shared_secret.clone(),
+                config
+                    .coturn_urls
+                    .split(',')
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty())
+                    .collect(),
+                std::time::Duration::from_secs(config.coturn_credential_ttl_secs),
+            )))
         } else {
-            log::warn!("no iceconfig backend, will not service iceconfig requests");
             None
         };
 
+    let iceconfig_backend: Option<Box<dyn iceconfig::Backend + Send + Sync + 'static>> =
+        if !config.ice_servers_path.is_empty() {
+            log::info!("using ICE servers from {}", config.ice_servers_path);
+            let file_backend: Box<dyn iceconfig::Backend + Send + Sync + 'static> = Box::new(
+                iceconfig::file::Backend::load(std::path::Path::new(&config.ice_servers_path))?,
+            );
+            Some(if let Some(iceconfig_backend) = iceconfig_backend {
+                Box::new(iceconfig::Combined::new(vec![iceconfig_backend, file_backend]))
+            } else {
+                file_backend
+            })
+        } else {
+            iceconfig_backend
+        };
+
+    if iceconfig_backend.is_none() {
+        log::warn!("no iceconfig backend, will not service iceconfig requests");
+    }
+
     let router = router(real_ip_getter, iceconfig_backend);
 
     let service = routerify::RouterService::new(router).unwrap();
@@ -4,6 +4,7 @@ use prost::Message;
 use crate::iceconfig;
 
 const ICECONFIG_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const QUEUE_SESSION_ID_LENGTH: usize = 16;
 
 struct Session {
     offer_sdp: String,
@@ -15,17 +16,62 @@ struct Session {
     >,
 }
 
+#[derive(PartialEq, Eq)]
+struct QueueKey {
+    netplay_compatibility: String,
+    match_type: u32,
+    match_subtype: u32,
+}
+
+struct QueueEntry {
+    id: u64,
+    key: QueueKey,
+    nickname: String,
+    matched_tx: tokio::sync::oneshot::Sender<tango_protos::matchmaking::packet::Matched>,
+}
+
 pub struct Server {
     sessions: std::sync::Arc<
         tokio::sync::Mutex<std::collections::HashMap<String, std::sync::Arc<tokio::sync::Mutex<Session>>>>,
     >,
+    lobbies: std::sync::Arc<tokio::sync::Mutex<std::collections::HashMap<String, tango_protos::matchmaking::Lobby>>>,
+    queue: std::sync::Arc<tokio::sync::Mutex<Vec<QueueEntry>>>,
+    next_queue_entry_id: std::sync::atomic::AtomicU64,
     iceconfig_backend: Option<Box<dyn iceconfig::Backend + Send + Sync + 'static>>,
 }
 
+fn generate_session_id() -> String {
+    use rand::Rng;
+    format!(
+        "queue-{}",
+        rand::thread_rng()
+            .sample_iter(&rand::distributions::Alphanumeric)
+            .take(QUEUE_SESSION_ID_LENGTH)
+            .map(char::from)
+            .collect::<String>()
+    )
+}
+
+fn queue_key(queue: &tango_protos::matchmaking::packet::Queue) -> anyhow::Result<QueueKey> {
+    let game_info = if let Some(game_info) = queue.game_info.as_ref() {
+        game_info
+    } else {
+        anyhow::bail!("queue request is missing game info");
+    };
+    Ok(QueueKey {
+        netplay_compatibility: game_info.netplay_compatibility.clone(),
+        match_type: queue.match_type,
+        match_subtype: queue.match_subtype,
+    })
+}
+
 impl Server {
     pub fn new(iceconfig_backend: Option<Box<dyn iceconfig::Backend + Send + Sync + 'static>>) -> Server {
         Server {
             sessions: std::sync::Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
+            lobbies: std::sync::Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
+            queue: std::sync::Arc::new(tokio::sync::Mutex::new(vec![])),
+            next_queue_entry_id: std::sync::atomic::AtomicU64::new(0),
             iceconfig_backend,
         }
     }
@@ -34,7 +80,7 @@ impl Server {
         &self,
         ws: hyper_tungstenite::WebSocketStream<hyper::upgrade::Upgraded>,
         remote_ip: std::net::IpAddr,
-        session_id: &str,
+        session_id: Option<String>,
     ) -> anyhow::Result<()> {
         let (mut tx, mut rx) = ws.split();
 
@@ -97,17 +143,52 @@ impl Server {
         .await?;
 
         let session_id_for_cleanup = std::sync::Arc::new(tokio::sync::Mutex::new(None));
+        let queue_entry_id = self
+            .next_queue_entry_id
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
         let r = {
             let sessions = self.sessions.clone();
+            let lobbies = self.lobbies.clone();
+            let queue = self.queue.clone();
             let session_id_for_cleanup = session_id_for_cleanup.clone();
             (move || async move {
+                let mut session_id = session_id;
                 let mut session = None;
                 let mut tx = Some(tx);
                 let mut me: usize;
+                let mut is_host = false;
+                let mut matched_rx: Option<tokio::sync::oneshot::Receiver<tango_protos::matchmaking::packet::Matched>> =
+                    None;
 
                 loop {
-                    let msg = match rx.try_next().await? {
+                    let raw = if let Some(matched_rx_ref) = matched_rx.as_mut() {
+                        tokio::select! {
+                            raw = rx.try_next() => raw?,
+                            matched = matched_rx_ref => {
+                                matched_rx = None;
+                                let matched = matched?;
+                                session_id = Some(matched.session_id.clone());
+                                let tx = if let Some(tx) = tx.as_mut() {
+                                    tx
+                                } else {
+                                    anyhow::bail!("matched after session started");
+                                };
+                                tx.send(tungstenite::Message::Binary(
+                                    tango_protos::matchmaking::Packet {
+                                        which: Some(tango_protos::matchmaking::packet::Which::Matched(matched)),
+                                    }
+                                    .encode_to_vec(),
+                                ))
+                                .await?;
+                                continue;
+                            }
+                        }
+                    } else {
+                        rx.try_next().await?
+                    };
+
+                    let msg = match raw {
                         Some(tungstenite::Message::Binary(d)) => {
                             tango_protos::matchmaking::Packet::decode(bytes::Bytes::from(d))?
                         }
@@ -121,10 +202,21 @@ impl Server {
                     log::debug!("received message: {:?}", msg);
                     match msg.which {
                         Some(tango_protos::matchmaking::packet::Which::Start(start)) => {
+                            let session_id = if let Some(session_id) = session_id.as_ref() {
+                                session_id
+                            } else {
+                                anyhow::bail!("no session id");
+                            };
+                            if matched_rx.is_some() {
+                                anyhow::bail!("attempted to start while queued");
+                            }
+
                             let mut sessions = sessions.lock().await;
                             session = Some(if let Some(session) = sessions.remove(session_id) {
+                                lobbies.lock().await.remove(session_id);
                                 session
                             } else {
+                                is_host = true;
                                 sessions
                                     .entry(session_id.to_string())
                                     .or_insert_with(|| {
@@ -142,7 +234,7 @@ impl Server {
                                 anyhow::bail!("no such session");
                             };
                             let mut session = session.lock().await;
-                            *session_id_for_cleanup.lock().await = Some(session_id);
+                            *session_id_for_cleanup.lock().await = Some(session_id.clone());
                             let offer_sdp = session.offer_sdp.to_string();
 
                             me = session.sinks.len();
@@ -188,6 +280,124 @@ impl Server {
                                 ))
                                 .await?;
                         }
+                        Some(tango_protos::matchmaking::packet::Which::Advertise(advertise)) => {
+                            let session_id = match session_id.as_ref() {
+                                Some(session_id) if session.is_some() && is_host => session_id,
+                                _ => {
+                                    anyhow::bail!("only the host of a started session may advertise it");
+                                }
+                            };
+
+                            // The session might have been joined between us starting it and advertising it. Start takes the sessions
+                            // lock before removing the lobby, so holding it here keeps a joined session from being listed.
+                            let sessions = sessions.lock().await;
+                            if !sessions.contains_key(session_id) {
+                                continue;
+                            }
+
+                            lobbies.lock().await.insert(
+                                session_id.clone(),
+                                tango_protos::matchmaking::Lobby {
+                                    session_id: session_id.clone(),
+                                    nickname: advertise.nickname,
+                                    game_info: advertise.game_info,
+                                    match_type: advertise.match_type,
+                                    match_subtype: advertise.match_subtype,
+                                },
+                            );
+                        }
+                        Some(tango_protos::matchmaking::packet::Which::ListLobbies(list_lobbies)) => {
+                            let lobbies = lobbies
+                                .lock()
+                                .await
+                                .values()
+                                .filter(|lobby| {
+                                    list_lobbies
+                                        .netplay_compatibility
+                                        .as_ref()
+                                        .map(|netplay_compatibility| {
+                                            lobby
+                                                .game_info
+                                                .as_ref()
+                                                .map(|game_info| {
+                                                    &game_info.netplay_compatibility == netplay_compatibility
+                                                })
+                                                .unwrap_or(false)
+                                        })
+                                        .unwrap_or(true)
+                                })
+                                .cloned()
+                                .collect();
+
+                            let tx = if let Some(tx) = tx.as_mut() {
+                                tx
+                            } else {
+                                anyhow::bail!("attempted to list lobbies after session started");
+                            };
+                            tx.send(tungstenite::Message::Binary(
+                                tango_protos::matchmaking::Packet {
+                                    which: Some(tango_protos::matchmaking::packet::Which::Lobbies(
+                                        tango_protos::matchmaking::packet::Lobbies { lobbies },
+                                    )),
+                                }
+                                .encode_to_vec(),
+                            ))
+                            .await?;
+                        }
+                        Some(tango_protos::matchmaking::packet::Which::Queue(q)) => {
+                            if session_id.is_some() || matched_rx.is_some() {
+                                anyhow::bail!("attempted to queue with a session id");
+                            }
+                            let key = queue_key(&q)?;
+
+                            let mut queue = queue.lock().await;
+                            let mut matched = None;
+                            while let Some(i) = queue.iter().position(|entry| entry.key == key) {
+                                let entry = queue.remove(i);
+                                let new_session_id = generate_session_id();
+                                if entry
+                                    .matched_tx
+                                    .send(tango_protos::matchmaking::packet::Matched {
+                                        session_id: new_session_id.clone(),
+                                        opponent_nickname: q.nickname.clone(),
+                                    })
+                                    .is_err()
+                                {
+                                    // The other side went away, try the next one.
+                                    continue;
+                                }
+                                matched = Some(tango_protos::matchmaking::packet::Matched {
+                                    session_id: new_session_id,
+                                    opponent_nickname: entry.nickname,
+                                });
+                                break;
+                            }
+
+                            if let Some(matched) = matched {
+                                session_id = Some(matched.session_id.clone());
+                                let tx = if let Some(tx) = tx.as_mut() {
+                                    tx
+                                } else {
+                                    anyhow::bail!("attempted to queue after session started");
+                                };
+                                tx.send(tungstenite::Message::Binary(
+                                    tango_protos::matchmaking::Packet {
+                                        which: Some(tango_protos::matchmaking::packet::Which::Matched(matched)),
+                                    }
+                                    .encode_to_vec(),
+                                ))
+                                .await?;
+                            } else {
+                                let (matched_tx, matched_rx_) = tokio::sync::oneshot::channel();
+                                queue.push(QueueEntry {
+                                    id: queue_entry_id,
+                                    key,
+                                    nickname: q.nickname,
+                                    matched_tx,
+                                });
+                                matched_rx = Some(matched_rx_);
+                            }
+                        }
                         p => anyhow::bail!("unknown packet: {:?}", p),
                     }
                 }
@@ -196,11 +406,15 @@ impl Server {
             .await
         };
 
-        if let Some(session_id) = *session_id_for_cleanup.lock().await {
+        if let Some(session_id) = session_id_for_cleanup.lock().await.as_ref() {
             let mut sessions = self.sessions.lock().await;
             sessions.remove(session_id);
+            let mut lobbies = self.lobbies.lock().await;
+            lobbies.remove(session_id);
         }
 
+        self.queue.lock().await.retain(|entry| entry.id != queue_entry_id);
+
         r
     }
 }
@@ -12,6 +12,17 @@ input-button-start = START
 input-button-select = SELECT
 input-button-speed-change = Speed change
 input-button-menu = Menu
+input-button-save-state = Save state
+input-button-load-state = Load state
+input-button-previous-state-slot = Previous state slot
+input-button-next-state-slot = Next state slot
+input-button-rewind = Rewind
+input-button-fast-forward = Fast forward
+input-button-slow-motion = Slow motion
+input-button-pause = Pause
+input-button-frame-advance = Frame advance
+input-button-turbo-a = Turbo A
+input-button-turbo-b = Turbo B
 
 physical-input-button-dpup = DPad ⬆
 physical-input-button-dpdown = DPad ⬇
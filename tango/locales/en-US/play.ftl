@@ -17,9 +17,18 @@ play-details-match-type = Match type
 play-details-reveal-setup = Reveal setup
 play-details-input-delay = Input delay
     .suggest = Suggest
+play-details-ruleset = Folder rules
+    .none = None
+    .legal = Legal
+    .unsupported = Not covered by this ruleset
+    .violations = {$count} problems
+    .remote = {$ruleset}: {$count} problems
+    .remote-legal = {$ruleset}: legal
+    .remote-unsupported = {$ruleset}: not covered by this ruleset
 
 play-connection-task-starting = Starting connection...
 play-connection-task-signaling = Connecting to matchmaking server...
+play-connection-task-queued = Looking for an opponent...
 play-connection-task-waiting = Waiting for opponent...
 
 select-save = Select save
@@ -38,9 +47,17 @@ lobby-issue-incompatible = Game is not compatible with the opponent's.
 lobby-issue-match-type-mismatch = Match type does not match the opponent's.
 lobby-issue-no-local-selection = You have not selected a game.
 lobby-issue-no-remote-selection = The opponent has not selected a game.
+lobby-issue-ruleset-violations = A folder breaks the selected rules.
+
+lobby-violation-too-many-copies = Too many copies of {$chip}: {$count} (max {$max})
+lobby-violation-too-many-mega-chips = Too many Mega chips: {$count} (max {$max})
+lobby-violation-too-many-giga-chips = Too many Giga chips: {$count} (max {$max})
+lobby-violation-regular-chip-too-large = Regular chip {$chip} is {$mb} MB (max {$max} MB)
+lobby-violation-banned-chip = Banned chip: {$chip}
 
 opponent-setup = Opponent's setup
 own-setup = Own setup
+opponent-setup-compare = Compare with own setup
 
 connection-error = Connection error
 connection-error-remote-protocol-version-too-old = Unable to connect to the other player: they are using an older version of Tango.
@@ -50,3 +67,18 @@ connection-error-other = A connection error has occurred: { $error }
 connection-error-confirm = Damn!
 
 play-show-link-code = Show link code
+play-quick-match = Quick match: play the next person looking for the same game and match type
+play-advertise = List my link code in the public lobbies
+play-public-lobbies = Public lobbies
+    .refresh = Refresh
+    .empty = No one is waiting right now.
+    .error = Failed to list lobbies: { $error }
+    .join = Join
+
+play-desync-detected = Desync detected in round { $round } at tick { $tick }
+
+savestate-slot = State slot {$slot}
+    .empty = State slot {$slot} (empty)
+savestate-saved = Saved state to slot {$slot}
+savestate-loaded = Loaded state from slot {$slot}
+savestate-error = Could not use state slot {$slot}: {$error}
@@ -1,14 +1,40 @@
 replays = Replays
 
 replays-export = Export
+replays-export-set = Export set
+replays-set = Set: {$local}–{$remote} ({$rounds} rounds)
+replays-upload-error = This replay could not be submitted to the replay collector: {$error}
+replays-set-upload-error = This set could not be submitted to the replay collector: {$error}
 replays-play = Play
 replays-scanning = Scanning...
+replays-spectate = Spectate
+replays-spectate-addr = Spectator address
+replays-spectate-view-remote = Opponent's view
+replays-spectate-error = Could not spectate: {$error}
 
 replay-subtitle = {$game_family} @ {$link_code}: vs {$nickname}
 
+replays-filter = Filter
+replays-filter-opponent = Opponent
+replays-filter-game = Game
+replays-filter-patch = Patch
+replays-filter-result = Result
+replays-filter-since = From
+replays-filter-until = To
+replays-filter-any = Any
+replays-filter-date-hint = YYYY-MM-DD
+
+replays-result-win = Win
+replays-result-loss = Loss
+replays-result-draw = Draw
+
+replays-export-encoder = Encoder
+    .ffmpeg = ffmpeg
+    .avi = Built-in (uncompressed AVI)
 replays-export-path = Save to
     .change = Change
 replays-export-scale-factor = Scale factor
+replays-export-video-filter = Video filter
 replays-export-disable-bgm = Disable music
 replays-export-twosided = Two-sided
 replays-export-success = Your replay was successfully exported.
@@ -23,3 +49,6 @@ replay-viewer-step = Step
 replay-viewer-speed = Speed
 replay-viewer-speed-up = Speed up
 replay-viewer-slow-down = Slow down
+replay-viewer-seek = Seek
+replay-viewer-rewind = Back 5 seconds
+replay-viewer-fast-forward = Forward 5 seconds
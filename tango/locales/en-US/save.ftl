@@ -9,6 +9,17 @@ save-cover-description = This tab intentionally left blank.
 
 save-group = Group by chip
 
+save-export-setup = Export setup
+    .json = Copy as JSON
+    .text = Copy as text
+    .sheet-copy = Copy setup sheet image
+    .sheet-save = Save setup sheet as PNG
+
+save-import-setup = Import setup
+    .clipboard = Paste from clipboard
+    .file = Open file
+    .error = Could not import the setup: {$error}
+
 dark-ai-secondary-standard-chips = Standard chips (secondary)
 dark-ai-standard-chips = Standard chips
 dark-ai-mega-chips = Mega chips
@@ -19,3 +30,43 @@ dark-ai-unset = Unset
 
 copy-navicust-image-to-clipboard = Copy NaviCust image to clipboard
     .copied = Copied!
+
+save-compare = Compare with
+    .none = Nothing
+
+save-diff-no-changes = No differences.
+save-diff-folder = Folder {$n}
+save-diff-regular-chip = Regular chip
+save-diff-tag-chips = Tag chips
+save-diff-modcard-on = On
+save-diff-modcard-off = Off
+save-diff-dark-ai-secondary = Secondary use counts
+
+save-history = History
+    .empty = No snapshots yet.
+    .restore = Restore
+    .unreadable = Unreadable
+    .identical = Same as current
+    .changes = Changes from current: {$n}
+
+save-history-reason-singleplayer = Before single player
+save-history-reason-netplay = Before netplay
+save-history-reason-restore = Before restore
+save-history-reason-convert = Before conversion
+save-history-reason-import-setup = Before setup import
+
+save-convert = Convert region
+    .new = Convert to {$game}
+    .into = Copy the folder, navicust and modcards into:
+    .no-targets = No saves of the other region's game.
+    .done = Converted save written to {$path}.
+    .error = Could not convert save: {$error}
+
+save-convert-unconvertible-progress = Only the folder, navicust and modcards were converted: everything else is kept from the target save.
+save-convert-unconvertible-modcards = {$count} modcard(s) dropped: the target game has no modcards.
+save-convert-unconvertible-chip = Chip not in the target game: {$chip}
+save-convert-unconvertible-navicust-part = NaviCust part not in the target game: {$part}
+save-convert-unconvertible-modcard = Modcard not in the target game: {$modcard}
+save-convert-unconvertible-dropped-modcard = Modcard dropped, as the target save can't take it: {$modcard}
+save-convert-unconvertible-removed-modcard = Modcard removed from the target save: {$modcard}
+save-convert-unconvertible-missing-content-unchecked = The target game's ROM was not found, so the save was not checked for content missing from it.
@@ -20,6 +20,20 @@ settings-video-filter = Video filter
     .hq3x = hq3x
     .hq4x = hq4x
     .mmpx = MMPX
+    .xbrz2x = xBRZ 2x
+    .xbrz3x = xBRZ 3x
+    .xbrz4x = xBRZ 4x
+    .xbrz5x = xBRZ 5x
+    .xbrz6x = xBRZ 6x
+    .scale2x = Scale2x (EPX)
+    .scale3x = Scale3x
+    .scale4x = Scale4x
+    .sharp-bilinear = Sharp bilinear
+    .scanlines = Scanlines
+    .lcd-grid = LCD grid
+    .gba-color = GBA LCD colors
+    .ghosting = Ghosting (interframe blending)
+    .add = Add filter
 settings-max-scale = Max scale
     .unset = Unset
 
@@ -29,10 +43,12 @@ settings-streamer-mode = Streamer privacy mode
     .tooltip = Enabling this mode will add an additional "Cover" tab to the save viewer that hides all information about your current save file.
 settings-debug = Show debug information
 settings-input-delay = Input delay
+settings-adaptive-input-delay = Adapt input delay to connection
 settings-ui-scale = UI scale
 settings-max-queue-length = Max queue length
 settings-matchmaking-endpoint = Matchmaking endpoint
 settings-replaycollector-endpoint = Replay collector endpoint
+settings-replaycollector-set-endpoint = Replay collector set endpoint
 settings-patch-repo = Patches repository
 settings-enable-patch-autoupdate = Enable autoupdate
 settings-data-path = Data path
@@ -58,3 +74,6 @@ settings-use-relay = Use relay
     .always = Always
     .never = Never
 settings-speed-change = Speed change
+settings-slow-motion = Slow motion speed
+settings-turbo-interval = Turbo interval (frames)
+settings-spectator-listen-addr = Spectator listen address
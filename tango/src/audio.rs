@@ -89,6 +89,18 @@ impl Stream for MGBAStream {
 
         let mut audio_guard = self.handle.lock_audio();
 
+        // Without audio sync, the emulator isn't paced to the audio and runs as fast as it can. Resampling audio at that
+        // speed is wasted work, so just drop it.
+        if !audio_guard.sync().audio_wait() {
+            let mut core = audio_guard.core_mut();
+            core.audio_channel(0).clear();
+            core.audio_channel(1).clear();
+            for v in buf.iter_mut() {
+                *v = [0, 0];
+            }
+            return frame_count;
+        }
+
         let mut fps_target = audio_guard.sync().fps_target();
         if fps_target <= 0.0 {
             fps_target = 1.0;
@@ -8,8 +8,43 @@ use crate::replay;
 use crate::replayer;
 use crate::session;
 use crate::shadow;
+use crate::spectator;
 use crate::stats;
 
+pub const MIN_INPUT_DELAY: u32 = 2;
+pub const MAX_INPUT_DELAY: u32 = 10;
+
+// How often each side proposes an input delay when adaptive input delay is enabled.
+const INPUT_DELAY_PROPOSAL_INTERVAL: u32 = 2 * 60;
+
+// How many ticks deep we are willing to roll back before asking for more input delay.
+const ROLLBACK_TOLERANCE: usize = 3;
+
+// How often each side sends a hash of its committed state, to be checked against the other side's shadow.
+const DESYNC_CHECK_INTERVAL: u32 = 60;
+
+// How many ticks of shadow snapshots to keep around waiting for the remote's hashes to arrive.
+const MAX_SHADOW_SNAPSHOTS: usize = 20 * 60;
+
+// How many of the most recent shadow snapshots also keep the full state, to be dumped on a desync. These are large, so
+// only the ones for hashes that arrive on time are kept.
+const MAX_SHADOW_SNAPSHOT_STATES: usize = 2 * 60;
+
+// How many of our most recently hashed committed states to keep, to be dumped on a desync.
+const MAX_LOCAL_HASHED_STATES: usize = 3;
+
+struct ShadowSnapshot {
+    tick: u32,
+    regions: Vec<u8>,
+    state: Option<mgba::state::State>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Desync {
+    pub round_number: u8,
+    pub tick: u32,
+}
+
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub enum BattleResult {
     Loss,
@@ -27,6 +62,7 @@ pub struct RoundState {
     pub number: u8,
     pub round: Option<Round>,
     pub last_result: Option<BattleResult>,
+    pub last_input_delay: Option<u32>,
 }
 
 impl RoundState {
@@ -34,6 +70,9 @@ impl RoundState {
         match self.round.take() {
             Some(round) => {
                 log::info!("round ended at {:x}", round.current_tick);
+                if let Some(input_delay) = round.agreed_input_delay() {
+                    self.last_input_delay = Some(input_delay);
+                }
             }
             None => {
                 return Ok(());
@@ -67,7 +106,25 @@ pub struct Match {
     primary_thread_handle: mgba::thread::Handle,
     round_started_tx: tokio::sync::mpsc::Sender<u8>,
     round_started_rx: tokio::sync::Mutex<tokio::sync::mpsc::Receiver<u8>>,
-    connection_latency_counter: tokio::sync::Mutex<stats::DeltaCounter>,
+    connection_latency_counter: std::sync::Arc<tokio::sync::Mutex<stats::DeltaCounter>>,
+    spectator_broadcaster: Option<spectator::Broadcaster>,
+    desync: std::sync::Arc<parking_lot::Mutex<Option<Desync>>>,
+    set_writer: std::sync::Arc<parking_lot::Mutex<replay::set::Writer>>,
+}
+
+fn format_now_for_filename() -> String {
+    time::OffsetDateTime::from(std::time::SystemTime::now())
+        .format(time::macros::format_description!(
+            "[year padding:zero][month padding:zero repr:numerical][day padding:zero][hour padding:zero][minute padding:zero][second padding:zero]"
+        ))
+        .expect("format time")
+}
+
+fn sanitize_filename(filename: &str) -> String {
+    filename
+        .chars()
+        .filter(|c| "/\\?%*:|\"<>. ".chars().all(|c2| c2 != *c))
+        .collect::<String>()
 }
 
 impl Match {
@@ -89,8 +146,31 @@ impl Match {
         remote_save: &[u8],
         replays_path: std::path::PathBuf,
         match_type: (u8, u8),
+        spectator_broadcaster: Option<spectator::Broadcaster>,
     ) -> anyhow::Result<std::sync::Arc<Self>> {
         let (round_started_tx, round_started_rx) = tokio::sync::mpsc::channel(1);
+        let set_writer = replay::set::Writer::new(
+            replays_path.join(format!(
+                "{}.{}",
+                sanitize_filename(&format!(
+                    "{}-{}-{}-vs-{}",
+                    format_now_for_filename(),
+                    link_code,
+                    netplay_compatiblity,
+                    remote_settings.nickname,
+                )),
+                replay::set::EXTENSION
+            )),
+            replay::set::Manifest::new(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64,
+                link_code.clone(),
+                local_settings.nickname.clone(),
+                remote_settings.nickname.clone(),
+            ),
+        );
         let did_polite_win_last_round = rng.gen::<bool>();
         let last_result = if did_polite_win_last_round == is_offerer {
             BattleResult::Win
@@ -123,12 +203,16 @@ impl Match {
                 number: 0,
                 round: None,
                 last_result: Some(last_result),
+                last_input_delay: None,
             }),
             is_offerer,
             primary_thread_handle,
             round_started_tx,
             round_started_rx: tokio::sync::Mutex::new(round_started_rx),
-            connection_latency_counter: tokio::sync::Mutex::new(stats::DeltaCounter::new(5)),
+            connection_latency_counter: std::sync::Arc::new(tokio::sync::Mutex::new(stats::DeltaCounter::new(5))),
+            spectator_broadcaster,
+            desync: std::sync::Arc::new(parking_lot::Mutex::new(None)),
+            set_writer: std::sync::Arc::new(parking_lot::Mutex::new(set_writer)),
         });
         Ok(match_)
     }
@@ -157,14 +241,53 @@ impl Match {
         self.connection_latency_counter.lock().await.median()
     }
 
+    pub fn desync(&self) -> Option<Desync> {
+        *self.desync.lock()
+    }
+
+    /// Submits the set manifest to the replay collector once the match is over, so it can group the rounds it was sent.
+    pub async fn end_set(&self) {
+        let endpoint = self.config.read().replaycollector_set_endpoint.clone();
+        if endpoint.is_empty() {
+            return;
+        }
+
+        let manifest = self.set_writer.lock().manifest().clone();
+        if manifest.rounds.is_empty() {
+            return;
+        }
+
+        if let Err(e) = async {
+            reqwest::Client::new()
+                .post(&endpoint)
+                .header("Content-Type", "application/json")
+                .body(serde_json::to_vec(&manifest)?)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok::<(), anyhow::Error>(())
+        }
+        .await
+        {
+            log::error!("failed to submit set manifest: {:?}", e);
+            if let Err(e) = self.set_writer.lock().set_upload_error(e.to_string()) {
+                log::error!("failed to record set upload error: {:?}", e);
+            }
+        }
+    }
+
     pub async fn run(&self, mut receiver: net::Receiver) -> anyhow::Result<()> {
         let mut last_round_number = 0;
         let mut ping_timer = tokio::time::interval(net::PING_INTERVAL);
+        let mut input_resend_timer = tokio::time::interval(net::INPUT_RESEND_INTERVAL);
         'l: loop {
             tokio::select! {
                 _ = ping_timer.tick() => {
                     self.sender.lock().await.send_ping(std::time::SystemTime::now()).await?;
                 }
+                _ = input_resend_timer.tick() => {
+                    self.sender.lock().await.send_unacked_inputs().await?;
+                }
                 p = receiver.receive() => {
                     match p? {
                         net::protocol::Packet::Ping(ping) => {
@@ -238,6 +361,41 @@ impl Match {
                                 joyflags: input.joyflags as u16,
                             });
                         }
+                        net::protocol::Packet::InputDelay(input_delay) => {
+                            let mut round_state = self.round_state.lock().await;
+                            if input_delay.round_number != round_state.number {
+                                // This is harmless: the remote will propose again later.
+                                log::info!("round number mismatch, dropping input delay proposal");
+                                continue 'l;
+                            }
+
+                            let round = match &mut round_state.round {
+                                None => {
+                                    log::info!("no round in progress, dropping input delay proposal");
+                                    continue 'l;
+                                }
+                                Some(b) => b,
+                            };
+
+                            round.set_remote_input_delay_proposal(input_delay.input_delay);
+                        }
+                        net::protocol::Packet::StateHash(state_hash) => {
+                            let mut round_state = self.round_state.lock().await;
+                            if state_hash.round_number != round_state.number {
+                                log::info!("round number mismatch, dropping state hash");
+                                continue 'l;
+                            }
+
+                            let round = match &mut round_state.round {
+                                None => {
+                                    log::info!("no round in progress, dropping state hash");
+                                    continue 'l;
+                                }
+                                Some(b) => b,
+                            };
+
+                            round.add_remote_state_hash(state_hash.tick, state_hash.hash);
+                        }
                         p => anyhow::bail!("unknown packet: {:?}", p),
                     }
                 }
@@ -273,34 +431,42 @@ impl Match {
         log::info!("starting round: local_player_index = {}", local_player_index);
         let replay_filename = self.replays_path.join(format!(
             "{}.tangoreplay",
-            format!(
+            sanitize_filename(&format!(
                 "{}-{}-{}-vs-{}-round{}-p{}",
-                time::OffsetDateTime::from(std::time::SystemTime::now())
-                    .format(time::macros::format_description!(
-                        "[year padding:zero][month padding:zero repr:numerical][day padding:zero][hour padding:zero][minute padding:zero][second padding:zero]"
-                    ))
-                    .expect("format time"),
+                format_now_for_filename(),
                 self.link_code,
                 self.netplay_compatiblity,
                 self.remote_settings.nickname,
                 round_state.number,
                 local_player_index + 1
-            )
-            .chars()
-            .filter(|c| "/\\?%*:|\"<>. ".chars().all(|c2| c2 != *c))
-            .collect::<String>()
+            ))
         ));
         log::info!("open replay: {}", replay_filename.display());
 
         let replay_file = std::fs::File::create(&replay_filename)?;
+        if let Err(e) = self
+            .set_writer
+            .lock()
+            .add_round(round_state.number as u32, &replay_filename)
+        {
+            log::error!("failed to add round to set: {:?}", e);
+        }
 
         log::info!("preparing round state");
 
         let (first_state_committed_local_packet, first_state_committed_rx) = tokio::sync::oneshot::channel();
 
-        let (input_delay, max_queue_length) = {
+        let (input_delay, adaptive_input_delay, max_queue_length) = {
             let config = self.config.read();
-            (config.input_delay, config.max_queue_length)
+            (
+                if config.adaptive_input_delay {
+                    round_state.last_input_delay.unwrap_or(config.input_delay)
+                } else {
+                    config.input_delay
+                },
+                config.adaptive_input_delay,
+                config.max_queue_length,
+            )
         };
 
         let mut iq = lockstep::PairQueue::new(max_queue_length as usize, input_delay);
@@ -323,6 +489,49 @@ impl Match {
         let local_game_settings = self.local_settings.game_info.as_ref().unwrap();
         let remote_game_settings = self.remote_settings.game_info.as_ref().unwrap();
 
+        let metadata = replay::Metadata {
+            ts: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            link_code: self.link_code.clone(),
+            local_side: Some(replay::metadata::Side {
+                nickname: self.local_settings.nickname.clone(),
+                game_info: Some(replay::metadata::GameInfo {
+                    rom_family: local_game_settings.family_and_variant.0.to_string(),
+                    rom_variant: local_game_settings.family_and_variant.1 as u32,
+                    patch: if let Some(patch) = local_game_settings.patch.as_ref() {
+                        Some(replay::metadata::game_info::Patch {
+                            name: patch.name.clone(),
+                            version: patch.version.to_string(),
+                        })
+                    } else {
+                        None
+                    },
+                }),
+                reveal_setup: self.local_settings.reveal_setup,
+            }),
+            remote_side: Some(replay::metadata::Side {
+                nickname: self.remote_settings.nickname.clone(),
+                game_info: Some(replay::metadata::GameInfo {
+                    rom_family: remote_game_settings.family_and_variant.0.to_string(),
+                    rom_variant: remote_game_settings.family_and_variant.1 as u32,
+                    patch: if let Some(patch) = remote_game_settings.patch.as_ref() {
+                        Some(replay::metadata::game_info::Patch {
+                            name: patch.name.clone(),
+                            version: patch.version.to_string(),
+                        })
+                    } else {
+                        None
+                    },
+                }),
+                reveal_setup: self.remote_settings.reveal_setup,
+            }),
+            round: round_state.number as u32,
+            match_type: self.match_type.0 as u32,
+            match_subtype: self.match_type.1 as u32,
+        };
+
         round_state.round = Some(Round {
             config: self.config.clone(),
             hooks,
@@ -341,53 +550,29 @@ impl Match {
             first_state_committed_rx: Some(first_state_committed_rx),
             committed_state: None,
             replay_filename,
+            set_writer: self.set_writer.clone(),
             replay_writer: Some(replay::Writer::new(
                 Box::new(replay_file),
-                replay::Metadata {
-                    ts: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis() as u64,
-                    link_code: self.link_code.clone(),
-                    local_side: Some(replay::metadata::Side {
-                        nickname: self.local_settings.nickname.clone(),
-                        game_info: Some(replay::metadata::GameInfo {
-                            rom_family: local_game_settings.family_and_variant.0.to_string(),
-                            rom_variant: local_game_settings.family_and_variant.1 as u32,
-                            patch: if let Some(patch) = local_game_settings.patch.as_ref() {
-                                Some(replay::metadata::game_info::Patch {
-                                    name: patch.name.clone(),
-                                    version: patch.version.to_string(),
-                                })
-                            } else {
-                                None
-                            },
-                        }),
-                        reveal_setup: self.local_settings.reveal_setup,
-                    }),
-                    remote_side: Some(replay::metadata::Side {
-                        nickname: self.remote_settings.nickname.clone(),
-                        game_info: Some(replay::metadata::GameInfo {
-                            rom_family: remote_game_settings.family_and_variant.0.to_string(),
-                            rom_variant: remote_game_settings.family_and_variant.1 as u32,
-                            patch: if let Some(patch) = remote_game_settings.patch.as_ref() {
-                                Some(replay::metadata::game_info::Patch {
-                                    name: patch.name.clone(),
-                                    version: patch.version.to_string(),
-                                })
-                            } else {
-                                None
-                            },
-                        }),
-                        reveal_setup: self.remote_settings.reveal_setup,
-                    }),
-                    round: round_state.number as u32,
-                    match_type: self.match_type.0 as u32,
-                    match_subtype: self.match_type.1 as u32,
-                },
+                metadata.clone(),
                 local_player_index,
                 hooks.packet_size() as u8,
             )?),
+            metadata,
+            spectator_broadcaster: self.spectator_broadcaster.clone(),
+            input_delay_adjuster: if adaptive_input_delay {
+                Some(InputDelayAdjuster {
+                    connection_latency_counter: self.connection_latency_counter.clone(),
+                    local_proposal: None,
+                    remote_proposal: None,
+                    max_rollback_depth: 0,
+                })
+            } else {
+                None
+            },
+            shadow_snapshots: std::sync::Arc::new(parking_lot::Mutex::new(std::collections::VecDeque::new())),
+            local_hashed_states: std::collections::VecDeque::new(),
+            pending_remote_state_hashes: vec![],
+            desync: self.desync.clone(),
             replayer: replayer::Fastforwarder::new(&self.rom, hooks, self.match_type, local_player_index)?,
             primary_thread_handle: self.primary_thread_handle.clone(),
             sender: self.sender.clone(),
@@ -399,6 +584,43 @@ impl Match {
     }
 }
 
+// Both sides propose an input delay from their own view of the connection. The delay that is actually used is the larger
+// of the two proposals, so it only changes once we have heard from the remote (i.e. both sides have adaptive input delay
+// enabled) and both sides end up agreeing on it.
+struct InputDelayAdjuster {
+    connection_latency_counter: std::sync::Arc<tokio::sync::Mutex<stats::DeltaCounter>>,
+    local_proposal: Option<u32>,
+    remote_proposal: Option<u32>,
+    max_rollback_depth: usize,
+}
+
+impl InputDelayAdjuster {
+    fn agreed_input_delay(&self) -> Option<u32> {
+        match (self.local_proposal, self.remote_proposal) {
+            (Some(local), Some(remote)) => Some(std::cmp::max(local, remote)),
+            _ => None,
+        }
+    }
+
+    fn propose(&mut self, current_input_delay: u32, latency: std::time::Duration) -> u32 {
+        let one_way_ticks = (latency.as_secs_f32() * session::EXPECTED_FPS / 2.0).ceil() as u32;
+        let proposal = if self.max_rollback_depth > ROLLBACK_TOLERANCE {
+            current_input_delay + 1
+        } else if self.max_rollback_depth + 1 < ROLLBACK_TOLERANCE
+            && one_way_ticks.saturating_sub(ROLLBACK_TOLERANCE as u32) < current_input_delay
+        {
+            // Lowering the delay by a tick adds roughly a tick of rollback, so only do it if we have room to spare.
+            current_input_delay.saturating_sub(1)
+        } else {
+            current_input_delay
+        }
+        .clamp(MIN_INPUT_DELAY, MAX_INPUT_DELAY);
+        self.local_proposal = Some(proposal);
+        self.max_rollback_depth = 0;
+        proposal
+    }
+}
+
 pub struct Round {
     config: std::sync::Arc<parking_lot::RwLock<config::Config>>,
     hooks: &'static (dyn game::Hooks + Send + Sync),
@@ -412,7 +634,15 @@ pub struct Round {
     first_state_committed_rx: Option<tokio::sync::oneshot::Receiver<()>>,
     committed_state: Option<CommittedState>,
     replay_filename: std::path::PathBuf,
+    set_writer: std::sync::Arc<parking_lot::Mutex<replay::set::Writer>>,
     replay_writer: Option<replay::Writer>,
+    metadata: replay::Metadata,
+    spectator_broadcaster: Option<spectator::Broadcaster>,
+    input_delay_adjuster: Option<InputDelayAdjuster>,
+    shadow_snapshots: std::sync::Arc<parking_lot::Mutex<std::collections::VecDeque<ShadowSnapshot>>>,
+    local_hashed_states: std::collections::VecDeque<(u32, mgba::state::State)>,
+    pending_remote_state_hashes: Vec<(u32, u32)>,
+    desync: std::sync::Arc<parking_lot::Mutex<Option<Desync>>>,
     replayer: replayer::Fastforwarder,
     primary_thread_handle: mgba::thread::Handle,
     sender: std::sync::Arc<tokio::sync::Mutex<net::Sender>>,
@@ -448,6 +678,9 @@ impl Round {
             .unwrap()
             .write_state(&remote_state)
             .expect("write remote state");
+        if let Some(spectator_broadcaster) = self.spectator_broadcaster.as_ref() {
+            spectator_broadcaster.start_round(&self.metadata, self.local_player_index, &state, &remote_state);
+        }
         self.committed_state = Some(CommittedState {
             state,
             tick: 0,
@@ -463,38 +696,69 @@ impl Round {
         mut core: mgba::core::CoreMutRef<'_>,
         joyflags: u16,
     ) -> anyhow::Result<Option<BattleResult>> {
-        let local_tick = self.current_tick + self.local_delay();
+        let local_delay = self.local_delay();
         let remote_tick = self.last_committed_remote_input.local_tick;
 
-        // We do it in this order such that:
-        // 1. We make sure that the input buffer does not overflow if we were to add an input.
-        // 2. We try to send it to the peer: if it fails, we don't end up desyncing the opponent as we haven't added the input ourselves yet.
-        // 3. We add the input to our buffer: no overflow is guaranteed because we already checked ahead of time.
-        //
-        // This is all done while the self is locked, so there are no TOCTTOU issues.
-        if !self.iq.can_add_local_input() {
-            anyhow::bail!("local input buffer overflow!");
+        // The delay is moved at most one tick at a time towards the agreed delay. When it increases, there is a one tick
+        // gap in our input stream that we fill by sending this tick's input twice. When it decreases, the input for this
+        // tick has already been sent, so there is nothing to send.
+        let new_local_delay = match self.agreed_input_delay() {
+            Some(input_delay) if input_delay > local_delay => local_delay + 1,
+            Some(input_delay) if input_delay < local_delay => local_delay - 1,
+            _ => local_delay,
+        };
+        if new_local_delay != local_delay {
+            log::info!(
+                "adjusting input delay from {} to {} at {:x}",
+                local_delay,
+                new_local_delay,
+                self.current_tick
+            );
         }
 
-        self.sender
-            .lock()
-            .await
-            .send_input(
-                self.number,
+        for local_tick in (self.current_tick + local_delay)..(self.current_tick + new_local_delay + 1) {
+            // We do it in this order such that:
+            // 1. We make sure that the input buffer does not overflow if we were to add an input.
+            // 2. We try to send it to the peer: if it fails, we don't end up desyncing the opponent as we haven't added the input ourselves yet.
+            // 3. We add the input to our buffer: no overflow is guaranteed because we already checked ahead of time.
+            //
+            // This is all done while the self is locked, so there are no TOCTTOU issues.
+            if !self.iq.can_add_local_input() {
+                anyhow::bail!("local input buffer overflow!");
+            }
+
+            self.sender
+                .lock()
+                .await
+                .send_input(
+                    self.number,
+                    local_tick,
+                    (remote_tick as i32 - local_tick as i32) as i8,
+                    joyflags,
+                )
+                .await?;
+
+            self.add_local_input(lockstep::PartialInput {
                 local_tick,
-                (remote_tick as i32 - local_tick as i32) as i8,
+                remote_tick,
                 joyflags,
-            )
-            .await?;
-
-        self.add_local_input(lockstep::PartialInput {
-            local_tick,
-            remote_tick,
-            joyflags,
-        });
+            });
+        }
+        self.iq.set_local_delay(new_local_delay);
 
         let (committable, predict_required) = self.iq.consume_and_peek_local();
 
+        let current_input_delay = self.agreed_input_delay().unwrap_or(new_local_delay);
+        if let Some(input_delay_adjuster) = self.input_delay_adjuster.as_mut() {
+            input_delay_adjuster.max_rollback_depth =
+                std::cmp::max(input_delay_adjuster.max_rollback_depth, predict_required.len());
+            if self.current_tick % INPUT_DELAY_PROPOSAL_INTERVAL == 0 {
+                let latency = input_delay_adjuster.connection_latency_counter.lock().await.median();
+                let proposal = input_delay_adjuster.propose(current_input_delay, latency);
+                self.sender.lock().await.send_input_delay(self.number, proposal).await?;
+            }
+        }
+
         let last_committed_state = self.committed_state.take().expect("committed state");
 
         let commit_tick = last_committed_state.tick + committable.len() as u32;
@@ -535,18 +799,36 @@ impl Round {
             &last_committed_state.packet,
             Box::new({
                 let shadow = self.shadow.clone();
+                let shadow_snapshots = self.shadow_snapshots.clone();
                 let hooks = self.hooks;
                 let mut last_commit = self.last_committed_remote_input.packet.clone();
                 move |ip| {
                     let local_tick = ip.local.local_tick;
                     Ok(if ip.local.local_tick < commit_tick {
-                        let r = shadow.lock().apply_input(ip)?;
+                        let mut shadow = shadow.lock();
+                        let r = shadow.apply_input(ip)?;
                         assert!(
                             r.tick == local_tick,
                             "shadow input did not match current tick: {} != {}",
                             r.tick,
                             local_tick
                         );
+
+                        // The shadow is now at the start of the next tick, which is where the remote takes its committed state.
+                        let mut shadow_snapshots = shadow_snapshots.lock();
+                        while shadow_snapshots.len() >= MAX_SHADOW_SNAPSHOTS {
+                            shadow_snapshots.pop_front();
+                        }
+                        shadow_snapshots.push_back(ShadowSnapshot {
+                            tick: local_tick + 1,
+                            regions: shadow.desync_check_snapshot(),
+                            state: Some(shadow.save_state()?),
+                        });
+                        if shadow_snapshots.len() > MAX_SHADOW_SNAPSHOT_STATES {
+                            let i = shadow_snapshots.len() - MAX_SHADOW_SNAPSHOT_STATES - 1;
+                            shadow_snapshots[i].state = None;
+                        }
+
                         last_commit = r.packet.clone();
                         r.packet
                     } else {
@@ -572,10 +854,40 @@ impl Round {
                         .write_input(self.local_player_index, ip)
                         .expect("write input");
                 }
+                if let Some(spectator_broadcaster) = self.spectator_broadcaster.as_ref() {
+                    spectator_broadcaster.send_input(ip);
+                }
             }
             self.last_committed_remote_input = ip.remote.clone();
         }
 
+        if commit_tick / replay::KEYFRAME_INTERVAL != last_committed_state.tick / replay::KEYFRAME_INTERVAL
+            && ff_result.round_result.map(|rr| commit_tick < rr.tick).unwrap_or(true)
+        {
+            if let Some(replay_writer) = self.replay_writer.as_mut() {
+                replay_writer
+                    .write_keyframe(commit_tick, &ff_result.committed_state.state)
+                    .expect("write keyframe");
+            }
+        }
+
+        if commit_tick / DESYNC_CHECK_INTERVAL != last_committed_state.tick / DESYNC_CHECK_INTERVAL
+            && ff_result.round_result.map(|rr| commit_tick < rr.tick).unwrap_or(true)
+        {
+            let hash = game::hash_desync_check_regions_in_state(self.hooks, &ff_result.committed_state.state);
+            while self.local_hashed_states.len() >= MAX_LOCAL_HASHED_STATES {
+                self.local_hashed_states.pop_front();
+            }
+            self.local_hashed_states
+                .push_back((commit_tick, ff_result.committed_state.state.clone()));
+            self.sender
+                .lock()
+                .await
+                .send_state_hash(self.number, commit_tick, hash)
+                .await?;
+        }
+        self.check_remote_state_hashes();
+
         core.load_state(&ff_result.dirty_state.state).expect("load dirty state");
         self.committed_state = Some(ff_result.committed_state);
 
@@ -598,6 +910,17 @@ impl Round {
 
         if let Some(replay_writer) = self.replay_writer.take() {
             replay_writer.finish().expect("finish");
+            let result = replay::index::RoundResult::from(round_result.result);
+            let replay_index_path = self.config.read().replay_index_path();
+            if let Err(e) = replay::index::record(&replay_index_path, &self.replay_filename, &self.metadata, result) {
+                log::error!("failed to add replay to index: {:?}", e);
+            }
+            if let Err(e) = self.set_writer.lock().set_round_result(self.number as u32, result) {
+                log::error!("failed to record round result in set: {:?}", e);
+            }
+            if let Some(spectator_broadcaster) = self.spectator_broadcaster.as_ref() {
+                spectator_broadcaster.end_round();
+            }
             log::info!(
                 "replay finished at {:x} (real tick {:x})",
                 round_result.tick,
@@ -609,6 +932,8 @@ impl Round {
             if !replaycollector_endpoint.is_empty() {
                 tokio::spawn({
                     let replay_path = self.replay_filename.clone();
+                    let set_writer = self.set_writer.clone();
+                    let number = self.number as u32;
                     async move {
                         let replay_path2 = replay_path.clone();
                         if let Err(e) = (move || async move {
@@ -616,7 +941,7 @@ impl Round {
                             let replay_file = tokio::fs::File::open(&replay_path2).await?;
 
                             client
-                                .post(replaycollector_endpoint)
+                                .post(&replaycollector_endpoint)
                                 .header("Content-Type", "application/x-tango-replay")
                                 .body(replay_file)
                                 .send()
@@ -628,6 +953,9 @@ impl Round {
                         .await
                         {
                             log::error!("failed to submit replay {}: {:?}", replay_path.display(), e);
+                            if let Err(e) = set_writer.lock().set_round_upload_error(number, e.to_string()) {
+                                log::error!("failed to record replay upload error in set: {:?}", e);
+                            }
                         }
                     }
                 });
@@ -641,6 +969,113 @@ impl Round {
         }))
     }
 
+    pub fn add_remote_state_hash(&mut self, tick: u32, hash: u32) {
+        self.pending_remote_state_hashes.push((tick, hash));
+    }
+
+    fn check_remote_state_hashes(&mut self) {
+        let mut mismatch = None;
+        {
+            let shadow_snapshots = self.shadow_snapshots.lock();
+            let latest_shadow_tick = shadow_snapshots.back().map(|snapshot| snapshot.tick);
+            self.pending_remote_state_hashes.retain(|(tick, remote_hash)| {
+                let snapshot = if let Some(snapshot) = shadow_snapshots.iter().find(|snapshot| snapshot.tick == *tick) {
+                    snapshot
+                } else {
+                    // Keep waiting if our shadow hasn't gotten there yet, otherwise it's too old to check.
+                    return latest_shadow_tick.map(|t| t < *tick).unwrap_or(true);
+                };
+                let shadow_hash = crc32fast::hash(&snapshot.regions);
+                if shadow_hash != *remote_hash && mismatch.is_none() {
+                    mismatch = Some((
+                        *tick,
+                        *remote_hash,
+                        shadow_hash,
+                        snapshot.regions.clone(),
+                        snapshot.state.clone(),
+                    ));
+                }
+                false
+            });
+        }
+
+        let (tick, remote_hash, shadow_hash, shadow_regions, shadow_state) = if let Some(mismatch) = mismatch {
+            mismatch
+        } else {
+            return;
+        };
+
+        let mut desync = self.desync.lock();
+        if desync.is_some() {
+            return;
+        }
+        *desync = Some(Desync {
+            round_number: self.number,
+            tick,
+        });
+
+        log::error!(
+            "desync detected in round {} at tick {}: remote hash = {:08x}, shadow hash = {:08x}",
+            self.number,
+            tick,
+            remote_hash,
+            shadow_hash
+        );
+
+        // Dump the shadow's state at the mismatching tick, which can be compared against the remote's local state dump
+        // for the same tick, and our own recently hashed states, which the remote's shadow dump can be compared against.
+        // The checked regions are also written out one file per region, so they can be compared directly.
+        let crashstates_path = self.config.read().crashstates_path();
+        let prefix = format!(
+            "desync-{}-round{}",
+            time::OffsetDateTime::from(std::time::SystemTime::now())
+                .format(time::macros::format_description!(
+                    "[year padding:zero][month padding:zero repr:numerical][day padding:zero][hour padding:zero][minute padding:zero][second padding:zero]"
+                ))
+                .expect("format time"),
+            self.number,
+        );
+
+        let write = |name: String, buf: &[u8]| {
+            let path = crashstates_path.join(format!("{}-{}", prefix, name));
+            log::error!("writing desync dump to {}", path.display());
+            if let Err(e) = std::fs::write(&path, buf) {
+                log::error!("failed to write desync dump: {:?}", e);
+            }
+        };
+
+        let write_regions = |name: &str, regions: &[u8]| {
+            let mut offset = 0;
+            for (address, len) in self.hooks.desync_check_regions() {
+                write(
+                    format!("{}-{:08x}.bin", name, address),
+                    &regions[offset..offset + len as usize],
+                );
+                offset += len as usize;
+            }
+        };
+
+        let shadow_name = format!("tick{}-shadow", tick);
+        if let Some(shadow_state) = shadow_state {
+            write(format!("{}.state", shadow_name), shadow_state.as_slice());
+        } else {
+            log::error!(
+                "shadow state at tick {} is too old to dump, dumping only its checked regions",
+                tick
+            );
+        }
+        write_regions(&shadow_name, &shadow_regions);
+
+        for (local_tick, local_state) in self.local_hashed_states.iter() {
+            let local_name = format!("tick{}-local", local_tick);
+            write(format!("{}.state", local_name), local_state.as_slice());
+            write_regions(
+                &local_name,
+                &game::read_desync_check_regions_in_state(self.hooks, local_state),
+            );
+        }
+    }
+
     pub fn on_draw_result(&self) -> BattleResult {
         match self.local_player_index {
             0 => BattleResult::Win,
@@ -657,6 +1092,23 @@ impl Round {
         self.iq.local_delay()
     }
 
+    pub fn agreed_input_delay(&self) -> Option<u32> {
+        self.input_delay_adjuster
+            .as_ref()
+            .and_then(|input_delay_adjuster| input_delay_adjuster.agreed_input_delay())
+    }
+
+    pub fn set_remote_input_delay_proposal(&mut self, input_delay: u32) {
+        let input_delay_adjuster = if let Some(input_delay_adjuster) = self.input_delay_adjuster.as_mut() {
+            input_delay_adjuster
+        } else {
+            // We don't have adaptive input delay enabled, so we never agree to anything.
+            return;
+        };
+        log::info!("remote proposed input delay: {}", input_delay);
+        input_delay_adjuster.remote_proposal = Some(input_delay.clamp(MIN_INPUT_DELAY, MAX_INPUT_DELAY));
+    }
+
     pub fn local_queue_length(&self) -> usize {
         self.iq.local_queue_length()
     }
@@ -0,0 +1,338 @@
+// Work RAM: the only memory worth searching or freezing, as everything else is either read-only or hardware registers.
+pub const SEARCH_REGIONS: &[std::ops::Range<u32>] = &[0x02000000..0x02040000, 0x03000000..0x03008000];
+
+const GAMESHARK_SEEDS: [u32; 4] = [0x09f4fbbd, 0x9681884a, 0x352027e9, 0xf3dee5a7];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ValueType {
+    U8,
+    U16,
+    U32,
+    I8,
+    I16,
+    I32,
+}
+
+impl ValueType {
+    pub const ALL: [ValueType; 6] = [
+        ValueType::U8,
+        ValueType::U16,
+        ValueType::U32,
+        ValueType::I8,
+        ValueType::I16,
+        ValueType::I32,
+    ];
+
+    pub fn size(self) -> u32 {
+        match self {
+            ValueType::U8 | ValueType::I8 => 1,
+            ValueType::U16 | ValueType::I16 => 2,
+            ValueType::U32 | ValueType::I32 => 4,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, ValueType::I8 | ValueType::I16 | ValueType::I32)
+    }
+
+    pub fn read(self, core: &mut mgba::core::CoreMutRef, address: u32) -> u32 {
+        match self.size() {
+            1 => core.raw_read_8(address, -1) as u32,
+            2 => core.raw_read_16(address, -1) as u32,
+            _ => core.raw_read_32(address, -1),
+        }
+    }
+
+    pub fn write(self, core: &mut mgba::core::CoreMutRef, address: u32, value: u32) {
+        match self.size() {
+            1 => core.raw_write_8(address, -1, value as u8),
+            2 => core.raw_write_16(address, -1, value as u16),
+            _ => core.raw_write_32(address, -1, value),
+        }
+    }
+
+    fn decode(self, buf: &[u8]) -> u32 {
+        buf[..self.size() as usize]
+            .iter()
+            .rev()
+            .fold(0, |acc, b| (acc << 8) | *b as u32)
+    }
+
+    /// Interprets raw bits as a number, sign extending them for signed types.
+    pub fn to_i64(self, value: u32) -> i64 {
+        match self {
+            ValueType::U8 => value as u8 as i64,
+            ValueType::U16 => value as u16 as i64,
+            ValueType::U32 => value as i64,
+            ValueType::I8 => value as i8 as i64,
+            ValueType::I16 => value as i16 as i64,
+            ValueType::I32 => value as i32 as i64,
+        }
+    }
+
+    pub fn format(self, value: u32) -> String {
+        self.to_i64(value).to_string()
+    }
+
+    /// Parses either a decimal number or a 0x-prefixed hex number into raw bits.
+    pub fn parse(self, s: &str) -> Option<u32> {
+        let s = s.trim();
+        let value = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            i64::from_str_radix(hex, 16).ok()?
+        } else {
+            s.parse::<i64>().ok()?
+        };
+        let bits = self.size() * 8;
+        let min = if self.is_signed() { -(1i64 << (bits - 1)) } else { 0 };
+        // Hex values are raw bits, so they may fill the whole width even for signed types.
+        if value < min || value >= 1i64 << bits {
+            return None;
+        }
+        Some(value as u32)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    LessThan,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operand {
+    Previous,
+    Value(u32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Candidate {
+    pub address: u32,
+    pub previous: u32,
+}
+
+pub struct Search {
+    value_type: ValueType,
+    candidates: Vec<Candidate>,
+}
+
+fn read_region(core: &mut mgba::core::CoreMutRef, region: &std::ops::Range<u32>) -> Vec<u8> {
+    let mut buf = vec![0u8; region.len()];
+    core.raw_read_range(region.start, -1, &mut buf[..]);
+    buf
+}
+
+impl Search {
+    /// Starts a search with every aligned address in work RAM as a candidate.
+    pub fn new(core: &mut mgba::core::CoreMutRef, value_type: ValueType) -> Self {
+        let size = value_type.size();
+        let mut candidates = vec![];
+        for region in SEARCH_REGIONS {
+            let buf = read_region(core, region);
+            candidates.extend(buf.chunks_exact(size as usize).enumerate().map(|(i, chunk)| Candidate {
+                address: region.start + i as u32 * size,
+                previous: value_type.decode(chunk),
+            }));
+        }
+        Self { value_type, candidates }
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// Keeps only the candidates whose current value compares as given, then remembers the current values for the
+    /// next comparison against previous values.
+    pub fn filter(&mut self, core: &mut mgba::core::CoreMutRef, comparison: Comparison, operand: Operand) {
+        let value_type = self.value_type;
+        let snapshots = SEARCH_REGIONS
+            .iter()
+            .map(|region| (region.start, read_region(core, region)))
+            .collect::<Vec<_>>();
+
+        self.candidates.retain_mut(|candidate| {
+            let (start, buf) = snapshots
+                .iter()
+                .find(|(start, buf)| (*start..*start + buf.len() as u32).contains(&candidate.address))
+                .unwrap();
+            let current = value_type.decode(&buf[(candidate.address - start) as usize..]);
+
+            let lhs = value_type.to_i64(current);
+            let rhs = value_type.to_i64(match operand {
+                Operand::Previous => candidate.previous,
+                Operand::Value(value) => value,
+            });
+            let keep = match comparison {
+                Comparison::Equal => lhs == rhs,
+                Comparison::NotEqual => lhs != rhs,
+                Comparison::GreaterThan => lhs > rhs,
+                Comparison::LessThan => lhs < rhs,
+            };
+            candidate.previous = current;
+            keep
+        });
+    }
+}
+
+pub struct Watch {
+    pub name: String,
+    pub address: u32,
+    pub value_type: ValueType,
+    pub frozen: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Write {
+    pub address: u32,
+    pub value_type: ValueType,
+    pub value: u32,
+}
+
+pub struct Code {
+    pub name: String,
+    pub enabled: bool,
+    pub writes: Vec<Write>,
+}
+
+#[derive(Default)]
+pub struct Cheats {
+    pub watches: Vec<Watch>,
+    pub codes: Vec<Code>,
+}
+
+impl Cheats {
+    /// Writes frozen watches and enabled codes back into memory. This is called once per frame.
+    pub fn apply(&self, core: &mut mgba::core::CoreMutRef) {
+        for watch in self.watches.iter() {
+            if let Some(value) = watch.frozen {
+                watch.value_type.write(core, watch.address, value);
+            }
+        }
+        for code in self.codes.iter().filter(|code| code.enabled) {
+            for write in code.writes.iter() {
+                write.value_type.write(core, write.address, write.value);
+            }
+        }
+    }
+}
+
+fn decrypt_gameshark(mut op1: u32, mut op2: u32) -> (u32, u32) {
+    let mut sum = 0xc6ef3720u32;
+    for _ in 0..32 {
+        op2 = op2.wrapping_sub(
+            (op1 << 4).wrapping_add(GAMESHARK_SEEDS[2])
+                ^ op1.wrapping_add(sum)
+                ^ (op1 >> 5).wrapping_add(GAMESHARK_SEEDS[3]),
+        );
+        op1 = op1.wrapping_sub(
+            (op2 << 4).wrapping_add(GAMESHARK_SEEDS[0])
+                ^ op2.wrapping_add(sum)
+                ^ (op2 >> 5).wrapping_add(GAMESHARK_SEEDS[1]),
+        );
+        sum = sum.wrapping_sub(0x9e3779b9);
+    }
+    (op1, op2)
+}
+
+fn is_ram_write(op1: u32) -> bool {
+    op1 >> 28 <= 2 && SEARCH_REGIONS.iter().any(|region| region.contains(&(op1 & 0x0fffffff)))
+}
+
+fn parse_gameshark(op1: u32, op2: u32) -> Result<Option<Write>, anyhow::Error> {
+    // Codes are shared both encrypted and raw: raw ones are recognizable as they write straight into RAM.
+    let (op1, op2) = if is_ram_write(op1) {
+        (op1, op2)
+    } else {
+        decrypt_gameshark(op1, op2)
+    };
+
+    if op1 == 0xdeadface {
+        anyhow::bail!("codes that change the encryption seeds are not supported");
+    }
+
+    let address = op1 & 0x0fffffff;
+    Ok(Some(match op1 >> 28 {
+        0x0 => Write {
+            address,
+            value_type: ValueType::U8,
+            value: op2 & 0xff,
+        },
+        0x1 => Write {
+            address,
+            value_type: ValueType::U16,
+            value: op2 & 0xffff,
+        },
+        0x2 => Write {
+            address,
+            value_type: ValueType::U32,
+            value: op2,
+        },
+        // Hooks (master codes) only matter to the real device.
+        0xf => {
+            return Ok(None);
+        }
+        t => {
+            anyhow::bail!("code type {:x} is not supported", t);
+        }
+    }))
+}
+
+fn parse_codebreaker(op1: u32, op2: u16) -> Result<Option<Write>, anyhow::Error> {
+    let address = op1 & 0x0fffffff;
+    Ok(Some(match op1 >> 28 {
+        // Game ID and hook codes only matter to the real device.
+        0x0 | 0x1 => {
+            return Ok(None);
+        }
+        0x3 => Write {
+            address,
+            value_type: ValueType::U8,
+            value: op2 as u32 & 0xff,
+        },
+        0x8 => Write {
+            address,
+            value_type: ValueType::U16,
+            value: op2 as u32,
+        },
+        0x9 => {
+            anyhow::bail!("encrypted codes are not supported");
+        }
+        t => {
+            anyhow::bail!("code type {:x} is not supported", t);
+        }
+    }))
+}
+
+/// Parses GameShark (XXXXXXXX YYYYYYYY, raw or encrypted) and CodeBreaker (XXXXXXXX YYYY) codes, one per line, into
+/// the writes they make. Only codes that unconditionally write a constant are supported.
+pub fn parse_codes(text: &str) -> Result<Vec<Write>, anyhow::Error> {
+    let mut writes = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let code = line.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+        if code.is_empty() {
+            continue;
+        }
+
+        let parse = |s: &str| u32::from_str_radix(s, 16).ok();
+        let write = match (
+            code.len(),
+            parse(code.get(..8).unwrap_or("")),
+            parse(code.get(8..).unwrap_or("")),
+        ) {
+            (16, Some(op1), Some(op2)) => parse_gameshark(op1, op2),
+            (12, Some(op1), Some(op2)) => parse_codebreaker(op1, op2 as u16),
+            _ => Err(anyhow::anyhow!("not a GameShark or CodeBreaker code")),
+        }
+        .map_err(|e| anyhow::anyhow!("line {}: {}", i + 1, e))?;
+        writes.extend(write);
+    }
+    if writes.is_empty() {
+        anyhow::bail!("no codes that write to memory");
+    }
+    Ok(writes)
+}
@@ -80,6 +80,25 @@ where
     buf.parse().map_err(serde::de::Error::custom)
 }
 
+// Older configs only had a single video filter, with "" for none.
+fn deserialize_video_filters<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum VideoFilters {
+        Single(String),
+        List(Vec<String>),
+    }
+
+    Ok(match VideoFilters::deserialize(deserializer)? {
+        VideoFilters::Single(name) if name.is_empty() => vec![],
+        VideoFilters::Single(name) => vec![name],
+        VideoFilters::List(names) => names,
+    })
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq)]
 #[serde(default)]
 pub struct Config {
@@ -92,14 +111,17 @@ pub struct Config {
     )]
     pub language: unic_langid::LanguageIdentifier,
     pub max_queue_length: u32,
-    pub video_filter: String,
+    #[serde(alias = "video_filter", deserialize_with = "deserialize_video_filters")]
+    pub video_filters: Vec<String>,
     pub max_scale: u32,
     pub input_mapping: input::Mapping,
     pub matchmaking_endpoint: String,
     pub replaycollector_endpoint: String,
+    pub replaycollector_set_endpoint: String,
     pub patch_repo: String,
     pub enable_patch_autoupdate: bool,
     pub input_delay: u32,
+    pub adaptive_input_delay: bool,
     pub default_match_type: u8,
     pub data_path: std::path::PathBuf,
     pub full_screen: bool,
@@ -119,6 +141,10 @@ pub struct Config {
     pub last_version: semver::Version,
     pub use_relay: Option<bool>,
     pub speed_change_percent: u32,
+    pub slow_motion_percent: u32,
+    pub turbo_interval_frames: u32,
+    pub spectator_listen_addr: String,
+    pub ruleset: Option<String>,
 }
 
 impl Default for Config {
@@ -131,14 +157,16 @@ impl Default for Config {
             show_debug: Default::default(),
             language: i18n::FALLBACK_LANG.clone(),
             max_queue_length: 1200,
-            video_filter: "".to_string(),
+            video_filters: vec![],
             max_scale: 0,
             input_mapping: Default::default(),
             matchmaking_endpoint: "".to_string(),
             replaycollector_endpoint: "https://replaycollector.tango.n1gp.net".to_string(),
+            replaycollector_set_endpoint: "".to_string(),
             patch_repo: "".to_string(),
             enable_patch_autoupdate: true,
             input_delay: 2,
+            adaptive_input_delay: false,
             default_match_type: 1,
             data_path: "".into(),
             full_screen: false,
@@ -156,6 +184,10 @@ impl Default for Config {
             last_version: version,
             use_relay: None,
             speed_change_percent: 300,
+            slow_motion_percent: 50,
+            turbo_interval_frames: 2,
+            spectator_listen_addr: "".to_string(),
+            ruleset: None,
         }
     }
 }
@@ -269,11 +301,30 @@ impl Config {
         self.data_path.join("crashstates")
     }
 
+    pub fn replay_index_path(&self) -> std::path::PathBuf {
+        self.data_path.join("replay-index.json")
+    }
+
+    pub fn rulesets_path(&self) -> std::path::PathBuf {
+        self.data_path.join("rulesets")
+    }
+
+    pub fn save_history_path(&self) -> std::path::PathBuf {
+        self.data_path.join("save-history")
+    }
+
+    pub fn savestates_path(&self) -> std::path::PathBuf {
+        self.data_path.join("savestates")
+    }
+
     pub fn ensure_dirs(&self) -> Result<(), anyhow::Error> {
         std::fs::create_dir_all(&self.saves_path())?;
         std::fs::create_dir_all(&self.roms_path())?;
         std::fs::create_dir_all(&self.replays_path())?;
         std::fs::create_dir_all(&self.patches_path())?;
+        std::fs::create_dir_all(&self.rulesets_path())?;
+        std::fs::create_dir_all(&self.save_history_path())?;
+        std::fs::create_dir_all(&self.savestates_path())?;
         std::fs::create_dir_all(&self.logs_path())?;
         std::fs::create_dir_all(&self.crashstates_path())?;
         Ok(())
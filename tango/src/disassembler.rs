@@ -0,0 +1,418 @@
+// Disassembles ARM7TDMI instructions (ARMv4T) for the debugger, in the usual GNU-style syntax.
+
+const CONDITIONS: [&str; 16] = [
+    "eq", "ne", "cs", "cc", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "", "nv",
+];
+
+fn reg(r: u32) -> &'static str {
+    [
+        "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "sp", "lr", "pc",
+    ][(r & 0xf) as usize]
+}
+
+fn reg_list(list: u32) -> String {
+    let mut parts = vec![];
+    let mut r = 0;
+    while r < 16 {
+        if list & (1 << r) == 0 {
+            r += 1;
+            continue;
+        }
+        let start = r;
+        while r < 16 && list & (1 << r) != 0 {
+            r += 1;
+        }
+        parts.push(match r - start {
+            1 => reg(start).to_string(),
+            2 => format!("{}, {}", reg(start), reg(start + 1)),
+            _ => format!("{}-{}", reg(start), reg(r - 1)),
+        });
+    }
+    format!("{{{}}}", parts.join(", "))
+}
+
+fn imm(v: u32) -> String {
+    if v < 10 {
+        format!("#{}", v)
+    } else {
+        format!("#0x{:x}", v)
+    }
+}
+
+fn signed_imm(up: bool, v: u32) -> String {
+    if up {
+        imm(v)
+    } else if v < 10 {
+        format!("#-{}", v)
+    } else {
+        format!("#-0x{:x}", v)
+    }
+}
+
+/// Disassembles the THUMB instruction at `address`. `next_opcode` is the halfword after it, which is needed for the
+/// two halves of a BL.
+pub fn disassemble_thumb(address: u32, opcode: u16, next_opcode: u16) -> String {
+    let op = opcode as u32;
+    let rd = op & 0x7;
+    let rs = (op >> 3) & 0x7;
+
+    match op >> 13 {
+        0b000 if (op >> 11) & 0x3 == 0x3 => {
+            let rn = (op >> 6) & 0x7;
+            let mnemonic = if op & 0x0200 != 0 { "sub" } else { "add" };
+            if op & 0x0400 != 0 {
+                format!("{} {}, {}, {}", mnemonic, reg(rd), reg(rs), imm(rn))
+            } else {
+                format!("{} {}, {}, {}", mnemonic, reg(rd), reg(rs), reg(rn))
+            }
+        }
+        0b000 => {
+            let offset = (op >> 6) & 0x1f;
+            match (op >> 11) & 0x3 {
+                0 if offset == 0 => format!("mov {}, {}", reg(rd), reg(rs)),
+                0 => format!("lsl {}, {}, {}", reg(rd), reg(rs), imm(offset)),
+                1 => format!(
+                    "lsr {}, {}, {}",
+                    reg(rd),
+                    reg(rs),
+                    imm(if offset == 0 { 32 } else { offset })
+                ),
+                _ => format!(
+                    "asr {}, {}, {}",
+                    reg(rd),
+                    reg(rs),
+                    imm(if offset == 0 { 32 } else { offset })
+                ),
+            }
+        }
+        0b001 => {
+            let rd = (op >> 8) & 0x7;
+            let mnemonic = ["mov", "cmp", "add", "sub"][((op >> 11) & 0x3) as usize];
+            format!("{} {}, {}", mnemonic, reg(rd), imm(op & 0xff))
+        }
+        0b010 => {
+            if op >> 10 == 0b010000 {
+                let mnemonic = [
+                    "and", "eor", "lsl", "lsr", "asr", "adc", "sbc", "ror", "tst", "neg", "cmp", "cmn", "orr", "mul",
+                    "bic", "mvn",
+                ][((op >> 6) & 0xf) as usize];
+                format!("{} {}, {}", mnemonic, reg(rd), reg(rs))
+            } else if op >> 10 == 0b010001 {
+                let rd = rd | ((op >> 4) & 0x8);
+                let rs = (op >> 3) & 0xf;
+                match (op >> 8) & 0x3 {
+                    0 => format!("add {}, {}", reg(rd), reg(rs)),
+                    1 => format!("cmp {}, {}", reg(rd), reg(rs)),
+                    2 if rd == 8 && rs == 8 => "nop".to_string(),
+                    2 => format!("mov {}, {}", reg(rd), reg(rs)),
+                    _ => format!("bx {}", reg(rs)),
+                }
+            } else if op >> 11 == 0b01001 {
+                let rd = (op >> 8) & 0x7;
+                let offset = (op & 0xff) * 4;
+                format!(
+                    "ldr {}, [pc, {}] ; =0x{:08x}",
+                    reg(rd),
+                    imm(offset),
+                    (address.wrapping_add(4) & !0x3).wrapping_add(offset)
+                )
+            } else {
+                let ro = (op >> 6) & 0x7;
+                let mnemonic = if op & 0x0200 == 0 {
+                    ["str", "strb", "ldr", "ldrb"][((op >> 10) & 0x3) as usize]
+                } else {
+                    ["strh", "ldsb", "ldrh", "ldsh"][((op >> 10) & 0x3) as usize]
+                };
+                format!("{} {}, [{}, {}]", mnemonic, reg(rd), reg(rs), reg(ro))
+            }
+        }
+        0b011 => {
+            let offset = (op >> 6) & 0x1f;
+            let (mnemonic, offset) = match (op >> 11) & 0x3 {
+                0 => ("str", offset * 4),
+                1 => ("ldr", offset * 4),
+                2 => ("strb", offset),
+                _ => ("ldrb", offset),
+            };
+            format!("{} {}, [{}, {}]", mnemonic, reg(rd), reg(rs), imm(offset))
+        }
+        0b100 => {
+            if op & 0x1000 == 0 {
+                let mnemonic = if op & 0x0800 != 0 { "ldrh" } else { "strh" };
+                format!(
+                    "{} {}, [{}, {}]",
+                    mnemonic,
+                    reg(rd),
+                    reg(rs),
+                    imm(((op >> 6) & 0x1f) * 2)
+                )
+            } else {
+                let mnemonic = if op & 0x0800 != 0 { "ldr" } else { "str" };
+                format!("{} {}, [sp, {}]", mnemonic, reg((op >> 8) & 0x7), imm((op & 0xff) * 4))
+            }
+        }
+        0b101 => {
+            if op & 0x1000 == 0 {
+                let base = if op & 0x0800 != 0 { "sp" } else { "pc" };
+                format!("add {}, {}, {}", reg((op >> 8) & 0x7), base, imm((op & 0xff) * 4))
+            } else if op & 0x0f00 == 0x0000 {
+                let mnemonic = if op & 0x0080 != 0 { "sub" } else { "add" };
+                format!("{} sp, {}", mnemonic, imm((op & 0x7f) * 4))
+            } else if op & 0x0600 == 0x0400 {
+                let pop = op & 0x0800 != 0;
+                let mut list = op & 0xff;
+                if op & 0x0100 != 0 {
+                    list |= if pop { 1 << 15 } else { 1 << 14 };
+                }
+                format!("{} {}", if pop { "pop" } else { "push" }, reg_list(list))
+            } else if op & 0x0f00 == 0x0e00 {
+                format!("bkpt {}", imm(op & 0xff))
+            } else {
+                format!("undefined 0x{:04x}", op)
+            }
+        }
+        0b110 => {
+            if op & 0x1000 == 0 {
+                let mnemonic = if op & 0x0800 != 0 { "ldmia" } else { "stmia" };
+                format!("{} {}!, {}", mnemonic, reg((op >> 8) & 0x7), reg_list(op & 0xff))
+            } else {
+                match (op >> 8) & 0xf {
+                    0xe => format!("undefined 0x{:04x}", op),
+                    0xf => format!("swi {}", imm(op & 0xff)),
+                    cond => {
+                        let offset = ((op & 0xff) as i8 as i32) << 1;
+                        format!(
+                            "b{} 0x{:08x}",
+                            CONDITIONS[cond as usize],
+                            address.wrapping_add(4).wrapping_add(offset as u32)
+                        )
+                    }
+                }
+            }
+        }
+        _ => match (op >> 11) & 0x3 {
+            0 => {
+                let offset = (((op & 0x7ff) << 21) as i32) >> 20;
+                format!("b 0x{:08x}", address.wrapping_add(4).wrapping_add(offset as u32))
+            }
+            2 if next_opcode & 0xf800 == 0xf800 => {
+                let high = (((op & 0x7ff) << 21) as i32) >> 9;
+                let low = ((next_opcode & 0x7ff) as i32) << 1;
+                format!(
+                    "bl 0x{:08x}",
+                    address
+                        .wrapping_add(4)
+                        .wrapping_add(high as u32)
+                        .wrapping_add(low as u32)
+                )
+            }
+            2 => format!("bl prefix 0x{:03x}", op & 0x7ff),
+            3 => format!("bl suffix 0x{:03x}", op & 0x7ff),
+            _ => format!("undefined 0x{:04x}", op),
+        },
+    }
+}
+
+fn arm_shifted_register(op: u32) -> String {
+    let rm = reg(op & 0xf);
+    let kind = ["lsl", "lsr", "asr", "ror"][((op >> 5) & 0x3) as usize];
+    if op & 0x10 != 0 {
+        return format!("{}, {} {}", rm, kind, reg((op >> 8) & 0xf));
+    }
+    match (kind, (op >> 7) & 0x1f) {
+        ("lsl", 0) => rm.to_string(),
+        ("ror", 0) => format!("{}, rrx", rm),
+        ("lsr" | "asr", 0) => format!("{}, {} #32", rm, kind),
+        (_, amount) => format!("{}, {} {}", rm, kind, imm(amount)),
+    }
+}
+
+/// Disassembles the ARM instruction at `address`.
+pub fn disassemble_arm(address: u32, opcode: u32) -> String {
+    let op = opcode;
+    let cond = CONDITIONS[(op >> 28) as usize];
+    let rn = (op >> 16) & 0xf;
+    let rd = (op >> 12) & 0xf;
+
+    if op & 0x0fff_fff0 == 0x012f_ff10 {
+        return format!("bx{} {}", cond, reg(op & 0xf));
+    }
+
+    if op & 0xfff0_00f0 == 0xe120_0070 {
+        return format!("bkpt {}", imm(((op >> 4) & 0xfff0) | (op & 0xf)));
+    }
+
+    if op & 0x0fc0_00f0 == 0x0000_0090 {
+        let s = if op & 0x0010_0000 != 0 { "s" } else { "" };
+        let (rd, rn, rs, rm) = ((op >> 16) & 0xf, (op >> 12) & 0xf, (op >> 8) & 0xf, op & 0xf);
+        return if op & 0x0020_0000 != 0 {
+            format!("mla{}{} {}, {}, {}, {}", cond, s, reg(rd), reg(rm), reg(rs), reg(rn))
+        } else {
+            format!("mul{}{} {}, {}, {}", cond, s, reg(rd), reg(rm), reg(rs))
+        };
+    }
+
+    if op & 0x0f80_00f0 == 0x0080_0090 {
+        let s = if op & 0x0010_0000 != 0 { "s" } else { "" };
+        let mnemonic = ["umull", "umlal", "smull", "smlal"][((op >> 21) & 0x3) as usize];
+        return format!(
+            "{}{}{} {}, {}, {}, {}",
+            mnemonic,
+            cond,
+            s,
+            reg(rd),
+            reg(rn),
+            reg(op & 0xf),
+            reg((op >> 8) & 0xf)
+        );
+    }
+
+    if op & 0x0fb0_0ff0 == 0x0100_0090 {
+        let b = if op & 0x0040_0000 != 0 { "b" } else { "" };
+        return format!("swp{}{} {}, {}, [{}]", cond, b, reg(rd), reg(op & 0xf), reg(rn));
+    }
+
+    if op & 0x0e00_0090 == 0x0000_0090 && op & 0x60 != 0 {
+        let load = op & 0x0010_0000 != 0;
+        let mnemonic = match ((op >> 5) & 0x3, load) {
+            (1, false) => "strh",
+            (1, true) => "ldrh",
+            (2, _) => "ldrsb",
+            _ => "ldrsh",
+        };
+        let up = op & 0x0080_0000 != 0;
+        let offset = if op & 0x0040_0000 != 0 {
+            signed_imm(up, ((op >> 4) & 0xf0) | (op & 0xf))
+        } else {
+            format!("{}{}", if up { "" } else { "-" }, reg(op & 0xf))
+        };
+        return format_transfer(mnemonic, cond, rd, rn, op, offset);
+    }
+
+    if op & 0x0fbf_0fff == 0x010f_0000 {
+        let psr = if op & 0x0040_0000 != 0 { "spsr" } else { "cpsr" };
+        return format!("mrs{} {}, {}", cond, reg(rd), psr);
+    }
+
+    if op & 0x0db0_f000 == 0x0120_f000 {
+        let psr = if op & 0x0040_0000 != 0 { "spsr" } else { "cpsr" };
+        let fields = ["c", "x", "s", "f"]
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| op & (1 << (16 + i)) != 0)
+            .map(|(_, f)| *f)
+            .collect::<String>();
+        let source = if op & 0x0200_0000 != 0 {
+            imm((op & 0xff).rotate_right(((op >> 8) & 0xf) * 2))
+        } else {
+            reg(op & 0xf).to_string()
+        };
+        return format!("msr{} {}_{}, {}", cond, psr, fields, source);
+    }
+
+    match (op >> 25) & 0x7 {
+        0b000 | 0b001 => {
+            let opcode = (op >> 21) & 0xf;
+            let mnemonic = [
+                "and", "eor", "sub", "rsb", "add", "adc", "sbc", "rsc", "tst", "teq", "cmp", "cmn", "orr", "mov",
+                "bic", "mvn",
+            ][opcode as usize];
+            let operand = if op & 0x0200_0000 != 0 {
+                imm((op & 0xff).rotate_right(((op >> 8) & 0xf) * 2))
+            } else {
+                arm_shifted_register(op)
+            };
+            match opcode {
+                0x8..=0xb => format!("{}{} {}, {}", mnemonic, cond, reg(rn), operand),
+                _ => {
+                    let s = if op & 0x0010_0000 != 0 { "s" } else { "" };
+                    if opcode == 0xd || opcode == 0xf {
+                        format!("{}{}{} {}, {}", mnemonic, cond, s, reg(rd), operand)
+                    } else {
+                        format!("{}{}{} {}, {}, {}", mnemonic, cond, s, reg(rd), reg(rn), operand)
+                    }
+                }
+            }
+        }
+        0b010 | 0b011 => {
+            if op & 0x0200_0010 == 0x0200_0010 {
+                return format!("undefined 0x{:08x}", op);
+            }
+            let load = op & 0x0010_0000 != 0;
+            let b = if op & 0x0040_0000 != 0 { "b" } else { "" };
+            let t = if op & 0x0100_0000 == 0 && op & 0x0020_0000 != 0 {
+                "t"
+            } else {
+                ""
+            };
+            let mnemonic = format!("{}{}{}", if load { "ldr" } else { "str" }, b, t);
+            let up = op & 0x0080_0000 != 0;
+            if op & 0x0200_0000 == 0 && rn == 15 && op & 0x0100_0000 != 0 {
+                let offset = op & 0xfff;
+                let target = if up {
+                    address.wrapping_add(8).wrapping_add(offset)
+                } else {
+                    address.wrapping_add(8).wrapping_sub(offset)
+                };
+                return format!(
+                    "{}{} {}, [pc, {}] ; =0x{:08x}",
+                    mnemonic,
+                    cond,
+                    reg(rd),
+                    signed_imm(up, offset),
+                    target
+                );
+            }
+            let offset = if op & 0x0200_0000 == 0 {
+                signed_imm(up, op & 0xfff)
+            } else {
+                format!("{}{}", if up { "" } else { "-" }, arm_shifted_register(op))
+            };
+            format_transfer(&mnemonic, cond, rd, rn, op, offset)
+        }
+        0b100 => {
+            let load = op & 0x0010_0000 != 0;
+            let mode = ["da", "ia", "db", "ib"][((op >> 23) & 0x3) as usize];
+            let writeback = if op & 0x0020_0000 != 0 { "!" } else { "" };
+            let user = if op & 0x0040_0000 != 0 { "^" } else { "" };
+            format!(
+                "{}{}{} {}{}, {}{}",
+                if load { "ldm" } else { "stm" },
+                mode,
+                cond,
+                reg(rn),
+                writeback,
+                reg_list(op & 0xffff),
+                user
+            )
+        }
+        0b101 => {
+            let offset = (((op & 0x00ff_ffff) << 8) as i32) >> 6;
+            format!(
+                "b{}{} 0x{:08x}",
+                if op & 0x0100_0000 != 0 { "l" } else { "" },
+                cond,
+                address.wrapping_add(8).wrapping_add(offset as u32)
+            )
+        }
+        0b111 if op & 0x0100_0000 != 0 => format!("swi{} {}", cond, imm(op & 0x00ff_ffff)),
+        _ => format!("undefined 0x{:08x}", op),
+    }
+}
+
+fn format_transfer(mnemonic: &str, cond: &str, rd: u32, rn: u32, op: u32, offset: String) -> String {
+    if op & 0x0100_0000 != 0 {
+        let writeback = if op & 0x0020_0000 != 0 { "!" } else { "" };
+        format!(
+            "{}{} {}, [{}, {}]{}",
+            mnemonic,
+            cond,
+            reg(rd),
+            reg(rn),
+            offset,
+            writeback
+        )
+    } else {
+        format!("{}{} {}, [{}], {}", mnemonic, cond, reg(rd), reg(rn), offset)
+    }
+}
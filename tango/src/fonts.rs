@@ -0,0 +1,19 @@
+// Fonts are embedded so they're available to both the GUI and the headless renderers.
+
+use std::str::FromStr;
+
+pub const LATN: &[u8] = include_bytes!("fonts/NotoSans-Regular.ttf");
+pub const JPAN: &[u8] = include_bytes!("fonts/NotoSansJP-Regular.otf");
+pub const HANS: &[u8] = include_bytes!("fonts/NotoSansSC-Regular.otf");
+pub const HANT: &[u8] = include_bytes!("fonts/NotoSansTC-Regular.otf");
+
+pub fn raw_for_language(lang: &unic_langid::LanguageIdentifier) -> &'static [u8] {
+    let mut lang = lang.clone();
+    lang.maximize();
+    match lang.script {
+        Some(s) if s == unic_langid::subtags::Script::from_str("Jpan").unwrap() => JPAN,
+        Some(s) if s == unic_langid::subtags::Script::from_str("Hans").unwrap() => HANS,
+        Some(s) if s == unic_langid::subtags::Script::from_str("Hant").unwrap() => HANT,
+        _ => LATN,
+    }
+}
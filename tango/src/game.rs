@@ -156,6 +156,10 @@ where
     fn hooks(&self) -> &'static (dyn Hooks + Send + Sync);
     fn parse_save(&self, data: &[u8]) -> Result<Box<dyn save::Save + Send + Sync>, anyhow::Error>;
     fn save_from_wram(&self, data: &[u8]) -> Result<Box<dyn save::Save + Send + Sync>, anyhow::Error>;
+    /// Converts WRAM from this game's release in the other region, if both releases lay out their saves the same way.
+    fn save_from_other_region(&self, _data: &[u8]) -> Option<Result<Box<dyn save::Save + Send + Sync>, anyhow::Error>> {
+        None
+    }
     fn load_rom_assets(
         &self,
         _rom: &[u8],
@@ -188,5 +192,33 @@ pub trait Hooks {
 
     fn prepare_for_fastforward(&self, core: mgba::core::CoreMutRef);
 
+    /// EWRAM regions, as (address, length), that must be identical between a player's own core and the opponent's shadow of it at the same tick.
+    ///
+    /// These are the RNG states, the outgoing packet (which the game fills in from its battle state every tick) and, where its location is known, the battle state struct.
+    fn desync_check_regions(&self) -> Vec<(u32, u32)>;
+
     fn predict_rx(&self, _rx: &mut Vec<u8>) {}
 }
+
+pub fn read_desync_check_regions(hooks: &(dyn Hooks + Send + Sync), mut read: impl FnMut(u32, &mut [u8])) -> Vec<u8> {
+    let regions = hooks.desync_check_regions();
+    let mut buf = vec![0u8; regions.iter().map(|(_, len)| *len as usize).sum()];
+    let mut offset = 0;
+    for (address, len) in regions {
+        read(address, &mut buf[offset..offset + len as usize]);
+        offset += len as usize;
+    }
+    buf
+}
+
+pub fn read_desync_check_regions_in_state(hooks: &(dyn Hooks + Send + Sync), state: &mgba::state::State) -> Vec<u8> {
+    let wram = state.wram();
+    read_desync_check_regions(hooks, |address, buf| {
+        let offset = (address - 0x02000000) as usize;
+        buf.copy_from_slice(&wram[offset..offset + buf.len()]);
+    })
+}
+
+pub fn hash_desync_check_regions_in_state(hooks: &(dyn Hooks + Send + Sync), state: &mgba::state::State) -> u32 {
+    crc32fast::hash(&read_desync_check_regions_in_state(hooks, state))
+}
@@ -55,6 +55,21 @@ impl game::Game for EXE1Impl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::JP,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -123,6 +138,21 @@ impl game::Game for BN1Impl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::US,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
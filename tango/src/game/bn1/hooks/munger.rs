@@ -65,4 +65,16 @@ impl Munger {
     pub(super) fn packet_seqnum(&self, mut core: mgba::core::CoreMutRef) -> u32 {
         core.raw_read_32(self.offsets.ewram.packet_seqnum, -1)
     }
+
+    pub(super) fn desync_check_regions(&self) -> Vec<(u32, u32)> {
+        vec![
+            (self.offsets.ewram.rng_state, 4),
+            (self.offsets.ewram.tx_packet, 0x10),
+            // The battle state runs right up to the outgoing packet.
+            (
+                self.offsets.ewram.battle_state,
+                self.offsets.ewram.tx_packet - self.offsets.ewram.battle_state,
+            ),
+        ]
+    }
 }
@@ -62,6 +62,17 @@ impl Save {
         })
     }
 
+    /// Makes a save from the other region's release, which only differs in the game name.
+    pub fn from_other_region(buf: &[u8], game_info: GameInfo) -> Result<Self, anyhow::Error> {
+        let mut save = Self::from_wram(buf, game_info)?;
+        save.buf[GAME_NAME_OFFSET..GAME_NAME_OFFSET + 20].copy_from_slice(match save.game_info.region {
+            Region::JP => b"ROCKMAN EXE 20010120",
+            Region::US => b"ROCKMAN EXE 20010727",
+        });
+        save.rebuild_checksum();
+        Ok(save)
+    }
+
     pub fn game_info(&self) -> &GameInfo {
         &self.game_info
     }
@@ -74,6 +85,11 @@ impl Save {
         save::compute_save_raw_checksum(&self.buf, CHECKSUM_OFFSET) + 0x16
     }
 
+    pub fn rebuild_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        byteorder::LittleEndian::write_u32(&mut self.buf[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4], checksum);
+    }
+
     #[allow(dead_code)]
     pub fn armor(&self) -> usize {
         self.buf[0x0227] as usize
@@ -89,6 +105,10 @@ impl save::Save for Save {
         &self.buf
     }
 
+    fn view_chips_mut(&mut self) -> Option<Box<dyn save::ChipsViewMut + '_>> {
+        Some(Box::new(ChipsViewMut { save: self }))
+    }
+
     fn to_vec(&self) -> Vec<u8> {
         let mut buf = vec![0; 65536];
         buf[..SRAM_SIZE].copy_from_slice(&self.buf);
@@ -136,3 +156,47 @@ impl<'a> save::ChipsView<'a> for ChipsView<'a> {
         })
     }
 }
+
+pub struct ChipsViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::ChipsViewMut<'a> for ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()> {
+        if folder_index >= 1 {
+            anyhow::bail!("invalid folder index: {}", folder_index);
+        }
+        Ok(())
+    }
+
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &save::Chip) -> anyhow::Result<()> {
+        if folder_index >= 1 || chip_index >= 30 {
+            anyhow::bail!("invalid chip position: {}, {}", folder_index, chip_index);
+        }
+        let code = save::chip_code_index(chip.code)
+            .filter(|code| *code < 26)
+            .ok_or_else(|| anyhow::anyhow!("invalid code: {}", chip.code))?;
+        if chip.id > 0xff {
+            anyhow::bail!("invalid chip id: {}", chip.id);
+        }
+
+        self.save.buf[0x01c0 + chip_index * 2] = chip.id as u8;
+        self.save.buf[0x01c0 + chip_index * 2 + 1] = code as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_regular_chip_index(&mut self, _folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()> {
+        if chip_index.is_some() {
+            anyhow::bail!("regular chips are not supported");
+        }
+        Ok(())
+    }
+
+    fn set_tag_chip_indexes(&mut self, _folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()> {
+        if chip_indexes.is_some() {
+            anyhow::bail!("tag chips are not supported");
+        }
+        Ok(())
+    }
+}
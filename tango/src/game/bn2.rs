@@ -42,6 +42,14 @@ impl game::Game for EXE2Impl {
         Ok(Box::new(save::Save::from_wram(data)?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        // Saves are the same in both regions.
+        Some(self.save_from_wram(data))
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -97,6 +105,14 @@ impl game::Game for BN2Impl {
         Ok(Box::new(save::Save::from_wram(data)?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        // Saves are the same in both regions.
+        Some(self.save_from_wram(data))
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -900,6 +900,10 @@ impl game::Hooks for Hooks {
         }
     }
 
+    fn desync_check_regions(&self) -> Vec<(u32, u32)> {
+        self.munger().desync_check_regions()
+    }
+
     fn prepare_for_fastforward(&self, mut core: mgba::core::CoreMutRef) {
         core.gba_mut()
             .cpu_mut()
@@ -63,4 +63,8 @@ impl Munger {
     pub(super) fn packet_seqnum(&self, mut core: mgba::core::CoreMutRef) -> u32 {
         core.raw_read_32(self.offsets.ewram.packet_seqnum, -1)
     }
+
+    pub(super) fn desync_check_regions(&self) -> Vec<(u32, u32)> {
+        vec![(self.offsets.ewram.rng_state, 4), (self.offsets.ewram.tx_packet, 0x10)]
+    }
 }
@@ -1,6 +1,6 @@
 use byteorder::ByteOrder;
 
-use crate::save;
+use crate::save::{self, ChipsView as _};
 
 const SRAM_SIZE: usize = 0x3a78;
 const GAME_NAME_OFFSET: usize = 0x1198;
@@ -47,6 +47,11 @@ impl Save {
     pub fn compute_checksum(&self) -> u32 {
         save::compute_save_raw_checksum(&self.buf, CHECKSUM_OFFSET) + 0x16
     }
+
+    pub fn rebuild_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        byteorder::LittleEndian::write_u32(&mut self.buf[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4], checksum);
+    }
 }
 
 impl save::Save for Save {
@@ -58,6 +63,10 @@ impl save::Save for Save {
         &self.buf
     }
 
+    fn view_chips_mut(&mut self) -> Option<Box<dyn save::ChipsViewMut + '_>> {
+        Some(Box::new(ChipsViewMut { save: self }))
+    }
+
     fn to_vec(&self) -> Vec<u8> {
         let mut buf = vec![0; 65536];
         buf[..SRAM_SIZE].copy_from_slice(&self.buf);
@@ -110,3 +119,50 @@ impl<'a> save::ChipsView<'a> for ChipsView<'a> {
         })
     }
 }
+
+pub struct ChipsViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::ChipsViewMut<'a> for ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() {
+            anyhow::bail!("invalid folder index: {}", folder_index);
+        }
+        self.save.buf[0x0dc2] = folder_index as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &save::Chip) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index >= 30 {
+            anyhow::bail!("invalid chip position: {}, {}", folder_index, chip_index);
+        }
+        let code = save::chip_code_index(chip.code).ok_or_else(|| anyhow::anyhow!("invalid code: {}", chip.code))?;
+        if chip.id > 0xffff {
+            anyhow::bail!("invalid chip id: {}", chip.id);
+        }
+
+        let offset = 0x0ab0 + folder_index * (30 * 4) + chip_index * 4;
+        byteorder::LittleEndian::write_u16(&mut self.save.buf[offset..offset + 2], chip.id as u16);
+        byteorder::LittleEndian::write_u16(&mut self.save.buf[offset + 2..offset + 4], code as u16);
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_regular_chip_index(&mut self, folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index.unwrap_or(0) >= 30 {
+            anyhow::bail!("invalid chip position: {}, {:?}", folder_index, chip_index);
+        }
+        self.save.buf[0x0ddd + folder_index] = chip_index.map(|i| i as u8).unwrap_or(0xff);
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_tag_chip_indexes(&mut self, _folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()> {
+        if chip_indexes.is_some() {
+            anyhow::bail!("tag chips are not supported");
+        }
+        Ok(())
+    }
+}
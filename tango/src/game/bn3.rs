@@ -43,6 +43,14 @@ impl game::Game for EXE3WImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        // Saves are the same in both regions.
+        Some(self.save_from_wram(data))
+    }
+
     fn parse_save(&self, data: &[u8]) -> Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error> {
         let save = save::Save::new(data)?;
         if save.game_info()
@@ -111,6 +119,14 @@ impl game::Game for EXE3BImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        // Saves are the same in both regions.
+        Some(self.save_from_wram(data))
+    }
+
     fn parse_save(&self, data: &[u8]) -> Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error> {
         let save = save::Save::new(data)?;
         if save.game_info()
@@ -179,6 +195,14 @@ impl game::Game for BN3WImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        // Saves are the same in both regions.
+        Some(self.save_from_wram(data))
+    }
+
     fn parse_save(&self, data: &[u8]) -> Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error> {
         let save = save::Save::new(data)?;
         if save.game_info()
@@ -247,6 +271,14 @@ impl game::Game for BN3BImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        // Saves are the same in both regions.
+        Some(self.save_from_wram(data))
+    }
+
     fn parse_save(&self, data: &[u8]) -> Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error> {
         let save = save::Save::new(data)?;
         if save.game_info()
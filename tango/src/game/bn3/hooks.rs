@@ -995,6 +995,10 @@ impl game::Hooks for Hooks {
         byteorder::LittleEndian::write_u32(&mut rx[0x4..0x8], tick + 1);
     }
 
+    fn desync_check_regions(&self) -> Vec<(u32, u32)> {
+        self.munger().desync_check_regions()
+    }
+
     fn prepare_for_fastforward(&self, mut core: mgba::core::CoreMutRef) {
         core.gba_mut()
             .cpu_mut()
@@ -69,4 +69,12 @@ impl Munger {
     pub(super) fn is_linking(&self, mut core: mgba::core::CoreMutRef) -> bool {
         core.raw_read_8(self.offsets.ewram.is_linking, -1) == 1
     }
+
+    pub(super) fn desync_check_regions(&self) -> Vec<(u32, u32)> {
+        vec![
+            (self.offsets.ewram.rng1_state, 4),
+            (self.offsets.ewram.rng2_state, 4),
+            (self.offsets.ewram.tx_packet, 0x10),
+        ]
+    }
 }
@@ -1,6 +1,6 @@
 use byteorder::ByteOrder;
 
-use crate::save;
+use crate::save::{self, ChipsView as _};
 
 const SRAM_SIZE: usize = 0x57b0;
 const GAME_NAME_OFFSET: usize = 0x1e00;
@@ -82,11 +82,15 @@ impl Save {
         byteorder::LittleEndian::read_u32(&self.buf[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4])
     }
 
-    #[allow(dead_code)]
     pub fn compute_checksum(&self) -> u32 {
         compute_raw_checksum(&self.buf) + checksum_start_for_variant(self.game_info.variant)
     }
 
+    pub fn rebuild_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        byteorder::LittleEndian::write_u32(&mut self.buf[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4], checksum);
+    }
+
     pub fn game_info(&self) -> &GameInfo {
         &self.game_info
     }
@@ -105,6 +109,14 @@ impl save::Save for Save {
         Some(Box::new(NavicustView { save: self }))
     }
 
+    fn view_chips_mut(&mut self) -> Option<Box<dyn save::ChipsViewMut + '_>> {
+        Some(Box::new(ChipsViewMut { save: self }))
+    }
+
+    fn view_navicust_mut(&mut self) -> Option<Box<dyn save::NavicustViewMut + '_>> {
+        Some(Box::new(NavicustViewMut { save: self }))
+    }
+
     fn to_vec(&self) -> Vec<u8> {
         let mut buf = vec![0; 65536];
         buf[..SRAM_SIZE].copy_from_slice(&self.buf);
@@ -204,3 +216,85 @@ impl<'a> save::NavicustView<'a> for NavicustView<'a> {
         })
     }
 }
+
+pub struct ChipsViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::ChipsViewMut<'a> for ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() {
+            anyhow::bail!("invalid folder index: {}", folder_index);
+        }
+        self.save.buf[0x1882] = folder_index as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &save::Chip) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index >= 30 {
+            anyhow::bail!("invalid chip position: {}, {}", folder_index, chip_index);
+        }
+        let code = save::chip_code_index(chip.code).ok_or_else(|| anyhow::anyhow!("invalid code: {}", chip.code))?;
+        if chip.id > 0xffff {
+            anyhow::bail!("invalid chip id: {}", chip.id);
+        }
+
+        let offset = 0x1410 + folder_index * (30 * 4) + chip_index * 4;
+        byteorder::LittleEndian::write_u16(&mut self.save.buf[offset..offset + 2], chip.id as u16);
+        byteorder::LittleEndian::write_u16(&mut self.save.buf[offset + 2..offset + 4], code as u16);
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_regular_chip_index(&mut self, folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index.unwrap_or(0) >= 30 {
+            anyhow::bail!("invalid chip position: {}, {:?}", folder_index, chip_index);
+        }
+        self.save.buf[0x189d + folder_index] = chip_index.map(|i| i as u8).unwrap_or(0xff);
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_tag_chip_indexes(&mut self, _folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()> {
+        if chip_indexes.is_some() {
+            anyhow::bail!("tag chips are not supported");
+        }
+        Ok(())
+    }
+}
+
+pub struct NavicustViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::NavicustViewMut<'a> for NavicustViewMut<'a> {
+    fn set_navicust_part(&mut self, i: usize, part: Option<&save::NavicustPart>) -> anyhow::Result<()> {
+        if i >= 25 {
+            anyhow::bail!("invalid navicust part index: {}", i);
+        }
+
+        let offset = 0x1300;
+        let buf = &mut self.save.buf[offset + i * 8..offset + (i + 1) * 8];
+        if let Some(part) = part {
+            if part.variant >= 4 || part.id * 4 + part.variant > 0xff {
+                anyhow::bail!("invalid navicust part: {:?}", part);
+            }
+            buf[0x0] = (part.id * 4 + part.variant) as u8;
+            buf[0x2] = part.col;
+            buf[0x3] = part.row;
+            buf[0x4] = part.rot;
+            let raw = buf[0x0];
+            let bit = 0x80 >> (raw >> 7);
+            if part.compressed {
+                self.save.buf[0x0310 + (raw >> 3) as usize] |= bit;
+            } else {
+                self.save.buf[0x0310 + (raw >> 3) as usize] &= !bit;
+            }
+        } else {
+            buf.fill(0);
+        }
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
@@ -55,6 +55,22 @@ impl game::Game for EXE4RSImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::JP,
+                    variant: save::Variant::RedSun,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -128,6 +144,22 @@ impl game::Game for EXE4BMImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::JP,
+                    variant: save::Variant::BlueMoon,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -201,6 +233,22 @@ impl game::Game for BN4RSImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::US,
+                    variant: save::Variant::RedSun,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -274,6 +322,22 @@ impl game::Game for BN4BMImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::US,
+                    variant: save::Variant::BlueMoon,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -83,4 +83,13 @@ impl Munger {
     pub(super) fn set_copy_data_input_state(&self, mut core: mgba::core::CoreMutRef, v: u8) {
         core.raw_write_8(self.offsets.ewram.copy_data_input_state, -1, v);
     }
+
+    pub(super) fn desync_check_regions(&self) -> Vec<(u32, u32)> {
+        vec![
+            (self.offsets.ewram.rng1_state, 4),
+            (self.offsets.ewram.rng2_state, 4),
+            (self.offsets.ewram.rng3_state, 4),
+            (self.offsets.ewram.tx_packet, 0x10),
+        ]
+    }
 }
@@ -1,6 +1,6 @@
 use byteorder::ByteOrder;
 
-use crate::save;
+use crate::save::{self, ChipsView as _};
 
 const SRAM_SIZE: usize = 0x73d2;
 const MASK_OFFSET: usize = 0x1554;
@@ -122,14 +122,20 @@ impl Save {
         Ok(Self { buf, game_info, shift })
     }
 
+    /// Makes a save from the other region's release. The region is only encoded in the checksum.
+    pub fn from_other_region(buf: &[u8], game_info: GameInfo) -> Result<Self, anyhow::Error> {
+        let mut save = Self::from_wram(buf, game_info)?;
+        save.rebuild_checksum();
+        Ok(save)
+    }
+
     #[allow(dead_code)]
     pub fn checksum(&self) -> u32 {
         byteorder::LittleEndian::read_u32(&self.buf[self.shift + CHECKSUM_OFFSET..self.shift + CHECKSUM_OFFSET + 4])
     }
 
-    #[allow(dead_code)]
     pub fn compute_checksum(&self) -> u32 {
-        compute_raw_checksum(&self.buf[self.shift..], self.shift) + checksum_start_for_variant(self.game_info.variant)
+        compute_raw_checksum(&self.buf, self.shift) + checksum_start_for_variant(self.game_info.variant)
             - if self.game_info.region == Region::JP {
                 self.buf[0] as u32
             } else {
@@ -137,6 +143,14 @@ impl Save {
             }
     }
 
+    pub fn rebuild_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        byteorder::LittleEndian::write_u32(
+            &mut self.buf[self.shift + CHECKSUM_OFFSET..self.shift + CHECKSUM_OFFSET + 4],
+            checksum,
+        );
+    }
+
     pub fn game_info(&self) -> &GameInfo {
         &self.game_info
     }
@@ -159,6 +173,20 @@ impl save::Save for Save {
         Some(Box::new(DarkAIView { save: self }))
     }
 
+    fn view_chips_mut(&mut self) -> Option<Box<dyn save::ChipsViewMut + '_>> {
+        Some(Box::new(ChipsViewMut { save: self }))
+    }
+
+    fn view_navicust_mut(&mut self) -> Option<Box<dyn save::NavicustViewMut + '_>> {
+        Some(Box::new(NavicustViewMut { save: self }))
+    }
+
+    fn view_modcards_mut(&mut self) -> Option<save::ModcardsViewMut> {
+        Some(save::ModcardsViewMut::Modcard4s(Box::new(Modcard4sViewMut {
+            save: self,
+        })))
+    }
+
     fn as_raw_wram(&self) -> &[u8] {
         &self.buf
     }
@@ -280,6 +308,116 @@ impl<'a> save::Modcard4sView<'a> for Modcard4sView<'a> {
     }
 }
 
+pub struct ChipsViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::ChipsViewMut<'a> for ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() {
+            anyhow::bail!("invalid folder index: {}", folder_index);
+        }
+        self.save.buf[self.save.shift + 0x2132] = folder_index as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &save::Chip) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index >= 30 {
+            anyhow::bail!("invalid chip position: {}, {}", folder_index, chip_index);
+        }
+        let code = save::chip_code_index(chip.code).ok_or_else(|| anyhow::anyhow!("invalid code: {}", chip.code))?;
+        if chip.id >= 0x200 {
+            anyhow::bail!("invalid chip id: {}", chip.id);
+        }
+
+        let offset = self.save.shift + 0x262c + folder_index * (30 * 2) + chip_index * 2;
+        byteorder::LittleEndian::write_u16(
+            &mut self.save.buf[offset..offset + 2],
+            chip.id as u16 | (code as u16) << 9,
+        );
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_regular_chip_index(&mut self, folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index.unwrap_or(0) >= 30 {
+            anyhow::bail!("invalid chip position: {}, {:?}", folder_index, chip_index);
+        }
+        self.save.buf[self.save.shift + 0x214d + folder_index] = chip_index.map(|i| i as u8).unwrap_or(0xff);
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_tag_chip_indexes(&mut self, _folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()> {
+        if chip_indexes.is_some() {
+            anyhow::bail!("tag chips are not supported");
+        }
+        Ok(())
+    }
+}
+
+pub struct NavicustViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::NavicustViewMut<'a> for NavicustViewMut<'a> {
+    fn set_navicust_part(&mut self, i: usize, part: Option<&save::NavicustPart>) -> anyhow::Result<()> {
+        if i >= 25 {
+            anyhow::bail!("invalid navicust part index: {}", i);
+        }
+
+        let offset = self.save.shift + 0x4564;
+        let buf = &mut self.save.buf[offset + i * 8..offset + (i + 1) * 8];
+        if let Some(part) = part {
+            if part.variant >= 4 || part.id * 4 + part.variant > 0xff {
+                anyhow::bail!("invalid navicust part: {:?}", part);
+            }
+            buf[0x0] = (part.id * 4 + part.variant) as u8;
+            buf[0x2] = part.col;
+            buf[0x3] = part.row;
+            buf[0x4] = part.rot;
+            buf[0x5] = part.compressed as u8;
+        } else {
+            buf.fill(0);
+        }
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
+
+pub struct Modcard4sViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::Modcard4sViewMut<'a> for Modcard4sViewMut<'a> {
+    fn set_modcard(&mut self, slot: usize, modcard: Option<&save::Modcard>) -> anyhow::Result<()> {
+        if slot >= 7 {
+            anyhow::bail!("invalid modcard slot: {}", slot);
+        }
+
+        // Disabled modcards are moved out of the first half of the table into the second, leaving behind an invalid ID.
+        // Empty slots have an invalid ID in both halves.
+        let offset = self.save.shift + 0x464c;
+        if let Some(modcard) = modcard {
+            if modcard.id >= 0x85 {
+                anyhow::bail!("invalid modcard id: {}", modcard.id);
+            }
+            if modcard.enabled {
+                self.save.buf[offset + slot] = modcard.id as u8;
+            } else {
+                self.save.buf[offset + slot] = 0xff;
+            }
+            self.save.buf[offset + 7 + slot] = modcard.id as u8;
+        } else {
+            self.save.buf[offset + slot] = 0xff;
+            self.save.buf[offset + 7 + slot] = 0xff;
+        }
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
+
 pub struct DarkAIView<'a> {
     save: &'a Save,
 }
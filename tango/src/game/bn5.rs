@@ -57,6 +57,22 @@ impl game::Game for EXE5BImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::JP,
+                    variant: save::Variant::Protoman,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -127,6 +143,22 @@ impl game::Game for EXE5CImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::JP,
+                    variant: save::Variant::Colonel,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -197,6 +229,22 @@ impl game::Game for BN5PImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::US,
+                    variant: save::Variant::Protoman,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -267,6 +315,22 @@ impl game::Game for BN5CImpl {
         )?))
     }
 
+    fn save_from_other_region(
+        &self,
+        data: &[u8],
+    ) -> Option<Result<Box<dyn crate::save::Save + Send + Sync>, anyhow::Error>> {
+        Some(
+            save::Save::from_other_region(
+                data,
+                save::GameInfo {
+                    region: save::Region::US,
+                    variant: save::Variant::Colonel,
+                },
+            )
+            .map(|save| Box::new(save) as Box<dyn crate::save::Save + Send + Sync>),
+        )
+    }
+
     fn load_rom_assets(
         &self,
         rom: &[u8],
@@ -1,6 +1,6 @@
 use byteorder::ByteOrder;
 
-use crate::save;
+use crate::save::{self, ChipsView as _};
 
 const SRAM_START_OFFSET: usize = 0x0100;
 const SRAM_SIZE: usize = 0x7c14;
@@ -86,6 +86,21 @@ impl Save {
         })
     }
 
+    /// Makes a save from the other region's release, which only differs in the game name.
+    pub fn from_other_region(buf: &[u8], game_info: GameInfo) -> Result<Self, anyhow::Error> {
+        let mut save = Self::from_wram(buf, game_info)?;
+        save.buf[GAME_NAME_OFFSET..GAME_NAME_OFFSET + 20].copy_from_slice(
+            match (save.game_info.region, save.game_info.variant) {
+                (Region::JP, Variant::Protoman) => b"REXE5TOB 20041104 JP",
+                (Region::JP, Variant::Colonel) => b"REXE5TOK 20041104 JP",
+                (Region::US, Variant::Protoman) => b"REXE5TOB 20041006 US",
+                (Region::US, Variant::Colonel) => b"REXE5TOK 20041006 US",
+            },
+        );
+        save.rebuild_checksum();
+        Ok(save)
+    }
+
     pub fn game_info(&self) -> &GameInfo {
         &self.game_info
     }
@@ -101,6 +116,11 @@ impl Save {
                 Variant::Colonel => 0x18,
             }
     }
+
+    pub fn rebuild_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        byteorder::LittleEndian::write_u32(&mut self.buf[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4], checksum);
+    }
 }
 
 impl save::Save for Save {
@@ -116,6 +136,20 @@ impl save::Save for Save {
         Some(save::ModcardsView::Modcard56s(Box::new(Modcard56sView { save: self })))
     }
 
+    fn view_chips_mut(&mut self) -> Option<Box<dyn save::ChipsViewMut + '_>> {
+        Some(Box::new(ChipsViewMut { save: self }))
+    }
+
+    fn view_navicust_mut(&mut self) -> Option<Box<dyn save::NavicustViewMut + '_>> {
+        Some(Box::new(NavicustViewMut { save: self }))
+    }
+
+    fn view_modcards_mut(&mut self) -> Option<save::ModcardsViewMut> {
+        Some(save::ModcardsViewMut::Modcard56s(Box::new(Modcard56sViewMut {
+            save: self,
+        })))
+    }
+
     // fn view_navi(&self) -> Option<Box<dyn save::NaviView + '_>> {
     //     Some(Box::new(NaviView { save: self }))
     // }
@@ -245,6 +279,122 @@ impl<'a> save::NavicustView<'a> for NavicustView<'a> {
     }
 }
 
+pub struct ChipsViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::ChipsViewMut<'a> for ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() {
+            anyhow::bail!("invalid folder index: {}", folder_index);
+        }
+        self.save.buf[0x52d5] = folder_index as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &save::Chip) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index >= 30 {
+            anyhow::bail!("invalid chip position: {}, {}", folder_index, chip_index);
+        }
+        let code = save::chip_code_index(chip.code).ok_or_else(|| anyhow::anyhow!("invalid code: {}", chip.code))?;
+        if chip.id >= 0x200 {
+            anyhow::bail!("invalid chip id: {}", chip.id);
+        }
+
+        let offset = 0x2df4 + folder_index * (30 * 2) + chip_index * 2;
+        byteorder::LittleEndian::write_u16(
+            &mut self.save.buf[offset..offset + 2],
+            chip.id as u16 | (code as u16) << 9,
+        );
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_regular_chip_index(&mut self, folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index.unwrap_or(0) >= 30 {
+            anyhow::bail!("invalid chip position: {}, {:?}", folder_index, chip_index);
+        }
+        self.save.buf[0x52d6 + folder_index] = chip_index.map(|i| i as u8).unwrap_or(0xff);
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_tag_chip_indexes(&mut self, _folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()> {
+        if chip_indexes.is_some() {
+            anyhow::bail!("tag chips are not supported");
+        }
+        Ok(())
+    }
+}
+
+pub struct Modcard56sViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::Modcard56sViewMut<'a> for Modcard56sViewMut<'a> {
+    fn set_modcard(&mut self, slot: usize, modcard: &save::Modcard) -> anyhow::Result<()> {
+        if slot >= self.save.buf[0x79a0] as usize {
+            anyhow::bail!("invalid modcard slot: {}", slot);
+        }
+        if modcard.id >= 0x80 {
+            anyhow::bail!("invalid modcard id: {}", modcard.id);
+        }
+        self.save.buf[0x79d0 + slot] = modcard.id as u8 | if modcard.enabled { 0 } else { 0x80 };
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_modcards(&mut self, modcards: &[save::Modcard]) -> anyhow::Result<()> {
+        // Each modcard can only be installed once, so the list never has more entries than there are IDs.
+        if modcards.len() > 0x80 {
+            anyhow::bail!("too many modcards: {}", modcards.len());
+        }
+        if let Some(modcard) = modcards.iter().find(|modcard| modcard.id >= 0x80) {
+            anyhow::bail!("invalid modcard id: {}", modcard.id);
+        }
+
+        // Clear out whatever was left past the end of the new list.
+        let old_count = self.save.buf[0x79a0] as usize;
+        self.save.buf[0x79d0 + modcards.len()..0x79d0 + old_count.max(modcards.len())].fill(0);
+        for (slot, modcard) in modcards.iter().enumerate() {
+            self.save.buf[0x79d0 + slot] = modcard.id as u8 | if modcard.enabled { 0 } else { 0x80 };
+        }
+        self.save.buf[0x79a0] = modcards.len() as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
+
+pub struct NavicustViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::NavicustViewMut<'a> for NavicustViewMut<'a> {
+    fn set_navicust_part(&mut self, i: usize, part: Option<&save::NavicustPart>) -> anyhow::Result<()> {
+        if i >= 25 {
+            anyhow::bail!("invalid navicust part index: {}", i);
+        }
+
+        let offset = 0x4d6c;
+        let buf = &mut self.save.buf[offset + i * 8..offset + (i + 1) * 8];
+        if let Some(part) = part {
+            if part.variant >= 4 || part.id * 4 + part.variant > 0xff {
+                anyhow::bail!("invalid navicust part: {:?}", part);
+            }
+            buf[0x0] = (part.id * 4 + part.variant) as u8;
+            buf[0x2] = part.col;
+            buf[0x3] = part.row;
+            buf[0x4] = part.rot;
+            buf[0x5] = part.compressed as u8;
+        } else {
+            buf.fill(0);
+        }
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
+
 pub struct DarkAIView<'a> {
     save: &'a Save,
 }
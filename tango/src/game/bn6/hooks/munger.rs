@@ -1,3 +1,6 @@
+// The battle state's tick counter, which is the last field of it the munger knows about.
+const BATTLE_STATE_CURRENT_TICK: u32 = 0x60;
+
 #[derive(Clone)]
 pub(super) struct Munger {
     pub(super) offsets: &'static super::offsets::Offsets,
@@ -74,14 +77,24 @@ impl Munger {
     }
 
     pub(super) fn current_tick(&self, mut core: mgba::core::CoreMutRef) -> u32 {
-        core.raw_read_32(self.offsets.ewram.battle_state + 0x60, -1)
+        core.raw_read_32(self.offsets.ewram.battle_state + BATTLE_STATE_CURRENT_TICK, -1)
     }
 
     pub(super) fn set_current_tick(&self, mut core: mgba::core::CoreMutRef, v: u32) {
-        core.raw_write_32(self.offsets.ewram.battle_state + 0x60, -1, v)
+        core.raw_write_32(self.offsets.ewram.battle_state + BATTLE_STATE_CURRENT_TICK, -1, v)
     }
 
     pub(super) fn set_copy_data_input_state(&self, mut core: mgba::core::CoreMutRef, v: u8) {
         core.raw_write_8(self.offsets.ewram.copy_data_input_state, -1, v);
     }
+
+    pub(super) fn desync_check_regions(&self) -> Vec<(u32, u32)> {
+        vec![
+            (self.offsets.ewram.rng1_state, 4),
+            (self.offsets.ewram.rng2_state, 4),
+            (self.offsets.ewram.rng3_state, 4),
+            (self.offsets.ewram.tx_packet, 0x10),
+            (self.offsets.ewram.battle_state, BATTLE_STATE_CURRENT_TICK + 4),
+        ]
+    }
 }
@@ -1,6 +1,6 @@
 use byteorder::ByteOrder;
 
-use crate::save::{self, NaviView as _};
+use crate::save::{self, ChipsView as _, NaviView as _};
 
 const SRAM_START_OFFSET: usize = 0x0100;
 const SRAM_SIZE: usize = 0x6710;
@@ -102,6 +102,19 @@ impl Save {
             }
     }
 
+    pub fn rebuild_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        byteorder::LittleEndian::write_u32(&mut self.buf[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4], checksum);
+    }
+
+    fn navicust_offset(&self) -> usize {
+        if self.game_info.region == Region::JP {
+            0x4150
+        } else {
+            0x4190
+        }
+    }
+
     fn navi_stats_offset(&self, id: usize) -> usize {
         (if self.game_info.region == Region::JP {
             0x478c
@@ -128,6 +141,24 @@ impl save::Save for Save {
         }
     }
 
+    fn view_chips_mut(&mut self) -> Option<Box<dyn save::ChipsViewMut + '_>> {
+        Some(Box::new(ChipsViewMut { save: self }))
+    }
+
+    fn view_navicust_mut(&mut self) -> Option<Box<dyn save::NavicustViewMut + '_>> {
+        Some(Box::new(NavicustViewMut { save: self }))
+    }
+
+    fn view_modcards_mut(&mut self) -> Option<save::ModcardsViewMut> {
+        if self.game_info.region == Region::JP {
+            Some(save::ModcardsViewMut::Modcard56s(Box::new(Modcard56sViewMut {
+                save: self,
+            })))
+        } else {
+            None
+        }
+    }
+
     // fn view_navi(&self) -> Option<Box<dyn save::NaviView + '_>> {
     //     Some(Box::new(NaviView { save: self }))
     // }
@@ -245,12 +276,7 @@ impl<'a> save::NavicustView<'a> for NavicustView<'a> {
             return None;
         }
 
-        let ncp_offset = if self.save.game_info.region == Region::JP {
-            0x4150
-        } else {
-            0x4190
-        };
-
+        let ncp_offset = self.save.navicust_offset();
         let buf = &self.save.buf[ncp_offset + i * 8..ncp_offset + (i + 1) * 8];
         let raw = buf[0];
         if raw == 0 {
@@ -267,6 +293,131 @@ impl<'a> save::NavicustView<'a> for NavicustView<'a> {
         })
     }
 }
+pub struct ChipsViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::ChipsViewMut<'a> for ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() {
+            anyhow::bail!("invalid folder index: {}", folder_index);
+        }
+        let navi_stats_offset = self.save.navi_stats_offset(NaviView { save: &*self.save }.navi());
+        self.save.buf[navi_stats_offset + 0x2d] = folder_index as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &save::Chip) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index >= 30 {
+            anyhow::bail!("invalid chip position: {}, {}", folder_index, chip_index);
+        }
+        let code = save::chip_code_index(chip.code).ok_or_else(|| anyhow::anyhow!("invalid code: {}", chip.code))?;
+        if chip.id >= 0x200 {
+            anyhow::bail!("invalid chip id: {}", chip.id);
+        }
+
+        let offset = 0x2178 + folder_index * (30 * 2) + chip_index * 2;
+        byteorder::LittleEndian::write_u16(
+            &mut self.save.buf[offset..offset + 2],
+            chip.id as u16 | (code as u16) << 9,
+        );
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_regular_chip_index(&mut self, folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders() || chip_index.unwrap_or(0) >= 30 {
+            anyhow::bail!("invalid chip position: {}, {:?}", folder_index, chip_index);
+        }
+        let navi_stats_offset = self.save.navi_stats_offset(NaviView { save: &*self.save }.navi());
+        self.save.buf[navi_stats_offset + 0x2e + folder_index] = chip_index.map(|i| i as u8).unwrap_or(0xff);
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_tag_chip_indexes(&mut self, folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()> {
+        if folder_index >= (ChipsView { save: &*self.save }).num_folders()
+            || chip_indexes.map(|[a, b]| a >= 30 || b >= 30 || a == b).unwrap_or(false)
+        {
+            anyhow::bail!("invalid tag chip positions: {}, {:?}", folder_index, chip_indexes);
+        }
+        let navi_stats_offset = self.save.navi_stats_offset(NaviView { save: &*self.save }.navi());
+        let [idx1, idx2] = chip_indexes.map(|[a, b]| [a as u8, b as u8]).unwrap_or([0xff, 0xff]);
+        self.save.buf[navi_stats_offset + 0x56 + folder_index * 2 + 0x00] = idx1;
+        self.save.buf[navi_stats_offset + 0x56 + folder_index * 2 + 0x01] = idx2;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
+
+pub struct Modcard56sViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::Modcard56sViewMut<'a> for Modcard56sViewMut<'a> {
+    fn set_modcard(&mut self, slot: usize, modcard: &save::Modcard) -> anyhow::Result<()> {
+        if slot >= self.save.buf[0x65f0] as usize {
+            anyhow::bail!("invalid modcard slot: {}", slot);
+        }
+        if modcard.id >= 0x80 {
+            anyhow::bail!("invalid modcard id: {}", modcard.id);
+        }
+        self.save.buf[0x6620 + slot] = modcard.id as u8 | if modcard.enabled { 0 } else { 0x80 };
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_modcards(&mut self, modcards: &[save::Modcard]) -> anyhow::Result<()> {
+        // Each modcard can only be installed once, so the list never has more entries than there are IDs.
+        if modcards.len() > 0x80 {
+            anyhow::bail!("too many modcards: {}", modcards.len());
+        }
+        if let Some(modcard) = modcards.iter().find(|modcard| modcard.id >= 0x80) {
+            anyhow::bail!("invalid modcard id: {}", modcard.id);
+        }
+
+        // Clear out whatever was left past the end of the new list.
+        let old_count = self.save.buf[0x65f0] as usize;
+        self.save.buf[0x6620 + modcards.len()..0x6620 + old_count.max(modcards.len())].fill(0);
+        for (slot, modcard) in modcards.iter().enumerate() {
+            self.save.buf[0x6620 + slot] = modcard.id as u8 | if modcard.enabled { 0 } else { 0x80 };
+        }
+        self.save.buf[0x65f0] = modcards.len() as u8;
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
+
+pub struct NavicustViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::NavicustViewMut<'a> for NavicustViewMut<'a> {
+    fn set_navicust_part(&mut self, i: usize, part: Option<&save::NavicustPart>) -> anyhow::Result<()> {
+        if i >= 25 {
+            anyhow::bail!("invalid navicust part index: {}", i);
+        }
+
+        let ncp_offset = self.save.navicust_offset();
+        let buf = &mut self.save.buf[ncp_offset + i * 8..ncp_offset + (i + 1) * 8];
+        if let Some(part) = part {
+            if part.variant >= 4 || part.id * 4 + part.variant > 0xff {
+                anyhow::bail!("invalid navicust part: {:?}", part);
+            }
+            buf[0x0] = (part.id * 4 + part.variant) as u8;
+            buf[0x3] = part.col;
+            buf[0x4] = part.row;
+            buf[0x5] = part.rot;
+            buf[0x6] = part.compressed as u8;
+        } else {
+            buf.fill(0);
+        }
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+}
+
 pub struct NaviView<'a> {
     save: &'a Save,
 }
@@ -57,6 +57,11 @@ impl Save {
             + 0x38
     }
 
+    pub fn rebuild_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        byteorder::LittleEndian::write_u32(&mut self.buf[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4], checksum);
+    }
+
     pub fn from_wram(buf: &[u8]) -> Result<Self, anyhow::Error> {
         Ok(Self {
             buf: buf
@@ -80,6 +85,10 @@ impl save::Save for Save {
         Some(Box::new(NaviView { save: self }))
     }
 
+    fn view_chips_mut(&mut self) -> Option<Box<dyn save::ChipsViewMut + '_>> {
+        Some(Box::new(ChipsViewMut { save: self }))
+    }
+
     fn as_raw_wram(&self) -> &[u8] {
         &self.buf
     }
@@ -132,6 +141,51 @@ impl<'a> save::ChipsView<'a> for ChipsView<'a> {
     }
 }
 
+pub struct ChipsViewMut<'a> {
+    save: &'a mut Save,
+}
+
+impl<'a> save::ChipsViewMut<'a> for ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()> {
+        if folder_index >= 1 {
+            anyhow::bail!("invalid folder index: {}", folder_index);
+        }
+        Ok(())
+    }
+
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &save::Chip) -> anyhow::Result<()> {
+        if folder_index >= 1 || chip_index >= 30 {
+            anyhow::bail!("invalid chip position: {}, {}", folder_index, chip_index);
+        }
+        let code = save::chip_code_index(chip.code).ok_or_else(|| anyhow::anyhow!("invalid code: {}", chip.code))?;
+        if chip.id >= 0x200 {
+            anyhow::bail!("invalid chip id: {}", chip.id);
+        }
+
+        let offset = 0x7500 + self.save.current_navi() as usize * (30 * 2) + chip_index * 2;
+        byteorder::LittleEndian::write_u16(
+            &mut self.save.buf[offset..offset + 2],
+            chip.id as u16 | (code as u16) << 9,
+        );
+        self.save.rebuild_checksum();
+        Ok(())
+    }
+
+    fn set_regular_chip_index(&mut self, _folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()> {
+        if chip_index.is_some() {
+            anyhow::bail!("regular chips are not supported");
+        }
+        Ok(())
+    }
+
+    fn set_tag_chip_indexes(&mut self, _folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()> {
+        if chip_indexes.is_some() {
+            anyhow::bail!("tag chips are not supported");
+        }
+        Ok(())
+    }
+}
+
 pub struct NaviView<'a> {
     save: &'a Save,
 }
@@ -1,6 +1,6 @@
 use fluent_templates::Loader;
 
-use crate::{audio, config, discord, game, i18n, input, patch, rom, save, session, stats, updater};
+use crate::{audio, config, discord, fonts, game, i18n, input, patch, rom, save, session, stats, updater};
 use std::str::FromStr;
 
 mod debug_window;
@@ -11,6 +11,7 @@ mod patches_pane;
 mod play_pane;
 mod replay_dump_windows;
 mod replays_pane;
+mod save_diff_view;
 mod save_select_view;
 mod save_view;
 mod session_view;
@@ -27,6 +28,10 @@ pub struct Selection {
     pub rom: Vec<u8>,
     pub patch: Option<(String, semver::Version, patch::Version)>,
     pub save_view_state: save_view::State,
+    // Another save of the same game to compare against, along with the differences from this one.
+    pub compare: Option<(save::ScannedSave, save::diff::Diff)>,
+    // Snapshots of this save along with how each differs from it, loaded when first needed.
+    pub history: Option<Vec<(save::history::Snapshot, Option<save::diff::Diff>)>>,
 }
 
 impl Selection {
@@ -53,6 +58,8 @@ impl Selection {
             patch,
             rom,
             save_view_state: save_view::State::new(),
+            compare: None,
+            history: None,
         }
     }
 
@@ -60,6 +67,8 @@ impl Selection {
         let raw = std::fs::read(&self.save.path)?;
         self.save.save = self.game.parse_save(&raw)?;
         self.save_view_state = save_view::State::new();
+        self.compare = None;
+        self.history = None;
         Ok(())
     }
 }
@@ -106,19 +115,19 @@ impl State {
         let font_families = FontFamilies {
             latn: FontFamily {
                 egui: egui::FontFamily::Name("Latn".into()),
-                raw: include_bytes!("fonts/NotoSans-Regular.ttf"),
+                raw: fonts::LATN,
             },
             jpan: FontFamily {
                 egui: egui::FontFamily::Name("Jpan".into()),
-                raw: include_bytes!("fonts/NotoSansJP-Regular.otf"),
+                raw: fonts::JPAN,
             },
             hans: FontFamily {
                 egui: egui::FontFamily::Name("Hans".into()),
-                raw: include_bytes!("fonts/NotoSansSC-Regular.otf"),
+                raw: fonts::HANS,
             },
             hant: FontFamily {
                 egui: egui::FontFamily::Name("Hant".into()),
-                raw: include_bytes!("fonts/NotoSansTC-Regular.otf"),
+                raw: fonts::HANT,
             },
         };
 
@@ -376,6 +385,8 @@ pub fn show(
         &mut state.show_escape_window,
         &config.language,
         &mut state.show_settings,
+        state.saves_scanner.clone(),
+        config.saves_path(),
     );
     replay_dump_windows::show(
         ctx,
@@ -394,10 +405,11 @@ pub fn show(
             input_state,
             &config.input_mapping,
             session,
-            &config.video_filter,
+            &config.video_filters,
             config.integer_scaling,
             config.max_scale,
             config.speed_change_percent as f32 / 100.0,
+            config.slow_motion_percent as f32 / 100.0,
             config.show_own_setup,
             &config.crashstates_path(),
             &state.last_mouse_motion_time,
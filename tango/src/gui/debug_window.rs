@@ -1,15 +1,157 @@
 use fluent_templates::Loader;
 
-use crate::{i18n, session};
+use crate::{cheats, disassembler, i18n, session};
+
+const FONT_WIDTH: f32 = 8.0;
+
+// How many instructions to show before and after PC.
+const DISASSEMBLY_BEFORE: u32 = 8;
+const DISASSEMBLY_AFTER: u32 = 24;
+
+// Listing every candidate of a fresh search would be hundreds of thousands of rows, so only the first few are shown.
+const SEARCH_CANDIDATES_SHOWN: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Memory,
+    CPU,
+    Breakpoints,
+    Cheats,
+}
 
 pub struct State {
+    tab: Tab,
     jump_to: String,
+    disassemble_at: String,
+    new_breakpoint: String,
+    new_breakpoint_mode: mgba::arm_core::ExecutionMode,
+    new_watchpoint_start: String,
+    new_watchpoint_end: String,
+    search: Option<cheats::Search>,
+    search_value_type: cheats::ValueType,
+    search_comparison: cheats::Comparison,
+    search_against_previous: bool,
+    search_value: String,
+    new_watch_name: String,
+    new_watch_address: String,
+    new_watch_value_type: cheats::ValueType,
+    editing_watch: Option<(usize, String)>,
+    new_code_name: String,
+    new_code: String,
+    code_error: Option<String>,
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
+            tab: Tab::Memory,
             jump_to: "".to_string(),
+            disassemble_at: "".to_string(),
+            new_breakpoint: "".to_string(),
+            new_breakpoint_mode: mgba::arm_core::ExecutionMode::Thumb,
+            new_watchpoint_start: "".to_string(),
+            new_watchpoint_end: "".to_string(),
+            search: None,
+            search_value_type: cheats::ValueType::U8,
+            search_comparison: cheats::Comparison::Equal,
+            search_against_previous: true,
+            search_value: "".to_string(),
+            new_watch_name: "".to_string(),
+            new_watch_address: "".to_string(),
+            new_watch_value_type: cheats::ValueType::U8,
+            editing_watch: None,
+            new_code_name: "".to_string(),
+            new_code: "".to_string(),
+            code_error: None,
+        }
+    }
+}
+
+// Changes to make once the window is drawn, as they need the emulator thread and can't be made while the core is
+// locked for drawing.
+enum Action {
+    Continue,
+    Pause,
+    StepInto,
+    StepOver,
+    AddBreakpoint(u32, mgba::arm_core::ExecutionMode),
+    RemoveBreakpoint(u32),
+    AddWatchpoint(std::ops::Range<u32>),
+    RemoveWatchpoint(std::ops::Range<u32>),
+}
+
+fn hex_input(ui: &mut egui::Ui, value: &mut String, hint: &str) -> egui::Response {
+    let resp = ui.add(
+        egui::TextEdit::singleline(value)
+            .desired_width(8.0 * FONT_WIDTH)
+            .hint_text(hint)
+            .font(egui::TextStyle::Monospace),
+    );
+    *value = value
+        .chars()
+        .filter(|c| "0123456789abcdefABCDEF".chars().any(|c2| c2 == *c))
+        .take(8)
+        .collect();
+    resp
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value, 16).ok()
+}
+
+fn mode_label(mode: mgba::arm_core::ExecutionMode) -> &'static str {
+    match mode {
+        mgba::arm_core::ExecutionMode::ARM => "ARM",
+        mgba::arm_core::ExecutionMode::Thumb => "THUMB",
+    }
+}
+
+fn value_type_label(value_type: cheats::ValueType) -> &'static str {
+    match value_type {
+        cheats::ValueType::U8 => "u8",
+        cheats::ValueType::U16 => "u16",
+        cheats::ValueType::U32 => "u32",
+        cheats::ValueType::I8 => "s8",
+        cheats::ValueType::I16 => "s16",
+        cheats::ValueType::I32 => "s32",
+    }
+}
+
+fn comparison_label(comparison: cheats::Comparison) -> &'static str {
+    match comparison {
+        cheats::Comparison::Equal => "=",
+        cheats::Comparison::NotEqual => "≠",
+        cheats::Comparison::GreaterThan => ">",
+        cheats::Comparison::LessThan => "<",
+    }
+}
+
+fn value_type_combo_box(ui: &mut egui::Ui, id: &str, value_type: &mut cheats::ValueType) {
+    egui::ComboBox::from_id_source(id)
+        .width(6.0 * FONT_WIDTH)
+        .selected_text(value_type_label(*value_type))
+        .show_ui(ui, |ui| {
+            for vt in cheats::ValueType::ALL {
+                ui.selectable_value(value_type, vt, value_type_label(vt));
+            }
+        });
+}
+
+fn disassemble(core: &mut mgba::core::CoreMutRef, address: u32, mode: mgba::arm_core::ExecutionMode) -> (u32, String) {
+    let debugger = core.debugger();
+    let original = |address: u32| debugger.as_ref().and_then(|debugger| debugger.original_opcode(address));
+    match mode {
+        mgba::arm_core::ExecutionMode::Thumb => {
+            let opcode = original(address).unwrap_or_else(|| core.raw_read_16(address, -1) as u32) as u16;
+            let next_opcode = original(address + 2).unwrap_or_else(|| core.raw_read_16(address + 2, -1) as u32) as u16;
+            (
+                opcode as u32,
+                disassembler::disassemble_thumb(address, opcode, next_opcode),
+            )
+        }
+        mgba::arm_core::ExecutionMode::ARM => {
+            let opcode = original(address).unwrap_or_else(|| core.raw_read_32(address, -1));
+            (opcode, disassembler::disassemble_arm(address, opcode))
         }
     }
 }
@@ -25,101 +167,706 @@ pub fn show(
         .id(egui::Id::new("debug"))
         .open(&mut open)
         .show(ctx, |ui| {
+            let state = state.as_mut().unwrap();
+
             ui.horizontal(|ui| {
-                let _ = ui.selectable_label(true, "Memory");
+                ui.selectable_value(&mut state.tab, Tab::Memory, "Memory");
+                ui.selectable_value(&mut state.tab, Tab::CPU, "CPU");
+                ui.selectable_value(&mut state.tab, Tab::Breakpoints, "Breakpoints");
+                ui.selectable_value(&mut state.tab, Tab::Cheats, "Cheats");
             });
 
             ui.separator();
 
-            let state = state.as_mut().unwrap();
+            let mut actions = vec![];
+            match state.tab {
+                Tab::Memory => show_memory(ui, session, state),
+                Tab::CPU => show_cpu(ui, session, state, &mut actions),
+                Tab::Breakpoints => show_breakpoints(ui, session, state, &mut actions),
+                Tab::Cheats => show_cheats(ui, session, state),
+            }
 
-            let mut jumping = false;
-            ui.horizontal(|ui| {
-                let input_resp = ui.add(
-                    egui::TextEdit::singleline(&mut state.jump_to)
-                        .desired_width(8.0 * FONT_WIDTH)
-                        .hint_text("Jump to")
-                        .font(egui::TextStyle::Monospace),
-                );
-                state.jump_to = state
-                    .jump_to
-                    .chars()
-                    .filter(|c| "0123456789abcdefABCDEF".chars().any(|c2| c2 == *c))
-                    .collect();
-                if input_resp.lost_focus() && ui.ctx().input().key_pressed(egui::Key::Enter) {
-                    jumping = true;
-                }
+            for action in actions {
+                apply_action(session, action);
+            }
+        });
+    if !open {
+        *state = None;
+    }
+}
 
-                if ui.button("Go!").clicked() {
-                    jumping = true;
+fn apply_action(session: &session::Session, action: Action) {
+    let thread_handle = session.thread_handle();
+    match action {
+        Action::Continue => {
+            {
+                let mut audio_guard = thread_handle.lock_audio();
+                let mut core = audio_guard.core_mut();
+                if let Some(mut debugger) = core.debugger() {
+                    debugger.clear_stop_reason();
+                }
+            }
+            session.set_paused(false);
+        }
+        Action::Pause => {
+            session.set_paused(true);
+        }
+        Action::StepInto => {
+            thread_handle.run_on_core(|mut core| {
+                if let Some(mut debugger) = core.debugger() {
+                    debugger.step_into();
+                }
+            });
+        }
+        Action::StepOver => {
+            // run_on_core only returns once the function has run, so the result is there by then.
+            let resume = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+            thread_handle.run_on_core({
+                let resume = resume.clone();
+                move |mut core| {
+                    if let Some(mut debugger) = core.debugger() {
+                        resume.store(debugger.step_over(), std::sync::atomic::Ordering::SeqCst);
+                    }
+                }
+            });
+            if resume.load(std::sync::atomic::Ordering::SeqCst) {
+                session.set_paused(false);
+            }
+        }
+        Action::AddBreakpoint(address, mode) => {
+            thread_handle.run_on_core(move |mut core| {
+                if let Some(mut debugger) = core.debugger() {
+                    debugger.add_breakpoint(address, mode);
+                }
+            });
+        }
+        Action::RemoveBreakpoint(address) => {
+            thread_handle.run_on_core(move |mut core| {
+                if let Some(mut debugger) = core.debugger() {
+                    debugger.remove_breakpoint(address);
+                }
+            });
+        }
+        Action::AddWatchpoint(range) => {
+            thread_handle.run_on_core(move |mut core| {
+                if let Some(mut debugger) = core.debugger() {
+                    debugger.add_watchpoint(range.clone());
                 }
             });
+        }
+        Action::RemoveWatchpoint(range) => {
+            thread_handle.run_on_core(move |mut core| {
+                if let Some(mut debugger) = core.debugger() {
+                    debugger.remove_watchpoint(range.clone());
+                }
+            });
+        }
+    }
+}
 
-            let thread_handle = session.thread_handle();
-            let mut audio_guard = thread_handle.lock_audio();
+fn show_unavailable(ui: &mut egui::Ui) {
+    ui.label(egui::RichText::new("The debugger is only available in single player and replays.").weak());
+}
+
+fn show_cpu(ui: &mut egui::Ui, session: &session::Session, state: &mut State, actions: &mut Vec<Action>) {
+    let thread_handle = session.thread_handle();
+    let paused = thread_handle.is_paused();
+
+    let mut audio_guard = thread_handle.lock_audio();
+    let mut core = audio_guard.core_mut();
 
-            let row_height = ui.text_style_height(&egui::TextStyle::Body);
-            let mut sa = egui::ScrollArea::vertical().auto_shrink([true, false]);
-            if jumping {
-                if let Ok(jump_to) = u32::from_str_radix(&state.jump_to, 16) {
-                    sa =
-                        sa.vertical_scroll_offset((row_height + ui.spacing().item_spacing.y) * (jump_to / 0x10) as f32);
+    let (gprs, cpsr, mode) = {
+        let core_ref = core.as_ref();
+        let gba = core_ref.gba();
+        let cpu = gba.cpu();
+        let gprs: [u32; 16] = std::array::from_fn(|r| cpu.gpr(r) as u32);
+        (gprs, cpu.cpsr() as u32, cpu.execution_mode())
+    };
+    let word_size = match mode {
+        mgba::arm_core::ExecutionMode::ARM => 4,
+        mgba::arm_core::ExecutionMode::Thumb => 2,
+    };
+    // Between instructions, PC is one ahead of the next instruction to run.
+    let pc = gprs[15].wrapping_sub(word_size);
+
+    let debugger = core.debugger();
+    let stop_reason = debugger.as_ref().and_then(|debugger| debugger.stop_reason());
+    let breakpoints = debugger
+        .as_ref()
+        .map(|debugger| debugger.breakpoints())
+        .unwrap_or_default();
+    let has_debugger = debugger.is_some();
+
+    if has_debugger {
+        ui.horizontal(|ui| {
+            if paused {
+                if ui.button("▶ Continue").clicked() {
+                    actions.push(Action::Continue);
                 }
+            } else if ui.button("⏸ Pause").clicked() {
+                actions.push(Action::Pause);
+            }
+            if ui.add_enabled(paused, egui::Button::new("Step into")).clicked() {
+                actions.push(Action::StepInto);
+            }
+            if ui.add_enabled(paused, egui::Button::new("Step over")).clicked() {
+                actions.push(Action::StepOver);
             }
 
-            const FONT_WIDTH: f32 = 8.0;
-            sa.show_rows(ui, row_height, 0x0fffffff / 0x10, |ui, range| {
-                egui_extras::StripBuilder::new(ui)
-                    .sizes(egui_extras::Size::exact(row_height), range.len())
-                    .vertical(|mut outer_strip| {
-                        for i in range {
-                            outer_strip.cell(|ui| {
-                                let rect = ui.available_rect_before_wrap().expand(ui.spacing().item_spacing.y);
-                                if i % 2 == 0 {
-                                    ui.painter().rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
-                                }
+            ui.separator();
 
-                                egui_extras::StripBuilder::new(ui)
-                                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                                    .size(egui_extras::Size::exact(8.0 * FONT_WIDTH))
-                                    .size(egui_extras::Size::exact(48.0 * FONT_WIDTH))
-                                    .size(egui_extras::Size::remainder())
-                                    .horizontal(|mut strip| {
-                                        let offset = i * 16;
-                                        strip.cell(|ui| {
-                                            ui.label(egui::RichText::new(format!("{:08x}", offset)).monospace().weak());
-                                        });
-                                        let mut buf = [0u8; 0x10];
-                                        audio_guard.core_mut().raw_read_range(offset as u32, -1, &mut buf[..]);
-                                        strip.cell(|ui| {
-                                            ui.add(
-                                                egui::TextEdit::singleline(
-                                                    &mut buf
-                                                        .iter()
-                                                        .map(|b| format!("{:02x}", b))
-                                                        .collect::<Vec<_>>()
-                                                        .join(" "),
-                                                )
-                                                .desired_width(ui.available_width())
-                                                .frame(false)
-                                                .font(egui::TextStyle::Monospace),
-                                            );
-                                        });
-
-                                        strip.cell(|ui| {
-                                            ui.monospace(
-                                                buf.map(|b| if b >= 32 && b < 127 { b as char } else { '.' })
-                                                    .iter()
-                                                    .collect::<String>(),
-                                            );
-                                        });
-                                    });
-                            });
+            ui.label(match (paused, &stop_reason) {
+                (false, _) => "Running".to_string(),
+                (true, None) => "Paused".to_string(),
+                (true, Some(mgba::debugger::StopReason::Breakpoint { address })) => {
+                    format!("Stopped at breakpoint {:08x}", address)
+                }
+                (
+                    true,
+                    Some(mgba::debugger::StopReason::Watchpoint {
+                        address,
+                        size,
+                        value,
+                        pc,
+                    }),
+                ) => match value {
+                    Some(value) => format!(
+                        "Stopped after {:08x} wrote {:0width$x} to {:08x}",
+                        pc,
+                        value,
+                        address,
+                        width = *size as usize * 2
+                    ),
+                    None => format!("Stopped after {:08x} wrote {} bytes to {:08x}", pc, size, address),
+                },
+            });
+        });
+    } else {
+        show_unavailable(ui);
+    }
+
+    ui.separator();
+
+    egui::Grid::new("debug-registers").num_columns(8).show(ui, |ui| {
+        for (r, value) in gprs.iter().enumerate() {
+            let name = match r {
+                13 => "sp".to_string(),
+                14 => "lr".to_string(),
+                15 => "pc".to_string(),
+                r => format!("r{}", r),
+            };
+            ui.label(egui::RichText::new(name).monospace().weak());
+            ui.monospace(format!("{:08x}", value));
+            if r % 4 == 3 {
+                ui.end_row();
+            }
+        }
+
+        let flags = [(31, 'N'), (30, 'Z'), (29, 'C'), (28, 'V'), (7, 'I'), (6, 'F'), (5, 'T')]
+            .iter()
+            .map(|(bit, flag)| if cpsr & (1 << bit) != 0 { *flag } else { '-' })
+            .collect::<String>();
+        let cpu_mode = match cpsr & 0x1f {
+            0x10 => "usr",
+            0x11 => "fiq",
+            0x12 => "irq",
+            0x13 => "svc",
+            0x17 => "abt",
+            0x1b => "und",
+            0x1f => "sys",
+            _ => "???",
+        };
+        ui.label(egui::RichText::new("cpsr").monospace().weak());
+        ui.monospace(format!("{:08x}", cpsr));
+        ui.monospace(flags);
+        ui.monospace(cpu_mode);
+        ui.monospace(mode_label(mode));
+        ui.end_row();
+    });
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        hex_input(ui, &mut state.disassemble_at, "Follow PC");
+        if ui.button("Follow PC").clicked() {
+            state.disassemble_at.clear();
+        }
+    });
+
+    let center = parse_hex(&state.disassemble_at).unwrap_or(pc) & !(word_size - 1);
+    let start = center.wrapping_sub(DISASSEMBLY_BEFORE * word_size);
+
+    egui::ScrollArea::vertical()
+        .id_source("debug-disassembly")
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            egui::Grid::new("debug-disassembly-grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for i in 0..DISASSEMBLY_BEFORE + DISASSEMBLY_AFTER {
+                        let address = start.wrapping_add(i * word_size);
+                        let has_breakpoint = breakpoints.iter().any(|(a, _)| *a == address);
+                        if has_debugger {
+                            if ui
+                                .selectable_label(has_breakpoint, if has_breakpoint { "●" } else { " " })
+                                .clicked()
+                            {
+                                actions.push(if has_breakpoint {
+                                    Action::RemoveBreakpoint(address)
+                                } else {
+                                    Action::AddBreakpoint(address, mode)
+                                });
+                            }
+                        } else {
+                            ui.label("");
                         }
+
+                        let (opcode, text) = disassemble(&mut core, address, mode);
+                        let color = if address == pc {
+                            ui.visuals().strong_text_color()
+                        } else {
+                            ui.visuals().text_color()
+                        };
+                        ui.label(
+                            egui::RichText::new(format!("{}{:08x}", if address == pc { "▶" } else { " " }, address))
+                                .monospace()
+                                .color(color),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!("{:0width$x}", opcode, width = word_size as usize * 2))
+                                .monospace()
+                                .weak(),
+                        );
+                        ui.label(egui::RichText::new(text).monospace().color(color));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn show_breakpoints(ui: &mut egui::Ui, session: &session::Session, state: &mut State, actions: &mut Vec<Action>) {
+    let thread_handle = session.thread_handle();
+    let mut audio_guard = thread_handle.lock_audio();
+    let mut core = audio_guard.core_mut();
+
+    let (breakpoints, watchpoints) = if let Some(debugger) = core.debugger() {
+        (debugger.breakpoints(), debugger.watchpoints())
+    } else {
+        show_unavailable(ui);
+        return;
+    };
+
+    ui.heading("Breakpoints");
+    ui.horizontal(|ui| {
+        hex_input(ui, &mut state.new_breakpoint, "Address");
+        egui::ComboBox::from_id_source("debug-new-breakpoint-mode")
+            .selected_text(mode_label(state.new_breakpoint_mode))
+            .show_ui(ui, |ui| {
+                for mode in [mgba::arm_core::ExecutionMode::Thumb, mgba::arm_core::ExecutionMode::ARM] {
+                    ui.selectable_value(&mut state.new_breakpoint_mode, mode, mode_label(mode));
+                }
+            });
+        let address = parse_hex(&state.new_breakpoint);
+        if ui.add_enabled(address.is_some(), egui::Button::new("➕")).clicked() {
+            actions.push(Action::AddBreakpoint(address.unwrap(), state.new_breakpoint_mode));
+            state.new_breakpoint.clear();
+        }
+    });
+    egui::Grid::new("debug-breakpoints")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for (address, mode) in breakpoints {
+                ui.monospace(format!("{:08x}", address));
+                ui.label(mode_label(mode));
+                ui.monospace(disassemble(&mut core, address, mode).1);
+                if ui.button("🗑").clicked() {
+                    actions.push(Action::RemoveBreakpoint(address));
+                }
+                ui.end_row();
+            }
+        });
+
+    ui.separator();
+
+    ui.heading("Write watchpoints");
+    ui.horizontal(|ui| {
+        hex_input(ui, &mut state.new_watchpoint_start, "02000000");
+        ui.label("–");
+        hex_input(ui, &mut state.new_watchpoint_end, "Last byte");
+        let start = parse_hex(&state.new_watchpoint_start);
+        // A single address is fine too: then the watchpoint covers just that byte.
+        let end = if state.new_watchpoint_end.is_empty() {
+            start
+        } else {
+            parse_hex(&state.new_watchpoint_end)
+        };
+        let range = match (start, end) {
+            (Some(start), Some(end)) if start <= end => Some(start..end.saturating_add(1)),
+            _ => None,
+        };
+        if ui.add_enabled(range.is_some(), egui::Button::new("➕")).clicked() {
+            actions.push(Action::AddWatchpoint(range.unwrap()));
+            state.new_watchpoint_start.clear();
+            state.new_watchpoint_end.clear();
+        }
+    });
+    egui::Grid::new("debug-watchpoints")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for range in watchpoints {
+                ui.monospace(format!("{:08x}–{:08x}", range.start, range.end - 1));
+                if ui.button("🗑").clicked() {
+                    actions.push(Action::RemoveWatchpoint(range));
+                }
+                ui.end_row();
+            }
+        });
+}
+
+fn show_cheats(ui: &mut egui::Ui, session: &session::Session, state: &mut State) {
+    let cheats = if let Some(cheats) = session.cheats() {
+        cheats
+    } else {
+        ui.label(egui::RichText::new("Cheats are only available in single player.").weak());
+        return;
+    };
+
+    // The core must be locked before the cheats, as the emulator thread locks the cheats while it runs.
+    let thread_handle = session.thread_handle();
+    let mut audio_guard = thread_handle.lock_audio();
+    let mut core = audio_guard.core_mut();
+    let mut cheats = cheats.lock();
+
+    egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+        show_search(ui, &mut core, &mut cheats, state);
+        ui.separator();
+        show_watches(ui, &mut core, &mut cheats, state);
+        ui.separator();
+        show_codes(ui, &mut cheats, state);
+    });
+}
+
+fn show_search(ui: &mut egui::Ui, core: &mut mgba::core::CoreMutRef, cheats: &mut cheats::Cheats, state: &mut State) {
+    ui.heading("RAM search");
+    ui.horizontal(|ui| {
+        value_type_combo_box(ui, "debug-search-value-type", &mut state.search_value_type);
+        if ui.button("New search").clicked() {
+            state.search = Some(cheats::Search::new(core, state.search_value_type));
+        }
+        if ui
+            .add_enabled(state.search.is_some(), egui::Button::new("Clear"))
+            .clicked()
+        {
+            state.search = None;
+        }
+    });
+
+    let search = if let Some(search) = state.search.as_mut() {
+        search
+    } else {
+        return;
+    };
+    let value_type = search.value_type();
+
+    ui.horizontal(|ui| {
+        ui.label("Current value");
+        egui::ComboBox::from_id_source("debug-search-comparison")
+            .width(4.0 * FONT_WIDTH)
+            .selected_text(comparison_label(state.search_comparison))
+            .show_ui(ui, |ui| {
+                for comparison in [
+                    cheats::Comparison::Equal,
+                    cheats::Comparison::NotEqual,
+                    cheats::Comparison::GreaterThan,
+                    cheats::Comparison::LessThan,
+                ] {
+                    ui.selectable_value(&mut state.search_comparison, comparison, comparison_label(comparison));
+                }
+            });
+        ui.radio_value(&mut state.search_against_previous, true, "previous value");
+        ui.radio_value(&mut state.search_against_previous, false, "");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search_value)
+                .desired_width(12.0 * FONT_WIDTH)
+                .hint_text("Value")
+                .font(egui::TextStyle::Monospace),
+        );
+        let operand = if state.search_against_previous {
+            Some(cheats::Operand::Previous)
+        } else {
+            value_type.parse(&state.search_value).map(cheats::Operand::Value)
+        };
+        if ui.add_enabled(operand.is_some(), egui::Button::new("Filter")).clicked() {
+            search.filter(core, state.search_comparison, operand.unwrap());
+        }
+    });
+
+    let candidates = search.candidates();
+    ui.label(format!("{} candidates", candidates.len()));
+    egui::Grid::new("debug-search-candidates")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for candidate in candidates.iter().take(SEARCH_CANDIDATES_SHOWN) {
+                let current = value_type.read(core, candidate.address);
+                ui.monospace(format!("{:08x}", candidate.address));
+                ui.monospace(value_type.format(candidate.previous));
+                ui.monospace(value_type.format(current));
+                if ui.button("👁").on_hover_text("Watch").clicked() {
+                    cheats.watches.push(cheats::Watch {
+                        name: format!("{:08x}", candidate.address),
+                        address: candidate.address,
+                        value_type,
+                        frozen: None,
                     });
+                }
+                ui.end_row();
+            }
+        });
+    if candidates.len() > SEARCH_CANDIDATES_SHOWN {
+        ui.label(egui::RichText::new(format!("…and {} more", candidates.len() - SEARCH_CANDIDATES_SHOWN)).weak());
+    }
+}
+
+fn show_watches(ui: &mut egui::Ui, core: &mut mgba::core::CoreMutRef, cheats: &mut cheats::Cheats, state: &mut State) {
+    ui.heading("Watches");
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.new_watch_name)
+                .desired_width(16.0 * FONT_WIDTH)
+                .hint_text("Name"),
+        );
+        hex_input(ui, &mut state.new_watch_address, "Address");
+        value_type_combo_box(ui, "debug-new-watch-value-type", &mut state.new_watch_value_type);
+        let address = parse_hex(&state.new_watch_address);
+        if ui.add_enabled(address.is_some(), egui::Button::new("➕")).clicked() {
+            let address = address.unwrap();
+            cheats.watches.push(cheats::Watch {
+                name: if state.new_watch_name.is_empty() {
+                    format!("{:08x}", address)
+                } else {
+                    state.new_watch_name.clone()
+                },
+                address,
+                value_type: state.new_watch_value_type,
+                frozen: None,
             });
+            state.new_watch_name.clear();
+            state.new_watch_address.clear();
+        }
+    });
+
+    let mut to_remove = None;
+    egui::Grid::new("debug-watches")
+        .num_columns(6)
+        .striped(true)
+        .show(ui, |ui| {
+            for (i, watch) in cheats.watches.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut watch.name).desired_width(16.0 * FONT_WIDTH));
+                ui.monospace(format!("{:08x}", watch.address));
+                ui.label(value_type_label(watch.value_type));
+
+                let current = watch.value_type.read(core, watch.address);
+                match state.editing_watch.as_mut() {
+                    Some((j, value)) if *j == i => {
+                        let resp = ui.add(
+                            egui::TextEdit::singleline(value)
+                                .desired_width(12.0 * FONT_WIDTH)
+                                .font(egui::TextStyle::Monospace),
+                        );
+                        if !resp.has_focus() && !resp.lost_focus() {
+                            resp.request_focus();
+                        }
+                        if resp.lost_focus() {
+                            // Enter sets the value (and the frozen value, if frozen); anything else cancels.
+                            if ui.ctx().input().key_pressed(egui::Key::Enter) {
+                                if let Some(value) = watch.value_type.parse(value) {
+                                    watch.value_type.write(core, watch.address, value);
+                                    if watch.frozen.is_some() {
+                                        watch.frozen = Some(value);
+                                    }
+                                }
+                            }
+                            state.editing_watch = None;
+                        }
+                    }
+                    _ => {
+                        if ui
+                            .add(
+                                egui::Label::new(
+                                    egui::RichText::new(format!(
+                                        "{} ({:#0width$x})",
+                                        watch.value_type.format(current),
+                                        current,
+                                        width = watch.value_type.size() as usize * 2 + 2
+                                    ))
+                                    .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text("Click to edit")
+                            .clicked()
+                        {
+                            state.editing_watch = Some((i, watch.value_type.format(current)));
+                        }
+                    }
+                }
+
+                let mut frozen = watch.frozen.is_some();
+                if ui.checkbox(&mut frozen, "Freeze").changed() {
+                    watch.frozen = if frozen { Some(current) } else { None };
+                }
+                if ui.button("🗑").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.end_row();
+            }
         });
-    if !open {
-        *state = None;
+    if let Some(i) = to_remove {
+        cheats.watches.remove(i);
+        state.editing_watch = None;
+    }
+}
+
+fn show_codes(ui: &mut egui::Ui, cheats: &mut cheats::Cheats, state: &mut State) {
+    ui.heading("Codes");
+    ui.add(
+        egui::TextEdit::singleline(&mut state.new_code_name)
+            .desired_width(ui.available_width())
+            .hint_text("Name"),
+    );
+    ui.add(
+        egui::TextEdit::multiline(&mut state.new_code)
+            .desired_width(ui.available_width())
+            .desired_rows(4)
+            .hint_text("GameShark or CodeBreaker codes, one per line")
+            .font(egui::TextStyle::Monospace),
+    );
+    if ui
+        .add_enabled(!state.new_code.trim().is_empty(), egui::Button::new("➕ Add"))
+        .clicked()
+    {
+        match cheats::parse_codes(&state.new_code) {
+            Ok(writes) => {
+                cheats.codes.push(cheats::Code {
+                    name: if state.new_code_name.is_empty() {
+                        format!("Code {}", cheats.codes.len() + 1)
+                    } else {
+                        state.new_code_name.clone()
+                    },
+                    enabled: true,
+                    writes,
+                });
+                state.new_code_name.clear();
+                state.new_code.clear();
+                state.code_error = None;
+            }
+            Err(e) => {
+                state.code_error = Some(e.to_string());
+            }
+        }
+    }
+    if let Some(error) = state.code_error.as_ref() {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    let mut to_remove = None;
+    egui::Grid::new("debug-codes")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (i, code) in cheats.codes.iter_mut().enumerate() {
+                ui.checkbox(&mut code.enabled, code.name.clone());
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} write{}",
+                        code.writes.len(),
+                        if code.writes.len() == 1 { "" } else { "s" }
+                    ))
+                    .weak(),
+                );
+                if ui.button("🗑").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(i) = to_remove {
+        cheats.codes.remove(i);
     }
 }
+
+fn show_memory(ui: &mut egui::Ui, session: &session::Session, state: &mut State) {
+    let mut jumping = false;
+    ui.horizontal(|ui| {
+        let input_resp = hex_input(ui, &mut state.jump_to, "Jump to");
+        if input_resp.lost_focus() && ui.ctx().input().key_pressed(egui::Key::Enter) {
+            jumping = true;
+        }
+
+        if ui.button("Go!").clicked() {
+            jumping = true;
+        }
+    });
+
+    let thread_handle = session.thread_handle();
+    let mut audio_guard = thread_handle.lock_audio();
+
+    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+    let mut sa = egui::ScrollArea::vertical().auto_shrink([true, false]);
+    if jumping {
+        if let Some(jump_to) = parse_hex(&state.jump_to) {
+            sa = sa.vertical_scroll_offset((row_height + ui.spacing().item_spacing.y) * (jump_to / 0x10) as f32);
+        }
+    }
+
+    sa.show_rows(ui, row_height, 0x0fffffff / 0x10, |ui, range| {
+        egui_extras::StripBuilder::new(ui)
+            .sizes(egui_extras::Size::exact(row_height), range.len())
+            .vertical(|mut outer_strip| {
+                for i in range {
+                    outer_strip.cell(|ui| {
+                        let rect = ui.available_rect_before_wrap().expand(ui.spacing().item_spacing.y);
+                        if i % 2 == 0 {
+                            ui.painter().rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+                        }
+
+                        egui_extras::StripBuilder::new(ui)
+                            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                            .size(egui_extras::Size::exact(8.0 * FONT_WIDTH))
+                            .size(egui_extras::Size::exact(48.0 * FONT_WIDTH))
+                            .size(egui_extras::Size::remainder())
+                            .horizontal(|mut strip| {
+                                let offset = i * 16;
+                                strip.cell(|ui| {
+                                    ui.label(egui::RichText::new(format!("{:08x}", offset)).monospace().weak());
+                                });
+                                let mut buf = [0u8; 0x10];
+                                audio_guard.core_mut().raw_read_range(offset as u32, -1, &mut buf[..]);
+                                strip.cell(|ui| {
+                                    ui.add(
+                                        egui::TextEdit::singleline(
+                                            &mut buf.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" "),
+                                        )
+                                        .desired_width(ui.available_width())
+                                        .frame(false)
+                                        .font(egui::TextStyle::Monospace),
+                                    );
+                                });
+
+                                strip.cell(|ui| {
+                                    ui.monospace(
+                                        buf.map(|b| if b >= 32 && b < 127 { b as char } else { '.' })
+                                            .iter()
+                                            .collect::<String>(),
+                                    );
+                                });
+                            });
+                    });
+                }
+            });
+    });
+}
@@ -1,6 +1,6 @@
 use fluent_templates::Loader;
 
-use crate::{gui, i18n, session};
+use crate::{gui, i18n, save, session};
 
 pub struct State {}
 
@@ -17,6 +17,8 @@ pub fn show(
     show_escape_window: &mut Option<State>,
     language: &unic_langid::LanguageIdentifier,
     show_settings: &mut Option<gui::settings_window::State>,
+    saves_scanner: save::Scanner,
+    saves_path: std::path::PathBuf,
 ) {
     let mut open = show_escape_window.is_some();
     egui::Window::new("")
@@ -40,10 +42,18 @@ pub fn show(
                 {
                     *session.lock() = None;
                     // Current save file needs to be reloaded from disk.
-                    // TODO: Maybe we even need to rescan saves if region lock status changed? (e.g. EXE4 -> BN4)
                     if let Some(selection) = selection.as_mut() {
                         let _ = selection.reload_save();
                     }
+                    // Playing can region lock a save that wasn't before (e.g. an EXE4 save that BN4 could also load), so
+                    // which games it belongs to may have changed.
+                    tokio::task::spawn_blocking({
+                        let saves_scanner = saves_scanner.clone();
+                        let saves_path = saves_path.clone();
+                        move || {
+                            saves_scanner.rescan(move || Some(save::scan_saves(&saves_path)));
+                        }
+                    });
                     *show_escape_window = None;
                 }
             });
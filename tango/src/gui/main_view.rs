@@ -114,7 +114,13 @@ pub fn show(
                                 .on_hover_text_at_pointer(i18n::LOCALES.lookup(&config.language, "replays").unwrap())
                                 .clicked()
                             {
-                                state.replays_pane.rescan(ui.ctx(), &config.replays_path());
+                                state.replays_pane.rescan(
+                                    ui.ctx(),
+                                    &config.replays_path(),
+                                    &config.replay_index_path(),
+                                    &config.patches_path(),
+                                    roms_scanner.clone(),
+                                );
                             }
 
                             if ui
@@ -188,6 +194,7 @@ pub fn show(
                     patches_scanner.clone(),
                     roms_scanner.clone(),
                     &config.replays_path(),
+                    &config.video_filters,
                     audio_binder.clone(),
                     emu_tps_counter.clone(),
                     session.clone(),
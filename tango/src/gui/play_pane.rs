@@ -190,6 +190,84 @@ struct RemoteSelection {
     pub patch: Option<(String, semver::Version, patch::Version)>,
 }
 
+struct LocalLegality {
+    ruleset: String,
+    // None if the ruleset has no rules for the selected game, so it can't say whether the folder is legal.
+    violations: Option<Vec<save::legality::Violation>>,
+    descriptions: Vec<String>,
+}
+
+// What the legality was last checked against: the ruleset, the selected game, patch and save, and the language the
+// descriptions are in.
+type LegalityKey = (
+    Option<String>,
+    Option<(
+        &'static (dyn game::Game + Send + Sync),
+        Option<(String, semver::Version)>,
+        Vec<u8>,
+    )>,
+    unic_langid::LanguageIdentifier,
+);
+
+fn describe_violation(
+    violation: &save::legality::Violation,
+    assets: &(dyn rom::Assets + Send + Sync),
+    language: &unic_langid::LanguageIdentifier,
+) -> String {
+    let chip_name = |id: usize| {
+        assets
+            .chip(id)
+            .map(|chip| chip.name())
+            .unwrap_or_else(|| format!("#{}", id))
+    };
+
+    match violation {
+        save::legality::Violation::TooManyCopies { id, count, max } => i18n::LOCALES
+            .lookup_with_args(
+                language,
+                "lobby-violation-too-many-copies",
+                &std::collections::HashMap::from([
+                    ("chip", chip_name(*id).into()),
+                    ("count", (*count).into()),
+                    ("max", (*max).into()),
+                ]),
+            )
+            .unwrap(),
+        save::legality::Violation::TooManyMegaChips { count, max } => i18n::LOCALES
+            .lookup_with_args(
+                language,
+                "lobby-violation-too-many-mega-chips",
+                &std::collections::HashMap::from([("count", (*count).into()), ("max", (*max).into())]),
+            )
+            .unwrap(),
+        save::legality::Violation::TooManyGigaChips { count, max } => i18n::LOCALES
+            .lookup_with_args(
+                language,
+                "lobby-violation-too-many-giga-chips",
+                &std::collections::HashMap::from([("count", (*count).into()), ("max", (*max).into())]),
+            )
+            .unwrap(),
+        save::legality::Violation::RegularChipTooLarge { id, mb, max } => i18n::LOCALES
+            .lookup_with_args(
+                language,
+                "lobby-violation-regular-chip-too-large",
+                &std::collections::HashMap::from([
+                    ("chip", chip_name(*id).into()),
+                    ("mb", (*mb).into()),
+                    ("max", (*max).into()),
+                ]),
+            )
+            .unwrap(),
+        save::legality::Violation::BannedChip { id } => i18n::LOCALES
+            .lookup_with_args(
+                language,
+                "lobby-violation-banned-chip",
+                &std::collections::HashMap::from([("chip", chip_name(*id).into())]),
+            )
+            .unwrap(),
+    }
+}
+
 struct Lobby {
     attention_requested: bool,
     link_code: String,
@@ -205,6 +283,9 @@ struct Lobby {
     local_negotiated_state: Option<(net::protocol::NegotiatedState, Vec<u8>)>,
     roms_scanner: rom::Scanner,
     patches_scanner: patch::Scanner,
+    rulesets: Vec<save::legality::Ruleset>,
+    legality: Option<LocalLegality>,
+    legality_key: Option<LegalityKey>,
 }
 
 pub fn get_netplay_compatibility(
@@ -394,6 +475,10 @@ impl Lobby {
                 .map(|(p, info)| (p.clone(), info.versions.keys().cloned().collect()))
                 .collect(),
             reveal_setup: self.reveal_setup,
+            legality: self.legality.as_ref().map(|legality| net::protocol::Legality {
+                ruleset: legality.ruleset.clone(),
+                num_violations: legality.violations.as_ref().map(|violations| violations.len() as u32),
+            }),
         }
     }
 
@@ -507,6 +592,87 @@ impl Lobby {
         Ok(())
     }
 
+    async fn update_legality(
+        &mut self,
+        ruleset: Option<&str>,
+        selection: &Option<gui::Selection>,
+        language: &unic_langid::LanguageIdentifier,
+    ) -> Result<(), anyhow::Error> {
+        if self.legality_key.as_ref().map(|(ruleset, selection, language)| {
+            (
+                ruleset.as_deref(),
+                selection
+                    .as_ref()
+                    .map(|(game, patch, wram)| (*game, patch.clone(), wram.as_slice())),
+                language,
+            )
+        }) == Some((
+            ruleset,
+            selection.as_ref().map(|selection| {
+                (
+                    selection.game,
+                    selection
+                        .patch
+                        .as_ref()
+                        .map(|(name, version, _)| (name.clone(), version.clone())),
+                    selection.save.save.as_raw_wram(),
+                )
+            }),
+            language,
+        )) {
+            return Ok(());
+        }
+        self.legality_key = Some((
+            ruleset.map(|ruleset| ruleset.to_string()),
+            selection.as_ref().map(|selection| {
+                (
+                    selection.game,
+                    selection
+                        .patch
+                        .as_ref()
+                        .map(|(name, version, _)| (name.clone(), version.clone())),
+                    selection.save.save.as_raw_wram().to_vec(),
+                )
+            }),
+            language.clone(),
+        ));
+
+        let legality = (|| {
+            let ruleset = self.rulesets.iter().find(|r| Some(r.name.as_str()) == ruleset)?;
+            let selection = selection.as_ref()?;
+            let rules = if let Some(rules) = ruleset.rules(selection.game.family_and_variant().0) {
+                rules
+            } else {
+                return Some(LocalLegality {
+                    ruleset: ruleset.name.clone(),
+                    violations: None,
+                    descriptions: vec![],
+                });
+            };
+            let assets = selection.assets.as_ref()?;
+            let chips_view = selection.save.save.view_chips()?;
+            let violations = save::legality::check(rules, chips_view.as_ref(), assets.as_ref());
+            Some(LocalLegality {
+                ruleset: ruleset.name.clone(),
+                descriptions: violations
+                    .iter()
+                    .map(|violation| describe_violation(violation, assets.as_ref(), language))
+                    .collect(),
+                violations: Some(violations),
+            })
+        })();
+
+        if legality.as_ref().map(|l| (&l.ruleset, &l.violations))
+            == self.legality.as_ref().map(|l| (&l.ruleset, &l.violations))
+        {
+            return Ok(());
+        }
+
+        self.legality = legality;
+        self.send_settings(self.make_local_settings()).await?;
+        Ok(())
+    }
+
     fn can_ready(&self) -> bool {
         are_settings_compatible(
             &self.make_local_settings(),
@@ -598,7 +764,7 @@ async fn run_connection_task(
     roms_scanner: rom::Scanner,
     patches_scanner: patch::Scanner,
     matchmaking_addr: String,
-    link_code: String,
+    signaling: Signaling,
     nickname: String,
     patches_path: std::path::PathBuf,
     replays_path: std::path::PathBuf,
@@ -625,15 +791,37 @@ async fn run_connection_task(
                         let config = config.read();
                         config.use_relay
                     };
-                    let pending_conn = tokio::time::timeout(
-                        OPEN_TIMEOUT,
-                        net::signaling::open(
-                            &matchmaking_addr,
-                            &link_code,
-                            use_relay,
-                        ),
-                    )
-                    .await.map_err(|e| std::io::Error::new(std::io::ErrorKind::TimedOut, e))??;
+                    let (link_code, pending_conn) = match signaling {
+                        Signaling::LinkCode { link_code, advertise } => {
+                            let pending_conn = tokio::time::timeout(
+                                OPEN_TIMEOUT,
+                                net::signaling::open(
+                                    &matchmaking_addr,
+                                    &link_code,
+                                    use_relay,
+                                    advertise,
+                                ),
+                            )
+                            .await.map_err(|e| std::io::Error::new(std::io::ErrorKind::TimedOut, e))??;
+                            (link_code, pending_conn)
+                        }
+                        Signaling::Queue(queue) => {
+                            *connection_task.lock().await =
+                                Some(ConnectionTask::InProgress {
+                                    state: ConnectionState::Queued,
+                                    cancellation_token:
+                                        cancellation_token.clone(),
+                                });
+                            // There's no telling how long it takes for someone to show up, so this only ends when cancelled.
+                            let (matched, pending_conn) = net::signaling::open_queued(
+                                &matchmaking_addr,
+                                queue,
+                                use_relay,
+                            )
+                            .await?;
+                            (matched.session_id, pending_conn)
+                        }
+                    };
 
                     *connection_task.lock().await =
                         Some(ConnectionTask::InProgress {
@@ -642,10 +830,8 @@ async fn run_connection_task(
                                 cancellation_token.clone(),
                         });
 
-                    let (dc, peer_conn) = pending_conn.connect().await?;
-                    let (dc_tx, dc_rx) = dc.split();
-                    let mut sender = net::Sender::new(dc_tx);
-                    let mut receiver = net::Receiver::new(dc_rx);
+                    let (dc, input_dc, peer_conn) = pending_conn.connect().await?;
+                    let (mut sender, mut receiver) = net::split(dc, input_dc);
                     net::negotiate(&mut sender, &mut receiver).await?;
 
                     let default_match_type = {
@@ -668,6 +854,9 @@ async fn run_connection_task(
                         local_negotiated_state: None,
                         roms_scanner: roms_scanner.clone(),
                         patches_scanner: patches_scanner.clone(),
+                        rulesets: save::legality::load_rulesets(&config.read().rulesets_path()),
+                        legality: None,
+                        legality_key: None,
                     }));
                     {
                         let mut lobby = lobby.lock().await;
@@ -898,13 +1087,34 @@ enum ConnectionTask {
 enum ConnectionState {
     Starting,
     Signaling,
+    Queued,
     Waiting,
     InLobby(std::sync::Arc<tokio::sync::Mutex<Lobby>>),
 }
 
+// How to find the opponent: either through a link code, optionally listed in the public lobbies, or through the
+// quick-match queue.
+enum Signaling {
+    LinkCode {
+        link_code: String,
+        advertise: Option<tango_protos::matchmaking::packet::Advertise>,
+    },
+    Queue(tango_protos::matchmaking::packet::Queue),
+}
+
+#[derive(Default)]
+struct PublicLobbies {
+    open: bool,
+    loading: bool,
+    lobbies: Option<Result<Vec<tango_protos::matchmaking::Lobby>, String>>,
+    join: Option<String>,
+}
+
 pub struct State {
     link_code: String,
     show_link_code: bool,
+    advertise: bool,
+    public_lobbies: std::sync::Arc<parking_lot::Mutex<PublicLobbies>>,
     connection_task: std::sync::Arc<tokio::sync::Mutex<Option<ConnectionTask>>>,
     show_save_select: Option<gui::save_select_view::State>,
 }
@@ -914,12 +1124,83 @@ impl State {
         Self {
             link_code: String::new(),
             show_link_code: false,
+            advertise: false,
+            public_lobbies: std::sync::Arc::new(parking_lot::Mutex::new(PublicLobbies::default())),
             connection_task: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
             show_save_select: None,
         }
     }
 }
 
+// Applies a shared setup to the selected save, taking a snapshot of it first.
+fn import_setup(config: &config::Config, selection: &gui::Selection, setup: save::setup::Setup) -> anyhow::Result<()> {
+    let mut save = selection.save.save.clone_box();
+    setup.apply(selection.game.family_and_variant().0, save.as_mut())?;
+    save::history::History::new(config.save_history_path(), config.saves_path())
+        .snapshot(&selection.save.path, save::history::Reason::ImportSetup)?;
+    std::fs::write(&selection.save.path, save.to_vec())?;
+    log::info!("imported setup into {}", selection.save.path.display());
+    Ok(())
+}
+
+// Converts a save to the other region's release, either next to the original or into an existing save of the target game.
+fn convert_save(
+    config: &config::Config,
+    roms_scanner: &rom::Scanner,
+    from: &'static (dyn game::Game + Send + Sync),
+    save: &save::ScannedSave,
+    to: &'static (dyn game::Game + Send + Sync),
+    target: Option<&save::ScannedSave>,
+) -> anyhow::Result<(std::path::PathBuf, Vec<String>)> {
+    let output_path = if let Some(target) = target {
+        target.path.clone()
+    } else {
+        let path = save.path.with_file_name(format!(
+            "{}-{}.sav",
+            save.path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(""),
+            to.family_and_variant().0
+        ));
+        if path.exists() {
+            anyhow::bail!("{} already exists", path.display());
+        }
+        path
+    };
+
+    let assets = roms_scanner.read().get(&to).and_then(|rom| {
+        to.load_rom_assets(
+            rom,
+            target.map(|target| &target.save).unwrap_or(&save.save).as_raw_wram(),
+            &Default::default(),
+        )
+        .ok()
+    });
+    let assets = assets.as_ref().map(|assets| assets.as_ref() as &dyn rom::Assets);
+
+    let conversion = save::convert::convert(
+        from,
+        save.save.as_ref(),
+        to,
+        target.map(|target| target.save.as_ref()),
+        assets,
+    )?;
+
+    if target.is_some() {
+        save::history::History::new(config.save_history_path(), config.saves_path())
+            .snapshot(&output_path, save::history::Reason::Convert)?;
+    }
+    std::fs::write(&output_path, conversion.save.to_vec())?;
+    log::info!("converted {} to {}", save.path.display(), output_path.display());
+
+    Ok((
+        output_path,
+        conversion
+            .unconvertible
+            .iter()
+            .map(|unconvertible| unconvertible.to_text(&config.language, assets))
+            .collect(),
+    ))
+}
+
 fn show_lobby_table(
     ui: &mut egui::Ui,
     cancellation_token: &tokio_util::sync::CancellationToken,
@@ -955,6 +1236,7 @@ fn show_lobby_table(
         .size(egui_extras::Size::exact(row_height + spacing_y))
         .size(egui_extras::Size::exact(row_height + spacing_y))
         .size(egui_extras::Size::exact(row_height + spacing_y))
+        .size(egui_extras::Size::exact(row_height + spacing_y))
         .vertical(|mut outer_strip| {
             const CELL_WIDTH: f32 = 200.0;
             outer_strip.strip(|sb| {
@@ -1186,6 +1468,145 @@ fn show_lobby_table(
                     });
             });
 
+            outer_strip.strip(|sb| {
+                sb.size(egui_extras::Size::remainder())
+                    .size(egui_extras::Size::exact(CELL_WIDTH))
+                    .size(egui_extras::Size::exact(CELL_WIDTH))
+                    .horizontal(|mut strip| {
+                        strip.cell(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.strong(i18n::LOCALES.lookup(&config.language, "play-details-ruleset").unwrap());
+                                if lobby
+                                    .legality
+                                    .as_ref()
+                                    .and_then(|legality| legality.violations.as_ref())
+                                    .map(|violations| !violations.is_empty())
+                                    .unwrap_or(false)
+                                    || lobby
+                                        .remote_settings
+                                        .legality
+                                        .as_ref()
+                                        .and_then(|legality| legality.num_violations)
+                                        .map(|num_violations| num_violations > 0)
+                                        .unwrap_or(false)
+                                {
+                                    gui::warning::show(
+                                        ui,
+                                        i18n::LOCALES
+                                            .lookup(&config.language, "lobby-issue-ruleset-violations")
+                                            .unwrap(),
+                                    );
+                                }
+                            });
+                        });
+                        strip.cell(|ui| {
+                            ui.horizontal(|ui| {
+                                egui::ComboBox::new("start-ruleset-combobox", "")
+                                    .width(100.0)
+                                    .selected_text(config.ruleset.clone().unwrap_or_else(|| {
+                                        i18n::LOCALES
+                                            .lookup(&config.language, "play-details-ruleset.none")
+                                            .unwrap()
+                                    }))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut config.ruleset,
+                                            None,
+                                            i18n::LOCALES
+                                                .lookup(&config.language, "play-details-ruleset.none")
+                                                .unwrap(),
+                                        );
+                                        for ruleset in lobby.rulesets.iter() {
+                                            ui.selectable_value(
+                                                &mut config.ruleset,
+                                                Some(ruleset.name.clone()),
+                                                ruleset.name.as_str(),
+                                            );
+                                        }
+                                    });
+                                if let Some(legality) = lobby.legality.as_ref() {
+                                    match legality.violations.as_ref() {
+                                        None => {
+                                            ui.label(
+                                                i18n::LOCALES
+                                                    .lookup(&config.language, "play-details-ruleset.unsupported")
+                                                    .unwrap(),
+                                            );
+                                        }
+                                        Some(violations) if violations.is_empty() => {
+                                            ui.label(
+                                                i18n::LOCALES
+                                                    .lookup(&config.language, "play-details-ruleset.legal")
+                                                    .unwrap(),
+                                            );
+                                        }
+                                        Some(violations) => {
+                                            ui.label(
+                                                egui::RichText::new(
+                                                    i18n::LOCALES
+                                                        .lookup_with_args(
+                                                            &config.language,
+                                                            "play-details-ruleset.violations",
+                                                            &std::collections::HashMap::from([(
+                                                                "count",
+                                                                violations.len().into(),
+                                                            )]),
+                                                        )
+                                                        .unwrap(),
+                                                )
+                                                .color(egui::Color32::from_rgb(0xf4, 0xba, 0x51)),
+                                            )
+                                            .on_hover_text(legality.descriptions.join("\n"));
+                                        }
+                                    }
+                                }
+                            });
+                        });
+                        strip.cell(|ui| {
+                            if let Some(legality) = lobby.remote_settings.legality.as_ref() {
+                                ui.label(match legality.num_violations {
+                                    None => i18n::LOCALES
+                                        .lookup_with_args(
+                                            &config.language,
+                                            "play-details-ruleset.remote-unsupported",
+                                            &std::collections::HashMap::from([(
+                                                "ruleset",
+                                                legality.ruleset.clone().into(),
+                                            )]),
+                                        )
+                                        .unwrap(),
+                                    Some(0) => i18n::LOCALES
+                                        .lookup_with_args(
+                                            &config.language,
+                                            "play-details-ruleset.remote-legal",
+                                            &std::collections::HashMap::from([(
+                                                "ruleset",
+                                                legality.ruleset.clone().into(),
+                                            )]),
+                                        )
+                                        .unwrap(),
+                                    Some(num_violations) => i18n::LOCALES
+                                        .lookup_with_args(
+                                            &config.language,
+                                            "play-details-ruleset.remote",
+                                            &std::collections::HashMap::from([
+                                                ("ruleset", legality.ruleset.clone().into()),
+                                                ("count", num_violations.into()),
+                                            ]),
+                                        )
+                                        .unwrap(),
+                                });
+                            } else {
+                                ui.label(
+                                    i18n::LOCALES
+                                        .lookup(&config.language, "play-details-ruleset.none")
+                                        .unwrap(),
+                                );
+                            }
+                        });
+                    });
+            });
+
             outer_strip.strip(|sb| {
                 sb.size(egui_extras::Size::remainder())
                     .size(egui_extras::Size::exact(CELL_WIDTH * 2.0 + spacing_x))
@@ -1245,6 +1666,191 @@ fn show_lobby_table(
         });
 }
 
+fn make_matchmaking_game_info(
+    selection: &gui::Selection,
+    patches: &std::collections::BTreeMap<String, patch::Patch>,
+) -> Option<tango_protos::matchmaking::GameInfo> {
+    let (family, variant) = selection.game.family_and_variant();
+    Some(tango_protos::matchmaking::GameInfo {
+        rom_family: family.to_string(),
+        rom_variant: variant as u32,
+        patch: selection
+            .patch
+            .as_ref()
+            .map(|(name, version, _)| tango_protos::matchmaking::game_info::Patch {
+                name: name.clone(),
+                version: version.to_string(),
+            }),
+        netplay_compatibility: get_netplay_compatibility(
+            selection.game,
+            selection
+                .patch
+                .as_ref()
+                .map(|(name, version, _)| (name.as_str(), version)),
+            patches,
+        )?,
+    })
+}
+
+fn refresh_public_lobbies(
+    egui_ctx: egui::Context,
+    matchmaking_endpoint: String,
+    public_lobbies: std::sync::Arc<parking_lot::Mutex<PublicLobbies>>,
+    netplay_compatibility: Option<String>,
+) {
+    public_lobbies.lock().loading = true;
+    tokio::task::spawn(async move {
+        let lobbies = net::signaling::list_lobbies(&matchmaking_endpoint, netplay_compatibility)
+            .await
+            .map_err(|e| e.to_string());
+        let mut public_lobbies = public_lobbies.lock();
+        public_lobbies.loading = false;
+        public_lobbies.lobbies = Some(lobbies);
+        egui_ctx.request_repaint();
+    });
+}
+
+fn show_public_lobbies_window(
+    ctx: &egui::Context,
+    config: &config::Config,
+    public_lobbies: &std::sync::Arc<parking_lot::Mutex<PublicLobbies>>,
+    netplay_compatibility: Option<String>,
+) {
+    let mut state = public_lobbies.lock();
+    let mut open = state.open;
+    let mut refresh = false;
+    egui::Window::new(format!(
+        "🌐 {}",
+        i18n::LOCALES.lookup(&config.language, "play-public-lobbies").unwrap()
+    ))
+    .id(egui::Id::new("public-lobbies-window"))
+    .open(&mut open)
+    .show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !state.loading,
+                    egui::Button::new(format!(
+                        "🔄 {}",
+                        i18n::LOCALES
+                            .lookup(&config.language, "play-public-lobbies.refresh")
+                            .unwrap()
+                    )),
+                )
+                .clicked()
+            {
+                refresh = true;
+            }
+            if state.loading {
+                ui.spinner();
+            }
+        });
+
+        let mut join = None;
+        match state.lobbies.as_ref() {
+            None => {}
+            Some(Err(e)) => {
+                ui.label(
+                    i18n::LOCALES
+                        .lookup_with_args(
+                            &config.language,
+                            "play-public-lobbies.error",
+                            &std::collections::HashMap::from([("error", e.clone().into())]),
+                        )
+                        .unwrap(),
+                );
+            }
+            Some(Ok(lobbies)) if lobbies.is_empty() => {
+                ui.label(
+                    i18n::LOCALES
+                        .lookup(&config.language, "play-public-lobbies.empty")
+                        .unwrap(),
+                );
+            }
+            Some(Ok(lobbies)) => {
+                egui::Grid::new("public-lobbies-grid").striped(true).show(ui, |ui| {
+                    for lobby in lobbies {
+                        ui.label(lobby.nickname.as_str());
+                        let game = lobby.game_info.as_ref().and_then(|game_info| {
+                            game::find_by_family_and_variant(&game_info.rom_family, game_info.rom_variant as u8)
+                        });
+                        if let Some(game) = game {
+                            let family = game.family_and_variant().0;
+                            ui.label(
+                                i18n::LOCALES
+                                    .lookup(&config.language, &format!("game-{}", family))
+                                    .unwrap(),
+                            );
+                            ui.label(
+                                i18n::LOCALES
+                                    .lookup(
+                                        &config.language,
+                                        &format!(
+                                            "game-{}.match-type-{}-{}",
+                                            family, lobby.match_type, lobby.match_subtype
+                                        ),
+                                    )
+                                    .unwrap_or_else(|| "".to_string()),
+                            );
+                        } else {
+                            ui.label(
+                                i18n::LOCALES
+                                    .lookup(&config.language, "play-details-game.unknown")
+                                    .unwrap(),
+                            );
+                            ui.label("");
+                        }
+                        ui.label(
+                            lobby
+                                .game_info
+                                .as_ref()
+                                .and_then(|game_info| game_info.patch.as_ref())
+                                .map(|patch| format!("{} v{}", patch.name, patch.version))
+                                .unwrap_or_else(|| "".to_string()),
+                        );
+                        if ui
+                            .add_enabled(
+                                game.is_some(),
+                                egui::Button::new(format!(
+                                    "🥊 {}",
+                                    i18n::LOCALES
+                                        .lookup(&config.language, "play-public-lobbies.join")
+                                        .unwrap()
+                                )),
+                            )
+                            .clicked()
+                        {
+                            join = Some(lobby.session_id.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+            }
+        }
+        if let Some(join) = join {
+            state.join = Some(join);
+        }
+    });
+    if state.join.is_some() {
+        open = false;
+    }
+    state.open = open;
+    drop(state);
+
+    if refresh {
+        refresh_public_lobbies(
+            ctx.clone(),
+            if !config.matchmaking_endpoint.is_empty() {
+                config.matchmaking_endpoint.clone()
+            } else {
+                config::DEFAULT_MATCHMAKING_ENDPOINT.to_string()
+            },
+            public_lobbies.clone(),
+            netplay_compatibility,
+        );
+    }
+}
+
 fn show_bottom_pane(
     ui: &mut egui::Ui,
     window: &winit::window::Window,
@@ -1262,6 +1868,8 @@ fn show_bottom_pane(
     connection_task_arc: std::sync::Arc<tokio::sync::Mutex<Option<ConnectionTask>>>,
     link_code: &mut String,
     show_link_code: &mut bool,
+    advertise: &mut bool,
+    public_lobbies: &std::sync::Arc<parking_lot::Mutex<PublicLobbies>>,
     show_save_select: &mut Option<gui::save_select_view::State>,
 ) {
     let error_window_open = {
@@ -1320,6 +1928,18 @@ fn show_bottom_pane(
     let roms = roms_scanner.read();
     let patches = patches_scanner.read();
 
+    let matchmaking_game_info = selection
+        .as_ref()
+        .and_then(|selection| make_matchmaking_game_info(selection, &patches));
+    show_public_lobbies_window(
+        ui.ctx(),
+        config,
+        public_lobbies,
+        matchmaking_game_info
+            .as_ref()
+            .map(|game_info| game_info.netplay_compatibility.clone()),
+    );
+
     egui::TopBottomPanel::bottom("play-bottom-pane").show_inside(ui, |ui| {
         ui.vertical(|ui| {
             {
@@ -1329,7 +1949,10 @@ fn show_bottom_pane(
                 }) = connection_task.as_ref()
                 {
                     match connection_state {
-                        ConnectionState::Starting | ConnectionState::Signaling | ConnectionState::Waiting => {
+                        ConnectionState::Starting
+                        | ConnectionState::Signaling
+                        | ConnectionState::Queued
+                        | ConnectionState::Waiting => {
                             ui.horizontal(|ui| {
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
                                     if ui
@@ -1352,6 +1975,9 @@ fn show_bottom_pane(
                                                 ConnectionState::Signaling => i18n::LOCALES
                                                     .lookup(&config.language, "play-connection-task-signaling")
                                                     .unwrap(),
+                                                ConnectionState::Queued => i18n::LOCALES
+                                                    .lookup(&config.language, "play-connection-task-queued")
+                                                    .unwrap(),
                                                 ConnectionState::Waiting => i18n::LOCALES
                                                     .lookup(&config.language, "play-connection-task-waiting")
                                                     .unwrap(),
@@ -1361,20 +1987,22 @@ fn show_bottom_pane(
                                     });
                                 });
                             });
-                            discord_client.set_current_activity(Some(discord::make_looking_activity(
-                                link_code,
-                                &config.language,
-                                selection.as_ref().map(|selection| {
-                                    discord::make_game_info(
-                                        selection.game,
-                                        selection
-                                            .patch
-                                            .as_ref()
-                                            .map(|(patch_name, patch_version, _)| (patch_name.as_str(), patch_version)),
-                                        &config.language,
-                                    )
-                                }),
-                            )));
+                            let game_info = selection.as_ref().map(|selection| {
+                                discord::make_game_info(
+                                    selection.game,
+                                    selection
+                                        .patch
+                                        .as_ref()
+                                        .map(|(patch_name, patch_version, _)| (patch_name.as_str(), patch_version)),
+                                    &config.language,
+                                )
+                            });
+                            // There's no link code to share while in the quick-match queue.
+                            discord_client.set_current_activity(Some(if link_code.is_empty() {
+                                discord::make_base_activity(game_info)
+                            } else {
+                                discord::make_looking_activity(link_code, &config.language, game_info)
+                            }));
                         }
                         ConnectionState::InLobby(lobby) => {
                             let mut lobby = lobby.blocking_lock();
@@ -1430,6 +2058,7 @@ fn show_bottom_pane(
                     };
 
                     let mut submitted = false;
+                    let mut queue_submitted = false;
                     if cancellation_token.is_none() {
                         if ui
                             .add_enabled(
@@ -1454,6 +2083,53 @@ fn show_bottom_pane(
                             let _ = clipboard.set_text(link_code.clone());
                         }
 
+                        if ui
+                            .add_enabled(
+                                !error_window_open && link_code.is_empty() && matchmaking_game_info.is_some(),
+                                egui::Button::new(egui::RichText::new("⚡")),
+                            )
+                            .on_hover_text(i18n::LOCALES.lookup(&config.language, "play-quick-match").unwrap())
+                            .clicked()
+                        {
+                            queue_submitted = true;
+                        }
+
+                        if ui
+                            .add_enabled(
+                                matchmaking_game_info.is_some(),
+                                egui::SelectableLabel::new(*advertise, "📣"),
+                            )
+                            .on_hover_text(i18n::LOCALES.lookup(&config.language, "play-advertise").unwrap())
+                            .clicked()
+                        {
+                            *advertise = !*advertise;
+                        }
+
+                        if ui
+                            .add_enabled(!error_window_open, egui::Button::new(egui::RichText::new("🌐")))
+                            .on_hover_text(i18n::LOCALES.lookup(&config.language, "play-public-lobbies").unwrap())
+                            .clicked()
+                        {
+                            let mut state = public_lobbies.lock();
+                            state.open = !state.open;
+                            let should_refresh = state.open && !state.loading;
+                            drop(state);
+                            if should_refresh {
+                                refresh_public_lobbies(
+                                    ui.ctx().clone(),
+                                    if !config.matchmaking_endpoint.is_empty() {
+                                        config.matchmaking_endpoint.clone()
+                                    } else {
+                                        config::DEFAULT_MATCHMAKING_ENDPOINT.to_string()
+                                    },
+                                    public_lobbies.clone(),
+                                    matchmaking_game_info
+                                        .as_ref()
+                                        .map(|game_info| game_info.netplay_compatibility.clone()),
+                                );
+                            }
+                        }
+
                         if config.streamer_mode {
                             if ui
                                 .selectable_label(*show_link_code, "👁️")
@@ -1488,6 +2164,14 @@ fn show_bottom_pane(
                         if lobby.sender.is_some() {
                             if !was_ready && ready {
                                 *show_save_select = None;
+                                if let Some(selection) = selection.as_ref() {
+                                    if let Err(e) =
+                                        save::history::History::new(config.save_history_path(), config.saves_path())
+                                            .snapshot(&selection.save.path, save::history::Reason::Netplay)
+                                    {
+                                        log::error!("failed to snapshot save: {:?}", e);
+                                    }
+                                }
                                 let save_data = lobby.local_selection.as_ref().map(|selection| selection.save.to_vec());
                                 if let Some(save_data) = save_data {
                                     let _ = sync::block_on(lobby.commit(&save_data));
@@ -1537,13 +2221,52 @@ fn show_bottom_pane(
                         submitted = true;
                     }
 
-                    if submitted {
+                    if let Some(session_id) = public_lobbies.lock().join.take() {
+                        if cancellation_token.is_none() {
+                            *link_code = session_id;
+                            // Joining someone else's lobby must not advertise it again.
+                            *advertise = false;
+                            submitted = true;
+                        }
+                    }
+
+                    if submitted || queue_submitted {
                         let audio_binder = audio_binder.clone();
                         let egui_ctx = ui.ctx().clone();
                         let session = session.clone();
                         let emu_tps_counter = emu_tps_counter.clone();
 
-                        if !link_code.is_empty() {
+                        let nickname = config.nickname.clone().unwrap_or_else(|| "".to_string());
+                        let signaling = if queue_submitted {
+                            matchmaking_game_info.clone().map(|game_info| {
+                                Signaling::Queue(tango_protos::matchmaking::packet::Queue {
+                                    nickname: nickname.clone(),
+                                    game_info: Some(game_info),
+                                    match_type: config.default_match_type as u32,
+                                    match_subtype: 0,
+                                })
+                            })
+                        } else if !link_code.is_empty() {
+                            Some(Signaling::LinkCode {
+                                link_code: link_code.to_owned(),
+                                advertise: if *advertise {
+                                    matchmaking_game_info.clone().map(|game_info| {
+                                        tango_protos::matchmaking::packet::Advertise {
+                                            nickname: nickname.clone(),
+                                            game_info: Some(game_info),
+                                            match_type: config.default_match_type as u32,
+                                            match_subtype: 0,
+                                        }
+                                    })
+                                } else {
+                                    None
+                                },
+                            })
+                        } else {
+                            None
+                        };
+
+                        if let Some(signaling) = signaling {
                             let cancellation_token = tokio_util::sync::CancellationToken::new();
                             *connection_task = Some(ConnectionTask::InProgress {
                                 state: ConnectionState::Starting,
@@ -1556,8 +2279,6 @@ fn show_bottom_pane(
                                 } else {
                                     config::DEFAULT_MATCHMAKING_ENDPOINT.to_string()
                                 };
-                                let link_code = link_code.to_owned();
-                                let nickname = config.nickname.clone().unwrap_or_else(|| "".to_string());
                                 let patches_path = config.patches_path();
                                 let replays_path = config.replays_path();
                                 let config_arc = config_arc.clone();
@@ -1574,7 +2295,7 @@ fn show_bottom_pane(
                                         roms_scanner,
                                         patches_scanner,
                                         matchmaking_endpoint,
-                                        link_code,
+                                        signaling,
                                         nickname,
                                         patches_path,
                                         replays_path,
@@ -1585,8 +2306,11 @@ fn show_bottom_pane(
                                     egui_ctx.request_repaint();
                                 }
                             });
-                        } else if let Some(selection) = selection.as_ref() {
+                        } else if let Some(selection) = selection.as_ref().filter(|_| !queue_submitted) {
                             let save_path = selection.save.path.clone();
+                            let save_history =
+                                save::history::History::new(config.save_history_path(), config.saves_path());
+                            let savestates_path = config.savestates_path();
                             let game = selection.game;
                             let rom = selection.rom.clone();
                             let patch = selection
@@ -1596,6 +2320,9 @@ fn show_bottom_pane(
 
                             // We have to run this in a thread in order to lock main_view safely. Furthermore, we have to use a real thread because of parking_lot::Mutex.
                             tokio::task::spawn_blocking(move || {
+                                if let Err(e) = save_history.snapshot(&save_path, save::history::Reason::SinglePlayer) {
+                                    log::error!("failed to snapshot save: {:?}", e);
+                                }
                                 *session.lock() = Some(
                                     session::Session::new_singleplayer(
                                         audio_binder,
@@ -1603,6 +2330,7 @@ fn show_bottom_pane(
                                         patch,
                                         &rom,
                                         &save_path,
+                                        &savestates_path,
                                         emu_tps_counter,
                                     )
                                     .unwrap(),
@@ -1656,6 +2384,8 @@ pub fn show(
             connection_task_arc,
             &mut state.link_code,
             &mut state.show_link_code,
+            &mut state.advertise,
+            &state.public_lobbies,
             &mut state.show_save_select,
         );
     }
@@ -2404,28 +3134,370 @@ pub fn show(
                 ui.separator();
 
                 if let Some(selection) = selection.as_mut() {
+                    let saves_path = config.saves_path();
+                    let save_label =
+                        |path: &std::path::Path| path.strip_prefix(&saves_path).unwrap_or(path).display().to_string();
+
+                    // Comparing shows the save's contents, so it's not available in streamer mode.
+                    if config.streamer_mode {
+                        selection.compare = None;
+                    }
+
+                    ui.add_enabled_ui(!config.streamer_mode, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(i18n::LOCALES.lookup(&config.language, "save-compare").unwrap());
+                            egui::ComboBox::from_id_source("save-compare-combobox")
+                                .selected_text(
+                                    selection
+                                        .compare
+                                        .as_ref()
+                                        .map(|(save, _)| save_label(&save.path))
+                                        .unwrap_or_else(|| {
+                                            i18n::LOCALES.lookup(&config.language, "save-compare.none").unwrap()
+                                        }),
+                                )
+                                .show_ui(ui, |ui| {
+                                    if ui
+                                        .selectable_label(
+                                            selection.compare.is_none(),
+                                            i18n::LOCALES.lookup(&config.language, "save-compare.none").unwrap(),
+                                        )
+                                        .clicked()
+                                    {
+                                        selection.compare = None;
+                                    }
+
+                                    let (family, _) = selection.game.family_and_variant();
+                                    let saves = saves_scanner.read();
+                                    let mut candidates = saves
+                                        .iter()
+                                        .filter(|(game, _)| game.family_and_variant().0 == family)
+                                        .flat_map(|(_, saves)| saves.iter())
+                                        .filter(|save| save.path != selection.save.path)
+                                        .collect::<Vec<_>>();
+                                    candidates.sort_by(|a, b| a.path.cmp(&b.path));
+
+                                    for save in candidates {
+                                        let checked = selection
+                                            .compare
+                                            .as_ref()
+                                            .map(|(compare, _)| compare.path == save.path)
+                                            .unwrap_or(false);
+                                        if ui.selectable_label(checked, save_label(&save.path)).clicked() {
+                                            let diff =
+                                                save::diff::diff(selection.save.save.as_ref(), save.save.as_ref());
+                                            selection.compare = Some((save.clone(), diff));
+                                        }
+                                    }
+                                });
+
+                            let mut restored = false;
+                            ui.menu_button(
+                                format!("🕓 {}", i18n::LOCALES.lookup(&config.language, "save-history").unwrap()),
+                                |ui| {
+                                    let save_history =
+                                        save::history::History::new(config.save_history_path(), config.saves_path());
+                                    let game = selection.game;
+                                    let save_path = &selection.save.path;
+                                    let current_save = &selection.save.save;
+                                    let assets = selection
+                                        .assets
+                                        .as_ref()
+                                        .map(|assets| assets.as_ref() as &dyn rom::Assets);
+                                    let snapshots = selection.history.get_or_insert_with(|| {
+                                        save_history
+                                            .list(save_path)
+                                            .into_iter()
+                                            .map(|snapshot| {
+                                                let diff = std::fs::read(&snapshot.path)
+                                                    .ok()
+                                                    .and_then(|buf| game.parse_save(&buf).ok())
+                                                    .map(|save| save::diff::diff(save.as_ref(), current_save.as_ref()));
+                                                (snapshot, diff)
+                                            })
+                                            .collect()
+                                    });
+
+                                    if snapshots.is_empty() {
+                                        ui.label(i18n::LOCALES.lookup(&config.language, "save-history.empty").unwrap());
+                                    }
+
+                                    for (snapshot, diff) in snapshots.iter() {
+                                        ui.horizontal(|ui| {
+                                            if ui
+                                                .button(
+                                                    i18n::LOCALES
+                                                        .lookup(&config.language, "save-history.restore")
+                                                        .unwrap(),
+                                                )
+                                                .clicked()
+                                            {
+                                                match save_history.restore(save_path, snapshot) {
+                                                    Ok(()) => {
+                                                        restored = true;
+                                                    }
+                                                    Err(e) => {
+                                                        log::error!("failed to restore snapshot: {:?}", e);
+                                                    }
+                                                }
+                                                ui.close_menu();
+                                            }
+
+                                            ui.label(
+                                                chrono::DateTime::<chrono::Local>::from(snapshot.ts)
+                                                    .formatl("%c", &config.language.to_string())
+                                                    .to_string(),
+                                            );
+                                            ui.label(
+                                                i18n::LOCALES
+                                                    .lookup(
+                                                        &config.language,
+                                                        &format!("save-history-reason-{}", snapshot.reason.as_str()),
+                                                    )
+                                                    .unwrap(),
+                                            );
+
+                                            match diff {
+                                                None => {
+                                                    ui.label(
+                                                        i18n::LOCALES
+                                                            .lookup(&config.language, "save-history.unreadable")
+                                                            .unwrap(),
+                                                    );
+                                                }
+                                                Some(diff) if diff.is_empty() => {
+                                                    ui.label(
+                                                        i18n::LOCALES
+                                                            .lookup(&config.language, "save-history.identical")
+                                                            .unwrap(),
+                                                    );
+                                                }
+                                                Some(diff) => {
+                                                    ui.label(
+                                                        i18n::LOCALES
+                                                            .lookup_with_args(
+                                                                &config.language,
+                                                                "save-history.changes",
+                                                                &std::collections::HashMap::from([(
+                                                                    "n",
+                                                                    diff.num_changes().into(),
+                                                                )]),
+                                                            )
+                                                            .unwrap(),
+                                                    )
+                                                    .on_hover_text(diff.to_text(assets));
+                                                }
+                                            }
+                                        });
+                                    }
+                                },
+                            );
+
+                            let mut converted = false;
+                            if let Some(to) = save::convert::counterpart(selection.game) {
+                                ui.menu_button(
+                                    format!("🌐 {}", i18n::LOCALES.lookup(&config.language, "save-convert").unwrap()),
+                                    |ui| {
+                                        let mut convert_into = None;
+                                        if save::convert::is_direct(selection.game, to) {
+                                            if ui
+                                                .button(
+                                                    i18n::LOCALES
+                                                        .lookup_with_args(
+                                                            &config.language,
+                                                            "save-convert.new",
+                                                            &std::collections::HashMap::from([(
+                                                                "game",
+                                                                i18n::LOCALES
+                                                                    .lookup(
+                                                                        &config.language,
+                                                                        &format!(
+                                                                            "game-{}.variant-{}",
+                                                                            to.family_and_variant().0,
+                                                                            to.family_and_variant().1
+                                                                        ),
+                                                                    )
+                                                                    .unwrap()
+                                                                    .into(),
+                                                            )]),
+                                                        )
+                                                        .unwrap(),
+                                                )
+                                                .clicked()
+                                            {
+                                                convert_into = Some(None);
+                                            }
+                                        } else {
+                                            ui.label(
+                                                i18n::LOCALES.lookup(&config.language, "save-convert.into").unwrap(),
+                                            );
+                                            let saves = saves_scanner.read();
+                                            let targets = saves.get(&to).map(|saves| saves.as_slice()).unwrap_or(&[]);
+                                            if targets.is_empty() {
+                                                ui.label(
+                                                    i18n::LOCALES
+                                                        .lookup(&config.language, "save-convert.no-targets")
+                                                        .unwrap(),
+                                                );
+                                            }
+                                            for target in targets {
+                                                if ui.button(save_label(&target.path)).clicked() {
+                                                    convert_into = Some(Some(target.clone()));
+                                                }
+                                            }
+                                        }
+
+                                        if let Some(target) = convert_into {
+                                            ui.close_menu();
+                                            let (description, level) = match convert_save(
+                                                config,
+                                                &roms_scanner,
+                                                selection.game,
+                                                &selection.save,
+                                                to,
+                                                target.as_ref(),
+                                            ) {
+                                                Ok((path, unconvertible)) => {
+                                                    converted = true;
+                                                    let mut description = i18n::LOCALES
+                                                        .lookup_with_args(
+                                                            &config.language,
+                                                            "save-convert.done",
+                                                            &std::collections::HashMap::from([(
+                                                                "path",
+                                                                format!("{}", path.display()).into(),
+                                                            )]),
+                                                        )
+                                                        .unwrap();
+                                                    for line in unconvertible.iter() {
+                                                        description.push_str(&format!("\n• {}", line));
+                                                    }
+                                                    (
+                                                        description,
+                                                        if unconvertible.is_empty() {
+                                                            rfd::MessageLevel::Info
+                                                        } else {
+                                                            rfd::MessageLevel::Warning
+                                                        },
+                                                    )
+                                                }
+                                                Err(e) => {
+                                                    log::error!("failed to convert save: {:?}", e);
+                                                    (
+                                                        i18n::LOCALES
+                                                            .lookup_with_args(
+                                                                &config.language,
+                                                                "save-convert.error",
+                                                                &std::collections::HashMap::from([(
+                                                                    "error",
+                                                                    format!("{}", e).into(),
+                                                                )]),
+                                                            )
+                                                            .unwrap(),
+                                                        rfd::MessageLevel::Error,
+                                                    )
+                                                }
+                                            };
+                                            rfd::MessageDialog::new()
+                                                .set_title(
+                                                    &i18n::LOCALES.lookup(&config.language, "save-convert").unwrap(),
+                                                )
+                                                .set_description(&description)
+                                                .set_level(level)
+                                                .show();
+                                        }
+                                    },
+                                );
+                            }
+
+                            if restored || converted {
+                                if let Err(e) = selection.reload_save() {
+                                    log::error!("failed to reload save: {:?}", e);
+                                }
+                                tokio::task::spawn_blocking({
+                                    let saves_scanner = saves_scanner.clone();
+                                    let saves_path = config.saves_path();
+                                    move || {
+                                        saves_scanner.rescan(move || Some(save::scan_saves(&saves_path)));
+                                    }
+                                });
+                            }
+                        });
+                    });
+
                     if let Some(assets) = selection.assets.as_ref() {
                         let game_language = selection.game.language();
-                        gui::save_view::show(
+                        let game_language = if let Some((_, _, metadata)) = selection.patch.as_ref() {
+                            if let Some(language) = metadata.rom_overrides.language.as_ref() {
+                                language
+                            } else {
+                                &game_language
+                            }
+                        } else {
+                            &game_language
+                        };
+                        if let Some((compare, diff)) = selection.compare.as_ref() {
+                            gui::save_diff_view::show(
+                                ui,
+                                clipboard,
+                                font_families,
+                                &config.language,
+                                game_language,
+                                &save_label(&selection.save.path),
+                                &save_label(&compare.path),
+                                diff,
+                                assets,
+                            );
+                        } else if let Some(setup) = gui::save_view::show(
                             ui,
                             config.streamer_mode,
                             clipboard,
                             font_families,
                             &config.language,
-                            if let Some((_, _, metadata)) = selection.patch.as_ref() {
-                                if let Some(language) = metadata.rom_overrides.language.as_ref() {
-                                    language
-                                } else {
-                                    &game_language
-                                }
-                            } else {
-                                &game_language
-                            },
+                            game_language,
+                            selection.game,
                             &selection.save.save,
                             assets,
                             &mut selection.save_view_state,
                             false,
-                        );
+                            true,
+                        ) {
+                            match setup.and_then(|setup| import_setup(config, selection, setup)) {
+                                Ok(()) => {
+                                    if let Err(e) = selection.reload_save() {
+                                        log::error!("failed to reload save: {:?}", e);
+                                    }
+                                    tokio::task::spawn_blocking({
+                                        let saves_scanner = saves_scanner.clone();
+                                        let saves_path = config.saves_path();
+                                        move || {
+                                            saves_scanner.rescan(move || Some(save::scan_saves(&saves_path)));
+                                        }
+                                    });
+                                }
+                                Err(e) => {
+                                    log::error!("failed to import setup: {:?}", e);
+                                    rfd::MessageDialog::new()
+                                        .set_title(
+                                            &i18n::LOCALES.lookup(&config.language, "save-import-setup").unwrap(),
+                                        )
+                                        .set_description(
+                                            &i18n::LOCALES
+                                                .lookup_with_args(
+                                                    &config.language,
+                                                    "save-import-setup.error",
+                                                    &std::collections::HashMap::from([(
+                                                        "error",
+                                                        format!("{}", e).into(),
+                                                    )]),
+                                                )
+                                                .unwrap(),
+                                        )
+                                        .set_level(rfd::MessageLevel::Error)
+                                        .show();
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -2438,5 +3510,6 @@ pub fn show(
     {
         let mut lobby = lobby.blocking_lock();
         let _ = sync::block_on(lobby.set_local_selection(&selection));
+        let _ = sync::block_on(lobby.update_legality(config.ruleset.as_deref(), &selection, &config.language));
     }
 }
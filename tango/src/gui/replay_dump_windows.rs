@@ -1,6 +1,6 @@
 use fluent_templates::Loader;
 
-use crate::{i18n, replay};
+use crate::{gui, i18n, replay};
 
 pub struct State {
     children: std::collections::HashMap<u64, ChildState>,
@@ -21,23 +21,31 @@ impl State {
         &mut self,
         local_rom: Vec<u8>,
         remote_rom: Option<Vec<u8>>,
-        replay: replay::Replay,
+        replays: Vec<replay::Replay>,
         path: std::path::PathBuf,
+        video_filters: Vec<String>,
     ) {
         let id = self.next_id;
         self.next_id += 1;
+        let encoder = if replay::export::is_ffmpeg_available(&None) {
+            replay::export::Encoder::Ffmpeg
+        } else {
+            replay::export::Encoder::Avi
+        };
         let mut output_path = path.clone();
-        output_path.set_extension("mp4");
+        output_path.set_extension(output_extension(encoder));
         self.children.insert(
             id,
             ChildState {
                 cancellation_token: None,
+                encoder,
                 output_path,
                 local_rom,
                 remote_rom,
-                replay,
+                replays,
                 path,
                 scale: Some(DEFAULT_SCALE),
+                video_filters,
                 disable_bgm: false,
                 twosided: false,
                 progress: std::sync::Arc::new(parking_lot::Mutex::new((0, 0))),
@@ -47,14 +55,23 @@ impl State {
     }
 }
 
+fn output_extension(encoder: replay::export::Encoder) -> &'static str {
+    match encoder {
+        replay::export::Encoder::Ffmpeg => "mp4",
+        replay::export::Encoder::Avi => "avi",
+    }
+}
+
 pub struct ChildState {
     cancellation_token: Option<tokio_util::sync::CancellationToken>,
+    encoder: replay::export::Encoder,
     output_path: std::path::PathBuf,
     local_rom: Vec<u8>,
     remote_rom: Option<Vec<u8>>,
-    replay: replay::Replay,
+    replays: Vec<replay::Replay>,
     path: std::path::PathBuf,
     scale: Option<usize>,
+    video_filters: Vec<String>,
     disable_bgm: bool,
     twosided: bool,
     progress: std::sync::Arc<parking_lot::Mutex<(usize, usize)>>,
@@ -88,6 +105,25 @@ pub fn show(
                     egui::Grid::new(format!("replay-dump-window-{}-grid", id))
                         .num_columns(2)
                         .show(ui, |ui| {
+                            ui.strong(i18n::LOCALES.lookup(language, "replays-export-encoder").unwrap());
+                            ui.horizontal(|ui| {
+                                let old_encoder = state.encoder;
+                                ui.selectable_value(
+                                    &mut state.encoder,
+                                    replay::export::Encoder::Ffmpeg,
+                                    i18n::LOCALES.lookup(language, "replays-export-encoder.ffmpeg").unwrap(),
+                                );
+                                ui.selectable_value(
+                                    &mut state.encoder,
+                                    replay::export::Encoder::Avi,
+                                    i18n::LOCALES.lookup(language, "replays-export-encoder.avi").unwrap(),
+                                );
+                                if state.encoder != old_encoder {
+                                    state.output_path.set_extension(output_extension(state.encoder));
+                                }
+                            });
+                            ui.end_row();
+
                             ui.strong(i18n::LOCALES.lookup(language, "replays-export-path").unwrap());
                             ui.horizontal(|ui| {
                                 ui.add(
@@ -107,9 +143,12 @@ pub fn show(
                                                 .output_path
                                                 .file_name()
                                                 .and_then(|filename| filename.to_str())
-                                                .unwrap_or("replay.mp4"),
+                                                .unwrap_or(&format!("replay.{}", output_extension(state.encoder))),
+                                        )
+                                        .add_filter(
+                                            &output_extension(state.encoder).to_uppercase(),
+                                            &[output_extension(state.encoder)],
                                         )
-                                        .add_filter("MP4", &["mp4"])
                                         .save_file()
                                     {
                                         state.output_path = path;
@@ -119,7 +158,8 @@ pub fn show(
                             ui.end_row();
 
                             ui.strong(i18n::LOCALES.lookup(language, "replays-export-scale-factor").unwrap());
-                            ui.horizontal(|ui| {
+                            ui.add_enabled_ui(state.encoder == replay::export::Encoder::Ffmpeg, |ui| {
+                                ui.horizontal(|ui| {
                                 let mut scale = state.scale.unwrap_or(DEFAULT_SCALE);
                                 ui.add_enabled(state.scale.is_some(), egui::DragValue::new(&mut scale).speed(1).clamp_range(1..=10));
                                 if state.scale.is_some() {
@@ -130,9 +170,19 @@ pub fn show(
                                 if lossless {
                                     state.scale = None;
                                 }
+                                });
                             });
                             ui.end_row();
 
+                            ui.strong(i18n::LOCALES.lookup(language, "replays-export-video-filter").unwrap());
+                            gui::settings_window::show_video_filters(
+                                ui,
+                                language,
+                                &format!("replay-dump-window-{}-video-filter", id),
+                                &mut state.video_filters,
+                            );
+                            ui.end_row();
+
                             ui.strong(i18n::LOCALES.lookup(language, "replays-export-disable-bgm").unwrap());
                             ui.add(egui::Checkbox::new(&mut state.disable_bgm, ""));
                             ui.end_row();
@@ -215,11 +265,13 @@ pub fn show(
                         let egui_ctx = ui.ctx().clone();
                         let local_rom = state.local_rom.clone();
                         let remote_rom = state.remote_rom.clone();
-                        let replay = state.replay.clone();
+                        let replays = state.replays.clone();
                         let path = state.output_path.clone();
                         let progress = state.progress.clone();
                         let result = state.result.clone();
                         let mut settings = replay::export::Settings::default_with_scale(state.scale);
+                        settings.encoder = state.encoder;
+                        settings.video_filters = state.video_filters.clone();
                         let twosided = state.twosided;
                         settings.disable_bgm = state.disable_bgm;
                         let cancellation_token = tokio_util::sync::CancellationToken::new();
@@ -231,7 +283,7 @@ pub fn show(
                             };
                             if twosided {
                                 tokio::select! {
-                                    r = replay::export::export_twosided(&local_rom, remote_rom.as_ref().unwrap(), &replay, &path, &settings, cb) => {
+                                    r = replay::export::export_twosided(&local_rom, remote_rom.as_ref().unwrap(), &replays, &path, &settings, cb) => {
                                         *result.lock() = Some(r);
                                         egui_ctx.request_repaint();
                                     }
@@ -239,7 +291,7 @@ pub fn show(
                                 }
                             } else {
                                 tokio::select! {
-                                    r = replay::export::export(&local_rom, &replay, &path, &settings, cb) => {
+                                    r = replay::export::export(&local_rom, &replays, &path, &settings, cb) => {
                                         *result.lock() = Some(r);
                                         egui_ctx.request_repaint();
                                     }
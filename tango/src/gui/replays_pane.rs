@@ -1,12 +1,13 @@
 use chrono_locale::LocaleDate;
 use fluent_templates::Loader;
 
-use crate::{audio, game, gui, i18n, patch, replay, rom, save, scanner, session, stats};
+use crate::{audio, game, gui, i18n, patch, replay, rom, save, scanner, session, spectator, stats};
 
 struct Selection {
     path: std::path::PathBuf,
     game: &'static (dyn game::Game + Send + Sync),
     replay: replay::Replay,
+    set: Option<(std::path::PathBuf, replay::set::Manifest)>,
     save: Box<dyn save::Save + Send + Sync>,
     local_rom: Vec<u8>,
     remote_rom: Option<Vec<u8>>,
@@ -15,9 +16,22 @@ struct Selection {
     save_view: gui::save_view::State,
 }
 
+#[derive(Clone)]
+enum SpectateStatus {
+    Idle,
+    Connecting,
+    Error(String),
+}
+
 pub struct State {
-    replays_scanner: scanner::Scanner<Vec<(std::path::PathBuf, bool, replay::Metadata)>>,
+    replays_scanner: scanner::Scanner<Vec<replay::index::Entry>>,
     selection: Option<Selection>,
+    query: replay::index::Query,
+    since_text: String,
+    until_text: String,
+    spectate_addr: String,
+    spectate_view_remote: bool,
+    spectate_status: std::sync::Arc<parking_lot::Mutex<SpectateStatus>>,
 }
 
 impl State {
@@ -25,56 +39,49 @@ impl State {
         Self {
             selection: None,
             replays_scanner: scanner::Scanner::new(),
+            query: Default::default(),
+            since_text: "".to_string(),
+            until_text: "".to_string(),
+            spectate_addr: "".to_string(),
+            spectate_view_remote: false,
+            spectate_status: std::sync::Arc::new(parking_lot::Mutex::new(SpectateStatus::Idle)),
         }
     }
 
-    pub fn rescan(&self, ctx: &egui::Context, replays_path: &std::path::Path) {
+    pub fn rescan(
+        &self,
+        ctx: &egui::Context,
+        replays_path: &std::path::Path,
+        replay_index_path: &std::path::Path,
+        patches_path: &std::path::Path,
+        roms_scanner: rom::Scanner,
+    ) {
         tokio::task::spawn_blocking({
             let replays_scanner = self.replays_scanner.clone();
             let replays_path = replays_path.to_path_buf();
+            let replay_index_path = replay_index_path.to_path_buf();
+            let patches_path = patches_path.to_path_buf();
             let egui_ctx = ctx.clone();
             move || {
-                replays_scanner.rescan(move || {
-                    let mut replays = vec![];
-                    for entry in walkdir::WalkDir::new(&replays_path) {
-                        let entry = match entry {
-                            Ok(entry) => entry,
-                            Err(_) => {
-                                continue;
-                            }
-                        };
-
-                        if !entry.file_type().is_file() {
-                            continue;
-                        }
-
-                        let path = entry.path();
-                        let mut f = match std::fs::File::open(path) {
-                            Ok(f) => f,
-                            Err(_) => {
-                                continue;
-                            }
-                        };
-
-                        let (num_inputs, metadata) = match replay::read_metadata(&mut f) {
-                            Ok((n, metadata)) => (n, metadata),
-                            Err(_) => {
-                                continue;
-                            }
-                        };
-
-                        replays.push((path.to_path_buf(), num_inputs > 0, metadata));
-                    }
-                    replays.sort_by_key(|(_, _, metadata)| {
-                        (
-                            std::cmp::Reverse(metadata.ts),
-                            metadata.link_code.clone(),
-                            metadata.round,
-                        )
-                    });
-                    Some(replays)
-                });
+                let refresh = || Some(replay::index::refresh(&replay_index_path, &replays_path));
+                replays_scanner.rescan(refresh);
                 egui_ctx.request_repaint();
+
+                // Show the list first, then fill in any missing results: this needs the replays to be run.
+                if replay::index::backfill_results(&replay_index_path, |game_info| {
+                    let game = game::find_by_family_and_variant(&game_info.rom_family, game_info.rom_variant as u8)?;
+                    let rom = roms_scanner.read().get(&game).cloned()?;
+                    let rom = if let Some(patch_info) = game_info.patch.as_ref() {
+                        let version = semver::Version::parse(&patch_info.version).ok()?;
+                        patch::apply_patch_from_disk(&rom, game, &patches_path, &patch_info.name, &version).ok()?
+                    } else {
+                        rom
+                    };
+                    Some((game, rom))
+                }) {
+                    replays_scanner.rescan(refresh);
+                    egui_ctx.request_repaint();
+                }
             }
         });
     }
@@ -91,6 +98,7 @@ pub fn show(
     patches_scanner: patch::Scanner,
     roms_scanner: rom::Scanner,
     replays_path: &std::path::Path,
+    video_filters: &[String],
     audio_binder: audio::LateBinder,
     emu_tps_counter: std::sync::Arc<parking_lot::Mutex<stats::Counter>>,
     session: std::sync::Arc<parking_lot::Mutex<Option<session::Session>>>,
@@ -99,6 +107,20 @@ pub fn show(
     let patches = patches_scanner.read();
 
     egui::SidePanel::left("replays-window-left-panel").show_inside(ui, |ui| {
+        show_spectate(
+            ui,
+            state,
+            language,
+            patches_path,
+            roms_scanner.clone(),
+            audio_binder.clone(),
+            emu_tps_counter.clone(),
+            session.clone(),
+        );
+        ui.separator();
+        show_filter(ui, state, language);
+        ui.separator();
+
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
             .id_source("replays-window-left")
@@ -113,51 +135,33 @@ pub fn show(
 
                 let replays = state.replays_scanner.read();
                 ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui| {
-                    for (path, _, metadata) in replays.iter() {
+                    for entry in replays.iter().filter(|entry| state.query.matches(entry)) {
+                        let path = &entry.path;
                         let ts = if let Some(ts) =
-                            std::time::UNIX_EPOCH.checked_add(std::time::Duration::from_millis(metadata.ts))
+                            std::time::UNIX_EPOCH.checked_add(std::time::Duration::from_millis(entry.ts))
                         {
                             ts
                         } else {
                             continue;
                         };
 
-                        let local_side = if let Some(side) = metadata.local_side.as_ref() {
-                            side
-                        } else {
-                            continue;
-                        };
-
-                        let remote_side = if let Some(side) = metadata.remote_side.as_ref() {
-                            side
-                        } else {
-                            continue;
-                        };
-
-                        let local_game_info = if let Some(game_info) = local_side.game_info.as_ref() {
-                            game_info
-                        } else {
-                            continue;
-                        };
+                        let local_game_info = &entry.local_side;
+                        let remote_side = &entry.remote_side;
 
                         let local_game = if let Some(game) = game::find_by_family_and_variant(
                             local_game_info.rom_family.as_str(),
-                            local_game_info.rom_variant as u8,
+                            local_game_info.rom_variant,
                         ) {
                             game
                         } else {
                             continue;
                         };
 
-                        let remote_game_info = if let Some(game_info) = remote_side.game_info.as_ref() {
-                            game_info
-                        } else {
-                            continue;
-                        };
+                        let remote_game_info = remote_side;
 
                         let remote_game = if let Some(game) = game::find_by_family_and_variant(
                             remote_game_info.rom_family.as_str(),
-                            remote_game_info.rom_variant as u8,
+                            remote_game_info.rom_variant,
                         ) {
                             game
                         } else {
@@ -206,7 +210,7 @@ pub fn show(
                                                 .unwrap()
                                                 .into(),
                                         ),
-                                        ("link_code", metadata.link_code.clone().into()),
+                                        ("link_code", entry.link_code.clone().into()),
                                         ("nickname", remote_side.nickname.clone().into()),
                                     ]),
                                 )
@@ -218,6 +222,29 @@ pub fn show(
                             ),
                         );
 
+                        if let Some(result) = entry.result {
+                            layout_job.append(
+                                &format!(
+                                    " · {}",
+                                    i18n::LOCALES
+                                        .lookup(
+                                            language,
+                                            match result {
+                                                replay::index::RoundResult::Win => "replays-result-win",
+                                                replay::index::RoundResult::Loss => "replays-result-loss",
+                                                replay::index::RoundResult::Draw => "replays-result-draw",
+                                            },
+                                        )
+                                        .unwrap()
+                                ),
+                                0.0,
+                                egui::TextFormat::simple(
+                                    ui.style().text_styles.get(&egui::TextStyle::Small).unwrap().clone(),
+                                    text_color,
+                                ),
+                            );
+                        }
+
                         if ui.selectable_label(selected, layout_job).clicked() {
                             let mut f = match std::fs::File::open(&path) {
                                 Ok(f) => f,
@@ -235,6 +262,18 @@ pub fn show(
                                 }
                             };
 
+                            let set =
+                                entry
+                                    .set
+                                    .as_ref()
+                                    .and_then(|set_path| match replay::set::Manifest::load(set_path) {
+                                        Ok(manifest) => Some((set_path.clone(), manifest)),
+                                        Err(e) => {
+                                            log::error!("failed to load set {}: {:?}", set_path.display(), e);
+                                            None
+                                        }
+                                    });
+
                             let save = match local_game.save_from_wram(replay.local_state.wram()) {
                                 Ok(save) => save,
                                 Err(e) => {
@@ -350,6 +389,7 @@ pub fn show(
                                 path: path.clone(),
                                 game: local_game,
                                 replay,
+                                set,
                                 save,
                                 local_rom,
                                 remote_rom,
@@ -420,11 +460,50 @@ pub fn show(
                             replay_dump_windows.add_child(
                                 selection.local_rom.clone(),
                                 selection.remote_rom.clone(),
-                                selection.replay.clone(),
+                                vec![selection.replay.clone()],
                                 selection.path.clone(),
+                                video_filters.to_vec(),
                             );
                         }
 
+                        if let Some((set_path, manifest)) = selection.set.as_ref() {
+                            if ui
+                                .button(format!(
+                                    "💾 {}",
+                                    i18n::LOCALES.lookup(language, "replays-export-set").unwrap()
+                                ))
+                                .clicked()
+                            {
+                                let replays = manifest
+                                    .round_paths(set_path)
+                                    .into_iter()
+                                    .flat_map(|path| {
+                                        let mut f = match std::fs::File::open(&path) {
+                                            Ok(f) => f,
+                                            Err(e) => {
+                                                log::error!("failed to load replay {}: {:?}", path.display(), e);
+                                                return None;
+                                            }
+                                        };
+                                        match replay::Replay::decode(&mut f) {
+                                            Ok(replay) => Some(replay),
+                                            Err(e) => {
+                                                log::error!("failed to load replay {}: {:?}", path.display(), e);
+                                                None
+                                            }
+                                        }
+                                    })
+                                    .collect::<Vec<_>>();
+                                replay_dump_windows.add_child(
+                                    selection.local_rom.clone(),
+                                    selection.remote_rom.clone(),
+                                    replays,
+                                    set_path.clone(),
+                                    video_filters.to_vec(),
+                                );
+                            }
+                        }
+
                         ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
                             ui.horizontal(|ui| {
                                 ui.with_layout(
@@ -441,6 +520,49 @@ pub fn show(
                                     },
                                 );
                             });
+                            if let Some((_, manifest)) = selection.set.as_ref() {
+                                ui.label(
+                                    i18n::LOCALES
+                                        .lookup_with_args(
+                                            language,
+                                            "replays-set",
+                                            &std::collections::HashMap::from([
+                                                ("local", manifest.score.0.into()),
+                                                ("remote", manifest.score.1.into()),
+                                                ("rounds", manifest.rounds.len().into()),
+                                            ]),
+                                        )
+                                        .unwrap(),
+                                );
+
+                                let round_upload_error = manifest
+                                    .rounds
+                                    .iter()
+                                    .find(|round| Some(round.filename.as_os_str()) == selection.path.file_name())
+                                    .and_then(|round| round.upload_error.as_ref());
+                                for (key, error) in [
+                                    ("replays-upload-error", round_upload_error),
+                                    ("replays-set-upload-error", manifest.upload_error.as_ref()),
+                                ] {
+                                    if let Some(error) = error {
+                                        ui.label(
+                                            egui::RichText::new(
+                                                i18n::LOCALES
+                                                    .lookup_with_args(
+                                                        language,
+                                                        key,
+                                                        &std::collections::HashMap::from([(
+                                                            "error",
+                                                            error.clone().into(),
+                                                        )]),
+                                                    )
+                                                    .unwrap(),
+                                            )
+                                            .color(egui::Color32::RED),
+                                        );
+                                    }
+                                }
+                            }
                         });
                     });
                     if let Some(assets) = selection.assets.as_ref() {
@@ -460,13 +582,279 @@ pub fn show(
                             } else {
                                 &game_language
                             },
+                            selection.game,
                             &selection.save,
                             &assets,
                             &mut selection.save_view,
                             false,
+                            false,
                         );
                     }
                 });
             });
     });
 }
+
+fn show_filter(ui: &mut egui::Ui, state: &mut State, language: &unic_langid::LanguageIdentifier) {
+    egui::CollapsingHeader::new(i18n::LOCALES.lookup(language, "replays-filter").unwrap())
+        .id_source("replays-filter")
+        .show(ui, |ui| {
+            let (rom_families, patches) = {
+                let replays = state.replays_scanner.read();
+                let mut rom_families = replays
+                    .iter()
+                    .map(|entry| entry.local_side.rom_family.clone())
+                    .collect::<Vec<_>>();
+                rom_families.sort();
+                rom_families.dedup();
+                let mut patches = replays
+                    .iter()
+                    .flat_map(|entry| entry.local_side.patch.clone())
+                    .collect::<Vec<_>>();
+                patches.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+                patches.dedup();
+                (rom_families, patches)
+            };
+
+            let any = i18n::LOCALES.lookup(language, "replays-filter-any").unwrap();
+
+            egui::Grid::new("replays-filter-grid").num_columns(2).show(ui, |ui| {
+                ui.label(i18n::LOCALES.lookup(language, "replays-filter-opponent").unwrap());
+                ui.add(egui::TextEdit::singleline(&mut state.query.opponent).desired_width(f32::INFINITY));
+                ui.end_row();
+
+                ui.label(i18n::LOCALES.lookup(language, "replays-filter-game").unwrap());
+                egui::ComboBox::from_id_source("replays-filter-game")
+                    .width(ui.available_width())
+                    .selected_text(
+                        state
+                            .query
+                            .rom_family
+                            .as_ref()
+                            .map(|family| {
+                                i18n::LOCALES
+                                    .lookup(language, &format!("game-{}.short", family))
+                                    .unwrap_or_else(|| family.clone())
+                            })
+                            .unwrap_or_else(|| any.clone()),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.query.rom_family, None, any.as_str());
+                        for family in rom_families {
+                            let label = i18n::LOCALES
+                                .lookup(language, &format!("game-{}.short", family))
+                                .unwrap_or_else(|| family.clone());
+                            ui.selectable_value(&mut state.query.rom_family, Some(family), label);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(i18n::LOCALES.lookup(language, "replays-filter-patch").unwrap());
+                egui::ComboBox::from_id_source("replays-filter-patch")
+                    .width(ui.available_width())
+                    .selected_text(
+                        state
+                            .query
+                            .patch
+                            .as_ref()
+                            .map(|patch| format!("{} v{}", patch.name, patch.version))
+                            .unwrap_or_else(|| any.clone()),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.query.patch, None, any.as_str());
+                        for patch in patches {
+                            let label = format!("{} v{}", patch.name, patch.version);
+                            ui.selectable_value(&mut state.query.patch, Some(patch), label);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(i18n::LOCALES.lookup(language, "replays-filter-result").unwrap());
+                let result_label = |result: Option<replay::index::RoundResult>| match result {
+                    None => any.clone(),
+                    Some(replay::index::RoundResult::Win) => {
+                        i18n::LOCALES.lookup(language, "replays-result-win").unwrap()
+                    }
+                    Some(replay::index::RoundResult::Loss) => {
+                        i18n::LOCALES.lookup(language, "replays-result-loss").unwrap()
+                    }
+                    Some(replay::index::RoundResult::Draw) => {
+                        i18n::LOCALES.lookup(language, "replays-result-draw").unwrap()
+                    }
+                };
+                egui::ComboBox::from_id_source("replays-filter-result")
+                    .width(ui.available_width())
+                    .selected_text(result_label(state.query.result))
+                    .show_ui(ui, |ui| {
+                        for result in [
+                            None,
+                            Some(replay::index::RoundResult::Win),
+                            Some(replay::index::RoundResult::Loss),
+                            Some(replay::index::RoundResult::Draw),
+                        ] {
+                            ui.selectable_value(&mut state.query.result, result, result_label(result));
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(i18n::LOCALES.lookup(language, "replays-filter-since").unwrap());
+                if ui
+                    .add(
+                        egui::TextEdit::singleline(&mut state.since_text)
+                            .desired_width(f32::INFINITY)
+                            .hint_text(i18n::LOCALES.lookup(language, "replays-filter-date-hint").unwrap()),
+                    )
+                    .changed()
+                {
+                    state.query.since = chrono::NaiveDate::parse_from_str(state.since_text.trim(), "%Y-%m-%d").ok();
+                }
+                ui.end_row();
+
+                ui.label(i18n::LOCALES.lookup(language, "replays-filter-until").unwrap());
+                if ui
+                    .add(
+                        egui::TextEdit::singleline(&mut state.until_text)
+                            .desired_width(f32::INFINITY)
+                            .hint_text(i18n::LOCALES.lookup(language, "replays-filter-date-hint").unwrap()),
+                    )
+                    .changed()
+                {
+                    state.query.until = chrono::NaiveDate::parse_from_str(state.until_text.trim(), "%Y-%m-%d").ok();
+                }
+                ui.end_row();
+            });
+        });
+}
+
+fn show_spectate(
+    ui: &mut egui::Ui,
+    state: &mut State,
+    language: &unic_langid::LanguageIdentifier,
+    patches_path: &std::path::Path,
+    roms_scanner: rom::Scanner,
+    audio_binder: audio::LateBinder,
+    emu_tps_counter: std::sync::Arc<parking_lot::Mutex<stats::Counter>>,
+    session: std::sync::Arc<parking_lot::Mutex<Option<session::Session>>>,
+) {
+    let spectate_status = state.spectate_status.lock().clone();
+    let connecting = matches!(spectate_status, SpectateStatus::Connecting);
+
+    ui.add_enabled_ui(!connecting, |ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.spectate_addr)
+                .desired_width(f32::INFINITY)
+                .hint_text(i18n::LOCALES.lookup(language, "replays-spectate-addr").unwrap()),
+        );
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut state.spectate_view_remote,
+                i18n::LOCALES.lookup(language, "replays-spectate-view-remote").unwrap(),
+            );
+
+            if ui
+                .button(format!(
+                    "👁️ {}",
+                    i18n::LOCALES.lookup(language, "replays-spectate").unwrap()
+                ))
+                .clicked()
+                && !state.spectate_addr.is_empty()
+            {
+                *state.spectate_status.lock() = SpectateStatus::Connecting;
+                tokio::task::spawn({
+                    let egui_ctx = ui.ctx().clone();
+                    let addr = state.spectate_addr.clone();
+                    let view_remote = state.spectate_view_remote;
+                    let spectate_status = state.spectate_status.clone();
+                    let patches_path = patches_path.to_path_buf();
+                    let roms_scanner = roms_scanner.clone();
+                    let audio_binder = audio_binder.clone();
+                    let emu_tps_counter = emu_tps_counter.clone();
+                    let session = session.clone();
+                    async move {
+                        let r = async {
+                            let mut receiver = spectator::Receiver::connect(&addr).await?;
+                            // If we joined between rounds, wait until the next one starts.
+                            let round_start = loop {
+                                if let spectator::Packet::RoundStart(round_start) = receiver.receive().await? {
+                                    break round_start;
+                                }
+                            };
+
+                            let metadata = round_start.metadata()?;
+                            let side = if view_remote {
+                                metadata.remote_side
+                            } else {
+                                metadata.local_side
+                            }
+                            .ok_or_else(|| anyhow::anyhow!("missing side"))?;
+                            let game_info = side.game_info.ok_or_else(|| anyhow::anyhow!("missing game info"))?;
+                            let game =
+                                game::find_by_family_and_variant(&game_info.rom_family, game_info.rom_variant as u8)
+                                    .ok_or_else(|| anyhow::anyhow!("unknown game"))?;
+                            let mut rom = roms_scanner
+                                .read()
+                                .get(&game)
+                                .cloned()
+                                .ok_or_else(|| anyhow::anyhow!("missing rom"))?;
+
+                            let patch = if let Some(patch_info) = game_info.patch.as_ref() {
+                                let version = semver::Version::parse(&patch_info.version)?;
+                                rom = patch::apply_patch_from_disk(
+                                    &rom,
+                                    game,
+                                    &patches_path,
+                                    &patch_info.name,
+                                    &version,
+                                )?;
+                                Some((patch_info.name.clone(), version))
+                            } else {
+                                None
+                            };
+
+                            *session.lock() = Some(session::Session::new_spectator(
+                                audio_binder,
+                                game,
+                                patch,
+                                &rom,
+                                emu_tps_counter,
+                                round_start,
+                                receiver,
+                                view_remote,
+                            )?);
+                            Ok::<(), anyhow::Error>(())
+                        }
+                        .await;
+
+                        *spectate_status.lock() = match r {
+                            Ok(()) => SpectateStatus::Idle,
+                            Err(e) => {
+                                log::error!("failed to spectate: {:?}", e);
+                                SpectateStatus::Error(format!("{}", e))
+                            }
+                        };
+                        egui_ctx.request_repaint();
+                    }
+                });
+            }
+
+            if connecting {
+                ui.spinner();
+            }
+        });
+    });
+
+    if let SpectateStatus::Error(e) = spectate_status {
+        ui.label(
+            egui::RichText::new(
+                i18n::LOCALES
+                    .lookup_with_args(
+                        language,
+                        "replays-spectate-error",
+                        &std::collections::HashMap::from([("error", e.into())]),
+                    )
+                    .unwrap(),
+            )
+            .color(egui::Color32::RED),
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use fluent_templates::Loader;
+
+use crate::{gui, i18n, rom, save};
+
+fn modcard_state(lang: &unic_langid::LanguageIdentifier, enabled: Option<bool>) -> String {
+    match enabled {
+        Some(true) => i18n::LOCALES.lookup(lang, "save-diff-modcard-on").unwrap(),
+        Some(false) => i18n::LOCALES.lookup(lang, "save-diff-modcard-off").unwrap(),
+        None => "—".to_string(),
+    }
+}
+
+// Shows a change with the name on the left and each side's value in its own column.
+fn show_row(ui: &mut egui::Ui, name: egui::RichText, left: String, right: String) {
+    ui.label(name);
+    ui.label(left);
+    ui.label(right);
+    ui.end_row();
+}
+
+pub fn show(
+    ui: &mut egui::Ui,
+    clipboard: &mut arboard::Clipboard,
+    font_families: &gui::FontFamilies,
+    lang: &unic_langid::LanguageIdentifier,
+    game_lang: &unic_langid::LanguageIdentifier,
+    left_label: &str,
+    right_label: &str,
+    diff: &save::diff::Diff,
+    assets: &Box<dyn rom::Assets + Send + Sync>,
+) {
+    let assets = Some(assets.as_ref() as &dyn rom::Assets);
+    let game_text = |s: String| egui::RichText::new(s).family(font_families.for_language(game_lang));
+    let or_none = |s: Option<String>| s.unwrap_or_else(|| "—".to_string());
+
+    ui.horizontal(|ui| {
+        if ui
+            .button(format!(
+                "📋 {}",
+                i18n::LOCALES.lookup(lang, "copy-to-clipboard").unwrap(),
+            ))
+            .clicked()
+        {
+            let _ = clipboard.set_text(diff.to_text(assets));
+        }
+    });
+
+    if diff.is_empty() {
+        ui.label(i18n::LOCALES.lookup(lang, "save-diff-no-changes").unwrap());
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .id_source("save-diff-view")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("save-diff-view-grid")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong(left_label);
+                    ui.strong(right_label);
+                    ui.end_row();
+
+                    for folder in diff.folders.iter() {
+                        ui.heading(
+                            i18n::LOCALES
+                                .lookup_with_args(
+                                    lang,
+                                    "save-diff-folder",
+                                    &std::collections::HashMap::from([("n", (folder.folder_index + 1).into())]),
+                                )
+                                .unwrap(),
+                        );
+                        ui.end_row();
+
+                        for change in folder.chips.iter() {
+                            show_row(
+                                ui,
+                                game_text(save::diff::chip_name(assets, &change.chip)),
+                                change.left.to_string(),
+                                change.right.to_string(),
+                            );
+                        }
+
+                        if let Some((left, right)) = folder.regular_chip.as_ref() {
+                            show_row(
+                                ui,
+                                egui::RichText::new(i18n::LOCALES.lookup(lang, "save-diff-regular-chip").unwrap()),
+                                or_none(left.as_ref().map(|chip| save::diff::chip_name(assets, chip))),
+                                or_none(right.as_ref().map(|chip| save::diff::chip_name(assets, chip))),
+                            );
+                        }
+
+                        if let Some((left, right)) = folder.tag_chips.as_ref() {
+                            let tag_chip_names = |chips: &[save::Chip; 2]| {
+                                format!(
+                                    "{} + {}",
+                                    save::diff::chip_name(assets, &chips[0]),
+                                    save::diff::chip_name(assets, &chips[1])
+                                )
+                            };
+                            show_row(
+                                ui,
+                                egui::RichText::new(i18n::LOCALES.lookup(lang, "save-diff-tag-chips").unwrap()),
+                                or_none(left.as_ref().map(tag_chip_names)),
+                                or_none(right.as_ref().map(tag_chip_names)),
+                            );
+                        }
+                    }
+
+                    if !diff.navicust.is_empty() {
+                        ui.heading(i18n::LOCALES.lookup(lang, "save-tab-navicust").unwrap());
+                        ui.end_row();
+
+                        for change in diff.navicust.iter() {
+                            let part = if let Some(part) = change.left.as_ref().or(change.right.as_ref()) {
+                                part
+                            } else {
+                                continue;
+                            };
+                            show_row(
+                                ui,
+                                game_text(save::diff::navicust_part_name(assets, part)),
+                                or_none(change.left.as_ref().map(save::diff::navicust_part_position)),
+                                or_none(change.right.as_ref().map(save::diff::navicust_part_position)),
+                            );
+                        }
+                    }
+
+                    if !diff.modcards.is_empty() {
+                        ui.heading(i18n::LOCALES.lookup(lang, "save-tab-modcards").unwrap());
+                        ui.end_row();
+
+                        for change in diff.modcards.iter() {
+                            show_row(
+                                ui,
+                                game_text(save::diff::modcard_name(assets, change.id)),
+                                modcard_state(lang, change.left),
+                                modcard_state(lang, change.right),
+                            );
+                        }
+                    }
+
+                    if !diff.dark_ai.is_empty() {
+                        ui.heading(i18n::LOCALES.lookup(lang, "save-tab-dark-ai").unwrap());
+                        ui.end_row();
+
+                        // Secondary use counts come after the main ones.
+                        let mut shown_secondary_heading = false;
+                        for change in diff.dark_ai.iter() {
+                            if change.secondary && !shown_secondary_heading {
+                                ui.strong(i18n::LOCALES.lookup(lang, "save-diff-dark-ai-secondary").unwrap());
+                                ui.end_row();
+                                shown_secondary_heading = true;
+                            }
+                            show_row(
+                                ui,
+                                game_text(
+                                    assets
+                                        .and_then(|assets| assets.chip(change.id))
+                                        .map(|info| info.name())
+                                        .unwrap_or_else(|| format!("#{}", change.id)),
+                                ),
+                                change.left.to_string(),
+                                change.right.to_string(),
+                            );
+                        }
+                    }
+                });
+        });
+}
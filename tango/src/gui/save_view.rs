@@ -6,7 +6,7 @@ mod navicust_view;
 
 use fluent_templates::Loader;
 
-use crate::{gui, i18n, rom, save};
+use crate::{game, gui, i18n, rom, save, sheet};
 
 #[derive(PartialEq, Clone)]
 enum Tab {
@@ -45,11 +45,14 @@ pub fn show(
     font_families: &gui::FontFamilies,
     lang: &unic_langid::LanguageIdentifier,
     game_lang: &unic_langid::LanguageIdentifier,
+    game: &'static (dyn game::Game + Send + Sync),
     save: &Box<dyn save::Save + Send + Sync>,
     assets: &Box<dyn rom::Assets + Send + Sync>,
     state: &mut State,
     prefer_vertical: bool,
-) {
+    can_import_setup: bool,
+) -> Option<anyhow::Result<save::setup::Setup>> {
+    let mut imported_setup = None;
     ui.vertical(|ui| {
         let navi_view = save.view_navi();
         let navicust_view = save.view_navicust();
@@ -105,6 +108,96 @@ pub fn show(
                     state.tab = Some(tab.clone());
                 }
             }
+
+            ui.menu_button(
+                format!("📋 {}", i18n::LOCALES.lookup(lang, "save-export-setup").unwrap()),
+                |ui| {
+                    let setup = save::setup::Setup::from_save(game.family_and_variant().0, save.as_ref());
+                    if ui
+                        .button(i18n::LOCALES.lookup(lang, "save-export-setup.json").unwrap())
+                        .clicked()
+                    {
+                        match setup.to_json() {
+                            Ok(json) => {
+                                let _ = clipboard.set_text(json);
+                            }
+                            Err(e) => {
+                                log::error!("failed to export setup: {:?}", e);
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button(i18n::LOCALES.lookup(lang, "save-export-setup.text").unwrap())
+                        .clicked()
+                    {
+                        let _ = clipboard.set_text(setup.to_text(Some(assets.as_ref())));
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui
+                        .button(i18n::LOCALES.lookup(lang, "save-export-setup.sheet-copy").unwrap())
+                        .clicked()
+                    {
+                        let image = sheet::render(lang, game_lang, save, assets);
+                        let _ = clipboard.set_image(arboard::ImageData {
+                            width: image.width() as usize,
+                            height: image.height() as usize,
+                            bytes: std::borrow::Cow::Borrowed(&image),
+                        });
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button(i18n::LOCALES.lookup(lang, "save-export-setup.sheet-save").unwrap())
+                        .clicked()
+                    {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .set_file_name("setup.png")
+                            .add_filter("PNG", &["png"])
+                            .save_file()
+                        {
+                            if let Err(e) = sheet::render(lang, game_lang, save, assets).save(&path) {
+                                log::error!("failed to save setup sheet to {}: {:?}", path.display(), e);
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                },
+            );
+
+            if can_import_setup {
+                ui.menu_button(
+                    format!("📥 {}", i18n::LOCALES.lookup(lang, "save-import-setup").unwrap()),
+                    |ui| {
+                        if ui
+                            .button(i18n::LOCALES.lookup(lang, "save-import-setup.clipboard").unwrap())
+                            .clicked()
+                        {
+                            imported_setup = Some(
+                                clipboard
+                                    .get_text()
+                                    .map_err(anyhow::Error::from)
+                                    .and_then(|text| save::setup::Setup::parse(&text)),
+                            );
+                            ui.close_menu();
+                        }
+                        if ui
+                            .button(i18n::LOCALES.lookup(lang, "save-import-setup.file").unwrap())
+                            .clicked()
+                        {
+                            if let Some(path) = rfd::FileDialog::new().add_filter("Setup", &["json", "txt"]).pick_file()
+                            {
+                                imported_setup = Some(
+                                    std::fs::read_to_string(&path)
+                                        .map_err(anyhow::Error::from)
+                                        .and_then(|text| save::setup::Setup::parse(&text)),
+                                );
+                            }
+                            ui.close_menu();
+                        }
+                    },
+                );
+            }
         });
 
         if state.tab.is_none() && !streamer_mode {
@@ -194,4 +287,5 @@ pub fn show(
             }
         }
     });
+    imported_setup
 }
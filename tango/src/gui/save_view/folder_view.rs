@@ -36,16 +36,7 @@ pub fn show<'a>(
         tag_count: usize,
     }
 
-    let mut chips = (0..30)
-        .map(|i| chips_view.chip(chips_view.equipped_folder_index(), i))
-        .collect::<Vec<_>>();
-
-    if !chips_view.regular_chip_is_in_place() {
-        if let Some(regular_chip_index) = chips_view.regular_chip_index(chips_view.equipped_folder_index()) {
-            let chip = chips.remove(0);
-            chips.insert(regular_chip_index, chip);
-        }
-    }
+    let chips = chips_view.folder_chips(chips_view.equipped_folder_index());
 
     let items = if state.grouped {
         let mut grouped = indexmap::IndexMap::new();
@@ -1,10 +1,9 @@
 use fluent_templates::Loader;
-use itertools::Itertools;
 
-use crate::{gui, i18n, rom, save};
+use crate::{gui, i18n, rom, save, sheet::navicust};
 
 pub struct State {
-    rendered_navicust_cache: Option<(image::RgbaImage, ComposedNavicust, egui::TextureHandle)>,
+    rendered_navicust_cache: Option<(image::RgbaImage, navicust::ComposedNavicust, egui::TextureHandle)>,
 }
 
 impl State {
@@ -15,47 +14,6 @@ impl State {
     }
 }
 
-fn navicust_part_colors(color: &rom::NavicustPartColor) -> (image::Rgba<u8>, image::Rgba<u8>) {
-    match color {
-        rom::NavicustPartColor::Red => (
-            image::Rgba([0xde, 0x10, 0x00, 0xff]),
-            image::Rgba([0xbd, 0x00, 0x00, 0xff]),
-        ),
-        rom::NavicustPartColor::Pink => (
-            image::Rgba([0xde, 0x8c, 0xc6, 0xff]),
-            image::Rgba([0xbd, 0x6b, 0xa5, 0xff]),
-        ),
-        rom::NavicustPartColor::Yellow => (
-            image::Rgba([0xde, 0xde, 0x00, 0xff]),
-            image::Rgba([0xbd, 0xbd, 0x00, 0xff]),
-        ),
-        rom::NavicustPartColor::Green => (
-            image::Rgba([0x18, 0xc6, 0x00, 0xff]),
-            image::Rgba([0x00, 0xa5, 0x00, 0xff]),
-        ),
-        rom::NavicustPartColor::Blue => (
-            image::Rgba([0x29, 0x84, 0xde, 0xff]),
-            image::Rgba([0x08, 0x60, 0xb8, 0xff]),
-        ),
-        rom::NavicustPartColor::White => (
-            image::Rgba([0xde, 0xde, 0xde, 0xff]),
-            image::Rgba([0xbd, 0xbd, 0xbd, 0xff]),
-        ),
-        rom::NavicustPartColor::Orange => (
-            image::Rgba([0xde, 0x7b, 0x00, 0xff]),
-            image::Rgba([0xbd, 0x5a, 0x00, 0xff]),
-        ),
-        rom::NavicustPartColor::Purple => (
-            image::Rgba([0x94, 0x00, 0xce, 0xff]),
-            image::Rgba([0x73, 0x00, 0xad, 0xff]),
-        ),
-        rom::NavicustPartColor::Gray => (
-            image::Rgba([0x84, 0x84, 0x84, 0xff]),
-            image::Rgba([0x63, 0x63, 0x63, 0xff]),
-        ),
-    }
-}
-
 fn show_part_name(
     ui: &mut egui::Ui,
     name: egui::RichText,
@@ -67,7 +25,7 @@ fn show_part_name(
         .inner_margin(egui::style::Margin::symmetric(4.0, 0.0))
         .rounding(egui::Rounding::same(2.0))
         .fill(if is_enabled {
-            let (color, _) = navicust_part_colors(color);
+            let (color, _) = navicust::navicust_part_colors(color);
             egui::Color32::from_rgb(color.0[0], color.0[1], color.0[2])
         } else {
             egui::Color32::from_rgb(0xbd, 0xbd, 0xbd)
@@ -79,593 +37,6 @@ fn show_part_name(
         .on_hover_text(description);
 }
 
-fn ncp_bitmap<'a>(info: &'a Box<dyn rom::NavicustPart + 'a>, compressed: bool, rot: u8) -> rom::NavicustBitmap {
-    let mut bitmap = if compressed {
-        info.compressed_bitmap()
-    } else {
-        info.uncompressed_bitmap()
-    };
-
-    match rot {
-        1 => {
-            bitmap = image::imageops::rotate90(&bitmap);
-        }
-        2 => {
-            image::imageops::rotate180_in_place(&mut bitmap);
-        }
-        3 => {
-            bitmap = image::imageops::rotate270(&bitmap);
-        }
-        _ => {}
-    }
-
-    bitmap
-}
-
-type ComposedNavicust = image::ImageBuffer<image::LumaA<u8>, Vec<u8>>;
-
-fn compose_navicust<'a>(
-    navicust_view: &Box<dyn save::NavicustView<'a> + 'a>,
-    assets: &Box<dyn rom::Assets + Send + Sync + 'a>,
-) -> ComposedNavicust {
-    let mut composed = image::ImageBuffer::new(navicust_view.width() as u32, navicust_view.height() as u32);
-    for i in 0..navicust_view.count() {
-        let ncp = if let Some(ncp) = navicust_view.navicust_part(i) {
-            ncp
-        } else {
-            continue;
-        };
-
-        let info = if let Some(info) = assets.navicust_part(ncp.id, ncp.variant) {
-            info
-        } else {
-            continue;
-        };
-
-        let bitmap = ncp_bitmap(&info, ncp.compressed, ncp.rot);
-        let width = bitmap.width();
-        let height = bitmap.height();
-
-        // Convert bitmap to composable Navicust image (LumaA).
-        image::imageops::overlay(
-            &mut composed,
-            &image::ImageBuffer::from_vec(
-                width,
-                height,
-                bitmap
-                    .into_iter()
-                    .flat_map(|b| [i as u8, if *b != 0 { 0xff } else { 0 }])
-                    .collect::<Vec<u8>>(),
-            )
-            .unwrap(),
-            ncp.col as i64 - (width / 2) as i64,
-            ncp.row as i64 - (height / 2) as i64,
-        );
-    }
-    composed
-}
-
-const PADDING_H: u32 = 20;
-const PADDING_V: u32 = 20;
-
-const BORDER_WIDTH: f32 = 6.0;
-const SQUARE_SIZE: f32 = 60.0;
-
-const BG_FILL_COLOR: image::Rgba<u8> = image::Rgba([0x20, 0x20, 0x20, 0xff]);
-const BORDER_STROKE_COLOR: image::Rgba<u8> = image::Rgba([0x00, 0x00, 0x00, 0xff]);
-
-fn render_navicust<'a>(
-    composed: &ComposedNavicust,
-    navicust_view: &Box<dyn save::NavicustView<'a> + 'a>,
-    assets: &Box<dyn rom::Assets + Send + Sync + 'a>,
-    raw_font: &[u8],
-) -> image::RgbaImage {
-    let body = render_navicust_body(composed, navicust_view, assets);
-
-    let color_bar = if let Some(style) = navicust_view.style() {
-        let color_bar_right = render_navicust_color_bar3(assets.style(style).and_then(|style| style.extra_ncp_color()));
-        let mut color_bar = image::RgbaImage::new(body.width(), color_bar_right.height());
-        let width = color_bar.width();
-        image::imageops::overlay(
-            &mut color_bar,
-            &color_bar_right,
-            (width - color_bar_right.width()) as i64,
-            0,
-        );
-
-        if let Some(info) = assets.style(style) {
-            let font = fontdue::Font::from_bytes(raw_font, fontdue::FontSettings::default()).unwrap();
-            let px = color_bar.height() as f32 * 2.0 / 3.0;
-            let mut layout = fontdue::layout::Layout::new(fontdue::layout::CoordinateSystem::PositiveYDown);
-            layout.append(&[&font], &fontdue::layout::TextStyle::new(&info.name(), px, 0));
-
-            for glyph in layout.glyphs() {
-                let (metrics, coverage) = font.rasterize(glyph.parent, px);
-                let g = image::RgbaImage::from_vec(
-                    metrics.width as u32,
-                    metrics.height as u32,
-                    coverage.into_iter().flat_map(|a| [0xff, 0xff, 0xff, a]).collect(),
-                )
-                .unwrap();
-                image::imageops::overlay(&mut color_bar, &g, glyph.x as i64, glyph.y as i64);
-            }
-        }
-
-        color_bar
-    } else {
-        render_navicust_color_bar456(navicust_view, assets)
-    };
-
-    let mut image = image::RgbaImage::new(
-        body.width() + PADDING_H * 2,
-        body.height() + PADDING_V * 2 + color_bar.height() + PADDING_V,
-    );
-
-    let bg = assets.navicust_bg().unwrap_or(image::Rgba([0, 0, 0, 0]));
-    for pixel in image.pixels_mut() {
-        *pixel = bg;
-    }
-
-    image::imageops::overlay(&mut image, &color_bar, PADDING_H as i64, PADDING_V as i64);
-    image::imageops::overlay(
-        &mut image,
-        &body,
-        PADDING_H as i64,
-        (PADDING_V + color_bar.height() + PADDING_V) as i64,
-    );
-
-    image
-}
-
-fn gather_ncp_colors<'a>(
-    navicust_view: &Box<dyn save::NavicustView<'a> + 'a>,
-    assets: &Box<dyn rom::Assets + Send + Sync + 'a>,
-) -> Vec<rom::NavicustPartColor> {
-    (0..navicust_view.count())
-        .flat_map(|i| {
-            let ncp = if let Some(ncp) = navicust_view.navicust_part(i) {
-                ncp
-            } else {
-                return vec![];
-            };
-
-            let info = if let Some(info) = assets.navicust_part(ncp.id, ncp.variant) {
-                info
-            } else {
-                return vec![];
-            };
-
-            let color = if let Some(color) = info.color() {
-                color
-            } else {
-                return vec![];
-            };
-
-            return vec![color];
-        })
-        .unique()
-        .collect::<Vec<_>>()
-}
-
-fn render_navicust_color_bar3<'a>(extra_color: Option<rom::NavicustPartColor>) -> image::RgbaImage {
-    const TILE_WIDTH: f32 = SQUARE_SIZE / 4.0;
-
-    let mut pixmap = tiny_skia::Pixmap::new(
-        (TILE_WIDTH * 4.0 + BORDER_WIDTH) as u32,
-        (SQUARE_SIZE / 2.0 + BORDER_WIDTH) as u32,
-    )
-    .unwrap();
-
-    let mut bg_fill_paint = tiny_skia::Paint::default();
-    bg_fill_paint.set_color_rgba8(
-        BG_FILL_COLOR.0[0],
-        BG_FILL_COLOR.0[1],
-        BG_FILL_COLOR.0[2],
-        BG_FILL_COLOR.0[3],
-    );
-
-    let mut border_stroke_paint = tiny_skia::Paint::default();
-    border_stroke_paint.set_color_rgba8(
-        BORDER_STROKE_COLOR.0[0],
-        BORDER_STROKE_COLOR.0[1],
-        BORDER_STROKE_COLOR.0[2],
-        BORDER_STROKE_COLOR.0[3],
-    );
-
-    let mut stroke = tiny_skia::Stroke::default();
-    stroke.width = BORDER_WIDTH as f32;
-    stroke.line_cap = tiny_skia::LineCap::Square;
-
-    let path = {
-        let mut pb = tiny_skia::PathBuilder::new();
-        pb.push_rect(0.0, 0.0, TILE_WIDTH, SQUARE_SIZE / 2.0);
-        pb.finish().unwrap()
-    };
-
-    let root_transform = tiny_skia::Transform::from_translate(BORDER_WIDTH / 2.0, BORDER_WIDTH / 2.0);
-
-    for (i, color) in [
-        Some(rom::NavicustPartColor::White),
-        Some(rom::NavicustPartColor::Pink),
-        Some(rom::NavicustPartColor::Yellow),
-        extra_color,
-    ]
-    .into_iter()
-    .enumerate()
-    {
-        let transform = root_transform.pre_translate(i as f32 * TILE_WIDTH, 0.0);
-        pixmap.fill_path(
-            &path,
-            &if let Some(color) = color {
-                let (_, plus_color) = navicust_part_colors(&color);
-                let mut fill_paint = tiny_skia::Paint::default();
-                fill_paint.set_color_rgba8(plus_color.0[0], plus_color.0[1], plus_color.0[2], plus_color.0[3]);
-                fill_paint
-            } else {
-                bg_fill_paint.clone()
-            },
-            tiny_skia::FillRule::Winding,
-            transform,
-            None,
-        );
-        pixmap.stroke_path(&path, &border_stroke_paint, &stroke, transform, None);
-    }
-
-    image::ImageBuffer::from_raw(pixmap.width(), pixmap.height(), pixmap.take()).unwrap()
-}
-
-fn render_navicust_color_bar456<'a>(
-    navicust_view: &Box<dyn save::NavicustView<'a> + 'a>,
-    assets: &Box<dyn rom::Assets + Send + Sync + 'a>,
-) -> image::RgbaImage {
-    const TILE_WIDTH: f32 = SQUARE_SIZE * 3.0 / 4.0;
-
-    let colors = gather_ncp_colors(navicust_view, assets);
-    let mut pixmap = tiny_skia::Pixmap::new(
-        TILE_WIDTH as u32 * std::cmp::max(4, colors.len()) as u32 + BORDER_WIDTH as u32 + BORDER_WIDTH as u32,
-        (SQUARE_SIZE / 2.0 + BORDER_WIDTH) as u32,
-    )
-    .unwrap();
-
-    let nonbug_colors = &colors[..std::cmp::min(colors.len(), 4)];
-    let bug_colors = colors.get(4..).unwrap_or(&[]);
-
-    let root_transform = tiny_skia::Transform::from_translate(BORDER_WIDTH / 2.0, BORDER_WIDTH / 2.0);
-
-    let mut bg_fill_paint = tiny_skia::Paint::default();
-    bg_fill_paint.set_color_rgba8(
-        BG_FILL_COLOR.0[0],
-        BG_FILL_COLOR.0[1],
-        BG_FILL_COLOR.0[2],
-        BG_FILL_COLOR.0[3],
-    );
-
-    let mut border_stroke_paint = tiny_skia::Paint::default();
-    border_stroke_paint.set_color_rgba8(
-        BORDER_STROKE_COLOR.0[0],
-        BORDER_STROKE_COLOR.0[1],
-        BORDER_STROKE_COLOR.0[2],
-        BORDER_STROKE_COLOR.0[3],
-    );
-
-    let mut stroke = tiny_skia::Stroke::default();
-    stroke.width = BORDER_WIDTH as f32;
-    stroke.line_cap = tiny_skia::LineCap::Square;
-
-    let outline_path = {
-        let mut pb = tiny_skia::PathBuilder::new();
-        pb.push_rect(0.0, 0.0, TILE_WIDTH, SQUARE_SIZE / 2.0);
-        pb.finish().unwrap()
-    };
-
-    let tile_path = {
-        let mut pb = tiny_skia::PathBuilder::new();
-        pb.push_rect(
-            BORDER_WIDTH / 2.0,
-            BORDER_WIDTH / 2.0,
-            TILE_WIDTH - BORDER_WIDTH,
-            SQUARE_SIZE / 2.0 - BORDER_WIDTH,
-        );
-        pb.finish().unwrap()
-    };
-
-    for i in 0..4 {
-        let transform = root_transform.pre_translate(i as f32 * TILE_WIDTH, 0.0);
-        pixmap.fill_path(
-            &tile_path,
-            &if let Some(color) = nonbug_colors.get(i) {
-                let (_, plus_color) = navicust_part_colors(color);
-                let mut fill_paint = tiny_skia::Paint::default();
-                fill_paint.set_color_rgba8(plus_color.0[0], plus_color.0[1], plus_color.0[2], plus_color.0[3]);
-                fill_paint
-            } else {
-                bg_fill_paint.clone()
-            },
-            tiny_skia::FillRule::Winding,
-            transform,
-            None,
-        );
-        pixmap.stroke_path(&outline_path, &border_stroke_paint, &stroke, transform, None);
-    }
-
-    for (i, bug_color) in bug_colors.iter().enumerate() {
-        let transform = root_transform.pre_translate((i + 4) as f32 * TILE_WIDTH + BORDER_WIDTH, 0.0);
-        pixmap.fill_path(
-            &tile_path,
-            &{
-                let (_, plus_color) = navicust_part_colors(bug_color);
-                let mut fill_paint = tiny_skia::Paint::default();
-                fill_paint.set_color_rgba8(plus_color.0[0], plus_color.0[1], plus_color.0[2], plus_color.0[3]);
-                fill_paint
-            },
-            tiny_skia::FillRule::Winding,
-            transform,
-            None,
-        );
-    }
-
-    image::ImageBuffer::from_raw(pixmap.width(), pixmap.height(), pixmap.take()).unwrap()
-}
-
-fn render_navicust_body<'a>(
-    composed: &ComposedNavicust,
-    navicust_view: &Box<dyn save::NavicustView<'a> + 'a>,
-    assets: &Box<dyn rom::Assets + Send + Sync + 'a>,
-) -> image::RgbaImage {
-    let mut pixmap = tiny_skia::Pixmap::new(
-        (composed.width() as f32 * SQUARE_SIZE + BORDER_WIDTH) as u32,
-        (composed.height() as f32 * SQUARE_SIZE + BORDER_WIDTH) as u32,
-    )
-    .unwrap();
-
-    let root_transform = tiny_skia::Transform::from_translate(BORDER_WIDTH / 2.0, BORDER_WIDTH / 2.0);
-
-    let mut bg_fill_paint = tiny_skia::Paint::default();
-    bg_fill_paint.set_color_rgba8(
-        BG_FILL_COLOR.0[0],
-        BG_FILL_COLOR.0[1],
-        BG_FILL_COLOR.0[2],
-        BG_FILL_COLOR.0[3],
-    );
-
-    let mut border_stroke_paint = tiny_skia::Paint::default();
-    border_stroke_paint.set_color_rgba8(
-        BORDER_STROKE_COLOR.0[0],
-        BORDER_STROKE_COLOR.0[1],
-        BORDER_STROKE_COLOR.0[2],
-        BORDER_STROKE_COLOR.0[3],
-    );
-
-    let mut stroke = tiny_skia::Stroke::default();
-    stroke.width = BORDER_WIDTH as f32;
-    stroke.line_cap = tiny_skia::LineCap::Square;
-
-    let square_path = {
-        let mut pb = tiny_skia::PathBuilder::new();
-        pb.push_rect(0.0, 0.0, SQUARE_SIZE, SQUARE_SIZE);
-        pb.finish().unwrap()
-    };
-
-    let plus_path = {
-        let mut pb = tiny_skia::PathBuilder::new();
-        pb.move_to(SQUARE_SIZE / 2.0, 0.0);
-        pb.line_to(SQUARE_SIZE / 2.0, SQUARE_SIZE);
-        pb.move_to(0.0, SQUARE_SIZE / 2.0);
-        pb.line_to(SQUARE_SIZE, SQUARE_SIZE / 2.0);
-        pb.finish().unwrap()
-    };
-
-    let command_line_path = {
-        let mut pb = tiny_skia::PathBuilder::new();
-        pb.move_to(0.0, 0.0);
-        pb.line_to(SQUARE_SIZE * composed.width() as f32, 0.0);
-        pb.finish().unwrap()
-    };
-
-    struct Neighbor {
-        offset: [isize; 2],
-        border_path: tiny_skia::Path,
-    }
-
-    let neighbors = [
-        Neighbor {
-            offset: [0, -1],
-            border_path: {
-                let mut pb = tiny_skia::PathBuilder::new();
-                pb.move_to(0.0, 0.0);
-                pb.line_to(SQUARE_SIZE, 0.0);
-                pb.finish().unwrap()
-            },
-        },
-        Neighbor {
-            offset: [-1, 0],
-            border_path: {
-                let mut pb = tiny_skia::PathBuilder::new();
-                pb.move_to(0.0, 0.0);
-                pb.line_to(0.0, SQUARE_SIZE);
-                pb.finish().unwrap()
-            },
-        },
-        Neighbor {
-            offset: [0, 1],
-            border_path: {
-                let mut pb = tiny_skia::PathBuilder::new();
-                pb.move_to(0.0, SQUARE_SIZE);
-                pb.line_to(SQUARE_SIZE, SQUARE_SIZE);
-                pb.finish().unwrap()
-            },
-        },
-        Neighbor {
-            offset: [1, 0],
-            border_path: {
-                let mut pb = tiny_skia::PathBuilder::new();
-                pb.move_to(SQUARE_SIZE, 0.0);
-                pb.line_to(SQUARE_SIZE, SQUARE_SIZE);
-                pb.finish().unwrap()
-            },
-        },
-    ];
-
-    // First pass: draw background.
-    for y in 0..composed.width() {
-        for x in 0..composed.height() {
-            if navicust_view.has_out_of_bounds()
-                && ((x == 0 && y == 0)
-                    || (x == 0 && y == composed.height() - 1)
-                    || (x == composed.width() - 1 && y == 0)
-                    || (x == composed.width() - 1 && y == composed.height() - 1))
-            {
-                continue;
-            }
-
-            let transform = root_transform.pre_translate(x as f32 * SQUARE_SIZE, y as f32 * SQUARE_SIZE);
-
-            pixmap.fill_path(
-                &square_path,
-                &bg_fill_paint,
-                tiny_skia::FillRule::Winding,
-                transform,
-                None,
-            );
-            pixmap.stroke_path(&square_path, &border_stroke_paint, &stroke, transform, None);
-        }
-    }
-
-    // Second pass: draw squares.
-    for (i, p) in composed.pixels().enumerate() {
-        let x = i % composed.width() as usize;
-        let y = i / composed.width() as usize;
-        let [l, a] = p.0;
-
-        if a == 0 {
-            continue;
-        }
-
-        let ncp_i = l as usize;
-        let ncp = if let Some(ncp) = navicust_view.navicust_part(ncp_i) {
-            ncp
-        } else {
-            continue;
-        };
-
-        let info = if let Some(info) = assets.navicust_part(ncp.id, ncp.variant) {
-            info
-        } else {
-            continue;
-        };
-
-        let color = if let Some(color) = info.color() {
-            color
-        } else {
-            continue;
-        };
-
-        let transform = root_transform.pre_translate(x as f32 * SQUARE_SIZE, y as f32 * SQUARE_SIZE);
-
-        let (solid_color, plus_color) = navicust_part_colors(&color);
-        let mut fill_paint = tiny_skia::Paint::default();
-        fill_paint.set_color_rgba8(solid_color.0[0], solid_color.0[1], solid_color.0[2], solid_color.0[3]);
-
-        let mut stroke_paint = tiny_skia::Paint::default();
-        stroke_paint.set_color_rgba8(plus_color.0[0], plus_color.0[1], plus_color.0[2], plus_color.0[3]);
-
-        pixmap.fill_path(&square_path, &fill_paint, tiny_skia::FillRule::Winding, transform, None);
-        pixmap.stroke_path(&square_path, &stroke_paint, &stroke, transform, None);
-        if !info.is_solid() {
-            pixmap.stroke_path(&plus_path, &stroke_paint, &stroke, transform, None);
-        }
-    }
-
-    // Third pass: draw borders.
-    for (i, p) in composed.pixels().enumerate() {
-        let x = i % composed.width() as usize;
-        let y = i / composed.width() as usize;
-        let [l, a] = p.0;
-
-        if a == 0 {
-            continue;
-        }
-
-        let transform = root_transform.pre_translate(x as f32 * SQUARE_SIZE, y as f32 * SQUARE_SIZE);
-
-        let ncp_i = l as usize;
-        for neighbor in neighbors.iter() {
-            let x = x as isize + neighbor.offset[0];
-            let y = y as isize + neighbor.offset[1];
-
-            let mut should_stroke = x < 0 || x >= composed.width() as isize || y < 0 || y >= composed.height() as isize;
-            if !should_stroke {
-                let [l, a] = composed.get_pixel(x as u32, y as u32).0;
-                if a == 0 || l as usize != ncp_i {
-                    should_stroke = true;
-                }
-            }
-
-            if should_stroke {
-                pixmap.stroke_path(&neighbor.border_path, &border_stroke_paint, &stroke, transform, None);
-            }
-        }
-    }
-
-    // Fourth pass: draw command line.
-    let command_line_top = navicust_view.command_line() as f32 * SQUARE_SIZE;
-    pixmap.stroke_path(
-        &command_line_path,
-        &border_stroke_paint,
-        &stroke,
-        root_transform.pre_translate(0.0, command_line_top + SQUARE_SIZE * 1.0 / 4.0),
-        None,
-    );
-    pixmap.stroke_path(
-        &command_line_path,
-        &border_stroke_paint,
-        &stroke,
-        root_transform.pre_translate(0.0, command_line_top + SQUARE_SIZE * 3.0 / 4.0),
-        None,
-    );
-
-    // Fifth pass: draw out of bounds overlay.
-    if navicust_view.has_out_of_bounds() {
-        let path = {
-            let mut pb = tiny_skia::PathBuilder::new();
-
-            let w = SQUARE_SIZE + BORDER_WIDTH;
-            let h = (composed.height() - 2) as f32 * SQUARE_SIZE + BORDER_WIDTH;
-
-            // Left
-            pb.push_rect(-BORDER_WIDTH / 2.0, 1.0 * SQUARE_SIZE - BORDER_WIDTH / 2.0, w, h);
-
-            // Right
-            pb.push_rect(
-                (composed.width() - 1) as f32 * SQUARE_SIZE - BORDER_WIDTH / 2.0,
-                1.0 * SQUARE_SIZE - BORDER_WIDTH / 2.0,
-                w,
-                h,
-            );
-
-            // Top
-            pb.push_rect(1.0 * SQUARE_SIZE - BORDER_WIDTH / 2.0, -BORDER_WIDTH / 2.0, h, w);
-
-            // Bottom
-            pb.push_rect(
-                1.0 * SQUARE_SIZE - BORDER_WIDTH / 2.0,
-                (composed.height() - 1) as f32 * SQUARE_SIZE - BORDER_WIDTH / 2.0,
-                h,
-                w,
-            );
-
-            pb.finish().unwrap()
-        };
-
-        let mut oob_paint = tiny_skia::Paint::default();
-        oob_paint.set_color_rgba8(0x00, 0x00, 0x00, 0x80);
-
-        pixmap.fill_path(&path, &oob_paint, tiny_skia::FillRule::Winding, root_transform, None);
-    }
-
-    image::ImageBuffer::from_raw(pixmap.width(), pixmap.height(), pixmap.take()).unwrap()
-}
-
 pub fn show<'a>(
     ui: &mut egui::Ui,
     clipboard: &mut arboard::Clipboard,
@@ -762,8 +133,8 @@ pub fn show<'a>(
                 },
                 |ui| {
                     if !state.rendered_navicust_cache.is_some() {
-                        let composed = compose_navicust(navicust_view, assets);
-                        let image = render_navicust(
+                        let composed = navicust::compose_navicust(navicust_view, assets);
+                        let image = navicust::render_navicust(
                             &composed,
                             navicust_view,
                             assets,
@@ -789,20 +160,20 @@ pub fn show<'a>(
                             let x = ((hover_pos.x - resp.rect.min.x) * 2.0) as u32;
                             let y = ((hover_pos.y - resp.rect.min.y) * 2.0) as u32;
 
-                            const LEFT: u32 = PADDING_H + (BORDER_WIDTH / 2.0) as u32;
-                            const TOP: u32 = PADDING_V
-                                + (SQUARE_SIZE / 2.0) as u32
-                                + BORDER_WIDTH as u32
-                                + PADDING_V
-                                + (BORDER_WIDTH / 2.0) as u32;
+                            const LEFT: u32 = navicust::PADDING_H + (navicust::BORDER_WIDTH / 2.0) as u32;
+                            const TOP: u32 = navicust::PADDING_V
+                                + (navicust::SQUARE_SIZE / 2.0) as u32
+                                + navicust::BORDER_WIDTH as u32
+                                + navicust::PADDING_V
+                                + (navicust::BORDER_WIDTH / 2.0) as u32;
 
                             if x >= LEFT
-                                && x < image.width() - PADDING_H - (BORDER_WIDTH / 2.0) as u32
+                                && x < image.width() - navicust::PADDING_H - (navicust::BORDER_WIDTH / 2.0) as u32
                                 && y >= TOP
-                                && y < image.height() - PADDING_V - (BORDER_WIDTH / 2.0) as u32
+                                && y < image.height() - navicust::PADDING_V - (navicust::BORDER_WIDTH / 2.0) as u32
                             {
-                                let tx = (x - LEFT) / SQUARE_SIZE as u32;
-                                let ty = (y - TOP) / SQUARE_SIZE as u32;
+                                let tx = (x - LEFT) / navicust::SQUARE_SIZE as u32;
+                                let ty = (y - TOP) / navicust::SQUARE_SIZE as u32;
 
                                 let [l, a] = composed.get_pixel(tx, ty).0;
                                 if a != 0 {
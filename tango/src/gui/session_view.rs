@@ -1,30 +1,158 @@
 use fluent_templates::Loader;
 
-use crate::{discord, gui, i18n, input, session, stats, sync, video};
+use crate::video::Filter;
+use crate::{discord, gui, i18n, input, save, session, stats, sync, video};
 
 mod replay_controls_window;
 
 pub struct State {
     vbuf: Option<VBuf>,
+    // Rebuilt whenever the filters or the scale they're shown at change.
+    video_pipeline: Option<(Vec<String>, f32, video::Pipeline)>,
     opponent_save_view: gui::save_view::State,
     own_save_view: gui::save_view::State,
+    // Set when the opponent's setup is being compared against our own.
+    opponent_setup_diff: Option<save::diff::Diff>,
     debug_window: Option<gui::debug_window::State>,
+    savestate_slot: usize,
+    // A short message shown over the emulator, e.g. after saving a state, and when it was shown.
+    osd: Option<(String, std::time::Instant)>,
 }
 
 impl State {
     pub fn new() -> State {
         Self {
             vbuf: None,
+            video_pipeline: None,
             opponent_save_view: gui::save_view::State::new(),
             own_save_view: gui::save_view::State::new(),
+            opponent_setup_diff: None,
             debug_window: None,
+            savestate_slot: 0,
+            osd: None,
         }
     }
 }
 
+// Speed controls that work the same in single player and in replays.
+fn handle_speed_inputs(input_state: &input::State, input_mapping: &input::Mapping, session: &session::Session) {
+    let uncapped = input_mapping.fast_forward.iter().any(|c| c.is_active(input_state));
+    if uncapped != session.is_uncapped() {
+        session.set_uncapped(uncapped);
+    }
+
+    if input_mapping.pause.iter().any(|c| c.is_pressed(input_state)) {
+        session.set_paused(!session.is_paused());
+    }
+
+    if input_mapping.frame_advance.iter().any(|c| c.is_pressed(input_state)) {
+        session.frame_step();
+    }
+}
+
+fn handle_savestate_inputs(
+    language: &unic_langid::LanguageIdentifier,
+    input_state: &input::State,
+    input_mapping: &input::Mapping,
+    session: &session::Session,
+    single_player: &session::SinglePlayer,
+    state: &mut State,
+) {
+    session.set_rewinding(input_mapping.rewind.iter().any(|c| c.is_active(input_state)));
+
+    let slot_args = |slot: usize| std::collections::HashMap::from([("slot", (slot + 1).into())]);
+
+    let mut slot_changed = false;
+    if input_mapping
+        .previous_state_slot
+        .iter()
+        .any(|c| c.is_pressed(input_state))
+    {
+        state.savestate_slot = (state.savestate_slot + session::NUM_SAVESTATE_SLOTS - 1) % session::NUM_SAVESTATE_SLOTS;
+        slot_changed = true;
+    }
+    if input_mapping.next_state_slot.iter().any(|c| c.is_pressed(input_state)) {
+        state.savestate_slot = (state.savestate_slot + 1) % session::NUM_SAVESTATE_SLOTS;
+        slot_changed = true;
+    }
+    if slot_changed {
+        state.osd = Some((
+            i18n::LOCALES
+                .lookup_with_args(
+                    language,
+                    if single_player.savestate_time(state.savestate_slot).is_some() {
+                        "savestate-slot"
+                    } else {
+                        "savestate-slot.empty"
+                    },
+                    &slot_args(state.savestate_slot),
+                )
+                .unwrap(),
+            std::time::Instant::now(),
+        ));
+    }
+
+    let result = if input_mapping.save_state.iter().any(|c| c.is_pressed(input_state)) {
+        Some(
+            session
+                .save_state_to_slot(state.savestate_slot)
+                .map(|_| "savestate-saved"),
+        )
+    } else if input_mapping.load_state.iter().any(|c| c.is_pressed(input_state)) {
+        Some(
+            session
+                .load_state_from_slot(state.savestate_slot)
+                .map(|_| "savestate-loaded"),
+        )
+    } else {
+        None
+    };
+
+    if let Some(result) = result {
+        let mut args = slot_args(state.savestate_slot);
+        let message = match result {
+            Ok(key) => i18n::LOCALES.lookup_with_args(language, key, &args).unwrap(),
+            Err(e) => {
+                log::error!("save state slot {}: {:?}", state.savestate_slot, e);
+                args.insert("error", format!("{}", e).into());
+                i18n::LOCALES
+                    .lookup_with_args(language, "savestate-error", &args)
+                    .unwrap()
+            }
+        };
+        state.osd = Some((message, std::time::Instant::now()));
+    }
+}
+
+fn show_osd(ctx: &egui::Context, osd: &mut Option<(String, std::time::Instant)>) {
+    const SHOW_FOR: std::time::Duration = std::time::Duration::from_secs(2);
+    if osd
+        .as_ref()
+        .map(|(_, shown_at)| shown_at.elapsed() >= SHOW_FOR)
+        .unwrap_or(false)
+    {
+        *osd = None;
+    }
+    let message = if let Some((message, _)) = osd.as_ref() {
+        message
+    } else {
+        return;
+    };
+    egui::Area::new("session-osd")
+        .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(8.0, 8.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(message);
+            });
+        });
+}
+
 struct VBuf {
     image: egui::ColorImage,
     texture: egui::TextureHandle,
+    // The emulator frame the image was filtered from, if any.
+    frame: Option<u64>,
 }
 
 impl VBuf {
@@ -36,6 +164,7 @@ impl VBuf {
                 egui::ColorImage::new([width, height], egui::Color32::BLACK),
                 egui::TextureFilter::Nearest,
             ),
+            frame: None,
         }
     }
 }
@@ -43,12 +172,48 @@ impl VBuf {
 fn show_emulator(
     ui: &mut egui::Ui,
     session: &session::Session,
-    video_filter: &str,
+    video_filters: &[String],
     max_scale: u32,
     integer_scaling: bool,
     vbuf: &mut Option<VBuf>,
+    video_pipeline: &mut Option<(Vec<String>, f32, video::Pipeline)>,
 ) {
-    let video_filter = video::filter_by_name(video_filter).unwrap_or(Box::new(video::NullFilter));
+    let mut scaling_factor = std::cmp::min_by(
+        ui.available_width() * ui.ctx().pixels_per_point() / mgba::gba::SCREEN_WIDTH as f32,
+        ui.available_height() * ui.ctx().pixels_per_point() / mgba::gba::SCREEN_HEIGHT as f32,
+        |a, b| a.partial_cmp(b).unwrap(),
+    );
+
+    if integer_scaling {
+        scaling_factor = scaling_factor.floor();
+    }
+
+    scaling_factor = std::cmp::max_by(scaling_factor, 1.0, |a, b| a.partial_cmp(b).unwrap());
+    if max_scale > 0 {
+        scaling_factor = std::cmp::min_by(scaling_factor, max_scale as f32, |a, b| a.partial_cmp(b).unwrap());
+    }
+
+    if !video_pipeline
+        .as_ref()
+        .map(|(names, scale, _)| names == video_filters && *scale == scaling_factor)
+        .unwrap_or(false)
+    {
+        // Anything filtered with the old pipeline needs to be filtered again.
+        if let Some(vbuf) = vbuf.as_mut() {
+            vbuf.frame = None;
+        }
+        let pipeline = video::Pipeline::new(
+            video_filters,
+            (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+            scaling_factor,
+        )
+        .unwrap_or_else(|e| {
+            log::error!("failed to build video filter pipeline: {:?}", e);
+            video::Pipeline::default()
+        });
+        *video_pipeline = Some((video_filters.to_vec(), scaling_factor, pipeline));
+    }
+    let (_, _, video_filter) = video_pipeline.as_mut().unwrap();
 
     // Apply stupid video scaling filter that only mint wants 🥴
     let (vbuf_width, vbuf_height) =
@@ -65,28 +230,21 @@ fn show_emulator(
         vbuf.as_mut().unwrap()
     };
 
-    video_filter.apply(
-        &session.lock_vbuf(),
-        bytemuck::cast_slice_mut(&mut vbuf.image.pixels[..]),
-        (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
-    );
-
-    vbuf.texture.set(vbuf.image.clone(), egui::TextureFilter::Nearest);
-
-    let mut scaling_factor = std::cmp::min_by(
-        ui.available_width() * ui.ctx().pixels_per_point() / mgba::gba::SCREEN_WIDTH as f32,
-        ui.available_height() * ui.ctx().pixels_per_point() / mgba::gba::SCREEN_HEIGHT as f32,
-        |a, b| a.partial_cmp(b).unwrap(),
-    );
-
-    if integer_scaling {
-        scaling_factor = scaling_factor.floor();
+    // The GUI repaints more often than the emulator draws frames, and filters like ghosting blend each frame with the
+    // previous one, so only filter new frames.
+    {
+        let session_vbuf = session.lock_vbuf();
+        if vbuf.frame != Some(session_vbuf.frame) {
+            video_filter.apply(
+                &session_vbuf.buf,
+                bytemuck::cast_slice_mut(&mut vbuf.image.pixels[..]),
+                (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+            );
+            vbuf.frame = Some(session_vbuf.frame);
+            vbuf.texture.set(vbuf.image.clone(), egui::TextureFilter::Nearest);
+        }
     }
 
-    scaling_factor = std::cmp::max_by(scaling_factor, 1.0, |a, b| a.partial_cmp(b).unwrap());
-    if max_scale > 0 {
-        scaling_factor = std::cmp::min_by(scaling_factor, max_scale as f32, |a, b| a.partial_cmp(b).unwrap());
-    }
     ui.image(
         &vbuf.texture,
         egui::Vec2::new(
@@ -105,10 +263,11 @@ pub fn show(
     input_state: &input::State,
     input_mapping: &input::Mapping,
     session: &session::Session,
-    video_filter: &str,
+    video_filters: &[String],
     integer_scaling: bool,
     max_scale: u32,
     speed_change_factor: f32,
+    slow_motion_factor: f32,
     show_own_setup: bool,
     crashstates_path: &std::path::Path,
     last_mouse_motion_time: &Option<std::time::Instant>,
@@ -157,22 +316,27 @@ pub fn show(
                 )),
             )));
         }
-        session::Mode::Replayer => {
+        session::Mode::Replayer(_) | session::Mode::Spectator(_) => {
             discord_client.set_current_activity(Some(discord::make_base_activity(None)));
         }
     }
 
     match session.mode() {
-        session::Mode::SinglePlayer(_) => {
+        session::Mode::SinglePlayer(single_player) => {
             session.set_fps_target(
                 if input_mapping.speed_change.iter().any(|c| c.is_active(&input_state)) {
                     session::EXPECTED_FPS * speed_change_factor
+                } else if input_mapping.slow_motion.iter().any(|c| c.is_active(&input_state)) {
+                    session::EXPECTED_FPS * slow_motion_factor
                 } else {
                     session::EXPECTED_FPS
                 },
             );
+            handle_speed_inputs(input_state, input_mapping, session);
+            handle_savestate_inputs(language, input_state, input_mapping, session, single_player, state);
         }
-        session::Mode::Replayer => {
+        session::Mode::Replayer(_) => {
+            handle_speed_inputs(input_state, input_mapping, session);
             replay_controls_window::show(ctx, session, language, last_mouse_motion_time);
         }
         _ => {}
@@ -237,11 +401,13 @@ cpsr = {:08x}"#,
                             font_families,
                             language,
                             &own_setup.game_lang,
+                            own_setup.game,
                             &own_setup.save,
                             &own_setup.assets,
                             &mut state.own_save_view,
                             true,
-                        )
+                            false,
+                        );
                     });
             });
         }
@@ -253,18 +419,55 @@ cpsr = {:08x}"#,
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
                     ui.heading(i18n::LOCALES.lookup(language, "opponent-setup").unwrap());
-                    gui::save_view::show(
-                        ui,
-                        false,
-                        clipboard,
-                        font_families,
-                        language,
-                        &opponent_setup.game_lang,
-                        &opponent_setup.save,
-                        &opponent_setup.assets,
-                        &mut state.opponent_save_view,
-                        true,
-                    );
+
+                    // Only setups from the same game family can be compared.
+                    if let Some(own_setup) = session.own_setup().as_ref().filter(|own_setup| {
+                        own_setup.game.family_and_variant().0 == opponent_setup.game.family_and_variant().0
+                    }) {
+                        let mut compare = state.opponent_setup_diff.is_some();
+                        if ui
+                            .checkbox(
+                                &mut compare,
+                                i18n::LOCALES.lookup(language, "opponent-setup-compare").unwrap(),
+                            )
+                            .changed()
+                        {
+                            state.opponent_setup_diff = if compare {
+                                Some(save::diff::diff(own_setup.save.as_ref(), opponent_setup.save.as_ref()))
+                            } else {
+                                None
+                            };
+                        }
+                    }
+
+                    if let Some(diff) = state.opponent_setup_diff.as_ref() {
+                        gui::save_diff_view::show(
+                            ui,
+                            clipboard,
+                            font_families,
+                            language,
+                            &opponent_setup.game_lang,
+                            &i18n::LOCALES.lookup(language, "own-setup").unwrap(),
+                            &i18n::LOCALES.lookup(language, "opponent-setup").unwrap(),
+                            diff,
+                            &opponent_setup.assets,
+                        );
+                    } else {
+                        gui::save_view::show(
+                            ui,
+                            false,
+                            clipboard,
+                            font_families,
+                            language,
+                            &opponent_setup.game_lang,
+                            opponent_setup.game,
+                            &opponent_setup.save,
+                            &opponent_setup.assets,
+                            &mut state.opponent_save_view,
+                            true,
+                            false,
+                        );
+                    }
                 });
         });
     }
@@ -287,10 +490,19 @@ cpsr = {:08x}"#,
             ui.with_layout(
                 egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
                 |ui| {
-                    show_emulator(ui, session, video_filter, max_scale, integer_scaling, &mut state.vbuf);
+                    show_emulator(
+                        ui,
+                        session,
+                        video_filters,
+                        max_scale,
+                        integer_scaling,
+                        &mut state.vbuf,
+                        &mut state.video_pipeline,
+                    );
                 },
             );
         });
+    show_osd(ctx, &mut state.osd);
 
     const HIDE_AFTER: std::time::Duration = std::time::Duration::from_secs(3);
     if !always_show_status_bar
@@ -323,32 +535,34 @@ fn show_status_bar(
     egui::TopBottomPanel::bottom("session-status-bar").show(ctx, |ui| {
         ui.horizontal(|ui| {
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                let (tps_adjustment, latency, round_info) = (|| {
+                let (tps_adjustment, latency, desync, round_info) = (|| {
                     let pvp = if let session::Mode::PvP(pvp) = session.mode() {
                         pvp
                     } else {
-                        return (0.0, None, None);
+                        return (0.0, None, None, None);
                     };
 
                     let match_ = sync::block_on(pvp.match_.lock());
                     let match_ = if let Some(match_) = &*match_ {
                         match_
                     } else {
-                        return (0.0, None, None);
+                        return (0.0, None, None, None);
                     };
 
                     let latency = sync::block_on(match_.latency());
+                    let desync = match_.desync();
 
                     let round_state = sync::block_on(match_.lock_round_state());
                     let round = if let Some(round) = round_state.round.as_ref() {
                         round
                     } else {
-                        return (0.0, Some(latency), None);
+                        return (0.0, Some(latency), desync, None);
                     };
 
                     (
                         round.tps_adjustment(),
                         Some(latency),
+                        desync,
                         Some((
                             round.local_queue_length(),
                             round.remote_queue_length(),
@@ -392,6 +606,25 @@ fn show_status_bar(
                     ui.monospace(format!("ping {:4}ms", latency.as_millis()));
                 }
 
+                if let Some(desync) = desync {
+                    ui.add(egui::Separator::default().vertical());
+                    ui.label(
+                        egui::RichText::new(
+                            i18n::LOCALES
+                                .lookup_with_args(
+                                    language,
+                                    "play-desync-detected",
+                                    &std::collections::HashMap::from([
+                                        ("round", desync.round_number.into()),
+                                        ("tick", desync.tick.into()),
+                                    ]),
+                                )
+                                .unwrap(),
+                        )
+                        .color(egui::Color32::RED),
+                    );
+                }
+
                 if let Some((local_qlen, remote_qlen, local_delay, current_tick, local_player_index)) = round_info {
                     if show_debug {
                         ui.add(egui::Separator::default().vertical());
@@ -4,6 +4,13 @@ use crate::{i18n, session};
 
 const HIDE_AFTER: std::time::Duration = std::time::Duration::from_secs(5);
 
+const SKIP_TICKS: u32 = 5 * 60;
+
+fn format_tick(tick: u32) -> String {
+    let secs = (tick as f32 / session::EXPECTED_FPS) as u32;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
 pub fn show(
     ctx: &egui::Context,
     session: &session::Session,
@@ -23,6 +30,41 @@ pub fn show(
         })
         .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::new(0.0, -50.0))
         .show(ctx, |ui| {
+            if let session::Mode::Replayer(replayer) = session.mode() {
+                let current_tick = replayer.current_tick();
+                let last_tick = replayer.last_tick();
+                let mut seek_to = None;
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("⏪")
+                        .on_hover_text(i18n::LOCALES.lookup(language, "replay-viewer-rewind").unwrap())
+                        .clicked()
+                    {
+                        seek_to = Some(current_tick.saturating_sub(SKIP_TICKS));
+                    }
+                    let mut tick = current_tick;
+                    let response = ui
+                        .add(egui::Slider::new(&mut tick, 0..=last_tick).show_value(false))
+                        .on_hover_text(i18n::LOCALES.lookup(language, "replay-viewer-seek").unwrap());
+                    if response.drag_released() || (response.changed() && !response.dragged()) {
+                        seek_to = Some(tick);
+                    }
+                    if ui
+                        .button("⏩")
+                        .on_hover_text(i18n::LOCALES.lookup(language, "replay-viewer-fast-forward").unwrap())
+                        .clicked()
+                    {
+                        seek_to = Some(current_tick + SKIP_TICKS);
+                    }
+                    ui.label(format!("{} / {}", format_tick(tick), format_tick(last_tick)));
+                });
+                if let Some(tick) = seek_to {
+                    if let Err(e) = session.seek_replay(tick) {
+                        log::error!("failed to seek replay: {:?}", e);
+                    }
+                }
+            }
+
             ui.horizontal(|ui| {
                 if ui
                     .selectable_label(paused, "⏸️")
@@ -1,6 +1,6 @@
 use fluent_templates::Loader;
 
-use crate::{config, game, gui, i18n, input, patch, rom, save, version};
+use crate::{config, game, gui, i18n, input, patch, rom, save, version, video};
 
 #[derive(PartialEq, Eq)]
 enum Tab {
@@ -205,6 +205,31 @@ fn show_general_tab(ui: &mut egui::Ui, config: &mut config::Config, font_familie
                 );
                 ui.end_row();
             }
+
+            {
+                ui.strong(i18n::LOCALES.lookup(&config.language, "settings-slow-motion").unwrap());
+                ui.add(
+                    egui::DragValue::new(&mut config.slow_motion_percent)
+                        .clamp_range(10..=100)
+                        .suffix("%")
+                        .speed(5),
+                );
+                ui.end_row();
+            }
+
+            {
+                ui.strong(
+                    i18n::LOCALES
+                        .lookup(&config.language, "settings-turbo-interval")
+                        .unwrap(),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut config.turbo_interval_frames)
+                        .clamp_range(1..=30)
+                        .speed(1),
+                );
+                ui.end_row();
+            }
         });
 }
 
@@ -300,6 +325,27 @@ fn show_input_tab(
                 &mut input_mapping.speed_change
             });
             add_row("input-button-menu", |input_mapping| &mut input_mapping.menu);
+            add_row("input-button-save-state", |input_mapping| &mut input_mapping.save_state);
+            add_row("input-button-load-state", |input_mapping| &mut input_mapping.load_state);
+            add_row("input-button-previous-state-slot", |input_mapping| {
+                &mut input_mapping.previous_state_slot
+            });
+            add_row("input-button-next-state-slot", |input_mapping| {
+                &mut input_mapping.next_state_slot
+            });
+            add_row("input-button-rewind", |input_mapping| &mut input_mapping.rewind);
+            add_row("input-button-fast-forward", |input_mapping| {
+                &mut input_mapping.fast_forward
+            });
+            add_row("input-button-slow-motion", |input_mapping| {
+                &mut input_mapping.slow_motion
+            });
+            add_row("input-button-pause", |input_mapping| &mut input_mapping.pause);
+            add_row("input-button-frame-advance", |input_mapping| {
+                &mut input_mapping.frame_advance
+            });
+            add_row("input-button-turbo-a", |input_mapping| &mut input_mapping.turbo_a);
+            add_row("input-button-turbo-b", |input_mapping| &mut input_mapping.turbo_b);
         });
 }
 
@@ -384,39 +430,12 @@ fn show_graphics_tab(ui: &mut egui::Ui, config: &mut config::Config, window: &wi
             {
                 ui.strong(i18n::LOCALES.lookup(&config.language, "settings-video-filter").unwrap());
 
-                let null_label = i18n::LOCALES
-                    .lookup(&config.language, "settings-video-filter.null")
-                    .unwrap();
-                let hq2x_label = i18n::LOCALES
-                    .lookup(&config.language, "settings-video-filter.hq2x")
-                    .unwrap();
-                let hq3x_label = i18n::LOCALES
-                    .lookup(&config.language, "settings-video-filter.hq3x")
-                    .unwrap();
-                let hq4x_label = i18n::LOCALES
-                    .lookup(&config.language, "settings-video-filter.hq4x")
-                    .unwrap();
-                let mmpx_label = i18n::LOCALES
-                    .lookup(&config.language, "settings-video-filter.mmpx")
-                    .unwrap();
-
-                egui::ComboBox::from_id_source("settings-window-general-video-filter")
-                    .width(200.0)
-                    .selected_text(match config.video_filter.as_str() {
-                        "" => &null_label,
-                        "hq2x" => &hq2x_label,
-                        "hq3x" => &hq3x_label,
-                        "hq4x" => &hq4x_label,
-                        "mmpx" => &mmpx_label,
-                        _ => "",
-                    })
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut config.video_filter, "".to_string(), &null_label);
-                        ui.selectable_value(&mut config.video_filter, "hq2x".to_string(), &hq2x_label);
-                        ui.selectable_value(&mut config.video_filter, "hq3x".to_string(), &hq3x_label);
-                        ui.selectable_value(&mut config.video_filter, "hq4x".to_string(), &hq4x_label);
-                        ui.selectable_value(&mut config.video_filter, "mmpx".to_string(), &mmpx_label);
-                    });
+                show_video_filters(
+                    ui,
+                    &config.language,
+                    "settings-window-general-video-filter",
+                    &mut config.video_filters,
+                );
                 ui.end_row();
             }
 
@@ -459,6 +478,69 @@ fn show_graphics_tab(ui: &mut egui::Ui, config: &mut config::Config, window: &wi
         });
 }
 
+pub fn video_filter_label(language: &unic_langid::LanguageIdentifier, name: &str) -> String {
+    i18n::LOCALES
+        .lookup(
+            language,
+            &format!("settings-video-filter.{}", if name.is_empty() { "null" } else { name }),
+        )
+        .unwrap_or_else(|| name.to_string())
+}
+
+pub fn show_video_filters(
+    ui: &mut egui::Ui,
+    language: &unic_langid::LanguageIdentifier,
+    id_source: &str,
+    filters: &mut Vec<String>,
+) {
+    ui.vertical(|ui| {
+        if filters.is_empty() {
+            ui.label(video_filter_label(language, ""));
+        }
+
+        let num_filters = filters.len();
+        let mut swap = None;
+        let mut remove = None;
+        for (i, filter) in filters.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source(format!("{}-{}", id_source, i))
+                    .width(200.0)
+                    .selected_text(video_filter_label(language, filter))
+                    .show_ui(ui, |ui| {
+                        for name in video::FILTER_NAMES {
+                            ui.selectable_value(filter, name.to_string(), video_filter_label(language, name));
+                        }
+                    });
+                if ui.add_enabled(i > 0, egui::Button::new("⬆")).clicked() {
+                    swap = Some(i - 1);
+                }
+                if ui.add_enabled(i + 1 < num_filters, egui::Button::new("⬇")).clicked() {
+                    swap = Some(i);
+                }
+                if ui.button("🗑").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = swap {
+            filters.swap(i, i + 1);
+        }
+        if let Some(i) = remove {
+            filters.remove(i);
+        }
+
+        if ui
+            .button(format!(
+                "➕ {}",
+                i18n::LOCALES.lookup(language, "settings-video-filter.add").unwrap()
+            ))
+            .clicked()
+        {
+            filters.push(video::FILTER_NAMES[0].to_string());
+        }
+    });
+}
+
 fn show_audio_tab(ui: &mut egui::Ui, config: &mut config::Config) {
     egui::Grid::new("settings-window-audio-grid")
         .num_columns(2)
@@ -512,6 +594,14 @@ fn show_netplay_tab(ui: &mut egui::Ui, config: &mut config::Config) {
             ui.add(egui::Slider::new(&mut config.input_delay, 2..=10));
             ui.end_row();
 
+            ui.strong(
+                i18n::LOCALES
+                    .lookup(&config.language, "settings-adaptive-input-delay")
+                    .unwrap(),
+            );
+            ui.checkbox(&mut config.adaptive_input_delay, "");
+            ui.end_row();
+
             ui.strong(
                 i18n::LOCALES
                     .lookup(&config.language, "settings-max-queue-length")
@@ -572,6 +662,26 @@ fn show_netplay_tab(ui: &mut egui::Ui, config: &mut config::Config) {
             );
             ui.add(egui::TextEdit::singleline(&mut config.replaycollector_endpoint).desired_width(200.0));
             ui.end_row();
+
+            ui.strong(
+                i18n::LOCALES
+                    .lookup(&config.language, "settings-replaycollector-set-endpoint")
+                    .unwrap(),
+            );
+            ui.add(egui::TextEdit::singleline(&mut config.replaycollector_set_endpoint).desired_width(200.0));
+            ui.end_row();
+
+            ui.strong(
+                i18n::LOCALES
+                    .lookup(&config.language, "settings-spectator-listen-addr")
+                    .unwrap(),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut config.spectator_listen_addr)
+                    .desired_width(200.0)
+                    .hint_text("127.0.0.1:12345"),
+            );
+            ui.end_row();
         });
 }
 
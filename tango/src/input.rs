@@ -112,6 +112,17 @@ pub struct Mapping {
     pub start: Vec<PhysicalInput>,
     pub speed_change: Vec<PhysicalInput>,
     pub menu: Vec<PhysicalInput>,
+    pub save_state: Vec<PhysicalInput>,
+    pub load_state: Vec<PhysicalInput>,
+    pub previous_state_slot: Vec<PhysicalInput>,
+    pub next_state_slot: Vec<PhysicalInput>,
+    pub rewind: Vec<PhysicalInput>,
+    pub fast_forward: Vec<PhysicalInput>,
+    pub slow_motion: Vec<PhysicalInput>,
+    pub pause: Vec<PhysicalInput>,
+    pub frame_advance: Vec<PhysicalInput>,
+    pub turbo_a: Vec<PhysicalInput>,
+    pub turbo_b: Vec<PhysicalInput>,
 }
 
 impl Default for Mapping {
@@ -183,6 +194,17 @@ impl Default for Mapping {
             ],
             speed_change: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::LShift)],
             menu: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::Escape)],
+            save_state: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::F5)],
+            load_state: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::F8)],
+            previous_state_slot: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::F6)],
+            next_state_slot: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::F7)],
+            rewind: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::Back)],
+            fast_forward: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::Tab)],
+            slow_motion: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::LControl)],
+            pause: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::P)],
+            frame_advance: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::N)],
+            turbo_a: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::C)],
+            turbo_b: vec![PhysicalInput::Key(winit::event::VirtualKeyCode::V)],
         }
     }
 }
@@ -231,4 +253,15 @@ impl Mapping {
             0
         })
     }
+    pub fn to_mgba_turbo_keys(&self, input: &State) -> u32 {
+        (if self.turbo_a.iter().any(|c| c.is_active(input)) {
+            mgba::input::keys::A
+        } else {
+            0
+        }) | (if self.turbo_b.iter().any(|c| c.is_active(input)) {
+            mgba::input::keys::B
+        } else {
+            0
+        })
+    }
 }
@@ -16,6 +16,14 @@ impl Input {
     pub fn lag(&self) -> i32 {
         self.remote_tick as i32 - self.local_tick as i32
     }
+
+    pub fn to_partial(&self) -> PartialInput {
+        PartialInput {
+            local_tick: self.local_tick,
+            remote_tick: self.remote_tick,
+            joyflags: self.joyflags,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -99,6 +107,10 @@ where
         self.local_delay
     }
 
+    pub fn set_local_delay(&mut self, local_delay: u32) {
+        self.local_delay = local_delay;
+    }
+
     pub fn local_queue_length(&self) -> usize {
         self.local_queue.len()
     }
@@ -7,9 +7,12 @@ extern crate lazy_static;
 
 mod audio;
 mod battle;
+mod cheats;
 mod config;
+mod disassembler;
 mod discord;
 mod filesync;
+mod fonts;
 mod game;
 mod graphics;
 mod gui;
@@ -24,9 +27,12 @@ mod replayer;
 mod replaytool;
 mod rom;
 mod save;
+mod savetool;
 mod scanner;
 mod session;
 mod shadow;
+mod sheet;
+mod spectator;
 mod stats;
 mod sync;
 mod updater;
@@ -43,6 +49,31 @@ struct Args {
 
     #[command(subcommand)]
     replaytool_command: Option<replaytool::Command>,
+
+    /// List the snapshots of a save instead of starting Tango.
+    #[arg(long, value_name = "SAVE_PATH", conflicts_with = "replay_path")]
+    save_history: Option<std::path::PathBuf>,
+
+    /// Restore the snapshot with this number, as listed by --save-history.
+    #[arg(long, requires = "save_history")]
+    restore: Option<usize>,
+
+    /// Convert a save to the other region's release of its game, e.g. EXE6 to BN6, instead of starting Tango.
+    #[arg(
+        long,
+        value_name = "SAVE_PATH",
+        requires = "output_path",
+        conflicts_with_all = ["replay_path", "save_history"],
+    )]
+    convert_save: Option<std::path::PathBuf>,
+
+    /// Where to write the converted save.
+    #[arg(long, requires = "convert_save")]
+    output_path: Option<std::path::PathBuf>,
+
+    /// Save of the target game to convert into, for games whose releases lay out saves differently.
+    #[arg(long, value_name = "SAVE_PATH", requires = "convert_save")]
+    into: Option<std::path::PathBuf>,
 }
 
 enum UserEvent {
@@ -56,8 +87,16 @@ fn main() -> Result<(), anyhow::Error> {
     config.ensure_dirs()?;
 
     let args = Args::parse();
-    if let (Some(path), Some(command)) = (args.replay_path, args.replaytool_command) {
-        return replaytool::main(config, path, command);
+    if let Some(command) = args.replaytool_command {
+        return replaytool::main(config, args.replay_path, command);
+    }
+
+    if let Some(save_path) = args.save_history {
+        return savetool::history(config, save_path, args.restore);
+    }
+
+    if let (Some(save_path), Some(output_path)) = (args.convert_save, args.output_path) {
+        return savetool::convert(config, save_path, output_path, args.into);
     }
 
     env_logger::Builder::from_default_env()
@@ -451,6 +490,10 @@ fn child_main(mut config: config::Config) -> Result<(), anyhow::Error> {
 
         if let Some(session) = state.session.lock().as_mut() {
             session.set_joyflags(next_config.input_mapping.to_mgba_keys(&input_state));
+            session.set_turbo(
+                next_config.input_mapping.to_mgba_turbo_keys(&input_state),
+                next_config.turbo_interval_frames,
+            );
             session.set_master_volume(next_config.volume);
         }
 
@@ -42,25 +42,90 @@ pub async fn negotiate(sender: &mut Sender, receiver: &mut Receiver) -> Result<(
     Ok(())
 }
 
-pub struct Sender {
-    dc_tx: datachannel_wrapper::DataChannelSender,
+// Inputs are sent over an unreliable channel: each packet carries every input the remote hasn't acknowledged yet (up to
+// this many), so a single packet making it through fills any gaps left by lost ones.
+const MAX_BUNDLED_INPUTS: usize = 64;
+
+// How often unacknowledged inputs are sent again, in case they were sent right before a lull (e.g. at the end of a round).
+pub const INPUT_RESEND_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+#[derive(Default)]
+struct InputAcks {
+    // The round number and how many of our inputs the remote has received in that round.
+    remote: (u8, u32),
+    // The round number and how many of the remote's inputs we have received in that round.
+    local: (u8, u32),
 }
 
-impl Sender {
-    pub fn new(dc_tx: datachannel_wrapper::DataChannelSender) -> Self {
-        Self { dc_tx }
+pub fn split(dc: datachannel_wrapper::DataChannel, input_dc: datachannel_wrapper::DataChannel) -> (Sender, Receiver) {
+    let (dc_tx, dc_rx) = dc.split();
+    let (input_dc_tx, input_dc_rx) = input_dc.split();
+    let acks = std::sync::Arc::new(parking_lot::Mutex::new(InputAcks::default()));
+    (
+        Sender {
+            dc_tx,
+            input_dc_tx,
+            acks: acks.clone(),
+            unacked_inputs: std::collections::VecDeque::new(),
+            last_round_number: 0,
+            last_sent_ack: (0, 0),
+        },
+        Receiver {
+            dc_rx,
+            input_dc_rx,
+            acks,
+            pending: std::collections::VecDeque::new(),
+        },
+    )
+}
+
+async fn send_packet_on(
+    dc_tx: &mut datachannel_wrapper::DataChannelSender,
+    p: &protocol::Packet,
+) -> std::io::Result<()> {
+    match dc_tx.send(p.serialize().unwrap().as_slice()).await {
+        Ok(()) => Ok(()),
+        Err(datachannel_wrapper::Error::Closed) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "unexpected eof"));
+        }
+        Err(e) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, e));
+        }
     }
+}
 
-    async fn send_packet(&mut self, p: &protocol::Packet) -> std::io::Result<()> {
-        match self.dc_tx.send(p.serialize().unwrap().as_slice()).await {
-            Ok(()) => Ok(()),
-            Err(datachannel_wrapper::Error::Closed) => {
-                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "unexpected eof"));
-            }
-            Err(e) => {
-                return Err(std::io::Error::new(std::io::ErrorKind::Other, e));
+fn deserialize_packet(d: Option<Vec<u8>>) -> std::io::Result<protocol::Packet> {
+    match protocol::Packet::deserialize(
+        match d {
+            Some(d) => d,
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "stream is empty",
+                ));
             }
         }
+        .as_slice(),
+    ) {
+        Ok(p) => Ok(p),
+        Err(e) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+        }
+    }
+}
+
+pub struct Sender {
+    dc_tx: datachannel_wrapper::DataChannelSender,
+    input_dc_tx: datachannel_wrapper::DataChannelSender,
+    acks: std::sync::Arc<parking_lot::Mutex<InputAcks>>,
+    unacked_inputs: std::collections::VecDeque<(u8, protocol::BundledInput)>,
+    last_round_number: u8,
+    last_sent_ack: (u8, u32),
+}
+
+impl Sender {
+    async fn send_packet(&mut self, p: &protocol::Packet) -> std::io::Result<()> {
+        send_packet_on(&mut self.dc_tx, p).await
     }
 
     pub async fn send_hello(&mut self) -> std::io::Result<()> {
@@ -109,11 +174,74 @@ impl Sender {
         tick_diff: i8,
         joyflags: u16,
     ) -> std::io::Result<()> {
-        self.send_packet(&protocol::Packet::Input(protocol::Input {
+        self.unacked_inputs.push_back((
             round_number,
-            local_tick,
-            tick_diff,
-            joyflags,
+            protocol::BundledInput {
+                local_tick,
+                tick_diff,
+                joyflags,
+            },
+        ));
+        self.send_unacked_inputs().await
+    }
+
+    /// Sends all inputs the remote has not acknowledged yet, along with our own acknowledgement.
+    ///
+    /// Nothing is sent if there is nothing new to tell the remote.
+    pub async fn send_unacked_inputs(&mut self) -> std::io::Result<()> {
+        let ack = {
+            let acks = self.acks.lock();
+            while let Some((round_number, input)) = self.unacked_inputs.front() {
+                if (*round_number, input.local_tick) >= acks.remote {
+                    break;
+                }
+                self.unacked_inputs.pop_front();
+            }
+            acks.local
+        };
+
+        // Only inputs from a single round are sent at a time: the remote only moves on to the next round once it has
+        // received all of the previous one.
+        if let Some((round_number, _)) = self.unacked_inputs.front() {
+            self.last_round_number = *round_number;
+        } else if ack == self.last_sent_ack {
+            return Ok(());
+        }
+
+        let round_number = self.last_round_number;
+        let inputs = self
+            .unacked_inputs
+            .iter()
+            .take_while(|(r, _)| *r == round_number)
+            .take(MAX_BUNDLED_INPUTS)
+            .map(|(_, input)| input.clone())
+            .collect();
+        self.last_sent_ack = ack;
+
+        send_packet_on(
+            &mut self.input_dc_tx,
+            &protocol::Packet::Inputs(protocol::Inputs {
+                round_number,
+                inputs,
+                ack,
+            }),
+        )
+        .await
+    }
+
+    pub async fn send_input_delay(&mut self, round_number: u8, input_delay: u32) -> std::io::Result<()> {
+        self.send_packet(&protocol::Packet::InputDelay(protocol::InputDelay {
+            round_number,
+            input_delay,
+        }))
+        .await
+    }
+
+    pub async fn send_state_hash(&mut self, round_number: u8, tick: u32, hash: u32) -> std::io::Result<()> {
+        self.send_packet(&protocol::Packet::StateHash(protocol::StateHash {
+            round_number,
+            tick,
+            hash,
         }))
         .await
     }
@@ -121,30 +249,72 @@ impl Sender {
 
 pub struct Receiver {
     dc_rx: datachannel_wrapper::DataChannelReceiver,
+    input_dc_rx: datachannel_wrapper::DataChannelReceiver,
+    acks: std::sync::Arc<parking_lot::Mutex<InputAcks>>,
+    pending: std::collections::VecDeque<protocol::Packet>,
 }
 
 impl Receiver {
-    pub fn new(dc_rx: datachannel_wrapper::DataChannelReceiver) -> Self {
-        Self { dc_rx }
-    }
-
+    /// Receives the next packet from either channel.
+    ///
+    /// Bundled inputs are unpacked, deduplicated and returned in order as individual [`protocol::Packet::Input`]s.
     pub async fn receive(&mut self) -> std::io::Result<protocol::Packet> {
-        match protocol::Packet::deserialize(
-            match self.dc_rx.receive().await {
-                Some(d) => d,
-                None => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        "stream is empty",
-                    ));
+        loop {
+            if let Some(p) = self.pending.pop_front() {
+                return Ok(p);
+            }
+
+            tokio::select! {
+                d = self.dc_rx.receive() => {
+                    return deserialize_packet(d);
                 }
+                d = self.input_dc_rx.receive() => {
+                    match deserialize_packet(d)? {
+                        protocol::Packet::Inputs(inputs) => {
+                            self.handle_inputs(inputs);
+                        }
+                        p => {
+                            return Ok(p);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_inputs(&mut self, inputs: protocol::Inputs) {
+        let mut acks = self.acks.lock();
+
+        // Packets may arrive out of order, so only ever move the ack forward.
+        if inputs.ack > acks.remote {
+            acks.remote = inputs.ack;
+        }
+
+        if inputs.inputs.is_empty() || inputs.round_number < acks.local.0 {
+            return;
+        }
+
+        if inputs.round_number > acks.local.0 {
+            acks.local = (inputs.round_number, 0);
+        }
+
+        for input in inputs.inputs {
+            if input.local_tick < acks.local.1 {
+                continue;
             }
-            .as_slice(),
-        ) {
-            Ok(p) => Ok(p),
-            Err(e) => {
-                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+
+            if input.local_tick > acks.local.1 {
+                // There's a gap: wait for a packet that fills it.
+                break;
             }
+
+            acks.local.1 += 1;
+            self.pending.push_back(protocol::Packet::Input(protocol::Input {
+                round_number: inputs.round_number,
+                local_tick: input.local_tick,
+                tick_diff: input.tick_diff,
+                joyflags: input.joyflags,
+            }));
         }
     }
 }
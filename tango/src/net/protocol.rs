@@ -1,6 +1,6 @@
 use bincode::Options;
 
-pub const VERSION: u8 = 0x32;
+pub const VERSION: u8 = 0x36;
 
 lazy_static! {
     static ref BINCODE_OPTIONS: bincode::config::WithOtherLimit<
@@ -31,6 +31,11 @@ pub enum Packet {
 
     // In match.
     Input(Input),
+    InputDelay(InputDelay),
+    StateHash(StateHash),
+
+    // In match, over the unreliable channel.
+    Inputs(Inputs),
 }
 
 impl Packet {
@@ -91,6 +96,14 @@ pub struct Settings {
     pub available_games: Vec<(String, u8)>,
     pub available_patches: Vec<(String, Vec<semver::Version>)>,
     pub reveal_setup: bool,
+    pub legality: Option<Legality>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Legality {
+    pub ruleset: String,
+    // None if the ruleset has no rules for the selected game.
+    pub num_violations: Option<u32>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -101,6 +114,35 @@ pub struct Input {
     pub joyflags: u16,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BundledInput {
+    pub local_tick: u32,
+    pub tick_diff: i8,
+    pub joyflags: u16,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Inputs {
+    pub round_number: u8,
+    // All inputs the remote has not acknowledged yet, oldest first.
+    pub inputs: Vec<BundledInput>,
+    // The round number and the number of the remote's inputs we have received so far in that round.
+    pub ack: (u8, u32),
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct InputDelay {
+    pub round_number: u8,
+    pub input_delay: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct StateHash {
+    pub round_number: u8,
+    pub tick: u32,
+    pub hash: u32,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct StartMatch {}
 
@@ -9,6 +9,7 @@ async fn create_data_channel(
     rtc_config: datachannel_wrapper::RtcConfig,
 ) -> Result<
     (
+        datachannel_wrapper::DataChannel,
         datachannel_wrapper::DataChannel,
         tokio::sync::mpsc::Receiver<datachannel_wrapper::PeerConnectionEvent>,
         datachannel_wrapper::PeerConnection,
@@ -31,6 +32,22 @@ async fn create_data_channel(
             .stream(0),
     )?;
 
+    // In-match inputs go over their own channel: they are redundantly bundled, so retransmitting lost packets would
+    // only add head-of-line blocking.
+    let input_dc = peer_conn.create_data_channel(
+        "tango-input",
+        datachannel_wrapper::DataChannelInit::default()
+            .reliability(datachannel_wrapper::Reliability {
+                unordered: true,
+                unreliable: true,
+                max_packet_life_time: 0,
+                max_retransmits: 0,
+            })
+            .negotiated()
+            .manual_stream()
+            .stream(1),
+    )?;
+
     loop {
         if let Some(datachannel_wrapper::PeerConnectionEvent::GatheringStateChange(
             datachannel_wrapper::GatheringState::Complete,
@@ -40,31 +57,34 @@ async fn create_data_channel(
         }
     }
 
-    Ok((dc, event_rx, peer_conn))
+    Ok((dc, input_dc, event_rx, peer_conn))
 }
 
 pub struct PendingConnection {
-    signaling_stream: tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    signaling_stream: SignalingStream,
     dc: datachannel_wrapper::DataChannel,
+    input_dc: datachannel_wrapper::DataChannel,
     event_rx: tokio::sync::mpsc::Receiver<datachannel_wrapper::PeerConnectionEvent>,
     peer_conn: datachannel_wrapper::PeerConnection,
 }
 
-pub async fn open(addr: &str, session_id: &str, use_relay: Option<bool>) -> Result<PendingConnection, anyhow::Error> {
-    let mut url = url::Url::parse(addr)?;
-    url.set_query(Some(
-        &url::form_urlencoded::Serializer::new(String::new())
-            .append_pair("session_id", session_id)
-            .finish(),
-    ));
+type SignalingStream = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
 
-    let mut req = url.to_string().into_client_request()?;
-    req.headers_mut().append(
-        "User-Agent",
-        tokio_tungstenite::tungstenite::http::HeaderValue::from_str(&format!("tango/{}", version::current()))?,
-    );
-    let (mut signaling_stream, _) = tokio_tungstenite::connect_async(req).await?;
+async fn send_packet(
+    signaling_stream: &mut SignalingStream,
+    which: tango_protos::matchmaking::packet::Which,
+) -> Result<(), anyhow::Error> {
+    signaling_stream
+        .send(tokio_tungstenite::tungstenite::Message::Binary(
+            tango_protos::matchmaking::Packet { which: Some(which) }.encode_to_vec(),
+        ))
+        .await?;
+    Ok(())
+}
 
+async fn recv_packet(
+    signaling_stream: &mut SignalingStream,
+) -> Result<Option<tango_protos::matchmaking::packet::Which>, anyhow::Error> {
     let raw = if let Some(raw) = signaling_stream.try_next().await? {
         raw
     } else {
@@ -77,7 +97,34 @@ pub async fn open(addr: &str, session_id: &str, use_relay: Option<bool>) -> Resu
         anyhow::bail!("invalid packet");
     };
 
-    let hello = if let Some(tango_protos::matchmaking::packet::Which::Hello(hello)) = packet.which {
+    Ok(packet.which)
+}
+
+// Connects to the matchmaking server, which always greets us with the ICE servers to use. Without a session ID, the
+// connection may only be used to list lobbies or to queue.
+async fn connect(
+    addr: &str,
+    session_id: Option<&str>,
+) -> Result<(SignalingStream, tango_protos::matchmaking::packet::Hello), anyhow::Error> {
+    let mut url = url::Url::parse(addr)?;
+    if let Some(session_id) = session_id {
+        url.set_query(Some(
+            &url::form_urlencoded::Serializer::new(String::new())
+                .append_pair("session_id", session_id)
+                .finish(),
+        ));
+    }
+
+    let mut req = url.to_string().into_client_request()?;
+    req.headers_mut().append(
+        "User-Agent",
+        tokio_tungstenite::tungstenite::http::HeaderValue::from_str(&format!("tango/{}", version::current()))?,
+    );
+    let (mut signaling_stream, _) = tokio_tungstenite::connect_async(req).await?;
+
+    let hello = if let Some(tango_protos::matchmaking::packet::Which::Hello(hello)) =
+        recv_packet(&mut signaling_stream).await?
+    {
         hello
     } else {
         anyhow::bail!("invalid packet");
@@ -85,6 +132,13 @@ pub async fn open(addr: &str, session_id: &str, use_relay: Option<bool>) -> Resu
 
     log::info!("hello received from signaling stream: {:?}", hello);
 
+    Ok((signaling_stream, hello))
+}
+
+fn make_rtc_config(
+    hello: tango_protos::matchmaking::packet::Hello,
+    use_relay: Option<bool>,
+) -> datachannel_wrapper::RtcConfig {
     let mut rtc_config = datachannel_wrapper::RtcConfig::new(
         &hello
             .ice_servers
@@ -131,33 +185,122 @@ pub async fn open(addr: &str, session_id: &str, use_relay: Option<bool>) -> Resu
     if use_relay == Some(true) {
         rtc_config.ice_transport_policy = datachannel_wrapper::TransportPolicy::Relay;
     }
-    let (dc, event_rx, peer_conn) = create_data_channel(rtc_config).await?;
+    rtc_config
+}
 
-    signaling_stream
-        .send(tokio_tungstenite::tungstenite::Message::Binary(
-            tango_protos::matchmaking::Packet {
-                which: Some(tango_protos::matchmaking::packet::Which::Start(
-                    tango_protos::matchmaking::packet::Start {
-                        offer_sdp: peer_conn.local_description().unwrap().sdp.to_string(),
-                    },
-                )),
-            }
-            .encode_to_vec(),
-        ))
-        .await?;
+async fn start(
+    mut signaling_stream: SignalingStream,
+    dc: datachannel_wrapper::DataChannel,
+    input_dc: datachannel_wrapper::DataChannel,
+    event_rx: tokio::sync::mpsc::Receiver<datachannel_wrapper::PeerConnectionEvent>,
+    peer_conn: datachannel_wrapper::PeerConnection,
+) -> Result<PendingConnection, anyhow::Error> {
+    send_packet(
+        &mut signaling_stream,
+        tango_protos::matchmaking::packet::Which::Start(tango_protos::matchmaking::packet::Start {
+            offer_sdp: peer_conn.local_description().unwrap().sdp.to_string(),
+        }),
+    )
+    .await?;
 
     Ok(PendingConnection {
         signaling_stream,
         dc,
+        input_dc,
         event_rx,
         peer_conn,
     })
 }
 
+/// Opens a connection for the given link code. If advertise is given, the session is also listed in the public
+/// lobbies until someone joins it.
+pub async fn open(
+    addr: &str,
+    session_id: &str,
+    use_relay: Option<bool>,
+    advertise: Option<tango_protos::matchmaking::packet::Advertise>,
+) -> Result<PendingConnection, anyhow::Error> {
+    let (signaling_stream, hello) = connect(addr, Some(session_id)).await?;
+    let (dc, input_dc, event_rx, peer_conn) = create_data_channel(make_rtc_config(hello, use_relay)).await?;
+    let mut pending_conn = start(signaling_stream, dc, input_dc, event_rx, peer_conn).await?;
+
+    if let Some(advertise) = advertise {
+        send_packet(
+            &mut pending_conn.signaling_stream,
+            tango_protos::matchmaking::packet::Which::Advertise(advertise),
+        )
+        .await?;
+    }
+
+    Ok(pending_conn)
+}
+
+/// Waits in the quick-match queue until the server pairs us with a compatible opponent, then opens a connection for
+/// the session it assigned.
+pub async fn open_queued(
+    addr: &str,
+    queue: tango_protos::matchmaking::packet::Queue,
+    use_relay: Option<bool>,
+) -> Result<(tango_protos::matchmaking::packet::Matched, PendingConnection), anyhow::Error> {
+    let (mut signaling_stream, hello) = connect(addr, None).await?;
+    let (dc, input_dc, event_rx, peer_conn) = create_data_channel(make_rtc_config(hello, use_relay)).await?;
+
+    send_packet(
+        &mut signaling_stream,
+        tango_protos::matchmaking::packet::Which::Queue(queue),
+    )
+    .await?;
+
+    let matched = match recv_packet(&mut signaling_stream).await? {
+        Some(tango_protos::matchmaking::packet::Which::Matched(matched)) => matched,
+        p => {
+            anyhow::bail!("unexpected packet: {:?}", p);
+        }
+    };
+    log::info!("matched from queue: {:?}", matched);
+
+    Ok((
+        matched,
+        start(signaling_stream, dc, input_dc, event_rx, peer_conn).await?,
+    ))
+}
+
+pub async fn list_lobbies(
+    addr: &str,
+    netplay_compatibility: Option<String>,
+) -> Result<Vec<tango_protos::matchmaking::Lobby>, anyhow::Error> {
+    let (mut signaling_stream, _) = connect(addr, None).await?;
+
+    send_packet(
+        &mut signaling_stream,
+        tango_protos::matchmaking::packet::Which::ListLobbies(tango_protos::matchmaking::packet::ListLobbies {
+            netplay_compatibility,
+        }),
+    )
+    .await?;
+
+    let lobbies = match recv_packet(&mut signaling_stream).await? {
+        Some(tango_protos::matchmaking::packet::Which::Lobbies(lobbies)) => lobbies.lobbies,
+        p => {
+            anyhow::bail!("unexpected packet: {:?}", p);
+        }
+    };
+    signaling_stream.close(None).await?;
+
+    Ok(lobbies)
+}
+
 impl PendingConnection {
     pub async fn connect(
         mut self,
-    ) -> Result<(datachannel_wrapper::DataChannel, datachannel_wrapper::PeerConnection), anyhow::Error> {
+    ) -> Result<
+        (
+            datachannel_wrapper::DataChannel,
+            datachannel_wrapper::DataChannel,
+            datachannel_wrapper::PeerConnection,
+        ),
+        anyhow::Error,
+    > {
         loop {
             let raw = if let Some(raw) = self.signaling_stream.try_next().await? {
                 raw
@@ -255,6 +398,6 @@ impl PendingConnection {
             }
         }
 
-        Ok((self.dc, self.peer_conn))
+        Ok((self.dc, self.input_dc, self.peer_conn))
     }
 }
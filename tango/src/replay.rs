@@ -1,4 +1,4 @@
-use crate::lockstep;
+use crate::{game, lockstep, replayer};
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
 use prost::Message;
@@ -8,6 +8,8 @@ pub trait WriteSeek: std::io::Write + std::io::Seek {}
 impl<T: std::io::Write + std::io::Seek> WriteSeek for T {}
 
 pub mod export;
+pub mod index;
+pub mod set;
 
 mod protos;
 mod replay10;
@@ -18,10 +20,33 @@ pub type Metadata = protos::replay11::Metadata;
 pub struct Writer {
     encoder: Option<zstd::stream::write::Encoder<'static, Box<dyn WriteSeek + Send>>>,
     num_inputs: u32,
+    keyframe_index: Vec<(u32, u64)>,
 }
 
 const HEADER: &[u8] = b"TOOT";
-const VERSION: u8 = 0x11;
+const VERSION: u8 = 0x12;
+
+// How often the replay writer should be given a keyframe.
+pub const KEYFRAME_INTERVAL: u32 = 10 * 60;
+
+// As of 0x12, the stream after the initial states is made up of tagged records.
+const RECORD_INPUT: u8 = 0x00;
+const RECORD_KEYFRAME: u8 = 0x01;
+
+// The keyframe index is written at the end of the file in a zstd skippable frame, so decoders that read the stream
+// straight through will ignore it. It consists of (tick: u32, offset: u64) entries, followed by the number of entries
+// (u32) and INDEX_MAGIC, so it can be found by reading from the end of the file.
+const SKIPPABLE_FRAME_MAGIC: u32 = 0x184d2a50;
+const INDEX_MAGIC: &[u8] = b"TIDX";
+
+// How many frames to keep running after the last input pair has been consumed, waiting for the round to end.
+const MAX_TRAILING_FRAMES: usize = 60 * 60;
+
+#[derive(Clone)]
+pub struct Keyframe {
+    pub tick: u32,
+    pub state: mgba::state::State,
+}
 
 #[derive(Clone)]
 pub struct Replay {
@@ -31,12 +56,13 @@ pub struct Replay {
     pub local_state: mgba::state::State,
     pub remote_state: mgba::state::State,
     pub input_pairs: Vec<lockstep::Pair<lockstep::Input, lockstep::Input>>,
+    pub keyframes: Vec<Keyframe>,
 }
 
 fn decode_metadata(version: u8, raw: &[u8]) -> Result<Metadata, std::io::Error> {
     Ok(match version {
         0x10 => replay10::decode_metadata(&raw[..])?,
-        0x11 => protos::replay11::Metadata::decode(&raw[..])?,
+        0x11 | 0x12 => protos::replay11::Metadata::decode(&raw[..])?,
         _ => {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
@@ -47,6 +73,11 @@ fn decode_metadata(version: u8, raw: &[u8]) -> Result<Metadata, std::io::Error>
 }
 
 pub fn read_metadata(r: &mut impl std::io::Read) -> Result<(usize, Metadata), std::io::Error> {
+    let (_, num_inputs, metadata) = read_header(r)?;
+    Ok((num_inputs, metadata))
+}
+
+fn read_header(r: &mut impl std::io::Read) -> Result<(u8, usize, Metadata), std::io::Error> {
     let mut header = [0u8; 4];
     r.read_exact(&mut header)?;
     if &header != HEADER {
@@ -58,7 +89,28 @@ pub fn read_metadata(r: &mut impl std::io::Read) -> Result<(usize, Metadata), st
     let metadata_len = r.read_u32::<byteorder::LittleEndian>()?;
     let mut raw = vec![0u8; metadata_len as usize];
     r.read_exact(&mut raw[..])?;
-    Ok((num_inputs, decode_metadata(version, &raw)?))
+    Ok((version, num_inputs, decode_metadata(version, &raw)?))
+}
+
+pub fn read_keyframe_index(r: &mut (impl std::io::Read + std::io::Seek)) -> Result<Vec<(u32, u64)>, std::io::Error> {
+    r.seek(std::io::SeekFrom::End(-((INDEX_MAGIC.len() + 4) as i64)))?;
+    let num_entries = r.read_u32::<byteorder::LittleEndian>()? as usize;
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != INDEX_MAGIC {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no keyframe index"));
+    }
+
+    r.seek(std::io::SeekFrom::End(
+        -((INDEX_MAGIC.len() + 4 + num_entries * 12) as i64),
+    ))?;
+    let mut index = Vec::with_capacity(num_entries);
+    for _ in 0..num_entries {
+        let tick = r.read_u32::<byteorder::LittleEndian>()?;
+        let offset = r.read_u64::<byteorder::LittleEndian>()?;
+        index.push((tick, offset));
+    }
+    Ok(index)
 }
 
 impl Replay {
@@ -70,11 +122,135 @@ impl Replay {
         for ip in self.input_pairs.iter_mut() {
             std::mem::swap(&mut ip.local, &mut ip.remote);
         }
+        // Keyframes are states of the old local side, so they have to be rebuilt with rebuild_keyframes to seek.
+        self.keyframes.clear();
         self
     }
 
+    /// Replaces the keyframes with ones for the local side, taken by fast-forwarding through the whole replay.
+    pub fn rebuild_keyframes(&mut self, fastforwarder: &mut replayer::Fastforwarder) -> anyhow::Result<()> {
+        self.keyframes.clear();
+        let last_tick = self.last_tick();
+        let mut keyframe = self.keyframe_for_tick(0);
+        loop {
+            let tick = (keyframe.tick / KEYFRAME_INTERVAL + 1) * KEYFRAME_INTERVAL;
+            if tick > last_tick {
+                break;
+            }
+
+            let input_pairs = self
+                .input_pairs
+                .iter()
+                .filter(|ip| ip.local.local_tick >= keyframe.tick)
+                .collect::<Vec<_>>();
+            let first_input_pair = if let Some(ip) = input_pairs.first() {
+                ip
+            } else {
+                break;
+            };
+            let mut remote_packets = input_pairs
+                .iter()
+                .map(|ip| ip.remote.packet.clone())
+                .collect::<std::collections::VecDeque<_>>();
+            let state = fastforwarder
+                .fastforward(
+                    &keyframe.state,
+                    input_pairs
+                        .iter()
+                        .take_while(|ip| ip.local.local_tick <= tick)
+                        .map(|ip| lockstep::Pair {
+                            local: ip.local.to_partial(),
+                            remote: ip.remote.to_partial(),
+                        })
+                        .collect(),
+                    keyframe.tick,
+                    tick,
+                    tick,
+                    &first_input_pair.local.packet,
+                    Box::new(move |_| {
+                        remote_packets
+                            .pop_front()
+                            .ok_or_else(|| anyhow::anyhow!("no more committed inputs"))
+                    }),
+                )?
+                .committed_state
+                .state;
+
+            keyframe = Keyframe { tick, state };
+            self.keyframes.push(keyframe.clone());
+        }
+        Ok(())
+    }
+
+    /// Runs the replay from its initial state on a fresh core until the round ends, or until it has been out of inputs
+    /// for a while. The state it stopped in is returned, along with any error the replay ran into.
+    pub fn simulate_round(
+        &self,
+        hooks: &'static (dyn game::Hooks + Send + Sync),
+        rom: &[u8],
+    ) -> anyhow::Result<(replayer::State, Option<anyhow::Error>)> {
+        let mut core = mgba::core::Core::new_gba("tango")?;
+        core.as_mut().load_rom(mgba::vfile::VFile::open_memory(rom))?;
+        core.as_mut().reset();
+
+        let replayer_state = replayer::State::new(
+            (self.metadata.match_type as u8, self.metadata.match_subtype as u8),
+            self.local_player_index,
+            self.input_pairs.clone(),
+            0,
+            Box::new(|| {}),
+        );
+
+        hooks.patch(core.as_mut());
+        let mut traps = hooks.common_traps();
+        traps.extend(hooks.replayer_traps(replayer_state.clone()));
+        core.set_traps(traps);
+        core.as_mut().load_state(&self.local_state)?;
+
+        let mut trailing_frames = 0;
+        let error = loop {
+            {
+                let mut state = replayer_state.lock_inner();
+                if let Some(err) = state.take_error() {
+                    break Some(err);
+                }
+
+                if state.is_round_ended() {
+                    break None;
+                }
+
+                if state.input_pairs_left() == 0 {
+                    if trailing_frames >= MAX_TRAILING_FRAMES {
+                        break None;
+                    }
+                    trailing_frames += 1;
+                }
+            }
+            core.as_mut().run_frame();
+        };
+
+        Ok((replayer_state, error))
+    }
+
+    pub fn last_tick(&self) -> u32 {
+        self.input_pairs.last().map(|ip| ip.local.local_tick).unwrap_or(0)
+    }
+
+    // Returns the latest keyframe at or before the given tick, falling back to the initial state.
+    pub fn keyframe_for_tick(&self, tick: u32) -> Keyframe {
+        self.keyframes
+            .iter()
+            .rev()
+            .find(|keyframe| keyframe.tick <= tick)
+            .cloned()
+            .unwrap_or_else(|| Keyframe {
+                tick: self.input_pairs.first().map(|ip| ip.local.local_tick).unwrap_or(0),
+                state: self.local_state.clone(),
+            })
+    }
+
     pub fn decode(mut r: impl std::io::Read) -> std::io::Result<Self> {
-        let (num_inputs, metadata) = read_metadata(&mut r)?;
+        let (version, num_inputs, metadata) = read_header(&mut r)?;
 
         let mut zr = zstd::stream::read::Decoder::new(r)?;
 
@@ -91,8 +267,50 @@ impl Replay {
         let remote_state = mgba::state::State::from_slice(&remote_state);
 
         let mut input_pairs = vec![];
+        let mut keyframes = vec![];
 
         loop {
+            if version >= 0x12 {
+                let record_type = if let Ok(v) = zr.read_u8() {
+                    v
+                } else {
+                    break;
+                };
+
+                match record_type {
+                    RECORD_INPUT => {}
+                    RECORD_KEYFRAME => {
+                        let tick = if let Ok(v) = zr.read_u32::<byteorder::LittleEndian>() {
+                            v
+                        } else {
+                            break;
+                        };
+                        let mut state = vec![
+                            0u8;
+                            if let Ok(v) = zr.read_u32::<byteorder::LittleEndian>() {
+                                v as usize
+                            } else {
+                                break;
+                            }
+                        ];
+                        if zr.read_exact(&mut state).is_err() {
+                            break;
+                        }
+                        keyframes.push(Keyframe {
+                            tick,
+                            state: mgba::state::State::from_slice(&state),
+                        });
+                        continue;
+                    }
+                    _ => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("invalid record type: {:02x}", record_type),
+                        ));
+                    }
+                }
+            }
+
             let local_tick = if let Ok(v) = zr.read_u32::<byteorder::LittleEndian>() {
                 v
             } else {
@@ -148,6 +366,7 @@ impl Replay {
             local_state,
             remote_state,
             input_pairs,
+            keyframes,
         })
     }
 }
@@ -172,6 +391,7 @@ impl Writer {
         Ok(Writer {
             encoder: Some(encoder),
             num_inputs: 0,
+            keyframe_index: vec![],
         })
     }
 
@@ -185,11 +405,28 @@ impl Writer {
         Ok(())
     }
 
+    // Keyframes must be written in between the input for tick - 1 and the input for tick.
+    pub fn write_keyframe(&mut self, tick: u32, state: &mgba::state::State) -> std::io::Result<()> {
+        // Each keyframe starts a new zstd frame, so a reader can use the index to start decoding from any keyframe.
+        let mut w = self.encoder.take().unwrap().finish()?;
+        let offset = w.stream_position()?;
+        let mut encoder = zstd::Encoder::new(w, 3)?;
+        encoder.write_u8(RECORD_KEYFRAME)?;
+        encoder.write_u32::<byteorder::LittleEndian>(tick)?;
+        encoder.write_u32::<byteorder::LittleEndian>(state.as_slice().len() as u32)?;
+        encoder.write_all(state.as_slice())?;
+        encoder.flush()?;
+        self.encoder = Some(encoder);
+        self.keyframe_index.push((tick, offset));
+        Ok(())
+    }
+
     pub fn write_input(
         &mut self,
         local_player_index: u8,
         ip: &lockstep::Pair<lockstep::Input, lockstep::Input>,
     ) -> std::io::Result<()> {
+        self.encoder.as_mut().unwrap().write_u8(RECORD_INPUT)?;
         self.encoder
             .as_mut()
             .unwrap()
@@ -222,6 +459,18 @@ impl Writer {
 
     pub fn finish(mut self) -> std::io::Result<Box<dyn WriteSeek + Send>> {
         let mut w = self.encoder.take().unwrap().finish()?;
+
+        let mut index = vec![];
+        for (tick, offset) in self.keyframe_index.iter() {
+            index.write_u32::<byteorder::LittleEndian>(*tick)?;
+            index.write_u64::<byteorder::LittleEndian>(*offset)?;
+        }
+        index.write_u32::<byteorder::LittleEndian>(self.keyframe_index.len() as u32)?;
+        index.write_all(INDEX_MAGIC)?;
+        w.write_u32::<byteorder::LittleEndian>(SKIPPABLE_FRAME_MAGIC)?;
+        w.write_u32::<byteorder::LittleEndian>(index.len() as u32)?;
+        w.write_all(&index)?;
+
         w.seek(std::io::SeekFrom::Start((HEADER.len() + 1) as u64))?;
         w.write_u32::<byteorder::LittleEndian>(self.num_inputs)?;
         Ok(w)
@@ -2,20 +2,32 @@ use byteorder::ByteOrder;
 use image::EncodableLayout;
 use tokio::io::AsyncWriteExt;
 
+use crate::video::Filter;
 use crate::{game, replay, replayer, video};
 
+mod avi;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Encoder {
+    Ffmpeg,
+    // Uncompressed AVI, written in-process: this doesn't need ffmpeg but ignores the scale and ffmpeg flags.
+    Avi,
+}
+
 pub struct Settings {
+    pub encoder: Encoder,
     pub ffmpeg: Option<std::path::PathBuf>,
     pub ffmpeg_audio_flags: String,
     pub ffmpeg_video_flags: String,
     pub ffmpeg_mux_flags: String,
-    pub video_filter: String,
+    pub video_filters: Vec<String>,
     pub disable_bgm: bool,
 }
 
 impl Settings {
     pub fn default_with_scale(factor: Option<usize>) -> Self {
         Self {
+            encoder: Encoder::Ffmpeg,
             ffmpeg: None,
             ffmpeg_audio_flags: if factor.is_some() {
                 "-c:a aac -ar 48000 -b:a 384k -ac 2".to_string()
@@ -28,7 +40,7 @@ impl Settings {
                 "-c:v libx264rgb -preset ultrafast -qp 0".to_string()
             },
             ffmpeg_mux_flags: "-movflags +faststart -strict -2".to_string(),
-            video_filter: "".to_string(),
+            video_filters: vec![],
             disable_bgm: false,
         }
     }
@@ -118,6 +130,21 @@ fn resolve_ffmpeg_path(ffmpeg: &Option<std::path::PathBuf>) -> std::path::PathBu
     })
 }
 
+pub fn is_ffmpeg_available(ffmpeg: &Option<std::path::PathBuf>) -> bool {
+    let mut command = std::process::Command::new(resolve_ffmpeg_path(ffmpeg));
+    command
+        .arg("-version")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command.status().map(|status| status.success()).unwrap_or(false)
+}
+
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
@@ -209,88 +236,198 @@ fn make_mux_ffmpeg(
     Ok(child.spawn()?)
 }
 
+enum Output<'a> {
+    Ffmpeg {
+        settings: &'a Settings,
+        output_path: &'a std::path::Path,
+        video_output: tempfile::NamedTempFile,
+        video_child: tokio::process::Child,
+        audio_outputs: Vec<tempfile::NamedTempFile>,
+        audio_children: Vec<tokio::process::Child>,
+    },
+    Avi(avi::Writer<std::io::BufWriter<std::fs::File>>),
+}
+
+impl<'a> Output<'a> {
+    fn new(
+        settings: &'a Settings,
+        output_path: &'a std::path::Path,
+        width: usize,
+        height: usize,
+        num_audio_streams: usize,
+    ) -> anyhow::Result<Self> {
+        Ok(match settings.encoder {
+            Encoder::Ffmpeg => {
+                let video_output = tempfile::NamedTempFile::new()?;
+                let video_child = make_video_ffmpeg(
+                    &settings.ffmpeg,
+                    video_output.path(),
+                    width,
+                    height,
+                    &shell_words::split(&settings.ffmpeg_video_flags)?
+                        .into_iter()
+                        .map(|flag| std::ffi::OsString::from(flag))
+                        .collect::<Vec<_>>(),
+                )?;
+
+                let mut audio_outputs = vec![];
+                let mut audio_children = vec![];
+                for _ in 0..num_audio_streams {
+                    let audio_output = tempfile::NamedTempFile::new()?;
+                    audio_children.push(make_audio_ffmpeg(
+                        &settings.ffmpeg,
+                        audio_output.path(),
+                        &shell_words::split(&settings.ffmpeg_audio_flags)?
+                            .into_iter()
+                            .map(|flag| std::ffi::OsString::from(flag))
+                            .collect::<Vec<_>>(),
+                    )?);
+                    audio_outputs.push(audio_output);
+                }
+
+                Output::Ffmpeg {
+                    settings,
+                    output_path,
+                    video_output,
+                    video_child,
+                    audio_outputs,
+                    audio_children,
+                }
+            }
+            Encoder::Avi => Output::Avi(avi::Writer::new(
+                std::io::BufWriter::new(std::fs::File::create(output_path)?),
+                width,
+                height,
+                SAMPLE_RATE as u32,
+                num_audio_streams,
+            )?),
+        })
+    }
+
+    async fn write_video_frame(&mut self, buf: &[u8]) -> anyhow::Result<()> {
+        match self {
+            Output::Ffmpeg { video_child, .. } => {
+                video_child.stdin.as_mut().unwrap().write_all(buf).await?;
+            }
+            Output::Avi(writer) => {
+                writer.write_video_frame(buf)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_audio(&mut self, stream: usize, samples: &[i16]) -> anyhow::Result<()> {
+        match self {
+            Output::Ffmpeg { audio_children, .. } => {
+                let mut audio_bytes = vec![0u8; samples.len() * 2];
+                byteorder::LittleEndian::write_i16_into(&samples, &mut audio_bytes[..]);
+                audio_children[stream]
+                    .stdin
+                    .as_mut()
+                    .unwrap()
+                    .write_all(&audio_bytes)
+                    .await?;
+            }
+            Output::Avi(writer) => {
+                writer.write_audio(stream, samples)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish(self) -> anyhow::Result<()> {
+        match self {
+            Output::Ffmpeg {
+                settings,
+                output_path,
+                video_output,
+                mut video_child,
+                audio_outputs,
+                audio_children,
+            } => {
+                video_child.stdin = None;
+                video_child.wait().await?;
+                for mut audio_child in audio_children {
+                    audio_child.stdin = None;
+                    audio_child.wait().await?;
+                }
+
+                let mut mux_child = make_mux_ffmpeg(
+                    &settings.ffmpeg,
+                    output_path,
+                    video_output.path(),
+                    &audio_outputs
+                        .iter()
+                        .map(|audio_output| audio_output.path())
+                        .collect::<Vec<_>>(),
+                    &shell_words::split(&settings.ffmpeg_mux_flags)?
+                        .into_iter()
+                        .map(|flag| std::ffi::OsString::from(flag))
+                        .collect::<Vec<_>>(),
+                )?;
+                mux_child.wait().await?;
+            }
+            Output::Avi(writer) => {
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
 pub async fn export(
     rom: &[u8],
-    replay: &replay::Replay,
+    replays: &[replay::Replay],
     output_path: &std::path::Path,
     settings: &Settings,
     progress_callback: impl Fn(usize, usize),
 ) -> anyhow::Result<()> {
-    let (mut core, state) = make_core_and_state(rom, replay, settings)?;
-
-    let filter = video::filter_by_name(&settings.video_filter).ok_or(anyhow::anyhow!("unknown filter"))?;
+    // Exports are only ever scaled up by whole factors afterwards, where sharp-bilinear is the same as no filter.
+    let mut filter = video::Pipeline::new(
+        &settings.video_filters,
+        (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+        1.0,
+    )?;
     let (vbuf_width, vbuf_height) =
         filter.output_size((mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize));
     let mut emu_vbuf = vec![0u8; (mgba::gba::SCREEN_WIDTH * mgba::gba::SCREEN_HEIGHT * 4) as usize];
     let mut vbuf = vec![0u8; (vbuf_width * vbuf_height * 4) as usize];
 
-    let video_output = tempfile::NamedTempFile::new()?;
-    let mut video_child = make_video_ffmpeg(
-        &settings.ffmpeg,
-        video_output.path(),
-        vbuf_width,
-        vbuf_height,
-        &shell_words::split(&settings.ffmpeg_video_flags)?
-            .into_iter()
-            .map(|flag| std::ffi::OsString::from(flag))
-            .collect::<Vec<_>>(),
-    )?;
-
-    let audio_output = tempfile::NamedTempFile::new()?;
-    let mut audio_child = make_audio_ffmpeg(
-        &settings.ffmpeg,
-        audio_output.path(),
-        &shell_words::split(&settings.ffmpeg_audio_flags)?
-            .into_iter()
-            .map(|flag| std::ffi::OsString::from(flag))
-            .collect::<Vec<_>>(),
-    )?;
+    let mut output = Output::new(settings, output_path, vbuf_width, vbuf_height, 1)?;
 
     let mut samples = vec![0i16; SAMPLE_RATE as usize];
-    let total = state.lock_inner().input_pairs_left();
-    loop {
-        {
-            let state = state.lock_inner();
-            if (!replay.is_complete && state.input_pairs_left() == 0) || state.is_round_ended() {
-                break;
+    let total = replays.iter().map(|replay| replay.input_pairs.len()).sum::<usize>();
+    let mut done = 0;
+    for replay in replays {
+        let (mut core, state) = make_core_and_state(rom, replay, settings)?;
+        let replay_total = state.lock_inner().input_pairs_left();
+        loop {
+            {
+                let state = state.lock_inner();
+                if (!replay.is_complete && state.input_pairs_left() == 0) || state.is_round_ended() {
+                    break;
+                }
             }
-        }
 
-        if let Some(err) = state.lock_inner().take_error() {
-            Err(err)?;
-        }
-
-        let samples = run_frame(&mut core, &mut samples, &mut emu_vbuf);
-        filter.apply(
-            &emu_vbuf,
-            &mut vbuf,
-            (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
-        );
+            if let Some(err) = state.lock_inner().take_error() {
+                Err(err)?;
+            }
 
-        video_child.stdin.as_mut().unwrap().write_all(vbuf.as_slice()).await?;
+            let samples = run_frame(&mut core, &mut samples, &mut emu_vbuf);
+            filter.apply(
+                &emu_vbuf,
+                &mut vbuf,
+                (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+            );
 
-        let mut audio_bytes = vec![0u8; samples.len() * 2];
-        byteorder::LittleEndian::write_i16_into(&samples, &mut audio_bytes[..]);
-        audio_child.stdin.as_mut().unwrap().write_all(&audio_bytes).await?;
-        progress_callback(total - state.lock_inner().input_pairs_left(), total);
+            output.write_video_frame(vbuf.as_slice()).await?;
+            output.write_audio(0, samples).await?;
+            progress_callback(done + replay_total - state.lock_inner().input_pairs_left(), total);
+        }
+        done += replay_total;
     }
 
-    video_child.stdin = None;
-    video_child.wait().await?;
-    audio_child.stdin = None;
-    audio_child.wait().await?;
-
-    let mut mux_child = make_mux_ffmpeg(
-        &settings.ffmpeg,
-        output_path,
-        video_output.path(),
-        &[audio_output.path()],
-        &shell_words::split(&settings.ffmpeg_mux_flags)?
-            .into_iter()
-            .map(|flag| std::ffi::OsString::from(flag))
-            .collect::<Vec<_>>(),
-    )?;
-    mux_child.wait().await?;
+    output.finish().await?;
 
     Ok(())
 }
@@ -298,170 +435,120 @@ pub async fn export(
 pub async fn export_twosided(
     local_rom: &[u8],
     remote_rom: &[u8],
-    replay: &replay::Replay,
+    replays: &[replay::Replay],
     output_path: &std::path::Path,
     settings: &Settings,
     progress_callback: impl Fn(usize, usize),
 ) -> anyhow::Result<()> {
-    let local_replay = replay.clone();
-    let remote_replay = local_replay.clone().into_remote();
-
-    let (mut local_core, local_state) = make_core_and_state(local_rom, &local_replay, settings)?;
-    let (mut remote_core, remote_state) = make_core_and_state(remote_rom, &remote_replay, settings)?;
-
     let mut emu_vbuf = vec![0u8; (mgba::gba::SCREEN_WIDTH * mgba::gba::SCREEN_HEIGHT * 4) as usize];
 
-    let filter = video::filter_by_name(&settings.video_filter).ok_or(anyhow::anyhow!("unknown filter"))?;
+    // Each side gets its own pipeline, as filters like ghosting remember the previous frame.
+    let mut local_filter = video::Pipeline::new(
+        &settings.video_filters,
+        (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+        1.0,
+    )?;
+    let mut remote_filter = video::Pipeline::new(
+        &settings.video_filters,
+        (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+        1.0,
+    )?;
     let (vbuf_width, vbuf_height) =
-        filter.output_size((mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize));
+        local_filter.output_size((mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize));
     let mut vbuf = image::RgbaImage::new(vbuf_width as u32, vbuf_height as u32);
     let mut composed_vbuf = image::RgbaImage::new((vbuf_width * 2) as u32, vbuf_height as u32);
 
-    let video_output = tempfile::NamedTempFile::new()?;
-    let mut video_child = make_video_ffmpeg(
-        &settings.ffmpeg,
-        video_output.path(),
-        vbuf_width * 2,
-        vbuf_height,
-        &shell_words::split(&settings.ffmpeg_video_flags)?
-            .into_iter()
-            .map(|flag| std::ffi::OsString::from(flag))
-            .collect::<Vec<_>>(),
-    )?;
-
-    let local_audio_output = tempfile::NamedTempFile::new()?;
-    let mut local_audio_child = make_audio_ffmpeg(
-        &settings.ffmpeg,
-        local_audio_output.path(),
-        &shell_words::split(&settings.ffmpeg_audio_flags)?
-            .into_iter()
-            .map(|flag| std::ffi::OsString::from(flag))
-            .collect::<Vec<_>>(),
-    )?;
-
-    let remote_audio_output = tempfile::NamedTempFile::new()?;
-    let mut remote_audio_child = make_audio_ffmpeg(
-        &settings.ffmpeg,
-        remote_audio_output.path(),
-        &shell_words::split(&settings.ffmpeg_audio_flags)?
-            .into_iter()
-            .map(|flag| std::ffi::OsString::from(flag))
-            .collect::<Vec<_>>(),
-    )?;
+    let mut output = Output::new(settings, output_path, vbuf_width * 2, vbuf_height, 2)?;
 
     let mut samples = vec![0i16; SAMPLE_RATE as usize];
-    let total = std::cmp::min(
-        local_state.lock_inner().input_pairs_left(),
-        remote_state.lock_inner().input_pairs_left(),
-    );
-    loop {
-        {
-            let local_state = local_state.lock_inner();
-            if (!local_replay.is_complete && local_state.input_pairs_left() == 0) || local_state.is_round_ended() {
-                break;
-            }
-        }
-
-        {
-            let remote_state = remote_state.lock_inner();
-            if (!remote_replay.is_complete && remote_state.input_pairs_left() == 0) || remote_state.is_round_ended() {
-                break;
-            }
-        }
-
-        let current_tick = local_state.lock_inner().current_tick();
-        if remote_state.lock_inner().current_tick() != current_tick {
-            anyhow::bail!(
-                "tick misaligned! {} vs {}",
-                current_tick,
-                remote_state.lock_inner().current_tick()
-            );
-        }
-
-        while local_state.lock_inner().current_tick() == current_tick
-            && remote_state.lock_inner().current_tick() == current_tick
-        {
-            if let Some(err) = local_state.lock_inner().take_error() {
-                Err(err)?;
+    let total = replays.iter().map(|replay| replay.input_pairs.len()).sum::<usize>();
+    let mut done = 0;
+    for replay in replays {
+        let local_replay = replay.clone();
+        let remote_replay = local_replay.clone().into_remote();
+
+        let (mut local_core, local_state) = make_core_and_state(local_rom, &local_replay, settings)?;
+        let (mut remote_core, remote_state) = make_core_and_state(remote_rom, &remote_replay, settings)?;
+
+        let replay_total = std::cmp::min(
+            local_state.lock_inner().input_pairs_left(),
+            remote_state.lock_inner().input_pairs_left(),
+        );
+        loop {
+            {
+                let local_state = local_state.lock_inner();
+                if (!local_replay.is_complete && local_state.input_pairs_left() == 0) || local_state.is_round_ended() {
+                    break;
+                }
             }
 
-            if let Some(err) = remote_state.lock_inner().take_error() {
-                Err(err)?;
+            {
+                let remote_state = remote_state.lock_inner();
+                if (!remote_replay.is_complete && remote_state.input_pairs_left() == 0) || remote_state.is_round_ended()
+                {
+                    break;
+                }
             }
 
-            {
-                let local_samples = run_frame(&mut local_core, &mut samples, &mut emu_vbuf);
-                filter.apply(
-                    &emu_vbuf,
-                    &mut vbuf,
-                    (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+            let current_tick = local_state.lock_inner().current_tick();
+            if remote_state.lock_inner().current_tick() != current_tick {
+                anyhow::bail!(
+                    "tick misaligned! {} vs {}",
+                    current_tick,
+                    remote_state.lock_inner().current_tick()
                 );
-                image::imageops::replace(&mut composed_vbuf, &vbuf, 0, 0);
-                let mut audio_bytes = vec![0u8; local_samples.len() * 2];
-                byteorder::LittleEndian::write_i16_into(&local_samples, &mut audio_bytes[..]);
-                local_audio_child
-                    .stdin
-                    .as_mut()
-                    .unwrap()
-                    .write_all(&audio_bytes)
-                    .await?;
             }
 
+            while local_state.lock_inner().current_tick() == current_tick
+                && remote_state.lock_inner().current_tick() == current_tick
             {
-                let remote_samples = run_frame(&mut remote_core, &mut samples, &mut emu_vbuf);
-                filter.apply(
-                    &emu_vbuf,
-                    &mut vbuf,
-                    (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
-                );
-                image::imageops::replace(&mut composed_vbuf, &vbuf, vbuf_width as i64, 0);
-                let mut audio_bytes = vec![0u8; remote_samples.len() * 2];
-                byteorder::LittleEndian::write_i16_into(&remote_samples, &mut audio_bytes[..]);
-                remote_audio_child
-                    .stdin
-                    .as_mut()
-                    .unwrap()
-                    .write_all(&audio_bytes)
-                    .await?;
+                if let Some(err) = local_state.lock_inner().take_error() {
+                    Err(err)?;
+                }
+
+                if let Some(err) = remote_state.lock_inner().take_error() {
+                    Err(err)?;
+                }
+
+                {
+                    let local_samples = run_frame(&mut local_core, &mut samples, &mut emu_vbuf);
+                    local_filter.apply(
+                        &emu_vbuf,
+                        &mut vbuf,
+                        (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+                    );
+                    image::imageops::replace(&mut composed_vbuf, &vbuf, 0, 0);
+                    output.write_audio(0, local_samples).await?;
+                }
+
+                {
+                    let remote_samples = run_frame(&mut remote_core, &mut samples, &mut emu_vbuf);
+                    remote_filter.apply(
+                        &emu_vbuf,
+                        &mut vbuf,
+                        (mgba::gba::SCREEN_WIDTH as usize, mgba::gba::SCREEN_HEIGHT as usize),
+                    );
+                    image::imageops::replace(&mut composed_vbuf, &vbuf, vbuf_width as i64, 0);
+                    output.write_audio(1, remote_samples).await?;
+                }
+
+                output.write_video_frame(composed_vbuf.as_bytes()).await?;
             }
 
-            video_child
-                .stdin
-                .as_mut()
-                .unwrap()
-                .write_all(composed_vbuf.as_bytes())
-                .await?;
-        }
+            while local_state.lock_inner().current_tick() == current_tick {
+                run_frame(&mut local_core, &mut samples, &mut emu_vbuf);
+            }
 
-        while local_state.lock_inner().current_tick() == current_tick {
-            run_frame(&mut local_core, &mut samples, &mut emu_vbuf);
-        }
+            while remote_state.lock_inner().current_tick() == current_tick {
+                run_frame(&mut remote_core, &mut samples, &mut emu_vbuf);
+            }
 
-        while remote_state.lock_inner().current_tick() == current_tick {
-            run_frame(&mut remote_core, &mut samples, &mut emu_vbuf);
+            progress_callback(done + current_tick as usize, total);
         }
-
-        progress_callback(current_tick as usize, total);
+        done += replay_total;
     }
 
-    video_child.stdin = None;
-    video_child.wait().await?;
-    local_audio_child.stdin = None;
-    local_audio_child.wait().await?;
-    remote_audio_child.stdin = None;
-    remote_audio_child.wait().await?;
-
-    let mut mux_child = make_mux_ffmpeg(
-        &settings.ffmpeg,
-        output_path,
-        video_output.path(),
-        &[local_audio_output.path(), remote_audio_output.path()],
-        &shell_words::split(&settings.ffmpeg_mux_flags)?
-            .into_iter()
-            .map(|flag| std::ffi::OsString::from(flag))
-            .collect::<Vec<_>>(),
-    )?;
-    mux_child.wait().await?;
+    output.finish().await?;
 
     Ok(())
 }
@@ -0,0 +1,441 @@
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+
+// A minimal uncompressed AVI writer: one BGR24 video stream and any number of 16-bit stereo PCM audio streams.
+//
+// Uncompressed video hits the 4 GiB limit of a RIFF file in a few minutes, so this writes OpenDML (AVI 2.0) files: the
+// first RIFF 'AVI ' is followed by as many RIFF 'AVIX' as needed, each with its own standard index (ix##) that the
+// super index (indx) of every stream points to. The first RIFF also gets an AVI 1.0 idx1 for older players, which
+// then only see what is in it.
+
+const AVIF_HASINDEX: u32 = 0x10;
+const AVIF_ISINTERLEAVED: u32 = 0x100;
+const AVIIF_KEYFRAME: u32 = 0x10;
+
+const AVI_INDEX_OF_INDEXES: u8 = 0x00;
+const AVI_INDEX_OF_CHUNKS: u8 = 0x01;
+
+// Each RIFF is kept under 1 GiB, as that's what most readers expect of OpenDML files.
+const MAX_RIFF_SIZE: u64 = 1 << 30;
+
+// Space is reserved in the header for this many super index entries per stream, i.e. this many RIFFs.
+const MAX_SUPER_INDEX_ENTRIES: usize = 256;
+const SUPER_INDEX_SIZE: u32 = 24 + 16 * MAX_SUPER_INDEX_ENTRIES as u32;
+
+// Timings are expressed as a rational rate / scale: the GBA runs at 16777216 / 280896 frames per second.
+const VIDEO_RATE: u32 = 16777216;
+const VIDEO_SCALE: u32 = 280896;
+
+const AUDIO_CHANNELS: u16 = 2;
+const AUDIO_BITS_PER_SAMPLE: u16 = 16;
+const AUDIO_BLOCK_ALIGN: u16 = AUDIO_CHANNELS * AUDIO_BITS_PER_SAMPLE / 8;
+
+struct LegacyIndexEntry {
+    fourcc: [u8; 4],
+    offset: u32,
+    size: u32,
+}
+
+struct SuperIndexEntry {
+    offset: u64,
+    size: u32,
+    duration: u32,
+}
+
+struct Stream {
+    fourcc: [u8; 4],
+    super_index_pos: u64,
+    length_pos: u64,
+    length: u32,
+    super_index: Vec<SuperIndexEntry>,
+    // Chunks in the current RIFF, as (offset of the data from the start of the movi list, size).
+    chunks: Vec<(u32, u32)>,
+    duration: u32,
+}
+
+pub struct Writer<W>
+where
+    W: std::io::Write + std::io::Seek,
+{
+    w: W,
+    width: usize,
+    height: usize,
+    streams: Vec<Stream>,
+    total_frames_pos: u64,
+    dmlh_total_frames_pos: u64,
+    riff_start: u64,
+    movi_start: u64,
+    legacy_index: Option<Vec<LegacyIndexEntry>>,
+    first_riff_frames: Option<u32>,
+    frame_buf: Vec<u8>,
+}
+
+fn stride(width: usize) -> usize {
+    (width * 3 + 3) & !3
+}
+
+fn stream_fourcc(stream: usize, suffix: &[u8; 2]) -> [u8; 4] {
+    let id = format!("{:02}", stream);
+    [id.as_bytes()[0], id.as_bytes()[1], suffix[0], suffix[1]]
+}
+
+fn index_fourcc(stream: usize) -> [u8; 4] {
+    let id = format!("ix{:02}", stream);
+    id.as_bytes().try_into().unwrap()
+}
+
+fn write_super_index_placeholder(w: &mut impl std::io::Write, fourcc: [u8; 4]) -> std::io::Result<()> {
+    w.write_all(b"indx")?;
+    w.write_u32::<LittleEndian>(SUPER_INDEX_SIZE)?;
+    w.write_u16::<LittleEndian>(4)?; // wLongsPerEntry
+    w.write_u8(0)?; // bIndexSubType
+    w.write_u8(AVI_INDEX_OF_INDEXES)?; // bIndexType
+    w.write_u32::<LittleEndian>(0)?; // nEntriesInUse
+    w.write_all(&fourcc)?; // dwChunkId
+    for _ in 0..3 {
+        w.write_u32::<LittleEndian>(0)?; // dwReserved
+    }
+    w.write_all(&[0u8; 16 * MAX_SUPER_INDEX_ENTRIES])?;
+    Ok(())
+}
+
+// Starts a RIFF, returning where it starts.
+fn begin_riff(w: &mut (impl std::io::Write + std::io::Seek), form: &[u8; 4]) -> std::io::Result<u64> {
+    let riff_start = w.stream_position()?;
+    w.write_all(b"RIFF")?;
+    w.write_u32::<LittleEndian>(0)?;
+    w.write_all(form)?;
+    Ok(riff_start)
+}
+
+// Starts a movi list, returning where its contents start, which is what chunk offsets are relative to.
+fn begin_movi(w: &mut (impl std::io::Write + std::io::Seek)) -> std::io::Result<u64> {
+    w.write_all(b"LIST")?;
+    w.write_u32::<LittleEndian>(0)?;
+    let movi_start = w.stream_position()?;
+    w.write_all(b"movi")?;
+    Ok(movi_start)
+}
+
+fn patch_u32(w: &mut (impl std::io::Write + std::io::Seek), pos: u64, v: u32) -> std::io::Result<()> {
+    let end = w.stream_position()?;
+    w.seek(std::io::SeekFrom::Start(pos))?;
+    w.write_u32::<LittleEndian>(v)?;
+    w.seek(std::io::SeekFrom::Start(end))?;
+    Ok(())
+}
+
+impl<W> Writer<W>
+where
+    W: std::io::Write + std::io::Seek,
+{
+    pub fn new(
+        mut w: W,
+        width: usize,
+        height: usize,
+        sample_rate: u32,
+        num_audio_streams: usize,
+    ) -> anyhow::Result<Self> {
+        let frame_size = (stride(width) * height) as u32;
+        let num_streams = 1 + num_audio_streams as u32;
+
+        let riff_start = begin_riff(&mut w, b"AVI ")?;
+
+        w.write_all(b"LIST")?;
+        let hdrl_size_pos = w.stream_position()?;
+        w.write_u32::<LittleEndian>(0)?;
+        w.write_all(b"hdrl")?;
+
+        w.write_all(b"avih")?;
+        w.write_u32::<LittleEndian>(56)?;
+        w.write_u32::<LittleEndian>((VIDEO_SCALE as u64 * 1_000_000 / VIDEO_RATE as u64) as u32)?; // dwMicroSecPerFrame
+        w.write_u32::<LittleEndian>(0)?; // dwMaxBytesPerSec
+        w.write_u32::<LittleEndian>(0)?; // dwPaddingGranularity
+        w.write_u32::<LittleEndian>(AVIF_HASINDEX | AVIF_ISINTERLEAVED)?; // dwFlags
+        let total_frames_pos = w.stream_position()?;
+        w.write_u32::<LittleEndian>(0)?; // dwTotalFrames: only the frames in the first RIFF
+        w.write_u32::<LittleEndian>(0)?; // dwInitialFrames
+        w.write_u32::<LittleEndian>(num_streams)?; // dwStreams
+        w.write_u32::<LittleEndian>(frame_size)?; // dwSuggestedBufferSize
+        w.write_u32::<LittleEndian>(width as u32)?; // dwWidth
+        w.write_u32::<LittleEndian>(height as u32)?; // dwHeight
+        for _ in 0..4 {
+            w.write_u32::<LittleEndian>(0)?; // dwReserved
+        }
+
+        let mut streams = vec![];
+
+        // Video stream.
+        w.write_all(b"LIST")?;
+        w.write_u32::<LittleEndian>(4 + (8 + 56) + (8 + 40) + (8 + SUPER_INDEX_SIZE))?;
+        w.write_all(b"strl")?;
+
+        w.write_all(b"strh")?;
+        w.write_u32::<LittleEndian>(56)?;
+        w.write_all(b"vids")?; // fccType
+        w.write_all(b"DIB ")?; // fccHandler
+        w.write_u32::<LittleEndian>(0)?; // dwFlags
+        w.write_u16::<LittleEndian>(0)?; // wPriority
+        w.write_u16::<LittleEndian>(0)?; // wLanguage
+        w.write_u32::<LittleEndian>(0)?; // dwInitialFrames
+        w.write_u32::<LittleEndian>(VIDEO_SCALE)?; // dwScale
+        w.write_u32::<LittleEndian>(VIDEO_RATE)?; // dwRate
+        w.write_u32::<LittleEndian>(0)?; // dwStart
+        let length_pos = w.stream_position()?;
+        w.write_u32::<LittleEndian>(0)?; // dwLength
+        w.write_u32::<LittleEndian>(frame_size)?; // dwSuggestedBufferSize
+        w.write_u32::<LittleEndian>(0xffffffff)?; // dwQuality
+        w.write_u32::<LittleEndian>(0)?; // dwSampleSize
+        w.write_i16::<LittleEndian>(0)?; // rcFrame
+        w.write_i16::<LittleEndian>(0)?;
+        w.write_i16::<LittleEndian>(width as i16)?;
+        w.write_i16::<LittleEndian>(height as i16)?;
+
+        w.write_all(b"strf")?;
+        w.write_u32::<LittleEndian>(40)?;
+        w.write_u32::<LittleEndian>(40)?; // biSize
+        w.write_i32::<LittleEndian>(width as i32)?; // biWidth
+        w.write_i32::<LittleEndian>(height as i32)?; // biHeight: positive, so rows are stored bottom-up
+        w.write_u16::<LittleEndian>(1)?; // biPlanes
+        w.write_u16::<LittleEndian>(24)?; // biBitCount
+        w.write_u32::<LittleEndian>(0)?; // biCompression = BI_RGB
+        w.write_u32::<LittleEndian>(frame_size)?; // biSizeImage
+        w.write_i32::<LittleEndian>(0)?; // biXPelsPerMeter
+        w.write_i32::<LittleEndian>(0)?; // biYPelsPerMeter
+        w.write_u32::<LittleEndian>(0)?; // biClrUsed
+        w.write_u32::<LittleEndian>(0)?; // biClrImportant
+
+        let fourcc = stream_fourcc(0, b"db");
+        streams.push(Stream {
+            fourcc,
+            super_index_pos: w.stream_position()?,
+            length_pos,
+            length: 0,
+            super_index: vec![],
+            chunks: vec![],
+            duration: 0,
+        });
+        write_super_index_placeholder(&mut w, fourcc)?;
+
+        // Audio streams.
+        for i in 0..num_audio_streams {
+            w.write_all(b"LIST")?;
+            w.write_u32::<LittleEndian>(4 + (8 + 56) + (8 + 18) + (8 + SUPER_INDEX_SIZE))?;
+            w.write_all(b"strl")?;
+
+            w.write_all(b"strh")?;
+            w.write_u32::<LittleEndian>(56)?;
+            w.write_all(b"auds")?; // fccType
+            w.write_u32::<LittleEndian>(0)?; // fccHandler
+            w.write_u32::<LittleEndian>(0)?; // dwFlags
+            w.write_u16::<LittleEndian>(0)?; // wPriority
+            w.write_u16::<LittleEndian>(0)?; // wLanguage
+            w.write_u32::<LittleEndian>(0)?; // dwInitialFrames
+            w.write_u32::<LittleEndian>(1)?; // dwScale
+            w.write_u32::<LittleEndian>(sample_rate)?; // dwRate
+            w.write_u32::<LittleEndian>(0)?; // dwStart
+            let length_pos = w.stream_position()?;
+            w.write_u32::<LittleEndian>(0)?; // dwLength
+            w.write_u32::<LittleEndian>(sample_rate * AUDIO_BLOCK_ALIGN as u32)?; // dwSuggestedBufferSize
+            w.write_u32::<LittleEndian>(0xffffffff)?; // dwQuality
+            w.write_u32::<LittleEndian>(AUDIO_BLOCK_ALIGN as u32)?; // dwSampleSize
+            for _ in 0..4 {
+                w.write_i16::<LittleEndian>(0)?; // rcFrame
+            }
+
+            w.write_all(b"strf")?;
+            w.write_u32::<LittleEndian>(18)?;
+            w.write_u16::<LittleEndian>(1)?; // wFormatTag = WAVE_FORMAT_PCM
+            w.write_u16::<LittleEndian>(AUDIO_CHANNELS)?; // nChannels
+            w.write_u32::<LittleEndian>(sample_rate)?; // nSamplesPerSec
+            w.write_u32::<LittleEndian>(sample_rate * AUDIO_BLOCK_ALIGN as u32)?; // nAvgBytesPerSec
+            w.write_u16::<LittleEndian>(AUDIO_BLOCK_ALIGN)?; // nBlockAlign
+            w.write_u16::<LittleEndian>(AUDIO_BITS_PER_SAMPLE)?; // wBitsPerSample
+            w.write_u16::<LittleEndian>(0)?; // cbSize
+
+            let fourcc = stream_fourcc(i + 1, b"wb");
+            streams.push(Stream {
+                fourcc,
+                super_index_pos: w.stream_position()?,
+                length_pos,
+                length: 0,
+                super_index: vec![],
+                chunks: vec![],
+                duration: 0,
+            });
+            write_super_index_placeholder(&mut w, fourcc)?;
+        }
+
+        // OpenDML extended header, which holds the real total number of frames.
+        w.write_all(b"LIST")?;
+        w.write_u32::<LittleEndian>(4 + 8 + 248)?;
+        w.write_all(b"odml")?;
+        w.write_all(b"dmlh")?;
+        w.write_u32::<LittleEndian>(248)?;
+        let dmlh_total_frames_pos = w.stream_position()?;
+        w.write_all(&[0u8; 248])?;
+
+        let hdrl_end = w.stream_position()?;
+        w.seek(std::io::SeekFrom::Start(hdrl_size_pos))?;
+        w.write_u32::<LittleEndian>((hdrl_end - hdrl_size_pos - 4) as u32)?;
+        w.seek(std::io::SeekFrom::Start(hdrl_end))?;
+
+        let movi_start = begin_movi(&mut w)?;
+
+        Ok(Self {
+            w,
+            width,
+            height,
+            streams,
+            total_frames_pos,
+            dmlh_total_frames_pos,
+            riff_start,
+            movi_start,
+            legacy_index: Some(vec![]),
+            first_riff_frames: None,
+            frame_buf: vec![0u8; frame_size as usize],
+        })
+    }
+
+    // Writes the standard indexes at the end of the current movi list and closes the RIFF.
+    fn end_riff(&mut self) -> anyhow::Result<()> {
+        for (i, stream) in self.streams.iter_mut().enumerate() {
+            if stream.chunks.is_empty() {
+                continue;
+            }
+            if stream.super_index.len() >= MAX_SUPER_INDEX_ENTRIES {
+                anyhow::bail!("output is too large for an AVI file");
+            }
+
+            let offset = self.w.stream_position()?;
+            let size = 24 + 8 * stream.chunks.len() as u32;
+            self.w.write_all(&index_fourcc(i))?;
+            self.w.write_u32::<LittleEndian>(size)?;
+            self.w.write_u16::<LittleEndian>(2)?; // wLongsPerEntry
+            self.w.write_u8(0)?; // bIndexSubType
+            self.w.write_u8(AVI_INDEX_OF_CHUNKS)?; // bIndexType
+            self.w.write_u32::<LittleEndian>(stream.chunks.len() as u32)?; // nEntriesInUse
+            self.w.write_all(&stream.fourcc)?; // dwChunkId
+            self.w.write_u64::<LittleEndian>(self.movi_start)?; // qwBaseOffset
+            self.w.write_u32::<LittleEndian>(0)?; // dwReserved3
+            for (chunk_offset, chunk_size) in stream.chunks.drain(..) {
+                self.w.write_u32::<LittleEndian>(chunk_offset)?;
+                self.w.write_u32::<LittleEndian>(chunk_size)?; // Every chunk is a keyframe, so bit 31 stays clear.
+            }
+
+            stream.super_index.push(SuperIndexEntry {
+                offset,
+                size: 8 + size,
+                duration: std::mem::take(&mut stream.duration),
+            });
+        }
+
+        let movi_end = self.w.stream_position()?;
+        patch_u32(&mut self.w, self.movi_start - 4, (movi_end - self.movi_start) as u32)?;
+
+        if let Some(legacy_index) = self.legacy_index.take() {
+            self.w.write_all(b"idx1")?;
+            self.w.write_u32::<LittleEndian>(legacy_index.len() as u32 * 16)?;
+            for entry in legacy_index.iter() {
+                self.w.write_all(&entry.fourcc)?;
+                self.w.write_u32::<LittleEndian>(AVIIF_KEYFRAME)?;
+                self.w.write_u32::<LittleEndian>(entry.offset)?;
+                self.w.write_u32::<LittleEndian>(entry.size)?;
+            }
+            self.first_riff_frames = Some(self.streams[0].length);
+        }
+
+        let riff_end = self.w.stream_position()?;
+        patch_u32(
+            &mut self.w,
+            self.riff_start + 4,
+            (riff_end - self.riff_start - 8) as u32,
+        )?;
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, stream: usize, buf: &[u8], duration: u32) -> anyhow::Result<()> {
+        let padded_len = buf.len() as u64 + (buf.len() % 2) as u64;
+
+        // Leave room for the indexes that still have to go into this RIFF.
+        let pos = self.w.stream_position()?;
+        let pending_chunks = self.streams.iter().map(|s| s.chunks.len() as u64).sum::<u64>() + 1;
+        let index_size = self.streams.len() as u64 * (8 + 24)
+            + pending_chunks * 8
+            + self.legacy_index.as_ref().map(|_| 8 + pending_chunks * 16).unwrap_or(0);
+        if pos + 8 + padded_len + index_size - self.riff_start > MAX_RIFF_SIZE {
+            self.end_riff()?;
+            self.riff_start = begin_riff(&mut self.w, b"AVIX")?;
+            self.movi_start = begin_movi(&mut self.w)?;
+        }
+
+        let pos = self.w.stream_position()?;
+        let fourcc = self.streams[stream].fourcc;
+        if let Some(legacy_index) = self.legacy_index.as_mut() {
+            legacy_index.push(LegacyIndexEntry {
+                fourcc,
+                offset: (pos - self.movi_start) as u32,
+                size: buf.len() as u32,
+            });
+        }
+        let s = &mut self.streams[stream];
+        s.chunks.push(((pos + 8 - self.movi_start) as u32, buf.len() as u32));
+        s.duration += duration;
+        s.length += duration;
+
+        self.w.write_all(&fourcc)?;
+        self.w.write_u32::<LittleEndian>(buf.len() as u32)?;
+        self.w.write_all(buf)?;
+        if buf.len() % 2 != 0 {
+            self.w.write_u8(0)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_video_frame(&mut self, rgba: &[u8]) -> anyhow::Result<()> {
+        let stride = stride(self.width);
+        let mut frame_buf = std::mem::take(&mut self.frame_buf);
+        for (y, row) in rgba.chunks_exact(self.width * 4).enumerate() {
+            let out_row = &mut frame_buf[(self.height - y - 1) * stride..][..self.width * 3];
+            for (src, dst) in row.chunks_exact(4).zip(out_row.chunks_exact_mut(3)) {
+                dst[0] = src[2];
+                dst[1] = src[1];
+                dst[2] = src[0];
+            }
+        }
+        let r = self.write_chunk(0, &frame_buf, 1);
+        self.frame_buf = frame_buf;
+        r
+    }
+
+    pub fn write_audio(&mut self, stream: usize, samples: &[i16]) -> anyhow::Result<()> {
+        let mut buf = vec![0u8; samples.len() * 2];
+        LittleEndian::write_i16_into(samples, &mut buf[..]);
+        self.write_chunk(1 + stream, &buf, (samples.len() / AUDIO_CHANNELS as usize) as u32)
+    }
+
+    pub fn finish(mut self) -> anyhow::Result<W> {
+        self.end_riff()?;
+        let end = self.w.stream_position()?;
+
+        let first_riff_frames = self.first_riff_frames.unwrap_or(0);
+        patch_u32(&mut self.w, self.total_frames_pos, first_riff_frames)?;
+        patch_u32(&mut self.w, self.dmlh_total_frames_pos, self.streams[0].length)?;
+
+        for stream in self.streams.iter() {
+            patch_u32(&mut self.w, stream.length_pos, stream.length)?;
+
+            self.w.seek(std::io::SeekFrom::Start(stream.super_index_pos + 12))?;
+            self.w.write_u32::<LittleEndian>(stream.super_index.len() as u32)?; // nEntriesInUse
+            self.w.seek(std::io::SeekFrom::Start(stream.super_index_pos + 32))?;
+            for entry in stream.super_index.iter() {
+                self.w.write_u64::<LittleEndian>(entry.offset)?;
+                self.w.write_u32::<LittleEndian>(entry.size)?;
+                self.w.write_u32::<LittleEndian>(entry.duration)?;
+            }
+        }
+
+        self.w.seek(std::io::SeekFrom::Start(end))?;
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
@@ -0,0 +1,419 @@
+// A persistent index of replay metadata, so the replay list doesn't need to decode the header of every replay each
+// time it is shown. Entries are invalidated by file size and modification time.
+
+use crate::{game, replay, replayer};
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoundResult {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl From<replayer::BattleResult> for RoundResult {
+    fn from(result: replayer::BattleResult) -> Self {
+        match result {
+            replayer::BattleResult::Draw => RoundResult::Draw,
+            replayer::BattleResult::Loss => RoundResult::Loss,
+            replayer::BattleResult::Win => RoundResult::Win,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Patch {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Side {
+    pub nickname: String,
+    pub rom_family: String,
+    pub rom_variant: u8,
+    pub patch: Option<Patch>,
+}
+
+impl Side {
+    fn from_metadata(side: &replay::metadata::Side) -> Option<Self> {
+        let game_info = side.game_info.as_ref()?;
+        Some(Self {
+            nickname: side.nickname.clone(),
+            rom_family: game_info.rom_family.clone(),
+            rom_variant: game_info.rom_variant as u8,
+            patch: game_info.patch.as_ref().map(|patch| Patch {
+                name: patch.name.clone(),
+                version: patch.version.clone(),
+            }),
+        })
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Entry {
+    pub path: std::path::PathBuf,
+    pub size: u64,
+    pub mtime: u64,
+    pub ts: u64,
+    pub link_code: String,
+    pub round: u32,
+    pub match_type: (u8, u8),
+    pub local_side: Side,
+    pub remote_side: Side,
+    pub is_complete: bool,
+    pub result: Option<RoundResult>,
+    // Whether the replay has already been run to find a missing result, so replays without one aren't run every time.
+    #[serde(default)]
+    pub result_checked: bool,
+    // The set this round belongs to, if any. Derived from the set manifests on every refresh.
+    #[serde(skip)]
+    pub set: Option<std::path::PathBuf>,
+}
+
+impl Entry {
+    fn from_metadata(
+        path: &std::path::Path,
+        file_metadata: &std::fs::Metadata,
+        num_inputs: usize,
+        metadata: &replay::Metadata,
+    ) -> Option<Self> {
+        Some(Self {
+            path: path.to_path_buf(),
+            size: file_metadata.len(),
+            mtime: mtime(file_metadata),
+            ts: metadata.ts,
+            link_code: metadata.link_code.clone(),
+            round: metadata.round,
+            match_type: (metadata.match_type as u8, metadata.match_subtype as u8),
+            local_side: Side::from_metadata(metadata.local_side.as_ref()?)?,
+            remote_side: Side::from_metadata(metadata.remote_side.as_ref()?)?,
+            is_complete: num_inputs > 0,
+            result: None,
+            result_checked: false,
+            set: None,
+        })
+    }
+
+    fn is_fresh(&self, file_metadata: &std::fs::Metadata) -> bool {
+        self.size == file_metadata.len() && self.mtime == mtime(file_metadata)
+    }
+}
+
+fn mtime(file_metadata: &std::fs::Metadata) -> u64 {
+    file_metadata
+        .modified()
+        .ok()
+        .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[derive(Default)]
+struct Index {
+    entries: std::collections::HashMap<std::path::PathBuf, Entry>,
+}
+
+// The index is updated both by the GUI and by finished rounds, so all read-modify-write cycles go through this.
+static LOCK: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+// Held while results are being backfilled, so replays aren't run twice if the list is refreshed in the meantime.
+static BACKFILL_LOCK: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+impl Index {
+    fn load(index_path: &std::path::Path) -> Self {
+        let buf = match std::fs::read(index_path) {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Self::default();
+            }
+            Err(e) => {
+                log::error!("failed to read replay index, rebuilding: {:?}", e);
+                return Self::default();
+            }
+        };
+        match serde_json::from_slice::<Vec<Entry>>(&buf) {
+            Ok(entries) => Self {
+                entries: entries.into_iter().map(|entry| (entry.path.clone(), entry)).collect(),
+            },
+            Err(e) => {
+                log::error!("failed to parse replay index, rebuilding: {:?}", e);
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self, index_path: &std::path::Path) -> anyhow::Result<()> {
+        // Write to a temporary file first, so we never leave a half-written index behind.
+        let tmp_path = index_path.with_extension("tmp");
+        std::fs::write(
+            &tmp_path,
+            serde_json::to_vec(&self.entries.values().collect::<Vec<_>>())?,
+        )?;
+        std::fs::rename(&tmp_path, index_path)?;
+        Ok(())
+    }
+}
+
+/// Brings the index up to date with the replays directory and returns all entries, newest first.
+pub fn refresh(index_path: &std::path::Path, replays_path: &std::path::Path) -> Vec<Entry> {
+    let _guard = LOCK.lock();
+    let mut index = Index::load(index_path);
+    let mut entries = std::collections::HashMap::new();
+    let mut sets = std::collections::HashMap::new();
+    let mut dirty = false;
+
+    for entry in walkdir::WalkDir::new(replays_path) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => {
+                continue;
+            }
+        };
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path();
+        let file_metadata = match entry.metadata() {
+            Ok(file_metadata) => file_metadata,
+            Err(_) => {
+                continue;
+            }
+        };
+
+        if path.extension() == Some(std::ffi::OsStr::new(replay::set::EXTENSION)) {
+            match replay::set::Manifest::load(path) {
+                Ok(manifest) => {
+                    for round_path in manifest.round_paths(path) {
+                        sets.insert(round_path, path.to_path_buf());
+                    }
+                }
+                Err(e) => {
+                    log::warn!("failed to load set manifest {}: {:?}", path.display(), e);
+                }
+            }
+            continue;
+        }
+
+        if let Some(index_entry) = index.entries.remove(path) {
+            if index_entry.is_fresh(&file_metadata) {
+                entries.insert(path.to_path_buf(), index_entry);
+                continue;
+            }
+        }
+
+        dirty = true;
+        let mut f = match std::fs::File::open(path) {
+            Ok(f) => f,
+            Err(_) => {
+                continue;
+            }
+        };
+
+        let (num_inputs, metadata) = match replay::read_metadata(&mut f) {
+            Ok((n, metadata)) => (n, metadata),
+            Err(_) => {
+                continue;
+            }
+        };
+
+        if let Some(index_entry) = Entry::from_metadata(path, &file_metadata, num_inputs, &metadata) {
+            entries.insert(path.to_path_buf(), index_entry);
+        }
+    }
+
+    // Anything left over no longer exists on disk.
+    if !index.entries.is_empty() {
+        dirty = true;
+    }
+    index.entries = entries;
+
+    if dirty {
+        if let Err(e) = index.save(index_path) {
+            log::error!("failed to save replay index: {:?}", e);
+        }
+    }
+
+    let mut entries = index
+        .entries
+        .into_values()
+        .map(|mut entry| {
+            entry.set = sets.get(&entry.path).cloned();
+            entry
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| (std::cmp::Reverse(entry.ts), entry.link_code.clone(), entry.round));
+    entries
+}
+
+/// Adds a replay that was just finished to the index, along with its result.
+pub fn record(
+    index_path: &std::path::Path,
+    path: &std::path::Path,
+    metadata: &replay::Metadata,
+    result: RoundResult,
+) -> anyhow::Result<()> {
+    let _guard = LOCK.lock();
+    let mut index = Index::load(index_path);
+
+    let file_metadata = std::fs::metadata(path)?;
+    let mut f = std::fs::File::open(path)?;
+    let (num_inputs, _) = replay::read_metadata(&mut f)?;
+
+    let mut entry = Entry::from_metadata(path, &file_metadata, num_inputs, metadata)
+        .ok_or_else(|| anyhow::anyhow!("incomplete metadata"))?;
+    entry.result = Some(result);
+    entry.result_checked = true;
+    index.entries.insert(path.to_path_buf(), entry);
+    index.save(index_path)
+}
+
+// Runs a replay until the round ends to find out who won.
+fn read_result(
+    game: &'static (dyn game::Game + Send + Sync),
+    rom: &[u8],
+    replay: &replay::Replay,
+) -> anyhow::Result<Option<RoundResult>> {
+    let (replayer_state, error) = replay.simulate_round(game.hooks(), rom)?;
+    if let Some(err) = error {
+        return Err(err);
+    }
+
+    let round_result = replayer_state.lock_inner().round_result();
+    Ok(round_result.map(|rr| rr.result.into()))
+}
+
+/// Runs every complete replay in the index that doesn't have a result yet to find it. Replays whose ROM can't be loaded
+/// are left for later. Returns whether any entry was updated.
+pub fn backfill_results(
+    index_path: &std::path::Path,
+    load_rom: impl Fn(&replay::metadata::GameInfo) -> Option<(&'static (dyn game::Game + Send + Sync), Vec<u8>)>,
+) -> bool {
+    let _backfill_guard = if let Some(guard) = BACKFILL_LOCK.try_lock() {
+        guard
+    } else {
+        return false;
+    };
+
+    let paths = {
+        let _guard = LOCK.lock();
+        Index::load(index_path)
+            .entries
+            .into_values()
+            .filter(|entry| entry.is_complete && entry.result.is_none() && !entry.result_checked)
+            .map(|entry| entry.path)
+            .collect::<Vec<_>>()
+    };
+
+    // Replays are run without holding the lock, as this may take a while.
+    let mut results = vec![];
+    for path in paths {
+        let replay = match std::fs::File::open(&path).and_then(replay::Replay::decode) {
+            Ok(replay) => replay,
+            Err(e) => {
+                log::warn!("failed to read replay {}: {:?}", path.display(), e);
+                continue;
+            }
+        };
+
+        let (game, rom) = if let Some(rom) = replay
+            .metadata
+            .local_side
+            .as_ref()
+            .and_then(|side| side.game_info.as_ref())
+            .and_then(&load_rom)
+        {
+            rom
+        } else {
+            continue;
+        };
+
+        let result = match read_result(game, &rom, &replay) {
+            Ok(result) => result,
+            Err(e) => {
+                log::warn!("failed to read result of {}: {:?}", path.display(), e);
+                None
+            }
+        };
+        results.push((path, result));
+    }
+
+    if results.is_empty() {
+        return false;
+    }
+
+    let _guard = LOCK.lock();
+    let mut index = Index::load(index_path);
+    for (path, result) in results {
+        if let Some(entry) = index.entries.get_mut(&path) {
+            entry.result = result;
+            entry.result_checked = true;
+        }
+    }
+    if let Err(e) = index.save(index_path) {
+        log::error!("failed to save replay index: {:?}", e);
+    }
+    true
+}
+
+#[derive(Default, Clone)]
+pub struct Query {
+    pub opponent: String,
+    pub rom_family: Option<String>,
+    pub patch: Option<Patch>,
+    pub result: Option<RoundResult>,
+    pub since: Option<chrono::NaiveDate>,
+    pub until: Option<chrono::NaiveDate>,
+}
+
+impl Query {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if !self.opponent.is_empty()
+            && !entry
+                .remote_side
+                .nickname
+                .to_lowercase()
+                .contains(&self.opponent.to_lowercase())
+        {
+            return false;
+        }
+
+        if let Some(rom_family) = self.rom_family.as_ref() {
+            if &entry.local_side.rom_family != rom_family {
+                return false;
+            }
+        }
+
+        if let Some(patch) = self.patch.as_ref() {
+            if entry.local_side.patch.as_ref() != Some(patch) {
+                return false;
+            }
+        }
+
+        if let Some(result) = self.result {
+            if entry.result != Some(result) {
+                return false;
+            }
+        }
+
+        let date =
+            chrono::DateTime::<chrono::Local>::from(std::time::UNIX_EPOCH + std::time::Duration::from_millis(entry.ts))
+                .naive_local()
+                .date();
+
+        if let Some(since) = self.since {
+            if date < since {
+                return false;
+            }
+        }
+
+        if let Some(until) = self.until {
+            if date > until {
+                return false;
+            }
+        }
+
+        true
+    }
+}
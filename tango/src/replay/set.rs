@@ -0,0 +1,135 @@
+// A match set groups all the rounds played in a single match. The rounds themselves are still separate replay files: the
+// set is a manifest stored next to them that lists them in order along with their results.
+
+use crate::replay;
+
+pub const EXTENSION: &str = "tangoset";
+
+const VERSION: u32 = 1;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Round {
+    pub number: u32,
+    // Relative to the directory the manifest is in.
+    pub filename: std::path::PathBuf,
+    pub result: Option<replay::index::RoundResult>,
+    // Why the replay couldn't be submitted to the replay collector, if it couldn't.
+    #[serde(default)]
+    pub upload_error: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Manifest {
+    pub version: u32,
+    pub ts: u64,
+    pub link_code: String,
+    pub local_nickname: String,
+    pub remote_nickname: String,
+    pub rounds: Vec<Round>,
+    // Rounds won by the local and remote sides respectively.
+    pub score: (u32, u32),
+    // Why the manifest couldn't be submitted to the replay collector, if it couldn't.
+    #[serde(default)]
+    pub upload_error: Option<String>,
+}
+
+impl Manifest {
+    pub fn new(ts: u64, link_code: String, local_nickname: String, remote_nickname: String) -> Self {
+        Self {
+            version: VERSION,
+            ts,
+            link_code,
+            local_nickname,
+            remote_nickname,
+            rounds: vec![],
+            score: (0, 0),
+            upload_error: None,
+        }
+    }
+
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        let manifest = serde_json::from_slice::<Self>(&std::fs::read(path)?)?;
+        if manifest.version != VERSION {
+            anyhow::bail!("unsupported set version: {}", manifest.version);
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Returns the paths of all the rounds' replays, in order.
+    pub fn round_paths(&self, manifest_path: &std::path::Path) -> Vec<std::path::PathBuf> {
+        let dir = manifest_path.parent().unwrap_or(std::path::Path::new(""));
+        self.rounds.iter().map(|round| dir.join(&round.filename)).collect()
+    }
+}
+
+/// Keeps a match's manifest up to date on disk as rounds finish.
+pub struct Writer {
+    path: std::path::PathBuf,
+    manifest: Manifest,
+}
+
+impl Writer {
+    pub fn new(path: std::path::PathBuf, manifest: Manifest) -> Self {
+        Self { path, manifest }
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub fn add_round(&mut self, number: u32, replay_path: &std::path::Path) -> anyhow::Result<()> {
+        let filename = replay_path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("replay path has no filename"))?;
+        self.manifest.rounds.push(Round {
+            number,
+            filename: filename.into(),
+            result: None,
+            upload_error: None,
+        });
+        self.manifest.save(&self.path)
+    }
+
+    pub fn set_round_result(&mut self, number: u32, result: replay::index::RoundResult) -> anyhow::Result<()> {
+        let round = self
+            .manifest
+            .rounds
+            .iter_mut()
+            .find(|round| round.number == number)
+            .ok_or_else(|| anyhow::anyhow!("no such round: {}", number))?;
+        round.result = Some(result);
+        match result {
+            replay::index::RoundResult::Win => self.manifest.score.0 += 1,
+            replay::index::RoundResult::Loss => self.manifest.score.1 += 1,
+            replay::index::RoundResult::Draw => {}
+        }
+        self.manifest.save(&self.path)
+    }
+
+    pub fn set_round_upload_error(&mut self, number: u32, error: String) -> anyhow::Result<()> {
+        let round = self
+            .manifest
+            .rounds
+            .iter_mut()
+            .find(|round| round.number == number)
+            .ok_or_else(|| anyhow::anyhow!("no such round: {}", number))?;
+        round.upload_error = Some(error);
+        self.manifest.save(&self.path)
+    }
+
+    pub fn set_upload_error(&mut self, error: String) -> anyhow::Result<()> {
+        self.manifest.upload_error = Some(error);
+        self.manifest.save(&self.path)
+    }
+}
@@ -8,9 +8,12 @@ pub struct InnerState {
     local_player_index: u8,
     input_pairs: std::collections::VecDeque<lockstep::Pair<lockstep::PartialInput, lockstep::PartialInput>>,
     output_pairs: Vec<lockstep::Pair<lockstep::Input, lockstep::Input>>,
-    apply_shadow_input: Box<
-        dyn FnMut(lockstep::Pair<lockstep::Input, lockstep::PartialInput>) -> anyhow::Result<Vec<u8>> + Sync + Send,
+    apply_shadow_input: Option<
+        Box<
+            dyn FnMut(lockstep::Pair<lockstep::Input, lockstep::PartialInput>) -> anyhow::Result<Vec<u8>> + Sync + Send,
+        >,
     >,
+    committed_remote_packets: std::collections::VecDeque<Vec<u8>>,
     match_type: (u8, u8),
     local_packet: Option<lockstep::Packet>,
     commit_tick: u32,
@@ -101,7 +104,13 @@ impl InnerState {
         &mut self,
         input: lockstep::Pair<lockstep::Input, lockstep::PartialInput>,
     ) -> anyhow::Result<Vec<u8>> {
-        let remote_packet = (self.apply_shadow_input)(input.clone())?;
+        let remote_packet = if let Some(apply_shadow_input) = self.apply_shadow_input.as_mut() {
+            apply_shadow_input(input.clone())?
+        } else if let Some(remote_packet) = self.committed_remote_packets.pop_front() {
+            remote_packet
+        } else {
+            anyhow::bail!("no more committed inputs");
+        };
         self.output_pairs.push(lockstep::Pair {
             local: input.local,
             remote: input.remote.with_packet(remote_packet.clone()),
@@ -109,6 +118,28 @@ impl InnerState {
         Ok(remote_packet)
     }
 
+    pub fn add_input_pair(&mut self, ip: lockstep::Pair<lockstep::Input, lockstep::Input>) {
+        if self.local_packet.is_none() {
+            self.local_packet = Some(lockstep::Packet {
+                tick: ip.local.local_tick,
+                packet: ip.local.packet.clone(),
+            });
+        }
+        self.input_pairs.push_back(lockstep::Pair {
+            local: lockstep::PartialInput {
+                local_tick: ip.local.local_tick,
+                remote_tick: ip.local.remote_tick,
+                joyflags: ip.local.joyflags,
+            },
+            remote: lockstep::PartialInput {
+                local_tick: ip.remote.local_tick,
+                remote_tick: ip.remote.remote_tick,
+                joyflags: ip.remote.joyflags,
+            },
+        });
+        self.committed_remote_packets.push_back(ip.remote.packet);
+    }
+
     pub fn set_local_packet(&mut self, tick: u32, packet: Vec<u8>) {
         self.local_packet = Some(lockstep::Packet { tick, packet });
     }
@@ -204,51 +235,25 @@ pub struct Fastforwarder {
 #[derive(Clone)]
 pub struct State(std::sync::Arc<parking_lot::Mutex<Option<InnerState>>>);
 
-impl State {
-    pub fn new(
+impl InnerState {
+    fn new(
         match_type: (u8, u8),
         local_player_index: u8,
         input_pairs: Vec<lockstep::Pair<lockstep::Input, lockstep::Input>>,
+        current_tick: u32,
         commit_tick: u32,
         on_round_ended: Box<dyn FnOnce() + Send>,
-    ) -> State {
-        let local_packet = input_pairs.first().map(|ip| lockstep::Packet {
-            tick: ip.local.local_tick,
-            packet: ip.local.packet.clone(),
-        });
-        State(std::sync::Arc::new(parking_lot::Mutex::new(Some(InnerState {
+    ) -> Self {
+        let mut inner = InnerState {
             disable_bgm: false,
-            current_tick: 0,
+            current_tick,
             local_player_index,
-            input_pairs: input_pairs
-                .iter()
-                .map(|ip| lockstep::Pair {
-                    local: lockstep::PartialInput {
-                        local_tick: ip.local.local_tick,
-                        remote_tick: ip.local.remote_tick,
-                        joyflags: ip.local.joyflags,
-                    },
-                    remote: lockstep::PartialInput {
-                        local_tick: ip.remote.local_tick,
-                        remote_tick: ip.remote.remote_tick,
-                        joyflags: ip.remote.joyflags,
-                    },
-                })
-                .collect(),
-            apply_shadow_input: Box::new({
-                let mut iq = input_pairs.into_iter().collect::<std::collections::VecDeque<_>>();
-                move |_| {
-                    let ip = if let Some(ip) = iq.pop_front() {
-                        ip
-                    } else {
-                        anyhow::bail!("no more committed inputs");
-                    };
-                    Ok(ip.remote.packet)
-                }
-            }),
+            input_pairs: std::collections::VecDeque::new(),
+            apply_shadow_input: None,
+            committed_remote_packets: std::collections::VecDeque::new(),
             match_type,
             output_pairs: vec![],
-            local_packet,
+            local_packet: None,
             commit_tick,
             committed_state: None,
             dirty_tick: 0,
@@ -257,7 +262,56 @@ impl State {
             phase: RoundPhase::InProgress,
             error: None,
             on_round_ended: Some(on_round_ended),
-        }))))
+        };
+        for ip in input_pairs {
+            inner.add_input_pair(ip);
+        }
+        inner
+    }
+}
+
+impl State {
+    pub fn new(
+        match_type: (u8, u8),
+        local_player_index: u8,
+        input_pairs: Vec<lockstep::Pair<lockstep::Input, lockstep::Input>>,
+        commit_tick: u32,
+        on_round_ended: Box<dyn FnOnce() + Send>,
+    ) -> State {
+        State(std::sync::Arc::new(parking_lot::Mutex::new(Some(InnerState::new(
+            match_type,
+            local_player_index,
+            input_pairs,
+            0,
+            commit_tick,
+            on_round_ended,
+        )))))
+    }
+
+    // Traps holding a clone of this state will see the new inner state, so this can be used to start a new round on the same core.
+    //
+    // The disable BGM setting is carried over.
+    pub fn reset(
+        &self,
+        match_type: (u8, u8),
+        local_player_index: u8,
+        input_pairs: Vec<lockstep::Pair<lockstep::Input, lockstep::Input>>,
+        current_tick: u32,
+        commit_tick: u32,
+        on_round_ended: Box<dyn FnOnce() + Send>,
+    ) {
+        let mut inner = self.0.lock();
+        let disable_bgm = inner.as_ref().map(|inner| inner.disable_bgm).unwrap_or(false);
+        let mut new_inner = InnerState::new(
+            match_type,
+            local_player_index,
+            input_pairs,
+            current_tick,
+            commit_tick,
+            on_round_ended,
+        );
+        new_inner.disable_bgm = disable_bgm;
+        *inner = Some(new_inner);
     }
 
     pub fn lock_inner(&self) -> parking_lot::MappedMutexGuard<'_, InnerState> {
@@ -314,7 +368,8 @@ impl Fastforwarder {
             local_player_index: self.local_player_index,
             input_pairs: input_pairs.into_iter().collect(),
             output_pairs: vec![],
-            apply_shadow_input,
+            apply_shadow_input: Some(apply_shadow_input),
+            committed_remote_packets: std::collections::VecDeque::new(),
             match_type: self.match_type,
             local_packet: Some(lockstep::Packet {
                 tick: current_tick,
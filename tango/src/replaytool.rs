@@ -1,16 +1,52 @@
 use std::io::Write;
 
-use crate::{config, replay};
+use crate::{config, game, patch, replay, replayer, save, sheet};
 
 #[derive(clap::Subcommand)]
 pub enum Command {
-    Invert { output_path: std::path::PathBuf },
+    Invert {
+        output_path: std::path::PathBuf,
+    },
     Metadata,
     Wram,
     Text,
+    Verify,
+    Keyframes,
+    Setup {
+        #[arg(long)]
+        text: bool,
+        #[arg(long)]
+        remote: bool,
+    },
+    ApplySetup {
+        save_path: std::path::PathBuf,
+        output_path: std::path::PathBuf,
+        #[arg(long)]
+        remote: bool,
+        /// Apply a setup from a JSON or text file instead of from the replay. The replay path may then be left out.
+        #[arg(long)]
+        setup_path: Option<std::path::PathBuf>,
+    },
+    Sheet {
+        output_path: std::path::PathBuf,
+        #[arg(long)]
+        remote: bool,
+    },
+    Diff,
 }
 
-pub fn main(config: config::Config, path: std::path::PathBuf, command: Command) -> Result<(), anyhow::Error> {
+pub fn main(config: config::Config, path: Option<std::path::PathBuf>, command: Command) -> Result<(), anyhow::Error> {
+    if let Command::ApplySetup {
+        save_path,
+        output_path,
+        setup_path: Some(setup_path),
+        ..
+    } = command
+    {
+        return cmd_apply_setup_from_file(save_path, output_path, setup_path);
+    }
+
+    let path = path.ok_or_else(|| anyhow::anyhow!("a replay path is required"))?;
     let mut f = std::fs::File::open(&path)?;
     let replay = replay::Replay::decode(&mut f)?;
 
@@ -19,15 +55,43 @@ pub fn main(config: config::Config, path: std::path::PathBuf, command: Command)
         Command::Metadata => cmd_metadata(config, replay),
         Command::Wram => cmd_wram(config, replay),
         Command::Text => cmd_text(config, replay),
+        Command::Verify => cmd_verify(config, replay),
+        Command::Keyframes => cmd_keyframes(config, replay, &mut f),
+        Command::Setup { text, remote } => cmd_setup(config, replay, text, remote),
+        Command::ApplySetup {
+            save_path,
+            output_path,
+            remote,
+            ..
+        } => cmd_apply_setup(config, replay, save_path, output_path, remote),
+        Command::Sheet { output_path, remote } => cmd_sheet(config, replay, output_path, remote),
+        Command::Diff => cmd_diff(config, replay),
     }
 }
 
 fn cmd_invert(
-    _config: config::Config,
+    config: config::Config,
     replay: replay::Replay,
     output_path: std::path::PathBuf,
 ) -> Result<(), anyhow::Error> {
-    let replay = replay.into_remote();
+    let mut replay = replay.into_remote();
+
+    let game_info = replay
+        .metadata
+        .local_side
+        .as_ref()
+        .and_then(|side| side.game_info.as_ref())
+        .ok_or(anyhow::anyhow!("missing game info"))?;
+    let game = find_game(game_info)?;
+    let rom = load_rom(&config, game, game_info)?;
+    let mut fastforwarder = replayer::Fastforwarder::new(
+        &rom,
+        game.hooks(),
+        (replay.metadata.match_type as u8, replay.metadata.match_subtype as u8),
+        replay.local_player_index,
+    )?;
+    replay.rebuild_keyframes(&mut fastforwarder)?;
+
     let mut writer = replay::Writer::new(
         Box::new(std::fs::File::create(&output_path)?),
         replay.metadata,
@@ -36,7 +100,11 @@ fn cmd_invert(
     )?;
     writer.write_state(&replay.local_state)?;
     writer.write_state(&replay.remote_state)?;
+    let mut keyframes = replay.keyframes.iter().peekable();
     for ip in replay.input_pairs {
+        if let Some(keyframe) = keyframes.next_if(|keyframe| keyframe.tick <= ip.local.local_tick) {
+            writer.write_keyframe(keyframe.tick, &keyframe.state)?;
+        }
         writer.write_input(replay.local_player_index, &ip)?;
     }
     writer.finish()?;
@@ -53,6 +121,22 @@ fn cmd_text(_config: config::Config, replay: replay::Replay) -> Result<(), anyho
     Ok(())
 }
 
+fn cmd_keyframes(_config: config::Config, replay: replay::Replay, f: &mut std::fs::File) -> Result<(), anyhow::Error> {
+    let index = replay::read_keyframe_index(f).unwrap_or_else(|_| vec![]);
+    for keyframe in &replay.keyframes {
+        println!(
+            "tick = {:08x?}, offset = {:?}, size = {}",
+            keyframe.tick,
+            index
+                .iter()
+                .find(|(tick, _)| *tick == keyframe.tick)
+                .map(|(_, offset)| *offset),
+            keyframe.state.as_slice().len()
+        );
+    }
+    Ok(())
+}
+
 fn cmd_metadata(_config: config::Config, replay: replay::Replay) -> Result<(), anyhow::Error> {
     let mut stdout = std::io::stdout().lock();
     serde_json::to_writer_pretty(&mut stdout, &replay.metadata)?;
@@ -65,3 +149,220 @@ fn cmd_wram(_config: config::Config, replay: replay::Replay) -> Result<(), anyho
     stdout.write_all(replay.local_state.wram())?;
     Ok(())
 }
+
+fn find_game(game_info: &replay::metadata::GameInfo) -> Result<&'static (dyn game::Game + Send + Sync), anyhow::Error> {
+    game::find_by_family_and_variant(&game_info.rom_family, game_info.rom_variant as u8)
+        .ok_or(anyhow::anyhow!("game not found"))
+}
+
+fn load_rom(
+    config: &config::Config,
+    game: &'static (dyn game::Game + Send + Sync),
+    game_info: &replay::metadata::GameInfo,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let roms = game::scan_roms(&config.roms_path());
+    let mut rom = roms
+        .get(&game)
+        .ok_or_else(|| anyhow::anyhow!("no rom found for {:?}", game.family_and_variant()))?
+        .clone();
+    if let Some(patch_info) = game_info.patch.as_ref() {
+        rom = patch::apply_patch_from_disk(
+            &rom,
+            game,
+            &config.patches_path(),
+            &patch_info.name,
+            &semver::Version::parse(&patch_info.version)?,
+        )?;
+    }
+    Ok(rom)
+}
+
+// Reads the setup one side of the replay started the round with.
+fn replay_setup(
+    replay: &replay::Replay,
+    remote: bool,
+) -> Result<
+    (
+        &'static (dyn game::Game + Send + Sync),
+        &replay::metadata::GameInfo,
+        Box<dyn save::Save + Send + Sync>,
+    ),
+    anyhow::Error,
+> {
+    let (side, state) = if remote {
+        (replay.metadata.remote_side.as_ref(), &replay.remote_state)
+    } else {
+        (replay.metadata.local_side.as_ref(), &replay.local_state)
+    };
+    let game_info = side
+        .ok_or(anyhow::anyhow!("missing side"))?
+        .game_info
+        .as_ref()
+        .ok_or(anyhow::anyhow!("missing game info"))?;
+    let game = find_game(game_info)?;
+    let save = game.save_from_wram(state.wram())?;
+    Ok((game, game_info, save))
+}
+
+fn cmd_setup(config: config::Config, replay: replay::Replay, text: bool, remote: bool) -> Result<(), anyhow::Error> {
+    let (game, game_info, save) = replay_setup(&replay, remote)?;
+    let setup = save::setup::Setup::from_save(game.family_and_variant().0, save.as_ref());
+
+    let mut stdout = std::io::stdout().lock();
+    if text {
+        // Names are nice to have but not required, so don't fail if the ROM isn't around.
+        let assets = load_rom(&config, game, game_info)
+            .and_then(|rom| game.load_rom_assets(&rom, save.as_raw_wram(), &Default::default()))
+            .map_err(|e| {
+                log::warn!("not including names, could not load rom assets: {:?}", e);
+            })
+            .ok();
+        stdout.write_all(
+            setup
+                .to_text(assets.as_ref().map(|assets| assets.as_ref() as _))
+                .as_bytes(),
+        )?;
+    } else {
+        stdout.write_all(setup.to_json()?.as_bytes())?;
+        stdout.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn cmd_apply_setup(
+    _config: config::Config,
+    replay: replay::Replay,
+    save_path: std::path::PathBuf,
+    output_path: std::path::PathBuf,
+    remote: bool,
+) -> Result<(), anyhow::Error> {
+    let (game, _, replay_save) = replay_setup(&replay, remote)?;
+    let family = game.family_and_variant().0;
+    let setup = save::setup::Setup::from_save(family, replay_save.as_ref());
+
+    let mut save = game.parse_save(&std::fs::read(&save_path)?)?;
+    setup.apply(family, save.as_mut())?;
+    std::fs::write(&output_path, save.to_vec())?;
+    Ok(())
+}
+
+fn cmd_apply_setup_from_file(
+    save_path: std::path::PathBuf,
+    output_path: std::path::PathBuf,
+    setup_path: std::path::PathBuf,
+) -> Result<(), anyhow::Error> {
+    let setup = save::setup::Setup::parse(&std::fs::read_to_string(&setup_path)?)?;
+
+    // Without a replay, the save itself says which game it's for.
+    let buf = std::fs::read(&save_path)?;
+    let mut save = game::GAMES
+        .iter()
+        .filter(|game| game.family_and_variant().0 == setup.game)
+        .find_map(|game| game.parse_save(&buf).ok())
+        .ok_or_else(|| anyhow::anyhow!("not a save for {}", setup.game))?;
+    setup.apply(&setup.game, save.as_mut())?;
+    std::fs::write(&output_path, save.to_vec())?;
+    Ok(())
+}
+
+fn cmd_sheet(
+    config: config::Config,
+    replay: replay::Replay,
+    output_path: std::path::PathBuf,
+    remote: bool,
+) -> Result<(), anyhow::Error> {
+    let (game, game_info, save) = replay_setup(&replay, remote)?;
+    let rom = load_rom(&config, game, game_info)?;
+    let assets = game.load_rom_assets(&rom, save.as_raw_wram(), &Default::default())?;
+    sheet::render(&config.language, &game.language(), &save, &assets).save(&output_path)?;
+    Ok(())
+}
+
+// Compares the setups of both sides of a replay.
+fn cmd_diff(config: config::Config, replay: replay::Replay) -> Result<(), anyhow::Error> {
+    let (local_game, local_game_info, local_save) = replay_setup(&replay, false)?;
+    let (remote_game, _, remote_save) = replay_setup(&replay, true)?;
+    if local_game.family_and_variant().0 != remote_game.family_and_variant().0 {
+        anyhow::bail!("cannot compare setups from different games");
+    }
+
+    let diff = save::diff::diff(local_save.as_ref(), remote_save.as_ref());
+
+    let assets = load_rom(&config, local_game, local_game_info)
+        .and_then(|rom| local_game.load_rom_assets(&rom, local_save.as_raw_wram(), &Default::default()))
+        .map_err(|e| {
+            log::warn!("not including names, could not load rom assets: {:?}", e);
+        })
+        .ok();
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(
+        diff.to_text(assets.as_ref().map(|assets| assets.as_ref() as _))
+            .as_bytes(),
+    )?;
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct VerifyReport {
+    is_complete: bool,
+    round_ended: bool,
+    result: Option<replayer::BattleResult>,
+    winner: Option<String>,
+    end_tick: Option<u32>,
+    input_pairs_total: usize,
+    input_pairs_left: usize,
+    all_inputs_used: bool,
+    error: Option<String>,
+}
+
+fn cmd_verify(config: config::Config, replay: replay::Replay) -> Result<(), anyhow::Error> {
+    let local_side = replay
+        .metadata
+        .local_side
+        .as_ref()
+        .ok_or(anyhow::anyhow!("missing local side"))?;
+    let remote_side = replay
+        .metadata
+        .remote_side
+        .as_ref()
+        .ok_or(anyhow::anyhow!("missing remote side"))?;
+    let game_info = local_side
+        .game_info
+        .as_ref()
+        .ok_or(anyhow::anyhow!("missing game info"))?;
+    let game = find_game(game_info)?;
+    let rom = load_rom(&config, game, game_info)?;
+
+    let input_pairs_total = replay.input_pairs.len();
+    let (replayer_state, error) = replay.simulate_round(game.hooks(), &rom)?;
+
+    let state = replayer_state.lock_inner();
+    let round_result = state.round_result();
+    let input_pairs_left = state.input_pairs_left();
+    let report = VerifyReport {
+        is_complete: replay.is_complete,
+        round_ended: state.is_round_ended(),
+        result: round_result.map(|rr| rr.result),
+        winner: round_result.and_then(|rr| match rr.result {
+            replayer::BattleResult::Win => Some(local_side.nickname.clone()),
+            replayer::BattleResult::Loss => Some(remote_side.nickname.clone()),
+            replayer::BattleResult::Draw => None,
+        }),
+        end_tick: round_result.map(|rr| rr.tick),
+        input_pairs_total,
+        input_pairs_left,
+        all_inputs_used: input_pairs_left == 0,
+        error: error.map(|e| format!("{:?}", e)),
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &report)?;
+    stdout.write_all(b"\n")?;
+
+    if !report.is_complete || !report.round_ended || !report.all_inputs_used || report.error.is_some() {
+        anyhow::bail!("replay failed verification");
+    }
+
+    Ok(())
+}
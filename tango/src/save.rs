@@ -1,3 +1,9 @@
+pub mod convert;
+pub mod diff;
+pub mod history;
+pub mod legality;
+pub mod setup;
+
 use byteorder::ByteOrder;
 
 use crate::{game, scanner};
@@ -99,6 +105,11 @@ pub enum ModcardsView<'a> {
     Modcard56s(Box<dyn Modcard56sView<'a> + 'a>),
 }
 
+pub enum ModcardsViewMut<'a> {
+    Modcard4s(Box<dyn Modcard4sViewMut<'a> + 'a>),
+    Modcard56s(Box<dyn Modcard56sViewMut<'a> + 'a>),
+}
+
 pub trait Save
 where
     Self: SaveClone,
@@ -125,6 +136,20 @@ where
     fn view_navi(&self) -> Option<Box<dyn NaviView + '_>> {
         None
     }
+
+    // Mutable views keep the checksum up to date after every change, so the result of to_vec can be loaded by the
+    // game as-is.
+    fn view_chips_mut(&mut self) -> Option<Box<dyn ChipsViewMut + '_>> {
+        None
+    }
+
+    fn view_modcards_mut(&mut self) -> Option<ModcardsViewMut> {
+        None
+    }
+
+    fn view_navicust_mut(&mut self) -> Option<Box<dyn NavicustViewMut + '_>> {
+        None
+    }
 }
 
 impl Clone for Box<dyn Save + Send + Sync> {
@@ -149,7 +174,14 @@ pub fn compute_save_raw_checksum(buf: &[u8], checksum_offset: usize) -> u32 {
             .sum::<u32>()
 }
 
-#[derive(Clone, Debug, std::hash::Hash, Eq, PartialEq)]
+const CHIP_CODES: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ*";
+
+/// Returns the index of a chip code as stored in saves.
+pub fn chip_code_index(code: char) -> Option<usize> {
+    CHIP_CODES.iter().position(|c| *c as char == code)
+}
+
+#[derive(Clone, Debug, std::hash::Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Chip {
     pub id: usize,
     pub code: char,
@@ -165,9 +197,30 @@ pub trait ChipsView<'a> {
     fn regular_chip_index(&self, folder_index: usize) -> Option<usize>;
     fn tag_chip_indexes(&self, folder_index: usize) -> Option<[usize; 2]>;
     fn chip(&self, folder_index: usize, chip_index: usize) -> Option<Chip>;
+
+    /// Returns a folder's chips in folder order: games that keep the regular chip first have it moved back into place.
+    fn folder_chips(&self, folder_index: usize) -> Vec<Option<Chip>> {
+        let mut chips = (0..30).map(|i| self.chip(folder_index, i)).collect::<Vec<_>>();
+        if !self.regular_chip_is_in_place() {
+            if let Some(regular_chip_index) = self.regular_chip_index(folder_index) {
+                if regular_chip_index < chips.len() {
+                    let chip = chips.remove(0);
+                    chips.insert(regular_chip_index, chip);
+                }
+            }
+        }
+        chips
+    }
+}
+
+pub trait ChipsViewMut<'a> {
+    fn set_equipped_folder(&mut self, folder_index: usize) -> anyhow::Result<()>;
+    fn set_chip(&mut self, folder_index: usize, chip_index: usize, chip: &Chip) -> anyhow::Result<()>;
+    fn set_regular_chip_index(&mut self, folder_index: usize, chip_index: Option<usize>) -> anyhow::Result<()>;
+    fn set_tag_chip_indexes(&mut self, folder_index: usize, chip_indexes: Option<[usize; 2]>) -> anyhow::Result<()>;
 }
 
-#[derive(Clone, Debug, std::hash::Hash, Eq, PartialEq)]
+#[derive(Clone, Debug, std::hash::Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Modcard {
     pub id: usize,
     pub enabled: bool,
@@ -182,11 +235,20 @@ pub trait Modcard4sView<'a> {
     fn modcard(&self, slot: usize) -> Option<Modcard>;
 }
 
+pub trait Modcard56sViewMut<'a> {
+    fn set_modcard(&mut self, slot: usize, modcard: &Modcard) -> anyhow::Result<()>;
+    fn set_modcards(&mut self, modcards: &[Modcard]) -> anyhow::Result<()>;
+}
+
+pub trait Modcard4sViewMut<'a> {
+    fn set_modcard(&mut self, slot: usize, modcard: Option<&Modcard>) -> anyhow::Result<()>;
+}
+
 pub trait NaviView<'a> {
     fn navi(&self) -> usize;
 }
 
-#[derive(Clone, Debug, std::hash::Hash, Eq, PartialEq)]
+#[derive(Clone, Debug, std::hash::Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct NavicustPart {
     pub id: usize,
     pub variant: usize,
@@ -210,6 +272,10 @@ pub trait NavicustView<'a> {
     fn navicust_part(&self, i: usize) -> Option<NavicustPart>;
 }
 
+pub trait NavicustViewMut<'a> {
+    fn set_navicust_part(&mut self, i: usize, part: Option<&NavicustPart>) -> anyhow::Result<()>;
+}
+
 pub trait DarkAIView<'a> {
     fn chip_use_count(&self, id: usize) -> Option<u16>;
     fn secondary_chip_use_count(&self, id: usize) -> Option<u16>;
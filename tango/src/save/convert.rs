@@ -0,0 +1,287 @@
+// Conversion of saves between the JP (EXE) and US (BN) releases of the same game.
+//
+// Chip, navicust part and modcard IDs are shared between both releases of a game, so they carry over as they are. What
+// differs is how the save is laid out:
+//
+// - Where both releases lay out saves the same way, the whole save is converted by rewriting the game name and
+//   checksum (see game::Game::save_from_other_region).
+// - Otherwise, only the setup (folder, navicust and modcards) can be carried over, into an existing save of the target
+//   release. Everything else, e.g. story progress and the library, stays as it is in that save.
+
+use fluent_templates::Loader;
+
+use crate::{game, i18n, rom, save};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Unconvertible {
+    // Only the setup was carried over into the target save.
+    Progress,
+    // The target release has no modcards, so this many modcards were dropped.
+    Modcards(usize),
+    Chip(usize),
+    NavicustPart(usize, usize),
+    Modcard(usize),
+    // The target save couldn't take this modcard, so it was dropped.
+    DroppedModcard(usize),
+    // This modcard was installed in the target save but not in the converted one, so it was removed.
+    RemovedModcard(usize),
+    // The target game's assets weren't available, so content missing from it wasn't checked for.
+    MissingContentUnchecked,
+}
+
+impl Unconvertible {
+    pub fn to_text(&self, lang: &unic_langid::LanguageIdentifier, assets: Option<&dyn rom::Assets>) -> String {
+        match self {
+            Unconvertible::Progress => i18n::LOCALES
+                .lookup(lang, "save-convert-unconvertible-progress")
+                .unwrap(),
+            Unconvertible::Modcards(n) => i18n::LOCALES
+                .lookup_with_args(
+                    lang,
+                    "save-convert-unconvertible-modcards",
+                    &std::collections::HashMap::from([("count", (*n).into())]),
+                )
+                .unwrap(),
+            Unconvertible::Chip(id) => i18n::LOCALES
+                .lookup_with_args(
+                    lang,
+                    "save-convert-unconvertible-chip",
+                    &std::collections::HashMap::from([(
+                        "chip",
+                        assets
+                            .and_then(|assets| assets.chip(*id))
+                            .map(|info| info.name())
+                            .unwrap_or_else(|| format!("#{}", id))
+                            .into(),
+                    )]),
+                )
+                .unwrap(),
+            Unconvertible::NavicustPart(id, variant) => i18n::LOCALES
+                .lookup_with_args(
+                    lang,
+                    "save-convert-unconvertible-navicust-part",
+                    &std::collections::HashMap::from([(
+                        "part",
+                        assets
+                            .and_then(|assets| assets.navicust_part(*id, *variant))
+                            .map(|info| info.name())
+                            .unwrap_or_else(|| format!("#{}:{}", id, variant))
+                            .into(),
+                    )]),
+                )
+                .unwrap(),
+            Unconvertible::Modcard(id) => i18n::LOCALES
+                .lookup_with_args(
+                    lang,
+                    "save-convert-unconvertible-modcard",
+                    &std::collections::HashMap::from([("modcard", format!("#{}", id).into())]),
+                )
+                .unwrap(),
+            Unconvertible::DroppedModcard(id) => i18n::LOCALES
+                .lookup_with_args(
+                    lang,
+                    "save-convert-unconvertible-dropped-modcard",
+                    &std::collections::HashMap::from([("modcard", format!("#{}", id).into())]),
+                )
+                .unwrap(),
+            Unconvertible::RemovedModcard(id) => i18n::LOCALES
+                .lookup_with_args(
+                    lang,
+                    "save-convert-unconvertible-removed-modcard",
+                    &std::collections::HashMap::from([("modcard", format!("#{}", id).into())]),
+                )
+                .unwrap(),
+            Unconvertible::MissingContentUnchecked => i18n::LOCALES
+                .lookup(lang, "save-convert-unconvertible-missing-content-unchecked")
+                .unwrap(),
+        }
+    }
+}
+
+pub struct Conversion {
+    pub save: Box<dyn save::Save + Send + Sync>,
+    pub unconvertible: Vec<Unconvertible>,
+}
+
+/// Finds the release of the same game in the other region, e.g. BN6 Gregar for EXE6 Gregar.
+pub fn counterpart(game: &'static (dyn game::Game + Send + Sync)) -> Option<&'static (dyn game::Game + Send + Sync)> {
+    let (family, variant) = game.family_and_variant();
+    let other_family = if let Some(n) = family.strip_prefix("exe") {
+        format!("bn{}", n)
+    } else if let Some(n) = family.strip_prefix("bn") {
+        format!("exe{}", n)
+    } else {
+        return None;
+    };
+    game::find_by_family_and_variant(&other_family, variant)
+}
+
+/// Whether a save can be converted without a save of the target release to convert into.
+pub fn is_direct(from: &'static (dyn game::Game + Send + Sync), to: &'static (dyn game::Game + Send + Sync)) -> bool {
+    // Games that can't convert return None regardless of the data, so empty data is enough to ask.
+    counterpart(from) == Some(to) && to.save_from_other_region(&[]).is_some()
+}
+
+/// Converts a save to the other region's release.
+///
+/// If the two releases lay out saves differently, a save of the target release must be given to convert into. Content
+/// that does not exist in the target game can only be found with its assets: without them, the check is reported as
+/// skipped.
+pub fn convert(
+    from: &'static (dyn game::Game + Send + Sync),
+    save: &dyn save::Save,
+    to: &'static (dyn game::Game + Send + Sync),
+    target: Option<&(dyn save::Save + Send + Sync)>,
+    target_assets: Option<&dyn rom::Assets>,
+) -> anyhow::Result<Conversion> {
+    if counterpart(from) != Some(to) {
+        anyhow::bail!(
+            "{:?} is not the other region's release of {:?}",
+            to.family_and_variant(),
+            from.family_and_variant()
+        );
+    }
+
+    let mut unconvertible = vec![];
+
+    let converted = if let Some(converted) = to.save_from_other_region(save.as_raw_wram()) {
+        let converted = converted?;
+        // Make sure the game will actually accept it.
+        to.parse_save(&converted.to_vec())?;
+        converted
+    } else {
+        let mut converted = target
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "saves of {:?} and {:?} are laid out differently: a save of the target game is needed to convert into",
+                    from.family_and_variant(),
+                    to.family_and_variant()
+                )
+            })?
+            .clone_box();
+
+        let mut setup = save::setup::Setup::from_save(from.family_and_variant().0, save);
+        setup.game = to.family_and_variant().0.to_string();
+        // Modcards are replaced separately, so ones that don't fit are reported instead of failing the conversion.
+        let modcards = setup.modcards.take();
+        if converted.view_navicust().is_none() {
+            setup.navicust = None;
+        }
+        setup.apply(setup.game.as_str(), converted.as_mut())?;
+        if let Some(modcards) = modcards {
+            if converted.view_modcards().is_none() {
+                let count = modcards.iter().flatten().count();
+                if count > 0 {
+                    unconvertible.push(Unconvertible::Modcards(count));
+                }
+            } else {
+                unconvertible.extend(replace_modcards(converted.as_mut(), &modcards)?);
+            }
+        }
+        unconvertible.push(Unconvertible::Progress);
+        converted
+    };
+
+    if let Some(assets) = target_assets {
+        unconvertible.extend(missing_content(converted.as_ref(), assets));
+    } else {
+        unconvertible.push(Unconvertible::MissingContentUnchecked);
+    }
+
+    Ok(Conversion {
+        save: converted,
+        unconvertible,
+    })
+}
+
+fn installed_modcards(save: &dyn save::Save) -> Vec<save::Modcard> {
+    match save.view_modcards() {
+        Some(save::ModcardsView::Modcard4s(modcards_view)) => {
+            (0..6).flat_map(|slot| modcards_view.modcard(slot)).collect()
+        }
+        Some(save::ModcardsView::Modcard56s(modcards_view)) => (0..modcards_view.count())
+            .flat_map(|slot| modcards_view.modcard(slot))
+            .collect(),
+        None => vec![],
+    }
+}
+
+// Replaces the target save's modcards with the converted ones, dropping the ones it can't take.
+fn replace_modcards(
+    save: &mut dyn save::Save,
+    modcards: &[Option<save::Modcard>],
+) -> anyhow::Result<Vec<Unconvertible>> {
+    let previous = installed_modcards(save);
+
+    let mut unconvertible = vec![];
+    match save
+        .view_modcards_mut()
+        .ok_or_else(|| anyhow::anyhow!("modcards are not writable"))?
+    {
+        save::ModcardsViewMut::Modcard4s(mut modcards_view) => {
+            for slot in 0..6 {
+                let modcard = modcards.get(slot).and_then(|modcard| modcard.as_ref());
+                if modcards_view.set_modcard(slot, modcard).is_err() {
+                    unconvertible.extend(modcard.map(|modcard| Unconvertible::DroppedModcard(modcard.id)));
+                    modcards_view.set_modcard(slot, None)?;
+                }
+            }
+            for modcard in modcards.iter().skip(6).flatten() {
+                unconvertible.push(Unconvertible::DroppedModcard(modcard.id));
+            }
+        }
+        save::ModcardsViewMut::Modcard56s(mut modcards_view) => {
+            // The list is checked as a whole before anything is written, so add modcards one at a time to find the ones
+            // that don't fit.
+            let mut installed: Vec<save::Modcard> = vec![];
+            modcards_view.set_modcards(&installed)?;
+            for modcard in modcards.iter().flatten() {
+                installed.push(modcard.clone());
+                if modcards_view.set_modcards(&installed).is_err() {
+                    installed.pop();
+                    unconvertible.push(Unconvertible::DroppedModcard(modcard.id));
+                }
+            }
+        }
+    }
+
+    let current = installed_modcards(save);
+    for modcard in previous {
+        if !current.iter().any(|m| m.id == modcard.id) {
+            unconvertible.push(Unconvertible::RemovedModcard(modcard.id));
+        }
+    }
+
+    Ok(unconvertible)
+}
+
+// Finds content in a save that the game's assets don't know about, e.g. because a patch added it.
+fn missing_content(save: &dyn save::Save, assets: &dyn rom::Assets) -> Vec<Unconvertible> {
+    let mut missing = vec![];
+
+    if let Some(chips_view) = save.view_chips() {
+        let folder_index = chips_view.equipped_folder_index();
+        for chip in (0..30).flat_map(|i| chips_view.chip(folder_index, i)) {
+            let unconvertible = Unconvertible::Chip(chip.id);
+            if assets.chip(chip.id).is_none() && !missing.contains(&unconvertible) {
+                missing.push(unconvertible);
+            }
+        }
+    }
+
+    if let Some(navicust_view) = save.view_navicust() {
+        for part in (0..navicust_view.count()).flat_map(|i| navicust_view.navicust_part(i)) {
+            if assets.navicust_part(part.id, part.variant).is_none() {
+                missing.push(Unconvertible::NavicustPart(part.id, part.variant));
+            }
+        }
+    }
+
+    for modcard in installed_modcards(save) {
+        if assets.modcard56(modcard.id).is_none() && assets.modcard4(modcard.id).is_none() {
+            missing.push(Unconvertible::Modcard(modcard.id));
+        }
+    }
+
+    missing
+}
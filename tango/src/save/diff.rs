@@ -0,0 +1,389 @@
+// Structured differences between two saves of the same game family.
+//
+// Every change is expressed as a (left, right) pair so it can be shown side by side.
+
+use crate::{rom, save};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChipCountChange {
+    pub chip: save::Chip,
+    pub left: usize,
+    pub right: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FolderDiff {
+    pub folder_index: usize,
+    pub chips: Vec<ChipCountChange>,
+    pub regular_chip: Option<(Option<save::Chip>, Option<save::Chip>)>,
+    pub tag_chips: Option<(Option<[save::Chip; 2]>, Option<[save::Chip; 2]>)>,
+}
+
+impl FolderDiff {
+    pub fn is_empty(&self) -> bool {
+        self.chips.is_empty() && self.regular_chip.is_none() && self.tag_chips.is_none()
+    }
+}
+
+// A part only on the left was removed, a part only on the right was added, and a part on both sides was moved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavicustPartChange {
+    pub left: Option<save::NavicustPart>,
+    pub right: Option<save::NavicustPart>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModcardChange {
+    pub id: usize,
+    // None if the modcard isn't installed, otherwise whether it's enabled.
+    pub left: Option<bool>,
+    pub right: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DarkAIChange {
+    pub id: usize,
+    // Whether this is the secondary use count, which the games keep separately from the main one.
+    pub secondary: bool,
+    pub left: u16,
+    pub right: u16,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diff {
+    pub folders: Vec<FolderDiff>,
+    pub navicust: Vec<NavicustPartChange>,
+    pub modcards: Vec<ModcardChange>,
+    pub dark_ai: Vec<DarkAIChange>,
+}
+
+fn regular_chip(
+    chips_view: &dyn save::ChipsView,
+    chips: &[Option<save::Chip>],
+    folder_index: usize,
+) -> Option<save::Chip> {
+    chips_view
+        .regular_chip_index(folder_index)
+        .and_then(|i| chips.get(i).cloned().flatten())
+}
+
+fn tag_chips(
+    chips_view: &dyn save::ChipsView,
+    chips: &[Option<save::Chip>],
+    folder_index: usize,
+) -> Option<[save::Chip; 2]> {
+    let [a, b] = chips_view.tag_chip_indexes(folder_index)?;
+    Some([chips.get(a).cloned().flatten()?, chips.get(b).cloned().flatten()?])
+}
+
+fn diff_folder(left: &dyn save::ChipsView, right: &dyn save::ChipsView, folder_index: usize) -> FolderDiff {
+    let left_chips = left.folder_chips(folder_index);
+    let right_chips = right.folder_chips(folder_index);
+
+    let mut counts = indexmap::IndexMap::<save::Chip, (usize, usize)>::new();
+    for chip in left_chips.iter().flatten() {
+        counts.entry(chip.clone()).or_insert((0, 0)).0 += 1;
+    }
+    for chip in right_chips.iter().flatten() {
+        counts.entry(chip.clone()).or_insert((0, 0)).1 += 1;
+    }
+
+    let chips = counts
+        .into_iter()
+        .filter(|(_, (left, right))| left != right)
+        .map(|(chip, (left, right))| ChipCountChange { chip, left, right })
+        .collect();
+
+    let left_regular_chip = regular_chip(left, &left_chips, folder_index);
+    let right_regular_chip = regular_chip(right, &right_chips, folder_index);
+
+    let left_tag_chips = tag_chips(left, &left_chips, folder_index);
+    let right_tag_chips = tag_chips(right, &right_chips, folder_index);
+
+    FolderDiff {
+        folder_index,
+        chips,
+        regular_chip: if left_regular_chip != right_regular_chip {
+            Some((left_regular_chip, right_regular_chip))
+        } else {
+            None
+        },
+        tag_chips: if left_tag_chips != right_tag_chips {
+            Some((left_tag_chips, right_tag_chips))
+        } else {
+            None
+        },
+    }
+}
+
+fn diff_navicust(left: &dyn save::NavicustView, right: &dyn save::NavicustView) -> Vec<NavicustPartChange> {
+    let mut left_parts = (0..left.count())
+        .flat_map(|i| left.navicust_part(i))
+        .collect::<Vec<_>>();
+    let mut right_parts = (0..right.count())
+        .flat_map(|i| right.navicust_part(i))
+        .collect::<Vec<_>>();
+
+    // Parts that are exactly the same on both sides didn't change.
+    left_parts.retain(|part| {
+        if let Some(i) = right_parts.iter().position(|other| other == part) {
+            right_parts.remove(i);
+            false
+        } else {
+            true
+        }
+    });
+
+    // Of what's left, the same part on both sides was moved.
+    let mut changes = vec![];
+    for part in left_parts {
+        let right = right_parts
+            .iter()
+            .position(|other| other.id == part.id && other.variant == part.variant)
+            .map(|i| right_parts.remove(i));
+        changes.push(NavicustPartChange {
+            left: Some(part),
+            right,
+        });
+    }
+    changes.extend(right_parts.into_iter().map(|part| NavicustPartChange {
+        left: None,
+        right: Some(part),
+    }));
+    changes
+}
+
+fn modcards(modcards_view: &save::ModcardsView) -> Vec<save::Modcard> {
+    match modcards_view {
+        save::ModcardsView::Modcard4s(modcards_view) => (0..6).flat_map(|slot| modcards_view.modcard(slot)).collect(),
+        save::ModcardsView::Modcard56s(modcards_view) => (0..modcards_view.count())
+            .flat_map(|slot| modcards_view.modcard(slot))
+            .collect(),
+    }
+}
+
+fn diff_modcards(left: &save::ModcardsView, right: &save::ModcardsView) -> Vec<ModcardChange> {
+    let mut states = indexmap::IndexMap::<usize, (Option<bool>, Option<bool>)>::new();
+    for modcard in modcards(left) {
+        states.entry(modcard.id).or_insert((None, None)).0 = Some(modcard.enabled);
+    }
+    for modcard in modcards(right) {
+        states.entry(modcard.id).or_insert((None, None)).1 = Some(modcard.enabled);
+    }
+    states
+        .into_iter()
+        .filter(|(_, (left, right))| left != right)
+        .map(|(id, (left, right))| ModcardChange { id, left, right })
+        .collect()
+}
+
+fn diff_dark_ai(left: &dyn save::DarkAIView, right: &dyn save::DarkAIView) -> Vec<DarkAIChange> {
+    let mut changes = vec![];
+    for secondary in [false, true] {
+        let use_count = |view: &dyn save::DarkAIView, id| {
+            if secondary {
+                view.secondary_chip_use_count(id)
+            } else {
+                view.chip_use_count(id)
+            }
+        };
+        for id in 0.. {
+            let (left, right) = match (use_count(left, id), use_count(right, id)) {
+                (None, None) => {
+                    break;
+                }
+                (left, right) => (left.unwrap_or(0), right.unwrap_or(0)),
+            };
+            if left != right {
+                changes.push(DarkAIChange {
+                    id,
+                    secondary,
+                    left,
+                    right,
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Compares two saves. Both saves must be from the same game family.
+pub fn diff(left: &dyn save::Save, right: &dyn save::Save) -> Diff {
+    let mut diff = Diff::default();
+
+    if let (Some(left), Some(right)) = (left.view_chips(), right.view_chips()) {
+        for folder_index in 0..left.num_folders().min(right.num_folders()) {
+            let folder_diff = diff_folder(left.as_ref(), right.as_ref(), folder_index);
+            if !folder_diff.is_empty() {
+                diff.folders.push(folder_diff);
+            }
+        }
+    }
+
+    if let (Some(left), Some(right)) = (left.view_navicust(), right.view_navicust()) {
+        diff.navicust = diff_navicust(left.as_ref(), right.as_ref());
+    }
+
+    if let (Some(left), Some(right)) = (left.view_modcards(), right.view_modcards()) {
+        diff.modcards = diff_modcards(&left, &right);
+    }
+
+    if let (Some(left), Some(right)) = (left.view_dark_ai(), right.view_dark_ai()) {
+        diff.dark_ai = diff_dark_ai(left.as_ref(), right.as_ref());
+    }
+
+    diff
+}
+
+pub fn chip_name(assets: Option<&dyn rom::Assets>, chip: &save::Chip) -> String {
+    format!(
+        "{} {}",
+        assets
+            .and_then(|assets| assets.chip(chip.id))
+            .map(|info| info.name())
+            .unwrap_or_else(|| format!("#{}", chip.id)),
+        chip.code
+    )
+}
+
+pub fn navicust_part_name(assets: Option<&dyn rom::Assets>, part: &save::NavicustPart) -> String {
+    assets
+        .and_then(|assets| assets.navicust_part(part.id, part.variant))
+        .map(|info| info.name())
+        .unwrap_or_else(|| format!("#{}:{}", part.id, part.variant))
+}
+
+pub fn navicust_part_position(part: &save::NavicustPart) -> String {
+    format!(
+        "({}, {}) rot {}{}",
+        part.col,
+        part.row,
+        part.rot,
+        if part.compressed { " compressed" } else { "" }
+    )
+}
+
+pub fn modcard_name(assets: Option<&dyn rom::Assets>, id: usize) -> String {
+    assets
+        .and_then(|assets| {
+            assets
+                .modcard56(id)
+                .map(|info| info.name())
+                .or_else(|| assets.modcard4(id).map(|info| info.name()))
+        })
+        .unwrap_or_else(|| format!("#{}", id))
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty() && self.navicust.is_empty() && self.modcards.is_empty() && self.dark_ai.is_empty()
+    }
+
+    pub fn num_changes(&self) -> usize {
+        self.folders
+            .iter()
+            .map(|folder| {
+                folder.chips.len()
+                    + if folder.regular_chip.is_some() { 1 } else { 0 }
+                    + if folder.tag_chips.is_some() { 1 } else { 0 }
+            })
+            .sum::<usize>()
+            + self.navicust.len()
+            + self.modcards.len()
+            + self.dark_ai.len()
+    }
+
+    /// Renders the diff as text, one change per line. If assets are given, they're used for names.
+    pub fn to_text(&self, assets: Option<&dyn rom::Assets>) -> String {
+        let mut lines = vec![];
+
+        let or_none = |s: Option<String>| s.unwrap_or_else(|| "-".to_string());
+
+        for folder in self.folders.iter() {
+            lines.push(format!("folder {}:", folder.folder_index + 1));
+            for change in folder.chips.iter() {
+                lines.push(format!(
+                    "  {} {}: {} -> {}",
+                    if change.right > change.left { "+" } else { "-" },
+                    chip_name(assets, &change.chip),
+                    change.left,
+                    change.right
+                ));
+            }
+            if let Some((left, right)) = folder.regular_chip.as_ref() {
+                lines.push(format!(
+                    "  regular chip: {} -> {}",
+                    or_none(left.as_ref().map(|chip| chip_name(assets, chip))),
+                    or_none(right.as_ref().map(|chip| chip_name(assets, chip))),
+                ));
+            }
+            if let Some((left, right)) = folder.tag_chips.as_ref() {
+                let tag_chip_names = |chips: &[save::Chip; 2]| {
+                    format!("{} + {}", chip_name(assets, &chips[0]), chip_name(assets, &chips[1]))
+                };
+                lines.push(format!(
+                    "  tag chips: {} -> {}",
+                    or_none(left.as_ref().map(tag_chip_names)),
+                    or_none(right.as_ref().map(tag_chip_names)),
+                ));
+            }
+        }
+
+        if !self.navicust.is_empty() {
+            lines.push("navicust:".to_string());
+            for change in self.navicust.iter() {
+                let (sign, part) = match (change.left.as_ref(), change.right.as_ref()) {
+                    (Some(part), None) => ("-", part),
+                    (None, Some(part)) => ("+", part),
+                    (Some(part), Some(_)) => ("~", part),
+                    (None, None) => {
+                        continue;
+                    }
+                };
+                lines.push(format!(
+                    "  {} {}: {} -> {}",
+                    sign,
+                    navicust_part_name(assets, part),
+                    or_none(change.left.as_ref().map(navicust_part_position)),
+                    or_none(change.right.as_ref().map(navicust_part_position)),
+                ));
+            }
+        }
+
+        if !self.modcards.is_empty() {
+            let modcard_state = |enabled: Option<bool>| match enabled {
+                Some(true) => "on",
+                Some(false) => "off",
+                None => "-",
+            };
+            lines.push("modcards:".to_string());
+            for change in self.modcards.iter() {
+                lines.push(format!(
+                    "  {}: {} -> {}",
+                    modcard_name(assets, change.id),
+                    modcard_state(change.left),
+                    modcard_state(change.right),
+                ));
+            }
+        }
+
+        if !self.dark_ai.is_empty() {
+            lines.push("dark ai:".to_string());
+            for change in self.dark_ai.iter() {
+                lines.push(format!(
+                    "  {}{}: {} -> {}",
+                    assets
+                        .and_then(|assets| assets.chip(change.id))
+                        .map(|info| info.name())
+                        .unwrap_or_else(|| format!("#{}", change.id)),
+                    if change.secondary { " (secondary)" } else { "" },
+                    change.left,
+                    change.right
+                ));
+            }
+        }
+
+        lines.push("".to_string());
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,165 @@
+// Snapshots of saves taken before anything gets a chance to overwrite them.
+//
+// Each save gets its own directory under the history root, mirroring its path relative to the saves directory. Snapshots
+// in it are named <timestamp in ms>-<reason>.sav, e.g.:
+//
+//     save-history/exe6/gregar.sav/1666051200000-singleplayer.sav
+
+pub const MAX_SNAPSHOTS_PER_SAVE: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    SinglePlayer,
+    Netplay,
+    Restore,
+    Convert,
+    ImportSetup,
+}
+
+impl Reason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Reason::SinglePlayer => "singleplayer",
+            Reason::Netplay => "netplay",
+            Reason::Restore => "restore",
+            Reason::Convert => "convert",
+            Reason::ImportSetup => "import-setup",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "singleplayer" => Some(Reason::SinglePlayer),
+            "netplay" => Some(Reason::Netplay),
+            "restore" => Some(Reason::Restore),
+            "convert" => Some(Reason::Convert),
+            "import-setup" => Some(Reason::ImportSetup),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub path: std::path::PathBuf,
+    pub ts: std::time::SystemTime,
+    pub reason: Reason,
+}
+
+impl Snapshot {
+    fn from_path(path: std::path::PathBuf) -> Option<Self> {
+        if path.extension() != Some(std::ffi::OsStr::new("sav")) {
+            return None;
+        }
+        let stem = path.file_stem()?.to_str()?;
+        let (ts, reason) = stem.split_once('-')?;
+        Some(Self {
+            ts: std::time::UNIX_EPOCH + std::time::Duration::from_millis(ts.parse().ok()?),
+            reason: Reason::from_str(reason)?,
+            path,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct History {
+    root: std::path::PathBuf,
+    saves_path: std::path::PathBuf,
+}
+
+impl History {
+    pub fn new(root: std::path::PathBuf, saves_path: std::path::PathBuf) -> Self {
+        Self { root, saves_path }
+    }
+
+    fn snapshots_path(&self, save_path: &std::path::Path) -> std::path::PathBuf {
+        // Saves outside of the saves directory are keyed by their filename only.
+        let relative_path = save_path
+            .strip_prefix(&self.saves_path)
+            .ok()
+            .map(|path| path.to_path_buf())
+            .or_else(|| {
+                std::fs::canonicalize(&self.saves_path)
+                    .ok()
+                    .and_then(|saves_path| save_path.strip_prefix(saves_path).ok().map(|path| path.to_path_buf()))
+            })
+            .or_else(|| save_path.file_name().map(std::path::PathBuf::from))
+            .unwrap_or_default();
+        self.root.join(relative_path)
+    }
+
+    /// Lists the snapshots of a save, newest first.
+    pub fn list(&self, save_path: &std::path::Path) -> Vec<Snapshot> {
+        let read_dir = match std::fs::read_dir(self.snapshots_path(save_path)) {
+            Ok(read_dir) => read_dir,
+            Err(_) => {
+                return vec![];
+            }
+        };
+
+        let mut snapshots = read_dir
+            .flat_map(|entry| entry.ok())
+            .flat_map(|entry| Snapshot::from_path(entry.path()))
+            .collect::<Vec<_>>();
+        snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.ts));
+        snapshots
+    }
+
+    /// Takes a snapshot of a save as it is on disk.
+    ///
+    /// If the save is identical to its latest snapshot, no new snapshot is taken. Returns the new snapshot, if any.
+    pub fn snapshot(&self, save_path: &std::path::Path, reason: Reason) -> anyhow::Result<Option<Snapshot>> {
+        let data = match std::fs::read(save_path) {
+            Ok(data) => data,
+            // Nothing to back up yet.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+
+        let mut snapshots = self.list(save_path);
+        if let Some(latest) = snapshots.first() {
+            if std::fs::read(&latest.path).ok().as_ref() == Some(&data) {
+                return Ok(None);
+            }
+        }
+
+        let snapshots_path = self.snapshots_path(save_path);
+        std::fs::create_dir_all(&snapshots_path)?;
+
+        let ts = std::time::SystemTime::now();
+        let path = snapshots_path.join(format!(
+            "{}-{}.sav",
+            ts.duration_since(std::time::UNIX_EPOCH)?.as_millis(),
+            reason.as_str()
+        ));
+        std::fs::write(&path, &data)?;
+        log::info!("took snapshot of {} at {}", save_path.display(), path.display());
+
+        // Drop the oldest snapshots over the limit.
+        while snapshots.len() + 1 > MAX_SNAPSHOTS_PER_SAVE {
+            if let Some(oldest) = snapshots.pop() {
+                if let Err(e) = std::fs::remove_file(&oldest.path) {
+                    log::warn!("failed to remove snapshot {}: {:?}", oldest.path.display(), e);
+                }
+            }
+        }
+
+        Ok(Some(Snapshot { path, ts, reason }))
+    }
+
+    /// Restores a save from a snapshot. The current save is snapshotted first, so a restore can itself be undone.
+    pub fn restore(&self, save_path: &std::path::Path, snapshot: &Snapshot) -> anyhow::Result<()> {
+        let data = std::fs::read(&snapshot.path)?;
+        self.snapshot(save_path, Reason::Restore)?;
+
+        let tmp_path = save_path.with_extension("tmp");
+        std::fs::write(&tmp_path, &data)?;
+        std::fs::rename(&tmp_path, save_path)?;
+        log::info!("restored {} from {}", save_path.display(), snapshot.path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+// Folder legality checking against tournament rulesets.
+//
+// Rulesets are TOML files with a name and a table of rules per game family, e.g.:
+//
+//     name = "Example League"
+//
+//     [bn6]
+//     max_copies = 4
+//     max_mega_chips = 5
+//     max_giga_chips = 1
+//     max_regular_chip_mb = 50
+//     banned_chips = [190, 191]
+
+use crate::{rom, save};
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Rules {
+    pub max_copies: Option<usize>,
+    pub max_mega_chips: Option<usize>,
+    pub max_giga_chips: Option<usize>,
+    pub max_regular_chip_mb: Option<u8>,
+    pub banned_chips: Vec<usize>,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct Ruleset {
+    pub name: String,
+    #[serde(flatten)]
+    pub games: std::collections::HashMap<String, Rules>,
+}
+
+impl Ruleset {
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        Ok(toml::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn rules(&self, family: &str) -> Option<&Rules> {
+        self.games.get(family)
+    }
+}
+
+/// Loads all rulesets in a directory, sorted by name.
+pub fn load_rulesets(path: &std::path::Path) -> Vec<Ruleset> {
+    let mut rulesets = vec![];
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => {
+                continue;
+            }
+        };
+
+        if !entry.file_type().is_file() || entry.path().extension() != Some(std::ffi::OsStr::new("toml")) {
+            continue;
+        }
+
+        match Ruleset::load(entry.path()) {
+            Ok(ruleset) => {
+                rulesets.push(ruleset);
+            }
+            Err(e) => {
+                log::warn!("failed to load ruleset {}: {:?}", entry.path().display(), e);
+            }
+        }
+    }
+    rulesets.sort_by(|a, b| a.name.cmp(&b.name));
+    rulesets
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    TooManyCopies { id: usize, count: usize, max: usize },
+    TooManyMegaChips { count: usize, max: usize },
+    TooManyGigaChips { count: usize, max: usize },
+    RegularChipTooLarge { id: usize, mb: u8, max: u8 },
+    BannedChip { id: usize },
+}
+
+/// Checks the equipped folder against a set of rules.
+pub fn check(rules: &Rules, chips_view: &dyn save::ChipsView, assets: &dyn rom::Assets) -> Vec<Violation> {
+    let folder_index = chips_view.equipped_folder_index();
+    let chips = (0..30)
+        .flat_map(|i| chips_view.chip(folder_index, i))
+        .collect::<Vec<_>>();
+
+    let mut violations = vec![];
+
+    let mut counts = indexmap::IndexMap::<usize, usize>::new();
+    for chip in chips.iter() {
+        *counts.entry(chip.id).or_insert(0) += 1;
+    }
+
+    if let Some(max) = rules.max_copies {
+        for (id, count) in counts.iter() {
+            if *count > max {
+                violations.push(Violation::TooManyCopies {
+                    id: *id,
+                    count: *count,
+                    max,
+                });
+            }
+        }
+    }
+
+    let count_class = |class| {
+        chips
+            .iter()
+            .filter(|chip| assets.chip(chip.id).map(|info| info.class() == class).unwrap_or(false))
+            .count()
+    };
+
+    if let Some(max) = rules.max_mega_chips {
+        let count = count_class(rom::ChipClass::Mega);
+        if count > max {
+            violations.push(Violation::TooManyMegaChips { count, max });
+        }
+    }
+
+    if let Some(max) = rules.max_giga_chips {
+        let count = count_class(rom::ChipClass::Giga);
+        if count > max {
+            violations.push(Violation::TooManyGigaChips { count, max });
+        }
+    }
+
+    if let Some(max) = rules.max_regular_chip_mb {
+        if chips_view.chips_have_mb() {
+            if let Some(regular_chip_index) = chips_view.regular_chip_index(folder_index) {
+                // If the regular chip isn't in place, it's always moved to the front of the folder.
+                let regular_chip = chips.get(if chips_view.regular_chip_is_in_place() {
+                    regular_chip_index
+                } else {
+                    0
+                });
+                if let Some(regular_chip) = regular_chip {
+                    if let Some(mb) = assets.chip(regular_chip.id).map(|info| info.mb()) {
+                        if mb > max {
+                            violations.push(Violation::RegularChipTooLarge {
+                                id: regular_chip.id,
+                                mb,
+                                max,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for id in counts.keys() {
+        if rules.banned_chips.contains(id) {
+            violations.push(Violation::BannedChip { id: *id });
+        }
+    }
+
+    violations
+}
@@ -0,0 +1,465 @@
+// A setup is everything about a save that matters for a match: the equipped folder, the navicust, modcards, and the navi
+// and style where the game has them. Setups can be shared as JSON or as plain text, and applied back onto a save.
+//
+// The text format is line-based. Anything after a # is a comment: names are written there for humans, but are ignored
+// when parsing.
+//
+//     tango-setup 1
+//     game bn6
+//     navi 0
+//
+//     folder
+//     chip 15 A reg # Cannon
+//     chip 100 * tag # ...
+//
+//     navicust
+//     style 3
+//     part 1 0 2 3 0 compressed # ...
+//
+//     modcards
+//     modcard 12 on # ...
+//     modcard none
+
+use crate::{rom, save};
+
+pub const VERSION: u32 = 1;
+
+const TEXT_HEADER: &str = "tango-setup";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct Folder {
+    // In folder order, with the regular chip in place.
+    pub chips: Vec<save::Chip>,
+    pub regular_chip_index: Option<usize>,
+    pub tag_chip_indexes: Option<[usize; 2]>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct Navicust {
+    pub style: Option<usize>,
+    pub parts: Vec<save::NavicustPart>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct Setup {
+    pub version: u32,
+    pub game: String,
+    pub navi: Option<usize>,
+    pub folder: Option<Folder>,
+    pub navicust: Option<Navicust>,
+    // One entry per slot: games with fixed modcard slots can have empty ones.
+    pub modcards: Option<Vec<Option<save::Modcard>>>,
+}
+
+impl Setup {
+    pub fn from_save(family: &str, save: &dyn save::Save) -> Self {
+        let folder = save.view_chips().map(|chips_view| {
+            let folder_index = chips_view.equipped_folder_index();
+            Folder {
+                chips: chips_view.folder_chips(folder_index).into_iter().flatten().collect(),
+                regular_chip_index: chips_view.regular_chip_index(folder_index),
+                tag_chip_indexes: chips_view.tag_chip_indexes(folder_index),
+            }
+        });
+
+        let navicust = save.view_navicust().map(|navicust_view| Navicust {
+            style: navicust_view.style(),
+            parts: (0..navicust_view.count())
+                .flat_map(|i| navicust_view.navicust_part(i))
+                .collect(),
+        });
+
+        let modcards = save.view_modcards().map(|modcards_view| match modcards_view {
+            save::ModcardsView::Modcard4s(modcards_view) => {
+                (0..6).map(|slot| modcards_view.modcard(slot)).collect::<Vec<_>>()
+            }
+            save::ModcardsView::Modcard56s(modcards_view) => (0..modcards_view.count())
+                .map(|slot| modcards_view.modcard(slot))
+                .collect::<Vec<_>>(),
+        });
+
+        Self {
+            version: VERSION,
+            game: family.to_string(),
+            navi: save.view_navi().map(|navi_view| navi_view.navi()),
+            folder,
+            navicust,
+            modcards,
+        }
+    }
+
+    /// Writes the setup into a save.
+    ///
+    /// The navi and style are informational only and are not written.
+    pub fn apply(&self, family: &str, save: &mut dyn save::Save) -> anyhow::Result<()> {
+        if self.game != family {
+            anyhow::bail!("setup is for {}, not {}", self.game, family);
+        }
+
+        // Check this before anything is written, so a setup that doesn't fit doesn't get applied halfway.
+        if let Some(modcards) = self.modcards.as_ref() {
+            match save
+                .view_modcards()
+                .ok_or_else(|| anyhow::anyhow!("save does not have modcards"))?
+            {
+                save::ModcardsView::Modcard4s(_) => {
+                    if modcards.len() > 6 {
+                        anyhow::bail!("too many modcard slots: {} > 6", modcards.len());
+                    }
+                }
+                save::ModcardsView::Modcard56s(_) => {
+                    if modcards.iter().any(|modcard| modcard.is_none()) {
+                        anyhow::bail!("modcards cannot have empty slots");
+                    }
+                }
+            }
+        }
+
+        if let Some(folder) = self.folder.as_ref() {
+            let (folder_index, regular_chip_is_in_place) = {
+                let chips_view = save
+                    .view_chips()
+                    .ok_or_else(|| anyhow::anyhow!("save does not have a folder"))?;
+                (
+                    chips_view.equipped_folder_index(),
+                    chips_view.regular_chip_is_in_place(),
+                )
+            };
+
+            if folder.chips.len() != 30 {
+                anyhow::bail!("folder must have 30 chips, got {}", folder.chips.len());
+            }
+
+            if folder.regular_chip_index.map(|i| i >= 30).unwrap_or(false) {
+                anyhow::bail!("regular chip index out of range");
+            }
+
+            let mut chips = folder.chips.clone();
+            if !regular_chip_is_in_place {
+                if let Some(regular_chip_index) = folder.regular_chip_index {
+                    let chip = chips.remove(regular_chip_index);
+                    chips.insert(0, chip);
+                }
+            }
+
+            let mut chips_view = save
+                .view_chips_mut()
+                .ok_or_else(|| anyhow::anyhow!("folder is not writable"))?;
+            for (i, chip) in chips.iter().enumerate() {
+                chips_view.set_chip(folder_index, i, chip)?;
+            }
+            chips_view.set_regular_chip_index(folder_index, folder.regular_chip_index)?;
+            chips_view.set_tag_chip_indexes(folder_index, folder.tag_chip_indexes)?;
+        }
+
+        if let Some(navicust) = self.navicust.as_ref() {
+            let count = save
+                .view_navicust()
+                .ok_or_else(|| anyhow::anyhow!("save does not have a navicust"))?
+                .count();
+            if navicust.parts.len() > count {
+                anyhow::bail!("too many navicust parts: {} > {}", navicust.parts.len(), count);
+            }
+
+            let mut navicust_view = save
+                .view_navicust_mut()
+                .ok_or_else(|| anyhow::anyhow!("navicust is not writable"))?;
+            for i in 0..count {
+                navicust_view.set_navicust_part(i, navicust.parts.get(i))?;
+            }
+        }
+
+        if let Some(modcards) = self.modcards.as_ref() {
+            match save
+                .view_modcards_mut()
+                .ok_or_else(|| anyhow::anyhow!("modcards are not writable"))?
+            {
+                save::ModcardsViewMut::Modcard4s(mut modcards_view) => {
+                    for slot in 0..6 {
+                        modcards_view.set_modcard(slot, modcards.get(slot).and_then(|modcard| modcard.as_ref()))?;
+                    }
+                }
+                save::ModcardsViewMut::Modcard56s(mut modcards_view) => {
+                    modcards_view.set_modcards(&modcards.iter().flatten().cloned().collect::<Vec<_>>())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(s: &str) -> anyhow::Result<Self> {
+        let setup = serde_json::from_str::<Self>(s)?;
+        if setup.version != VERSION {
+            anyhow::bail!("unsupported setup version: {}", setup.version);
+        }
+        Ok(setup)
+    }
+
+    /// Renders the setup as text. If assets are given, names are added as comments.
+    pub fn to_text(&self, assets: Option<&dyn rom::Assets>) -> String {
+        let mut lines = vec![
+            format!("{} {}", TEXT_HEADER, self.version),
+            format!("game {}", self.game),
+        ];
+
+        let with_comment = |line: String, comment: Option<String>| {
+            if let Some(comment) = comment {
+                format!("{} # {}", line, comment)
+            } else {
+                line
+            }
+        };
+
+        if let Some(navi) = self.navi {
+            lines.push(with_comment(
+                format!("navi {}", navi),
+                assets.and_then(|assets| assets.navi(navi)).map(|navi| navi.name()),
+            ));
+        }
+
+        if let Some(folder) = self.folder.as_ref() {
+            lines.push("".to_string());
+            lines.push("folder".to_string());
+            for (i, chip) in folder.chips.iter().enumerate() {
+                let mut line = format!("chip {} {}", chip.id, chip.code);
+                if folder.regular_chip_index == Some(i) {
+                    line.push_str(" reg");
+                }
+                if folder.tag_chip_indexes.map(|is| is.contains(&i)).unwrap_or(false) {
+                    line.push_str(" tag");
+                }
+                lines.push(with_comment(
+                    line,
+                    assets.and_then(|assets| assets.chip(chip.id)).map(|info| info.name()),
+                ));
+            }
+        }
+
+        if let Some(navicust) = self.navicust.as_ref() {
+            lines.push("".to_string());
+            lines.push("navicust".to_string());
+            if let Some(style) = navicust.style {
+                lines.push(with_comment(
+                    format!("style {}", style),
+                    assets.and_then(|assets| assets.style(style)).map(|style| style.name()),
+                ));
+            }
+            for part in navicust.parts.iter() {
+                let mut line = format!(
+                    "part {} {} {} {} {}",
+                    part.id, part.variant, part.col, part.row, part.rot
+                );
+                if part.compressed {
+                    line.push_str(" compressed");
+                }
+                lines.push(with_comment(
+                    line,
+                    assets
+                        .and_then(|assets| assets.navicust_part(part.id, part.variant))
+                        .map(|info| info.name()),
+                ));
+            }
+        }
+
+        if let Some(modcards) = self.modcards.as_ref() {
+            lines.push("".to_string());
+            lines.push("modcards".to_string());
+            for modcard in modcards.iter() {
+                let modcard = if let Some(modcard) = modcard {
+                    modcard
+                } else {
+                    lines.push("modcard none".to_string());
+                    continue;
+                };
+                lines.push(with_comment(
+                    format!("modcard {} {}", modcard.id, if modcard.enabled { "on" } else { "off" }),
+                    assets.and_then(|assets| {
+                        assets
+                            .modcard56(modcard.id)
+                            .map(|info| info.name())
+                            .or_else(|| assets.modcard4(modcard.id).map(|info| info.name()))
+                    }),
+                ));
+            }
+        }
+
+        lines.push("".to_string());
+        lines.join("\n")
+    }
+
+    pub fn from_text(s: &str) -> anyhow::Result<Self> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or("").trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let version = match lines
+            .next()
+            .map(|(_, line)| line.split_whitespace().collect::<Vec<_>>())
+        {
+            Some(tokens) if tokens.len() == 2 && tokens[0] == TEXT_HEADER => tokens[1].parse::<u32>()?,
+            _ => {
+                anyhow::bail!("missing {} header", TEXT_HEADER);
+            }
+        };
+        if version != VERSION {
+            anyhow::bail!("unsupported setup version: {}", version);
+        }
+
+        let mut setup = Self {
+            version,
+            game: "".to_string(),
+            navi: None,
+            folder: None,
+            navicust: None,
+            modcards: None,
+        };
+
+        for (lineno, line) in lines {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            let parse_err = || anyhow::anyhow!("line {}: invalid line: {}", lineno, line);
+            match tokens.as_slice() {
+                ["game", game] => {
+                    setup.game = game.to_string();
+                }
+                ["navi", navi] => {
+                    setup.navi = Some(navi.parse().map_err(|_| parse_err())?);
+                }
+                ["folder"] => {
+                    setup.folder = Some(Folder {
+                        chips: vec![],
+                        regular_chip_index: None,
+                        tag_chip_indexes: None,
+                    });
+                }
+                ["chip", id, code, flags @ ..] => {
+                    let folder = setup
+                        .folder
+                        .as_mut()
+                        .ok_or_else(|| anyhow::anyhow!("line {}: chip outside of folder", lineno))?;
+                    let mut code_chars = code.chars();
+                    let code = match (code_chars.next(), code_chars.next()) {
+                        (Some(code), None) => code,
+                        _ => {
+                            return Err(parse_err());
+                        }
+                    };
+                    let i = folder.chips.len();
+                    folder.chips.push(save::Chip {
+                        id: id.parse().map_err(|_| parse_err())?,
+                        code,
+                    });
+                    for flag in flags {
+                        match *flag {
+                            "reg" => {
+                                folder.regular_chip_index = Some(i);
+                            }
+                            "tag" => {
+                                folder.tag_chip_indexes = Some(match folder.tag_chip_indexes {
+                                    None => [i, i],
+                                    Some([first, _]) => [first, i],
+                                });
+                            }
+                            _ => {
+                                return Err(parse_err());
+                            }
+                        }
+                    }
+                }
+                ["navicust"] => {
+                    setup.navicust = Some(Navicust {
+                        style: None,
+                        parts: vec![],
+                    });
+                }
+                ["style", style] => {
+                    setup
+                        .navicust
+                        .as_mut()
+                        .ok_or_else(|| anyhow::anyhow!("line {}: style outside of navicust", lineno))?
+                        .style = Some(style.parse().map_err(|_| parse_err())?);
+                }
+                ["part", id, variant, col, row, rot, flags @ ..] => {
+                    let navicust = setup
+                        .navicust
+                        .as_mut()
+                        .ok_or_else(|| anyhow::anyhow!("line {}: part outside of navicust", lineno))?;
+                    let compressed = match flags {
+                        [] => false,
+                        ["compressed"] => true,
+                        _ => {
+                            return Err(parse_err());
+                        }
+                    };
+                    navicust.parts.push(save::NavicustPart {
+                        id: id.parse().map_err(|_| parse_err())?,
+                        variant: variant.parse().map_err(|_| parse_err())?,
+                        col: col.parse().map_err(|_| parse_err())?,
+                        row: row.parse().map_err(|_| parse_err())?,
+                        rot: rot.parse().map_err(|_| parse_err())?,
+                        compressed,
+                    });
+                }
+                ["modcards"] => {
+                    setup.modcards = Some(vec![]);
+                }
+                ["modcard", "none"] => {
+                    setup
+                        .modcards
+                        .as_mut()
+                        .ok_or_else(|| anyhow::anyhow!("line {}: modcard outside of modcards", lineno))?
+                        .push(None);
+                }
+                ["modcard", id, enabled] => {
+                    let modcards = setup
+                        .modcards
+                        .as_mut()
+                        .ok_or_else(|| anyhow::anyhow!("line {}: modcard outside of modcards", lineno))?;
+                    modcards.push(Some(save::Modcard {
+                        id: id.parse().map_err(|_| parse_err())?,
+                        enabled: match *enabled {
+                            "on" => true,
+                            "off" => false,
+                            _ => {
+                                return Err(parse_err());
+                            }
+                        },
+                    }));
+                }
+                _ => {
+                    return Err(parse_err());
+                }
+            }
+        }
+
+        if setup.game.is_empty() {
+            anyhow::bail!("missing game");
+        }
+
+        if let Some(folder) = setup.folder.as_ref() {
+            // A single tag line is not a valid tag pair.
+            if let Some([a, b]) = folder.tag_chip_indexes {
+                if a == b {
+                    anyhow::bail!("tag chips must come in pairs");
+                }
+            }
+        }
+
+        Ok(setup)
+    }
+
+    /// Parses a setup in either format.
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        if s.trim_start().starts_with('{') {
+            Self::from_json(s)
+        } else {
+            Self::from_text(s)
+        }
+    }
+}
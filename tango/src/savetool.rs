@@ -0,0 +1,96 @@
+use crate::{config, game, rom, save};
+
+fn parse_save(buf: &[u8]) -> Option<Box<dyn save::Save + Send + Sync>> {
+    game::GAMES.iter().find_map(|game| game.parse_save(buf).ok())
+}
+
+pub fn history(
+    config: config::Config,
+    save_path: std::path::PathBuf,
+    restore: Option<usize>,
+) -> Result<(), anyhow::Error> {
+    let save_path = std::fs::canonicalize(&save_path).unwrap_or(save_path);
+    let save_history = save::history::History::new(config.save_history_path(), config.saves_path());
+    let snapshots = save_history.list(&save_path);
+
+    if let Some(i) = restore {
+        let snapshot = snapshots
+            .get(i)
+            .ok_or_else(|| anyhow::anyhow!("no such snapshot: {}", i))?;
+        save_history.restore(&save_path, snapshot)?;
+        println!("restored {} from {}", save_path.display(), snapshot.path.display());
+        return Ok(());
+    }
+
+    let current_save = std::fs::read(&save_path).ok().and_then(|buf| parse_save(&buf));
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let label = match (
+            current_save.as_ref(),
+            std::fs::read(&snapshot.path).ok().and_then(|buf| parse_save(&buf)),
+        ) {
+            (Some(current_save), Some(save)) => {
+                let diff = save::diff::diff(save.as_ref(), current_save.as_ref());
+                if diff.is_empty() {
+                    "same as current".to_string()
+                } else {
+                    format!("{} changes from current", diff.num_changes())
+                }
+            }
+            _ => "unreadable".to_string(),
+        };
+        println!(
+            "{:>3}  {}  {:<12}  {}",
+            i,
+            chrono::DateTime::<chrono::Local>::from(snapshot.ts).format("%Y-%m-%d %H:%M:%S"),
+            snapshot.reason.as_str(),
+            label
+        );
+    }
+    Ok(())
+}
+
+pub fn convert(
+    config: config::Config,
+    save_path: std::path::PathBuf,
+    output_path: std::path::PathBuf,
+    into_path: Option<std::path::PathBuf>,
+) -> Result<(), anyhow::Error> {
+    let buf = std::fs::read(&save_path)?;
+    let (from, save) = game::GAMES
+        .iter()
+        .find_map(|game| game.parse_save(&buf).ok().map(|save| (*game, save)))
+        .ok_or_else(|| anyhow::anyhow!("could not parse save"))?;
+    let to = save::convert::counterpart(from)
+        .ok_or_else(|| anyhow::anyhow!("{:?} has no release in another region", from.family_and_variant()))?;
+
+    let target = into_path
+        .map(|into_path| to.parse_save(&std::fs::read(&into_path)?))
+        .transpose()?;
+
+    // Names are nice to have but not required, so don't fail if the ROM isn't around.
+    let assets = game::scan_roms(&config.roms_path()).remove(&to).and_then(|rom| {
+        to.load_rom_assets(
+            &rom,
+            target.as_ref().unwrap_or(&save).as_raw_wram(),
+            &Default::default(),
+        )
+        .map_err(|e| {
+            log::warn!("not checking for missing content, could not load rom assets: {:?}", e);
+        })
+        .ok()
+    });
+    let assets = assets.as_ref().map(|assets| assets.as_ref() as &dyn rom::Assets);
+
+    let conversion = save::convert::convert(from, save.as_ref(), to, target.as_deref(), assets)?;
+    std::fs::write(&output_path, conversion.save.to_vec())?;
+    println!(
+        "converted {:?} save to {:?}: {}",
+        from.family_and_variant(),
+        to.family_and_variant(),
+        output_path.display()
+    );
+    for unconvertible in conversion.unconvertible.iter() {
+        println!("  {}", unconvertible.to_text(&config.language, assets));
+    }
+    Ok(())
+}
@@ -1,25 +1,53 @@
-use crate::{audio, battle, config, game, net, replay, replayer, rom, save, stats, video};
+use crate::{audio, battle, cheats, config, game, lockstep, net, replay, replayer, rom, save, spectator, stats, video};
 use parking_lot::Mutex;
 use rand::SeedableRng;
 use std::sync::Arc;
 
 pub const EXPECTED_FPS: f32 = 16777216.0 / 280896.0;
 
+pub const NUM_SAVESTATE_SLOTS: usize = 10;
+
+// How often a state is kept for rewinding, and how many are kept: 180 states every 10 frames is about 30 seconds.
+const REWIND_INTERVAL_FRAMES: u32 = 10;
+const REWIND_CAPACITY: usize = 180;
+
 pub struct GameInfo {
     pub game: &'static (dyn game::Game + Send + Sync),
     pub patch: Option<(String, semver::Version)>,
 }
 
 pub struct Setup {
+    pub game: &'static (dyn game::Game + Send + Sync),
     pub game_lang: unic_langid::LanguageIdentifier,
     pub save: Box<dyn save::Save + Send + Sync>,
     pub assets: Box<dyn rom::Assets + Send + Sync>,
 }
 
+// The last frame the emulator drew, numbered so it only needs to be filtered once no matter how often it is shown.
+pub struct VBuf {
+    pub buf: Vec<u8>,
+    pub frame: u64,
+}
+
+impl VBuf {
+    fn new() -> Self {
+        Self {
+            buf: vec![0u8; (mgba::gba::SCREEN_WIDTH * mgba::gba::SCREEN_HEIGHT * 4) as usize],
+            frame: 0,
+        }
+    }
+
+    fn set(&mut self, video_buffer: &[u8]) {
+        self.buf.copy_from_slice(video_buffer);
+        video::fix_vbuf_alpha(&mut self.buf);
+        self.frame += 1;
+    }
+}
+
 pub struct Session {
     start_time: std::time::SystemTime,
     game_info: GameInfo,
-    vbuf: std::sync::Arc<Mutex<Vec<u8>>>,
+    vbuf: std::sync::Arc<Mutex<VBuf>>,
     _audio_binding: audio::Binding,
     thread: mgba::thread::Thread,
     joyflags: std::sync::Arc<std::sync::atomic::AtomicU32>,
@@ -45,12 +73,114 @@ pub struct PvP {
     cancellation_token: tokio_util::sync::CancellationToken,
 }
 
-pub struct SinglePlayer {}
+struct Rewind {
+    states: std::collections::VecDeque<mgba::state::State>,
+    frames_since_last_state: u32,
+}
+
+pub struct SinglePlayer {
+    savestates_path: std::path::PathBuf,
+    rewinding: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    turbo_keys: std::sync::Arc<std::sync::atomic::AtomicU32>,
+    turbo_interval_frames: std::sync::Arc<std::sync::atomic::AtomicU32>,
+    cheats: std::sync::Arc<Mutex<cheats::Cheats>>,
+}
+
+impl SinglePlayer {
+    fn savestate_path(&self, slot: usize) -> std::path::PathBuf {
+        self.savestates_path.join(format!("{}.state", slot))
+    }
+
+    /// When the state in a slot was saved, if there is one.
+    pub fn savestate_time(&self, slot: usize) -> Option<std::time::SystemTime> {
+        std::fs::metadata(self.savestate_path(slot))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+pub struct Replayer {
+    replay: replay::Replay,
+    replayer_state: replayer::State,
+    fastforwarder: Mutex<replayer::Fastforwarder>,
+    rom: Vec<u8>,
+    hooks: &'static (dyn game::Hooks + Send + Sync),
+    rebuilt_keyframes: Arc<Mutex<RebuiltKeyframes>>,
+}
+
+// Inverted replays and replays from before keyframes have none, so they are rebuilt in the background on the first
+// seek. Until then, seeks start from the first tick.
+enum RebuiltKeyframes {
+    NotStarted,
+    Rebuilding,
+    Ready(Vec<replay::Keyframe>),
+}
+
+impl Replayer {
+    fn keyframe_for_tick(&self, tick: u32) -> replay::Keyframe {
+        if !self.replay.keyframes.is_empty() {
+            return self.replay.keyframe_for_tick(tick);
+        }
+
+        let mut rebuilt_keyframes = self.rebuilt_keyframes.lock();
+        match &*rebuilt_keyframes {
+            RebuiltKeyframes::NotStarted => {
+                *rebuilt_keyframes = RebuiltKeyframes::Rebuilding;
+                self.start_rebuilding_keyframes();
+            }
+            RebuiltKeyframes::Rebuilding => {}
+            RebuiltKeyframes::Ready(keyframes) => {
+                if let Some(keyframe) = keyframes.iter().rev().find(|keyframe| keyframe.tick <= tick) {
+                    return keyframe.clone();
+                }
+            }
+        }
+        self.replay.keyframe_for_tick(tick)
+    }
+
+    fn start_rebuilding_keyframes(&self) {
+        let mut replay = self.replay.clone();
+        let rom = self.rom.clone();
+        let hooks = self.hooks;
+        let rebuilt_keyframes = self.rebuilt_keyframes.clone();
+        std::thread::spawn(move || {
+            let r = replayer::Fastforwarder::new(
+                &rom,
+                hooks,
+                (replay.metadata.match_type as u8, replay.metadata.match_subtype as u8),
+                replay.local_player_index,
+            )
+            .and_then(|mut fastforwarder| replay.rebuild_keyframes(&mut fastforwarder));
+            // On failure, seeks keep starting from the first tick.
+            let keyframes = match r {
+                Ok(()) => replay.keyframes,
+                Err(e) => {
+                    log::error!("failed to rebuild replay keyframes: {:?}", e);
+                    vec![]
+                }
+            };
+            *rebuilt_keyframes.lock() = RebuiltKeyframes::Ready(keyframes);
+        });
+    }
+
+    pub fn current_tick(&self) -> u32 {
+        self.replayer_state.lock_inner().current_tick()
+    }
+
+    pub fn last_tick(&self) -> u32 {
+        self.replay.last_tick()
+    }
+}
+
+pub struct Spectator {
+    cancellation_token: tokio_util::sync::CancellationToken,
+}
 
 pub enum Mode {
     SinglePlayer(SinglePlayer),
     PvP(PvP),
-    Replayer,
+    Replayer(Replayer),
+    Spectator(Spectator),
 }
 
 impl Session {
@@ -125,6 +255,28 @@ impl Session {
         let thread = mgba::thread::Thread::new(core);
 
         let cancellation_token = tokio_util::sync::CancellationToken::new();
+
+        let spectator_listen_addr = config.read().spectator_listen_addr.clone();
+        let spectator_broadcaster = if !spectator_listen_addr.is_empty() {
+            let spectator_broadcaster = spectator::Broadcaster::new();
+            tokio::task::spawn({
+                let spectator_broadcaster = spectator_broadcaster.clone();
+                let cancellation_token = cancellation_token.clone();
+                async move {
+                    tokio::select! {
+                        r = spectator::serve(spectator_listen_addr, spectator_broadcaster) => {
+                            log::error!("spectator server ended: {:?}", r);
+                        }
+                        _ = cancellation_token.cancelled() => {
+                        }
+                    }
+                }
+            });
+            Some(spectator_broadcaster)
+        } else {
+            None
+        };
+
         let match_ = match_.clone();
         *match_.try_lock().unwrap() = Some({
             let inner_match = battle::Match::new(
@@ -145,6 +297,7 @@ impl Session {
                 remote_save,
                 replays_path,
                 match_type,
+                spectator_broadcaster,
             )
             .expect("new match");
 
@@ -160,6 +313,7 @@ impl Session {
                         }
                     }
                     log::info!("match thread ended");
+                    inner_match.end_set().await;
                     *match_.lock().await = None;
                 });
             }
@@ -175,20 +329,14 @@ impl Session {
             audio_binder.sample_rate(),
         ))))?;
 
-        let vbuf = Arc::new(Mutex::new(vec![
-            0u8;
-            (mgba::gba::SCREEN_WIDTH * mgba::gba::SCREEN_HEIGHT * 4)
-                as usize
-        ]));
+        let vbuf = Arc::new(Mutex::new(VBuf::new()));
         thread.set_frame_callback({
             let completion_flag = completion_flag.clone();
             let joyflags = joyflags.clone();
             let vbuf = vbuf.clone();
             let emu_tps_counter = emu_tps_counter.clone();
             move |mut core, video_buffer, mut thread_handle| {
-                let mut vbuf = vbuf.lock();
-                vbuf.copy_from_slice(video_buffer);
-                video::fix_vbuf_alpha(&mut *vbuf);
+                vbuf.lock().set(video_buffer);
                 core.set_keys(joyflags.load(std::sync::atomic::Ordering::Relaxed));
                 emu_tps_counter.lock().mark();
 
@@ -218,6 +366,7 @@ impl Session {
                 let save = local_game.parse_save(&local_save)?;
                 let assets = local_game.load_rom_assets(&local_rom, save.as_raw_wram(), local_patch_overrides)?;
                 Some(Setup {
+                    game: local_game,
                     game_lang: local_patch_overrides
                         .language
                         .clone()
@@ -230,6 +379,7 @@ impl Session {
                 let save = remote_game.parse_save(&remote_save)?;
                 let assets = remote_game.load_rom_assets(&remote_rom, save.as_raw_wram(), remote_patch_overrides)?;
                 Some(Setup {
+                    game: remote_game,
                     game_lang: remote_patch_overrides
                         .language
                         .clone()
@@ -249,6 +399,7 @@ impl Session {
         patch: Option<(String, semver::Version)>,
         rom: &[u8],
         save_path: &std::path::Path,
+        savestates_path: &std::path::Path,
         emu_tps_counter: Arc<Mutex<stats::Counter>>,
     ) -> Result<Self, anyhow::Error> {
         let mut core = mgba::core::Core::new_gba("tango")?;
@@ -265,6 +416,8 @@ impl Session {
         let hooks = game.hooks();
         hooks.patch(core.as_mut());
 
+        core.enable_debugger();
+
         let thread = mgba::thread::Thread::new(core);
 
         thread.start()?;
@@ -276,28 +429,92 @@ impl Session {
         ))))?;
 
         let pause_on_next_frame = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let vbuf = Arc::new(Mutex::new(vec![
-            0u8;
-            (mgba::gba::SCREEN_WIDTH * mgba::gba::SCREEN_HEIGHT * 4)
-                as usize
-        ]));
+        let rewinding = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let turbo_keys = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let turbo_interval_frames = Arc::new(std::sync::atomic::AtomicU32::new(1));
+        let cheats = Arc::new(Mutex::new(cheats::Cheats::default()));
+        let vbuf = Arc::new(Mutex::new(VBuf::new()));
         thread.set_frame_callback({
             let joyflags = joyflags.clone();
             let vbuf = vbuf.clone();
             let emu_tps_counter = emu_tps_counter.clone();
             let pause_on_next_frame = pause_on_next_frame.clone();
+            let rewinding = rewinding.clone();
+            let turbo_keys = turbo_keys.clone();
+            let turbo_interval_frames = turbo_interval_frames.clone();
+            let cheats = cheats.clone();
+            let turbo_frame = std::sync::atomic::AtomicU32::new(0);
+            let rewind = Mutex::new(Rewind {
+                states: std::collections::VecDeque::with_capacity(REWIND_CAPACITY),
+                frames_since_last_state: 0,
+            });
             move |mut core, video_buffer, mut thread_handle| {
-                let mut vbuf = vbuf.lock();
-                vbuf.copy_from_slice(video_buffer);
-                video::fix_vbuf_alpha(&mut *vbuf);
-                core.set_keys(joyflags.load(std::sync::atomic::Ordering::Relaxed));
+                vbuf.lock().set(video_buffer);
+
+                // Turbo keys are held for turbo_interval_frames frames, then released for as many.
+                let mut keys = joyflags.load(std::sync::atomic::Ordering::Relaxed);
+                let turbo_keys = turbo_keys.load(std::sync::atomic::Ordering::Relaxed);
+                if turbo_keys != 0 {
+                    let interval = std::cmp::max(turbo_interval_frames.load(std::sync::atomic::Ordering::Relaxed), 1);
+                    if (turbo_frame.fetch_add(1, std::sync::atomic::Ordering::Relaxed) / interval) % 2 == 0 {
+                        keys |= turbo_keys;
+                    }
+                } else {
+                    turbo_frame.store(0, std::sync::atomic::Ordering::Relaxed);
+                }
+                core.set_keys(keys);
                 emu_tps_counter.lock().mark();
 
+                let mut rewind = rewind.lock();
+                if rewinding.load(std::sync::atomic::Ordering::Relaxed) {
+                    // Step back one state per frame, but stop at the oldest one instead of running past it.
+                    if rewind.states.len() > 1 {
+                        rewind.states.pop_back();
+                    }
+                    if let Some(state) = rewind.states.back() {
+                        if let Err(e) = core.load_state(state) {
+                            log::error!("failed to load rewind state: {:?}", e);
+                        }
+                    }
+                    rewind.frames_since_last_state = 0;
+                } else {
+                    rewind.frames_since_last_state += 1;
+                    if rewind.frames_since_last_state >= REWIND_INTERVAL_FRAMES {
+                        rewind.frames_since_last_state = 0;
+                        match core.save_state() {
+                            Ok(state) => {
+                                if rewind.states.len() >= REWIND_CAPACITY {
+                                    rewind.states.pop_front();
+                                }
+                                rewind.states.push_back(state);
+                            }
+                            Err(e) => {
+                                log::error!("failed to save rewind state: {:?}", e);
+                            }
+                        }
+                    }
+                }
+
+                cheats.lock().apply(&mut core);
+
                 if pause_on_next_frame.swap(false, std::sync::atomic::Ordering::SeqCst) {
                     thread_handle.pause();
                 }
             }
         });
+
+        // Save states are only compatible with the exact ROM they were made with, so keep them per game and patch.
+        let savestates_path = savestates_path.join(match patch.as_ref() {
+            Some((name, version)) => format!(
+                "{}-{}-{}-{}",
+                game.family_and_variant().0,
+                game.family_and_variant().1,
+                name,
+                version
+            ),
+            None => format!("{}-{}", game.family_and_variant().0, game.family_and_variant().1),
+        });
+
         Ok(Session {
             start_time: std::time::SystemTime::now(),
             game_info: GameInfo { game, patch },
@@ -305,7 +522,13 @@ impl Session {
             _audio_binding: audio_binding,
             thread,
             joyflags,
-            mode: Mode::SinglePlayer(SinglePlayer {}),
+            mode: Mode::SinglePlayer(SinglePlayer {
+                savestates_path,
+                rewinding,
+                turbo_keys,
+                turbo_interval_frames,
+                cheats,
+            }),
             pause_on_next_frame,
             completion_flag: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
             own_setup: None,
@@ -333,6 +556,13 @@ impl Session {
 
         let replay_is_complete = replay.is_complete;
         let input_pairs = replay.input_pairs.clone();
+        let fastforwarder = replayer::Fastforwarder::new(
+            rom,
+            hooks,
+            (replay.metadata.match_type as u8, replay.metadata.match_subtype as u8),
+            replay.local_player_index,
+        )?;
+
         let replayer_state = replayer::State::new(
             (replay.metadata.match_type as u8, replay.metadata.match_subtype as u8),
             replay.local_player_index,
@@ -348,6 +578,7 @@ impl Session {
         let mut traps = hooks.common_traps();
         traps.extend(hooks.replayer_traps(replayer_state.clone()));
         core.set_traps(traps);
+        core.enable_debugger();
 
         let thread = mgba::thread::Thread::new(core);
 
@@ -367,11 +598,7 @@ impl Session {
         thread.handle().unpause();
 
         let pause_on_next_frame = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let vbuf = Arc::new(Mutex::new(vec![
-            0u8;
-            (mgba::gba::SCREEN_WIDTH * mgba::gba::SCREEN_HEIGHT * 4)
-                as usize
-        ]));
+        let vbuf = Arc::new(Mutex::new(VBuf::new()));
         thread.set_frame_callback({
             let vbuf = vbuf.clone();
             let emu_tps_counter = emu_tps_counter.clone();
@@ -379,9 +606,7 @@ impl Session {
             let replayer_state = replayer_state.clone();
             let pause_on_next_frame = pause_on_next_frame.clone();
             move |_core, video_buffer, mut thread_handle| {
-                let mut vbuf = vbuf.lock();
-                vbuf.copy_from_slice(video_buffer);
-                video::fix_vbuf_alpha(&mut *vbuf);
+                vbuf.lock().set(video_buffer);
                 emu_tps_counter.lock().mark();
 
                 if !replay_is_complete && replayer_state.lock_inner().input_pairs_left() == 0 {
@@ -403,7 +628,14 @@ impl Session {
             _audio_binding: audio_binding,
             thread,
             joyflags: Arc::new(std::sync::atomic::AtomicU32::new(0)),
-            mode: Mode::Replayer,
+            mode: Mode::Replayer(Replayer {
+                replay: replay.clone(),
+                replayer_state,
+                fastforwarder: Mutex::new(fastforwarder),
+                rom: rom.to_vec(),
+                hooks,
+                rebuilt_keyframes: Arc::new(Mutex::new(RebuiltKeyframes::NotStarted)),
+            }),
             completion_flag,
             pause_on_next_frame,
             own_setup: None,
@@ -411,6 +643,128 @@ impl Session {
         })
     }
 
+    pub fn new_spectator(
+        audio_binder: audio::LateBinder,
+        game: &'static (dyn game::Game + Send + Sync),
+        patch: Option<(String, semver::Version)>,
+        rom: &[u8],
+        emu_tps_counter: Arc<Mutex<stats::Counter>>,
+        first_round_start: spectator::RoundStart,
+        mut receiver: spectator::Receiver,
+        view_remote: bool,
+    ) -> Result<Self, anyhow::Error> {
+        let mut core = mgba::core::Core::new_gba("tango")?;
+        core.enable_video_buffer();
+
+        core.as_mut().load_rom(mgba::vfile::VFile::open_memory(&rom))?;
+
+        let hooks = game.hooks();
+        hooks.patch(core.as_mut());
+
+        let completion_flag = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let metadata = first_round_start.metadata()?;
+        let replayer_state = replayer::State::new(
+            (metadata.match_type as u8, metadata.match_subtype as u8),
+            first_round_start.local_player_index,
+            vec![],
+            0,
+            Box::new(|| {}),
+        );
+        let mut traps = hooks.common_traps();
+        traps.extend(hooks.replayer_traps(replayer_state.clone()));
+        core.set_traps(traps);
+
+        let thread = mgba::thread::Thread::new(core);
+
+        thread.start()?;
+        thread.handle().pause();
+        thread.handle().lock_audio().sync_mut().set_fps_target(EXPECTED_FPS);
+
+        let audio_binding = audio_binder.bind(Some(Box::new(audio::MGBAStream::new(
+            thread.handle(),
+            audio_binder.sample_rate(),
+        ))))?;
+
+        // Set while we are waiting for enough inputs to be buffered before playing.
+        let buffering = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+
+        // Set once the match has told us the current round is over, so we can play out whatever is left.
+        let upstream_round_ended = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let vbuf = Arc::new(Mutex::new(VBuf::new()));
+        thread.set_frame_callback({
+            let vbuf = vbuf.clone();
+            let emu_tps_counter = emu_tps_counter.clone();
+            let replayer_state = replayer_state.clone();
+            let buffering = buffering.clone();
+            let upstream_round_ended = upstream_round_ended.clone();
+            move |_core, video_buffer, mut thread_handle| {
+                vbuf.lock().set(video_buffer);
+                emu_tps_counter.lock().mark();
+
+                let replayer_state = replayer_state.lock_inner();
+                if replayer_state.is_round_ended() {
+                    // Wait for the next round to start.
+                    thread_handle.pause();
+                    return;
+                }
+
+                if !upstream_round_ended.load(std::sync::atomic::Ordering::SeqCst)
+                    && !replayer_state.is_round_ending()
+                    && replayer_state.input_pairs_left() == 0
+                {
+                    // We've caught up to the match, so wait for more inputs to arrive.
+                    buffering.store(true, std::sync::atomic::Ordering::SeqCst);
+                    thread_handle.pause();
+                }
+            }
+        });
+
+        let cancellation_token = tokio_util::sync::CancellationToken::new();
+        tokio::task::spawn({
+            let thread_handle = thread.handle();
+            let completion_flag = completion_flag.clone();
+            let cancellation_token = cancellation_token.clone();
+            let replayer_state = replayer_state.clone();
+            let first_round_start = if view_remote {
+                first_round_start.into_remote()?
+            } else {
+                first_round_start
+            };
+            async move {
+                let r = tokio::select! {
+                    r = run_spectator_stream(
+                        &mut receiver,
+                        first_round_start,
+                        view_remote,
+                        &thread_handle,
+                        &replayer_state,
+                        &buffering,
+                        &upstream_round_ended,
+                    ) => r,
+                    _ = cancellation_token.cancelled() => Ok(()),
+                };
+                log::info!("spectator stream ended: {:?}", r);
+                completion_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        });
+
+        Ok(Session {
+            start_time: std::time::SystemTime::now(),
+            game_info: GameInfo { game, patch },
+            vbuf,
+            _audio_binding: audio_binding,
+            thread,
+            joyflags: Arc::new(std::sync::atomic::AtomicU32::new(0)),
+            mode: Mode::Spectator(Spectator { cancellation_token }),
+            completion_flag,
+            pause_on_next_frame: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            own_setup: None,
+            opponent_setup: None,
+        })
+    }
+
     pub fn completed(&self) -> bool {
         self.completion_flag.load(std::sync::atomic::Ordering::SeqCst)
     }
@@ -440,6 +794,201 @@ impl Session {
         handle.unpause();
     }
 
+    pub fn seek_replay(&self, tick: u32) -> Result<(), anyhow::Error> {
+        let replayer = if let Mode::Replayer(replayer) = &self.mode {
+            replayer
+        } else {
+            anyhow::bail!("not a replay");
+        };
+
+        let tick = std::cmp::min(tick, replayer.replay.last_tick());
+        let keyframe = replayer.keyframe_for_tick(tick);
+        let input_pairs = replayer
+            .replay
+            .input_pairs
+            .iter()
+            .filter(|ip| ip.local.local_tick >= keyframe.tick)
+            .cloned()
+            .collect::<Vec<_>>();
+        let first_input_pair = if let Some(ip) = input_pairs.first() {
+            ip
+        } else {
+            anyhow::bail!("no inputs to seek to");
+        };
+
+        let state = if tick == keyframe.tick {
+            keyframe.state
+        } else {
+            let mut remote_packets = input_pairs
+                .iter()
+                .map(|ip| ip.remote.packet.clone())
+                .collect::<std::collections::VecDeque<_>>();
+            replayer
+                .fastforwarder
+                .lock()
+                .fastforward(
+                    &keyframe.state,
+                    input_pairs
+                        .iter()
+                        .take_while(|ip| ip.local.local_tick <= tick)
+                        .map(|ip| lockstep::Pair {
+                            local: ip.local.to_partial(),
+                            remote: ip.remote.to_partial(),
+                        })
+                        .collect(),
+                    keyframe.tick,
+                    tick,
+                    tick,
+                    &first_input_pair.local.packet,
+                    Box::new(move |_| {
+                        remote_packets
+                            .pop_front()
+                            .ok_or_else(|| anyhow::anyhow!("no more committed inputs"))
+                    }),
+                )?
+                .committed_state
+                .state
+        };
+
+        let handle = self.thread.handle();
+        let was_paused = handle.is_paused();
+        handle.pause();
+        self.completion_flag.store(false, std::sync::atomic::Ordering::SeqCst);
+        replayer.replayer_state.reset(
+            (
+                replayer.replay.metadata.match_type as u8,
+                replayer.replay.metadata.match_subtype as u8,
+            ),
+            replayer.replay.local_player_index,
+            input_pairs
+                .into_iter()
+                .filter(|ip| ip.local.local_tick >= tick)
+                .collect(),
+            tick,
+            tick,
+            Box::new({
+                let completion_flag = self.completion_flag.clone();
+                move || {
+                    completion_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+                }
+            }),
+        );
+        handle.run_on_core(move |mut core| {
+            core.load_state(&state).expect("load state");
+        });
+
+        if was_paused {
+            // Run a single frame so the new position is shown.
+            self.frame_step();
+        } else {
+            handle.unpause();
+        }
+        Ok(())
+    }
+
+    pub fn save_state_to_slot(&self, slot: usize) -> Result<(), anyhow::Error> {
+        let single_player = if let Mode::SinglePlayer(single_player) = &self.mode {
+            single_player
+        } else {
+            anyhow::bail!("save states are only available in single player");
+        };
+
+        // run_on_core only returns once the function has run, so the result is there by then.
+        let result = Arc::new(Mutex::new(None));
+        self.thread.handle().run_on_core({
+            let result = result.clone();
+            move |core| {
+                *result.lock() = Some(core.save_state());
+            }
+        });
+        let state = result
+            .lock()
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("core did not save state"))??;
+        std::fs::create_dir_all(&single_player.savestates_path)?;
+        std::fs::write(single_player.savestate_path(slot), state.as_slice())?;
+        Ok(())
+    }
+
+    pub fn load_state_from_slot(&self, slot: usize) -> Result<(), anyhow::Error> {
+        let single_player = if let Mode::SinglePlayer(single_player) = &self.mode {
+            single_player
+        } else {
+            anyhow::bail!("save states are only available in single player");
+        };
+
+        let buf = std::fs::read(single_player.savestate_path(slot))?;
+        if buf.len() != mgba::state::State::SIZE {
+            anyhow::bail!(
+                "save state is the wrong size: {} != {}",
+                buf.len(),
+                mgba::state::State::SIZE
+            );
+        }
+        let state = mgba::state::State::from_slice(&buf);
+
+        let result = Arc::new(Mutex::new(None));
+        self.thread.handle().run_on_core({
+            let result = result.clone();
+            move |mut core| {
+                *result.lock() = Some(core.load_state(&state));
+            }
+        });
+        let r = result.lock().take();
+        r.ok_or_else(|| anyhow::anyhow!("core did not load state"))?
+    }
+
+    /// While rewinding, the emulator steps backwards through recent states instead of running forwards.
+    pub fn set_rewinding(&self, rewinding: bool) {
+        if let Mode::SinglePlayer(single_player) = &self.mode {
+            single_player
+                .rewinding
+                .store(rewinding, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the player may change the speed of the emulator. Never in netplay, where both sides must run in step.
+    pub fn allows_speed_change(&self) -> bool {
+        matches!(self.mode, Mode::SinglePlayer(_) | Mode::Replayer(_))
+    }
+
+    /// Runs the emulator as fast as it can, instead of pacing it to the audio.
+    pub fn set_uncapped(&self, uncapped: bool) {
+        if !self.allows_speed_change() {
+            return;
+        }
+        let handle = self.thread.handle();
+        let audio_guard = handle.lock_audio();
+        audio_guard.sync_mut().set_audio_wait(!uncapped);
+    }
+
+    pub fn is_uncapped(&self) -> bool {
+        let handle = self.thread.handle();
+        let audio_guard = handle.lock_audio();
+        !audio_guard.sync().audio_wait()
+    }
+
+    /// Sets the keys that turbo, i.e. are automatically pressed and released every interval_frames frames.
+    pub fn set_turbo(&self, keys: u32, interval_frames: u32) {
+        if let Mode::SinglePlayer(single_player) = &self.mode {
+            single_player
+                .turbo_keys
+                .store(keys, std::sync::atomic::Ordering::Relaxed);
+            single_player
+                .turbo_interval_frames
+                .store(interval_frames, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Frozen values and codes, which are only available in single player: writing to memory would desync PvP and
+    /// replays.
+    pub fn cheats(&self) -> Option<&Mutex<cheats::Cheats>> {
+        match &self.mode {
+            Mode::SinglePlayer(single_player) => Some(&single_player.cheats),
+            Mode::PvP(_) | Mode::Replayer(_) | Mode::Spectator(_) => None,
+        }
+    }
+
     pub fn set_fps_target(&self, fps: f32) {
         let handle = self.thread.handle();
         let audio_guard = handle.lock_audio();
@@ -467,7 +1016,7 @@ impl Session {
         }
     }
 
-    pub fn lock_vbuf(&self) -> parking_lot::MutexGuard<Vec<u8>> {
+    pub fn lock_vbuf(&self) -> parking_lot::MutexGuard<VBuf> {
         self.vbuf.lock()
     }
 
@@ -502,7 +1051,94 @@ impl Drop for Session {
             Mode::PvP(pvp) => {
                 pvp.cancellation_token.cancel();
             }
+            Mode::Spectator(spectator) => {
+                spectator.cancellation_token.cancel();
+            }
             _ => {}
         }
     }
 }
+
+fn start_spectator_round(
+    round_start: spectator::RoundStart,
+    thread_handle: &mgba::thread::Handle,
+    replayer_state: &replayer::State,
+    buffering: &std::sync::atomic::AtomicBool,
+    upstream_round_ended: &std::sync::atomic::AtomicBool,
+) -> anyhow::Result<()> {
+    let metadata = round_start.metadata()?;
+    thread_handle.pause();
+    buffering.store(true, std::sync::atomic::Ordering::SeqCst);
+    upstream_round_ended.store(false, std::sync::atomic::Ordering::SeqCst);
+    replayer_state.reset(
+        (metadata.match_type as u8, metadata.match_subtype as u8),
+        round_start.local_player_index,
+        vec![],
+        0,
+        0,
+        Box::new(|| {}),
+    );
+    let local_state = mgba::state::State::from_slice(&round_start.local_state);
+    thread_handle.run_on_core(move |mut core| {
+        core.load_state(&local_state).expect("load state");
+    });
+    log::info!("spectating round {}", metadata.round);
+    Ok(())
+}
+
+async fn run_spectator_stream(
+    receiver: &mut spectator::Receiver,
+    first_round_start: spectator::RoundStart,
+    view_remote: bool,
+    thread_handle: &mgba::thread::Handle,
+    replayer_state: &replayer::State,
+    buffering: &std::sync::atomic::AtomicBool,
+    upstream_round_ended: &std::sync::atomic::AtomicBool,
+) -> anyhow::Result<()> {
+    start_spectator_round(
+        first_round_start,
+        thread_handle,
+        replayer_state,
+        buffering,
+        upstream_round_ended,
+    )?;
+    loop {
+        match receiver.receive().await? {
+            spectator::Packet::RoundStart(round_start) => {
+                start_spectator_round(
+                    if view_remote {
+                        round_start.into_remote()?
+                    } else {
+                        round_start
+                    },
+                    thread_handle,
+                    replayer_state,
+                    buffering,
+                    upstream_round_ended,
+                )?;
+            }
+            spectator::Packet::Input(input) => {
+                let input = if view_remote { input.into_remote() } else { input };
+                let input_pairs_left = {
+                    let mut replayer_state = replayer_state.lock_inner();
+                    replayer_state.add_input_pair(input.into_pair());
+                    replayer_state.input_pairs_left()
+                };
+                if input_pairs_left >= spectator::BUFFER_TICKS
+                    && buffering.swap(false, std::sync::atomic::Ordering::SeqCst)
+                {
+                    thread_handle.unpause();
+                }
+            }
+            spectator::Packet::RoundEnd(_) => {
+                upstream_round_ended.store(true, std::sync::atomic::Ordering::SeqCst);
+                if buffering.swap(false, std::sync::atomic::Ordering::SeqCst) {
+                    thread_handle.unpause();
+                }
+            }
+            p => {
+                anyhow::bail!("unexpected packet: {:?}", p);
+            }
+        }
+    }
+}
@@ -247,6 +247,15 @@ impl Shadow {
         }
     }
 
+    pub fn desync_check_snapshot(&mut self) -> Vec<u8> {
+        let mut core = self.core.as_mut();
+        game::read_desync_check_regions(self.hooks, |address, buf| core.raw_read_range(address, -1, buf))
+    }
+
+    pub fn save_state(&mut self) -> anyhow::Result<mgba::state::State> {
+        Ok(self.core.as_mut().save_state()?)
+    }
+
     pub fn apply_input(
         &mut self,
         ip: lockstep::Pair<lockstep::Input, lockstep::PartialInput>,
@@ -0,0 +1,355 @@
+// Renders a setup sheet: a single image with everything about a save that's worth sharing, for posting outside of
+// Tango.
+
+pub mod navicust;
+
+use fluent_templates::Loader;
+
+use crate::{fonts, i18n, rom, save};
+
+const PADDING: u32 = 20;
+const SECTION_SPACING: u32 = 24;
+const ROW_HEIGHT: u32 = 36;
+const ICON_SIZE: u32 = 32;
+const TEXT_PX: f32 = 22.0;
+const HEADING_PX: f32 = 28.0;
+const FOLDER_COLUMN_WIDTH: u32 = 320;
+
+const BG_COLOR: image::Rgba<u8> = image::Rgba([0x20, 0x20, 0x20, 0xff]);
+const TEXT_COLOR: image::Rgba<u8> = image::Rgba([0xff, 0xff, 0xff, 0xff]);
+const DIM_TEXT_COLOR: image::Rgba<u8> = image::Rgba([0x80, 0x80, 0x80, 0xff]);
+const REG_COLOR: image::Rgba<u8> = image::Rgba([0xff, 0x42, 0xa5, 0xff]);
+const TAG_COLOR: image::Rgba<u8> = image::Rgba([0x29, 0xf7, 0x21, 0xff]);
+
+// How many of the most used chips to show from the dark AI.
+const MAX_DARK_AI_CHIPS: usize = 10;
+
+struct Text {
+    font: fontdue::Font,
+}
+
+impl Text {
+    fn new(raw_font: &[u8]) -> Self {
+        Self {
+            font: fontdue::Font::from_bytes(raw_font, fontdue::FontSettings::default()).unwrap(),
+        }
+    }
+
+    fn render(&self, s: &str, px: f32, color: image::Rgba<u8>) -> image::RgbaImage {
+        let mut layout = fontdue::layout::Layout::new(fontdue::layout::CoordinateSystem::PositiveYDown);
+        layout.append(&[&self.font], &fontdue::layout::TextStyle::new(s, px, 0));
+
+        let width = layout
+            .glyphs()
+            .iter()
+            .map(|glyph| glyph.x as u32 + glyph.width as u32)
+            .max()
+            .unwrap_or(0);
+        let mut image = image::RgbaImage::new(width.max(1), layout.height().ceil() as u32);
+
+        for glyph in layout.glyphs() {
+            let (metrics, coverage) = self.font.rasterize(glyph.parent, px);
+            let g = image::RgbaImage::from_vec(
+                metrics.width as u32,
+                metrics.height as u32,
+                coverage
+                    .into_iter()
+                    .flat_map(|a| {
+                        [
+                            color[0],
+                            color[1],
+                            color[2],
+                            ((a as u16 * color[3] as u16) / 0xff) as u8,
+                        ]
+                    })
+                    .collect(),
+            )
+            .unwrap();
+            image::imageops::overlay(&mut image, &g, glyph.x as i64, glyph.y as i64);
+        }
+
+        image
+    }
+}
+
+fn scale_icon(icon: &image::RgbaImage) -> image::RgbaImage {
+    image::imageops::resize(icon, ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Nearest)
+}
+
+// Draws an image vertically centered in a row starting at y.
+fn overlay_in_row(image: &mut image::RgbaImage, top: &image::RgbaImage, x: u32, y: u32) {
+    image::imageops::overlay(
+        image,
+        top,
+        x as i64,
+        y as i64 + (ROW_HEIGHT as i64 - top.height() as i64) / 2,
+    );
+}
+
+// Stacks images top to bottom.
+fn stack_vertical(images: &[image::RgbaImage]) -> image::RgbaImage {
+    let width = images.iter().map(|image| image.width()).max().unwrap_or(0);
+    let height =
+        images.iter().map(|image| image.height()).sum::<u32>() + SECTION_SPACING * (images.len().max(1) as u32 - 1);
+    let mut stacked = image::RgbaImage::new(width.max(1), height.max(1));
+    let mut y = 0;
+    for image in images {
+        image::imageops::overlay(&mut stacked, image, 0, y as i64);
+        y += image.height() + SECTION_SPACING;
+    }
+    stacked
+}
+
+fn render_section(heading: &str, body: image::RgbaImage, ui_text: &Text) -> image::RgbaImage {
+    stack_vertical(&[ui_text.render(heading, HEADING_PX, TEXT_COLOR), body])
+}
+
+fn render_folder<'a>(
+    chips_view: &Box<dyn save::ChipsView<'a> + 'a>,
+    assets: &Box<dyn rom::Assets + Send + Sync>,
+    game_text: &Text,
+) -> image::RgbaImage {
+    let folder_index = chips_view.equipped_folder_index();
+    let chips = chips_view.folder_chips(folder_index);
+
+    let regular_chip_index = chips_view.regular_chip_index(folder_index);
+    let tag_chip_indexes = chips_view.tag_chip_indexes(folder_index);
+
+    // Two columns of 15 chips each.
+    let rows = (chips.len() as u32 + 1) / 2;
+    let mut image = image::RgbaImage::new(FOLDER_COLUMN_WIDTH * 2, rows * ROW_HEIGHT);
+
+    for (i, chip) in chips.iter().enumerate() {
+        let chip = if let Some(chip) = chip {
+            chip
+        } else {
+            continue;
+        };
+
+        let x = (i as u32 / rows) * FOLDER_COLUMN_WIDTH;
+        let y = (i as u32 % rows) * ROW_HEIGHT;
+
+        let info = assets.chip(chip.id);
+        let mut cx = x;
+
+        if let Some(info) = info.as_ref() {
+            overlay_in_row(&mut image, &scale_icon(&info.icon()), cx, y);
+        }
+        cx += ICON_SIZE + 4;
+
+        if let Some(element_icon) = info.as_ref().and_then(|info| assets.element_icon(info.element())) {
+            overlay_in_row(&mut image, &scale_icon(&element_icon), cx, y);
+        }
+        cx += ICON_SIZE + 8;
+
+        let code = game_text.render(&chip.code.to_string(), TEXT_PX, TEXT_COLOR);
+        overlay_in_row(&mut image, &code, cx, y);
+        cx += (TEXT_PX as u32) + 4;
+
+        let color = if regular_chip_index == Some(i) {
+            REG_COLOR
+        } else if tag_chip_indexes.map(|is| is.contains(&i)).unwrap_or(false) {
+            TAG_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        let name = game_text.render(
+            &info
+                .as_ref()
+                .map(|info| info.name())
+                .unwrap_or_else(|| format!("#{}", chip.id)),
+            TEXT_PX,
+            color,
+        );
+        overlay_in_row(&mut image, &name, cx, y);
+    }
+
+    image
+}
+
+fn render_modcards(
+    modcards_view: &save::ModcardsView,
+    assets: &Box<dyn rom::Assets + Send + Sync>,
+    game_text: &Text,
+) -> image::RgbaImage {
+    let rows = match modcards_view {
+        save::ModcardsView::Modcard4s(modcards_view) => (0..6)
+            .flat_map(|slot| modcards_view.modcard(slot))
+            .map(|modcard| {
+                (
+                    assets.modcard4(modcard.id).map(|info| info.name()),
+                    modcard.id,
+                    modcard.enabled,
+                )
+            })
+            .collect::<Vec<_>>(),
+        save::ModcardsView::Modcard56s(modcards_view) => (0..modcards_view.count())
+            .flat_map(|slot| modcards_view.modcard(slot))
+            .map(|modcard| {
+                (
+                    assets.modcard56(modcard.id).map(|info| info.name()),
+                    modcard.id,
+                    modcard.enabled,
+                )
+            })
+            .collect::<Vec<_>>(),
+    };
+
+    let rendered = rows
+        .into_iter()
+        .map(|(name, id, enabled)| {
+            game_text.render(
+                &name.unwrap_or_else(|| format!("#{}", id)),
+                TEXT_PX,
+                if enabled { TEXT_COLOR } else { DIM_TEXT_COLOR },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let width = rendered.iter().map(|image| image.width()).max().unwrap_or(0);
+    let mut image = image::RgbaImage::new(width.max(1), (rendered.len() as u32 * ROW_HEIGHT).max(1));
+    for (i, name) in rendered.iter().enumerate() {
+        overlay_in_row(&mut image, name, 0, i as u32 * ROW_HEIGHT);
+    }
+    image
+}
+
+fn render_dark_ai<'a>(
+    dark_ai_view: &Box<dyn save::DarkAIView + 'a>,
+    assets: &Box<dyn rom::Assets + Send + Sync>,
+    game_text: &Text,
+) -> image::RgbaImage {
+    let mut use_counts = vec![];
+    while let Some(count) = dark_ai_view.chip_use_count(use_counts.len()) {
+        use_counts.push(count);
+    }
+
+    let mut most_used = use_counts
+        .into_iter()
+        .enumerate()
+        .filter(|(_, count)| *count > 0)
+        .collect::<Vec<_>>();
+    most_used.sort_by_key(|(id, count)| (std::cmp::Reverse(*count), *id));
+    most_used.truncate(MAX_DARK_AI_CHIPS);
+
+    let rows = most_used
+        .into_iter()
+        .map(|(id, count)| {
+            let info = assets.chip(id);
+            (
+                info.as_ref().map(|info| scale_icon(&info.icon())),
+                game_text.render(
+                    &format!(
+                        "{} ×{}",
+                        info.as_ref()
+                            .map(|info| info.name())
+                            .unwrap_or_else(|| format!("#{}", id)),
+                        count
+                    ),
+                    TEXT_PX,
+                    TEXT_COLOR,
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let width = rows
+        .iter()
+        .map(|(_, text)| ICON_SIZE + 8 + text.width())
+        .max()
+        .unwrap_or(0);
+    let mut image = image::RgbaImage::new(width.max(1), (rows.len() as u32 * ROW_HEIGHT).max(1));
+    for (i, (icon, text)) in rows.iter().enumerate() {
+        let y = i as u32 * ROW_HEIGHT;
+        if let Some(icon) = icon {
+            overlay_in_row(&mut image, icon, 0, y);
+        }
+        overlay_in_row(&mut image, text, ICON_SIZE + 8, y);
+    }
+    image
+}
+
+/// Renders a setup sheet for a save.
+///
+/// Headings are in the UI language, everything else in the game's language.
+pub fn render(
+    lang: &unic_langid::LanguageIdentifier,
+    game_lang: &unic_langid::LanguageIdentifier,
+    save: &Box<dyn save::Save + Send + Sync>,
+    assets: &Box<dyn rom::Assets + Send + Sync>,
+) -> image::RgbaImage {
+    let ui_text = Text::new(fonts::raw_for_language(lang));
+    let game_text = Text::new(fonts::raw_for_language(game_lang));
+
+    let mut left = vec![];
+    let mut right = vec![];
+
+    if let Some(navi) = save.view_navi().and_then(|navi_view| assets.navi(navi_view.navi())) {
+        let emblem = scale_icon(&navi.emblem());
+        let name = game_text.render(&navi.name(), HEADING_PX, TEXT_COLOR);
+        let mut header = image::RgbaImage::new(ICON_SIZE + 8 + name.width(), ROW_HEIGHT);
+        overlay_in_row(&mut header, &emblem, 0, 0);
+        overlay_in_row(&mut header, &name, ICON_SIZE + 8, 0);
+        left.push(header);
+    }
+
+    if let Some(chips_view) = save.view_chips() {
+        left.push(render_section(
+            &i18n::LOCALES.lookup(lang, "save-tab-folder").unwrap(),
+            render_folder(&chips_view, assets, &game_text),
+            &ui_text,
+        ));
+    }
+
+    if let Some(navicust_view) = save.view_navicust() {
+        let composed = navicust::compose_navicust(&navicust_view, assets);
+        let navicust = navicust::render_navicust(&composed, &navicust_view, assets, fonts::raw_for_language(game_lang));
+        // The navicust is rendered at twice the size it's displayed at.
+        let navicust = image::imageops::resize(
+            &navicust,
+            navicust.width() / 2,
+            navicust.height() / 2,
+            image::imageops::FilterType::Triangle,
+        );
+        right.push(render_section(
+            &i18n::LOCALES.lookup(lang, "save-tab-navicust").unwrap(),
+            navicust,
+            &ui_text,
+        ));
+    }
+
+    if let Some(modcards_view) = save.view_modcards() {
+        right.push(render_section(
+            &i18n::LOCALES.lookup(lang, "save-tab-modcards").unwrap(),
+            render_modcards(&modcards_view, assets, &game_text),
+            &ui_text,
+        ));
+    }
+
+    if let Some(dark_ai_view) = save.view_dark_ai() {
+        right.push(render_section(
+            &i18n::LOCALES.lookup(lang, "save-tab-dark-ai").unwrap(),
+            render_dark_ai(&dark_ai_view, assets, &game_text),
+            &ui_text,
+        ));
+    }
+
+    let columns = [left, right]
+        .into_iter()
+        .filter(|sections| !sections.is_empty())
+        .map(|sections| stack_vertical(&sections))
+        .collect::<Vec<_>>();
+
+    let width = columns.iter().map(|column| column.width()).sum::<u32>()
+        + SECTION_SPACING * (columns.len().max(1) as u32 - 1)
+        + PADDING * 2;
+    let height = columns.iter().map(|column| column.height()).max().unwrap_or(0) + PADDING * 2;
+
+    let mut image = image::RgbaImage::from_pixel(width, height, BG_COLOR);
+    let mut x = PADDING;
+    for column in columns.iter() {
+        image::imageops::overlay(&mut image, column, x as i64, PADDING as i64);
+        x += column.width() + SECTION_SPACING;
+    }
+    image
+}
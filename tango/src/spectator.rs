@@ -0,0 +1,286 @@
+use bincode::Options;
+use byteorder::ByteOrder;
+use prost::Message;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{lockstep, replay};
+
+pub const VERSION: u8 = 0x01;
+
+// How many committed ticks a viewer buffers before it starts (or resumes) playing.
+pub const BUFFER_TICKS: usize = 3 * 60;
+
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+const CHANNEL_CAPACITY: usize = 4096;
+
+lazy_static! {
+    static ref BINCODE_OPTIONS: bincode::config::WithOtherIntEncoding<bincode::config::DefaultOptions, bincode::config::VarintEncoding> =
+        bincode::DefaultOptions::new().with_varint_encoding();
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub enum Packet {
+    Hello(Hello),
+    RoundStart(RoundStart),
+    Input(Input),
+    RoundEnd(RoundEnd),
+}
+
+impl Packet {
+    pub fn serialize(&self) -> bincode::Result<Vec<u8>> {
+        BINCODE_OPTIONS.serialize(self)
+    }
+
+    pub fn deserialize(d: &[u8]) -> bincode::Result<Self> {
+        BINCODE_OPTIONS.deserialize(d)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Hello {
+    pub protocol_version: u8,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct RoundStart {
+    pub raw_metadata: Vec<u8>,
+    pub local_player_index: u8,
+    pub local_state: Vec<u8>,
+    pub remote_state: Vec<u8>,
+}
+
+impl std::fmt::Debug for RoundStart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RoundStart")
+            .field("local_player_index", &self.local_player_index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RoundStart {
+    pub fn metadata(&self) -> Result<replay::Metadata, prost::DecodeError> {
+        replay::Metadata::decode(&self.raw_metadata[..])
+    }
+
+    pub fn into_remote(mut self) -> Result<Self, prost::DecodeError> {
+        let mut metadata = self.metadata()?;
+        std::mem::swap(&mut metadata.local_side, &mut metadata.remote_side);
+        self.raw_metadata = metadata.encode_to_vec();
+        self.local_player_index = 1 - self.local_player_index;
+        std::mem::swap(&mut self.local_state, &mut self.remote_state);
+        Ok(self)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Input {
+    pub local_tick: u32,
+    pub remote_tick: u32,
+    pub local_joyflags: u16,
+    pub local_packet: Vec<u8>,
+    pub remote_joyflags: u16,
+    pub remote_packet: Vec<u8>,
+}
+
+impl Input {
+    pub fn into_remote(self) -> Self {
+        Self {
+            local_tick: self.local_tick,
+            remote_tick: self.remote_tick,
+            local_joyflags: self.remote_joyflags,
+            local_packet: self.remote_packet,
+            remote_joyflags: self.local_joyflags,
+            remote_packet: self.local_packet,
+        }
+    }
+
+    pub fn into_pair(self) -> lockstep::Pair<lockstep::Input, lockstep::Input> {
+        // This mirrors what replay::Replay::decode produces.
+        lockstep::Pair {
+            local: lockstep::Input {
+                local_tick: self.local_tick,
+                remote_tick: self.remote_tick,
+                joyflags: self.local_joyflags,
+                packet: self.local_packet,
+            },
+            remote: lockstep::Input {
+                local_tick: self.local_tick,
+                remote_tick: self.local_tick,
+                joyflags: self.remote_joyflags,
+                packet: self.remote_packet,
+            },
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RoundEnd {}
+
+struct BroadcasterInner {
+    round_backlog: Vec<std::sync::Arc<Vec<u8>>>,
+    tx: tokio::sync::broadcast::Sender<std::sync::Arc<Vec<u8>>>,
+}
+
+impl BroadcasterInner {
+    fn send(&mut self, p: &Packet) {
+        let raw = std::sync::Arc::new(p.serialize().unwrap());
+        self.round_backlog.push(raw.clone());
+        // It's fine if there are no viewers.
+        let _ = self.tx.send(raw);
+    }
+}
+
+// Relays committed inputs of the current match to any connected viewers.
+//
+// The current round is kept in a backlog so viewers that connect mid-round can catch up.
+#[derive(Clone)]
+pub struct Broadcaster(std::sync::Arc<parking_lot::Mutex<BroadcasterInner>>);
+
+impl Broadcaster {
+    pub fn new() -> Self {
+        let (tx, _) = tokio::sync::broadcast::channel(CHANNEL_CAPACITY);
+        Self(std::sync::Arc::new(parking_lot::Mutex::new(BroadcasterInner {
+            round_backlog: vec![],
+            tx,
+        })))
+    }
+
+    pub fn start_round(
+        &self,
+        metadata: &replay::Metadata,
+        local_player_index: u8,
+        local_state: &mgba::state::State,
+        remote_state: &mgba::state::State,
+    ) {
+        let mut inner = self.0.lock();
+        inner.round_backlog.clear();
+        inner.send(&Packet::RoundStart(RoundStart {
+            raw_metadata: metadata.encode_to_vec(),
+            local_player_index,
+            local_state: local_state.as_slice().to_vec(),
+            remote_state: remote_state.as_slice().to_vec(),
+        }));
+    }
+
+    pub fn send_input(&self, ip: &lockstep::Pair<lockstep::Input, lockstep::Input>) {
+        self.0.lock().send(&Packet::Input(Input {
+            local_tick: ip.local.local_tick,
+            remote_tick: ip.local.remote_tick,
+            local_joyflags: ip.local.joyflags,
+            local_packet: ip.local.packet.clone(),
+            remote_joyflags: ip.remote.joyflags,
+            remote_packet: ip.remote.packet.clone(),
+        }));
+    }
+
+    pub fn end_round(&self) {
+        self.0.lock().send(&Packet::RoundEnd(RoundEnd {}));
+    }
+
+    fn subscribe(
+        &self,
+    ) -> (
+        Vec<std::sync::Arc<Vec<u8>>>,
+        tokio::sync::broadcast::Receiver<std::sync::Arc<Vec<u8>>>,
+    ) {
+        let inner = self.0.lock();
+        (inner.round_backlog.clone(), inner.tx.subscribe())
+    }
+}
+
+async fn write_frame(w: &mut (impl tokio::io::AsyncWrite + Unpin), raw: &[u8]) -> std::io::Result<()> {
+    let mut len = [0u8; 4];
+    byteorder::LittleEndian::write_u32(&mut len, raw.len() as u32);
+    w.write_all(&len).await?;
+    w.write_all(raw).await?;
+    Ok(())
+}
+
+async fn handle_viewer(mut stream: tokio::net::TcpStream, broadcaster: Broadcaster) -> anyhow::Result<()> {
+    stream.set_nodelay(true)?;
+    write_frame(
+        &mut stream,
+        &Packet::Hello(Hello {
+            protocol_version: VERSION,
+        })
+        .serialize()?,
+    )
+    .await?;
+
+    let (backlog, mut rx) = broadcaster.subscribe();
+    for raw in backlog {
+        write_frame(&mut stream, &raw).await?;
+    }
+
+    loop {
+        let raw = match rx.recv().await {
+            Ok(raw) => raw,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                break;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                anyhow::bail!("viewer lagged behind by {} packets", n);
+            }
+        };
+        write_frame(&mut stream, &raw).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn serve(listen_addr: String, broadcaster: Broadcaster) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(&listen_addr).await?;
+    log::info!("spectator server listening on {}", listener.local_addr()?);
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        log::info!("spectator connected: {}", addr);
+        tokio::task::spawn({
+            let broadcaster = broadcaster.clone();
+            async move {
+                let r = handle_viewer(stream, broadcaster).await;
+                log::info!("spectator disconnected: {}: {:?}", addr, r);
+            }
+        });
+    }
+}
+
+pub struct Receiver {
+    stream: tokio::net::TcpStream,
+}
+
+impl Receiver {
+    pub async fn connect(addr: &str) -> anyhow::Result<Self> {
+        let stream = tokio::net::TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let mut receiver = Self { stream };
+        match receiver.receive().await? {
+            Packet::Hello(hello) => {
+                if hello.protocol_version != VERSION {
+                    anyhow::bail!(
+                        "spectator protocol version mismatch: {:02x} != {:02x}",
+                        hello.protocol_version,
+                        VERSION
+                    );
+                }
+            }
+            p => {
+                anyhow::bail!("expected hello, got {:?}", p);
+            }
+        }
+        Ok(receiver)
+    }
+
+    pub async fn receive(&mut self) -> std::io::Result<Packet> {
+        let len = self.stream.read_u32_le().await? as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("frame too large: {}", len),
+            ));
+        }
+        let mut raw = vec![0u8; len];
+        self.stream.read_exact(&mut raw).await?;
+        Packet::deserialize(&raw).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
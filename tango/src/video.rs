@@ -1,9 +1,13 @@
+pub mod effects;
 pub mod hqx;
 pub mod mmpx;
+pub mod scalex;
+pub mod sharp_bilinear;
+pub mod xbrz;
 
 pub trait Filter {
     fn output_size(&self, size: (usize, usize)) -> (usize, usize);
-    fn apply(&self, input: &[u8], output: &mut [u8], size: (usize, usize));
+    fn apply(&mut self, input: &[u8], output: &mut [u8], size: (usize, usize));
 }
 
 pub struct NullFilter;
@@ -11,22 +15,107 @@ impl Filter for NullFilter {
     fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
         size
     }
-    fn apply(&self, input: &[u8], output: &mut [u8], _size: (usize, usize)) {
+    fn apply(&mut self, input: &[u8], output: &mut [u8], _size: (usize, usize)) {
         output.copy_from_slice(input)
     }
 }
 
-pub fn filter_by_name(name: &str) -> Option<Box<dyn Filter + Sync + Send>> {
+pub const FILTER_NAMES: &[&str] = &[
+    "hq2x",
+    "hq3x",
+    "hq4x",
+    "mmpx",
+    "xbrz2x",
+    "xbrz3x",
+    "xbrz4x",
+    "xbrz5x",
+    "xbrz6x",
+    "scale2x",
+    "scale3x",
+    "scale4x",
+    "sharp-bilinear",
+    "scanlines",
+    "lcd-grid",
+    "gba-color",
+    "ghosting",
+];
+
+/// Looks up a filter by name. `scale` is how much larger than the input the output will be shown, which only filters
+/// that can scale by any amount (i.e. sharp-bilinear) use.
+pub fn filter_by_name(name: &str, scale: f32) -> Option<Box<dyn Filter + Sync + Send>> {
     match name {
         "null" | "" => Some(Box::new(NullFilter)),
         "hq2x" => Some(Box::new(hqx::HQ2XFilter)),
         "hq3x" => Some(Box::new(hqx::HQ3XFilter)),
         "hq4x" => Some(Box::new(hqx::HQ4XFilter)),
         "mmpx" => Some(Box::new(mmpx::MMPXFilter)),
+        "xbrz2x" => Some(Box::new(xbrz::XBRZFilter(2))),
+        "xbrz3x" => Some(Box::new(xbrz::XBRZFilter(3))),
+        "xbrz4x" => Some(Box::new(xbrz::XBRZFilter(4))),
+        "xbrz5x" => Some(Box::new(xbrz::XBRZFilter(5))),
+        "xbrz6x" => Some(Box::new(xbrz::XBRZFilter(6))),
+        "scale2x" => Some(Box::new(scalex::Scale2XFilter)),
+        "scale3x" => Some(Box::new(scalex::Scale3XFilter)),
+        "scale4x" => Some(Box::new(scalex::Scale4XFilter)),
+        "sharp-bilinear" => Some(Box::new(sharp_bilinear::SharpBilinearFilter { scale })),
         _ => None,
     }
 }
 
+/// A chain of filters, each one taking the output of the one before.
+#[derive(Default)]
+pub struct Pipeline {
+    filters: Vec<Box<dyn Filter + Sync + Send>>,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Pipeline {
+    /// Builds a pipeline for images of `size`. `scale` is how much larger than `size` the final output will be shown.
+    pub fn new(names: &[String], size: (usize, usize), scale: f32) -> anyhow::Result<Self> {
+        let mut filters = vec![];
+        let mut current_size = size;
+        for name in names {
+            let filter = effects::by_name(name, size)
+                .or_else(|| filter_by_name(name, scale * size.0 as f32 / current_size.0 as f32))
+                .ok_or_else(|| anyhow::anyhow!("unknown filter: {}", name))?;
+            current_size = filter.output_size(current_size);
+            filters.push(filter);
+        }
+        Ok(Self {
+            filters,
+            buffers: vec![],
+        })
+    }
+}
+
+impl Filter for Pipeline {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        self.filters.iter().fold(size, |size, filter| filter.output_size(size))
+    }
+
+    fn apply(&mut self, input: &[u8], output: &mut [u8], size: (usize, usize)) {
+        let (last, rest) = match self.filters.split_last_mut() {
+            Some(filters) => filters,
+            None => {
+                output.copy_from_slice(input);
+                return;
+            }
+        };
+
+        self.buffers.resize_with(rest.len(), Vec::new);
+        let mut size = size;
+        for (i, filter) in rest.iter_mut().enumerate() {
+            let output_size = filter.output_size(size);
+            let (previous, next) = self.buffers.split_at_mut(i);
+            let buffer = &mut next[0];
+            buffer.resize(output_size.0 * output_size.1 * 4, 0);
+            filter.apply(previous.last().map(|b| b.as_slice()).unwrap_or(input), buffer, size);
+            size = output_size;
+        }
+        last.apply(self.buffers.last().map(|b| b.as_slice()).unwrap_or(input), output, size);
+    }
+}
+
 pub fn fix_vbuf_alpha(vbuf: &mut [u8]) {
     for chunk in vbuf.chunks_mut(4) {
         chunk[3] = 0xff;
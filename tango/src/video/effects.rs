@@ -0,0 +1,152 @@
+// Effects that make the image look more like it does on a real GBA screen. Unlike the scalers, these are meant to be
+// chained after other filters.
+
+pub fn by_name(name: &str, source_size: (usize, usize)) -> Option<Box<dyn super::Filter + Sync + Send>> {
+    match name {
+        "scanlines" => Some(Box::new(MaskFilter {
+            mask: Mask::Scanlines,
+            source_size,
+        })),
+        "lcd-grid" => Some(Box::new(MaskFilter {
+            mask: Mask::LCDGrid,
+            source_size,
+        })),
+        "gba-color" => Some(Box::new(GBAColorFilter::new())),
+        "ghosting" => Some(Box::new(GhostingFilter { previous: vec![] })),
+        _ => None,
+    }
+}
+
+// How much brightness the dark parts of a mask keep, out of 256.
+const MASK_DARK_WEIGHT: u32 = 144;
+
+// A mask needs a few pixels per source pixel to be visible, so smaller images are scaled up to at least this first.
+const MIN_MASK_SCALE: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mask {
+    Scanlines,
+    LCDGrid,
+}
+
+// Darkens the bottom (and for the LCD grid, right) edge of every source pixel, wherever earlier filters have moved it.
+pub struct MaskFilter {
+    mask: Mask,
+    source_size: (usize, usize),
+}
+
+impl MaskFilter {
+    fn prescale(&self, size: (usize, usize)) -> usize {
+        let scale = std::cmp::max(
+            1,
+            std::cmp::min(
+                size.0 / std::cmp::max(1, self.source_size.0),
+                size.1 / std::cmp::max(1, self.source_size.1),
+            ),
+        );
+        MIN_MASK_SCALE.div_ceil(scale)
+    }
+}
+
+fn mask_weights(source_len: usize, len: usize) -> Vec<u32> {
+    (0..len)
+        .map(|i| {
+            let pos = ((i as f32 + 0.5) * source_len as f32 / len as f32).fract();
+            if pos >= 2.0 / 3.0 {
+                MASK_DARK_WEIGHT
+            } else {
+                256
+            }
+        })
+        .collect()
+}
+
+impl super::Filter for MaskFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        let prescale = self.prescale(size);
+        (size.0 * prescale, size.1 * prescale)
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+        let prescale = self.prescale(size);
+        let (width, height) = (size.0 * prescale, size.1 * prescale);
+
+        let row_weights = mask_weights(self.source_size.1, height);
+        let col_weights = if self.mask == Mask::LCDGrid {
+            mask_weights(self.source_size.0, width)
+        } else {
+            vec![256; width]
+        };
+
+        for (y, row_weight) in row_weights.iter().enumerate() {
+            let src_row = &src[(y / prescale) * size.0 * 4..(y / prescale + 1) * size.0 * 4];
+            let dst_row = &mut dst[y * width * 4..(y + 1) * width * 4];
+            for (x, col_weight) in col_weights.iter().enumerate() {
+                let weight = row_weight * col_weight / 256;
+                let src_pixel = &src_row[(x / prescale) * 4..(x / prescale + 1) * 4];
+                let dst_pixel = &mut dst_row[x * 4..(x + 1) * 4];
+                for (dst, src) in dst_pixel[..3].iter_mut().zip(src_pixel[..3].iter()) {
+                    *dst = ((*src as u32 * weight) / 256) as u8;
+                }
+                dst_pixel[3] = src_pixel[3];
+            }
+        }
+    }
+}
+
+// Emulates how colors look on the GBA's LCD, which is much darker and less saturated than a modern screen.
+pub struct GBAColorFilter {
+    linear: [f32; 256],
+}
+
+impl GBAColorFilter {
+    const LCD_GAMMA: f32 = 4.0;
+    const OUT_GAMMA: f32 = 2.2;
+
+    fn new() -> Self {
+        Self {
+            linear: std::array::from_fn(|i| (i as f32 / 255.0).powf(Self::LCD_GAMMA)),
+        }
+    }
+}
+
+impl super::Filter for GBAColorFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        size
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], _size: (usize, usize)) {
+        let out = |v: f32| ((v / 255.0).powf(1.0 / Self::OUT_GAMMA) * (255.0 * 255.0 / 280.0)).min(255.0) as u8;
+        for (src_pixel, dst_pixel) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+            let r = self.linear[src_pixel[0] as usize];
+            let g = self.linear[src_pixel[1] as usize];
+            let b = self.linear[src_pixel[2] as usize];
+            dst_pixel[0] = out(50.0 * g + 255.0 * r);
+            dst_pixel[1] = out(30.0 * b + 230.0 * g + 10.0 * r);
+            dst_pixel[2] = out(220.0 * b + 10.0 * g + 50.0 * r);
+            dst_pixel[3] = src_pixel[3];
+        }
+    }
+}
+
+// Blends each frame with the one before, like the GBA's slow LCD does. Some effects rely on this to look transparent
+// instead of flickering.
+pub struct GhostingFilter {
+    previous: Vec<u8>,
+}
+
+impl super::Filter for GhostingFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        size
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], _size: (usize, usize)) {
+        if self.previous.len() != src.len() {
+            self.previous = src.to_vec();
+        }
+        for ((dst, src), previous) in dst.iter_mut().zip(src.iter()).zip(self.previous.iter_mut()) {
+            *dst = (*src as u16 + *previous as u16).div_ceil(2) as u8;
+            *previous = *src;
+        }
+    }
+}
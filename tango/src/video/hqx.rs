@@ -4,7 +4,7 @@ impl super::Filter for HQ2XFilter {
         (size.0 * 2, size.1 * 2)
     }
 
-    fn apply(&self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
         hqx::hq2x(
             unsafe { src.align_to::<u32>().1 },
             unsafe { dst.align_to_mut::<u32>().1 },
@@ -20,7 +20,7 @@ impl super::Filter for HQ3XFilter {
         (size.0 * 3, size.1 * 3)
     }
 
-    fn apply(&self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
         hqx::hq3x(
             unsafe { src.align_to::<u32>().1 },
             unsafe { dst.align_to_mut::<u32>().1 },
@@ -36,7 +36,7 @@ impl super::Filter for HQ4XFilter {
         (size.0 * 4, size.1 * 4)
     }
 
-    fn apply(&self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
         hqx::hq4x(
             unsafe { src.align_to::<u32>().1 },
             unsafe { dst.align_to_mut::<u32>().1 },
@@ -4,8 +4,12 @@ impl super::Filter for MMPXFilter {
         (size.0 * 2, size.1 * 2)
     }
 
-    fn apply(&self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
-        let src_img = image::RgbaImage::from_raw(size.0 as u32, size.1 as u32, src.to_vec()).unwrap();
-        dst.copy_from_slice(mmpx::magnify(&src_img).as_raw());
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+        mmpx::magnify_slice(
+            unsafe { src.align_to::<u32>().1 },
+            unsafe { dst.align_to_mut::<u32>().1 },
+            size.0,
+            size.1,
+        );
     }
 }
@@ -0,0 +1,47 @@
+pub struct Scale2XFilter;
+impl super::Filter for Scale2XFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        (size.0 * 2, size.1 * 2)
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+        scalex::scale2x(
+            unsafe { src.align_to::<u32>().1 },
+            unsafe { dst.align_to_mut::<u32>().1 },
+            size.0,
+            size.1,
+        );
+    }
+}
+
+pub struct Scale3XFilter;
+impl super::Filter for Scale3XFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        (size.0 * 3, size.1 * 3)
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+        scalex::scale3x(
+            unsafe { src.align_to::<u32>().1 },
+            unsafe { dst.align_to_mut::<u32>().1 },
+            size.0,
+            size.1,
+        );
+    }
+}
+
+pub struct Scale4XFilter;
+impl super::Filter for Scale4XFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        (size.0 * 4, size.1 * 4)
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+        scalex::scale4x(
+            unsafe { src.align_to::<u32>().1 },
+            unsafe { dst.align_to_mut::<u32>().1 },
+            size.0,
+            size.1,
+        );
+    }
+}
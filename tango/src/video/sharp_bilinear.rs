@@ -0,0 +1,27 @@
+// Unlike the other filters, this one scales straight to the size the output will be shown at, which doesn't have to be
+// a whole multiple of the input.
+pub struct SharpBilinearFilter {
+    pub scale: f32,
+}
+
+impl super::Filter for SharpBilinearFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        let scale = self.scale.max(1.0);
+        (
+            (size.0 as f32 * scale).round() as usize,
+            (size.1 as f32 * scale).round() as usize,
+        )
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+        let (dst_width, dst_height) = self.output_size(size);
+        sharp_bilinear::scale(
+            unsafe { src.align_to::<u32>().1 },
+            size.0,
+            size.1,
+            unsafe { dst.align_to_mut::<u32>().1 },
+            dst_width,
+            dst_height,
+        );
+    }
+}
@@ -0,0 +1,16 @@
+pub struct XBRZFilter(pub usize);
+impl super::Filter for XBRZFilter {
+    fn output_size(&self, size: (usize, usize)) -> (usize, usize) {
+        (size.0 * self.0, size.1 * self.0)
+    }
+
+    fn apply(&mut self, src: &[u8], dst: &mut [u8], size: (usize, usize)) {
+        xbrz::scale(
+            self.0,
+            unsafe { src.align_to::<u32>().1 },
+            unsafe { dst.align_to_mut::<u32>().1 },
+            size.0,
+            size.1,
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! xBRZ, a pixel-art scaler by Zenju, at 2x to 6x.
+//!
+//! Pixels are RGBA in memory order. Alpha is blended like the other channels but ignored when comparing colors.
+
+mod scalers;
+
+use scalers::{Out, Scaler};
+
+const LUMINANCE_WEIGHT: f64 = 1.0;
+const EQUAL_COLOR_TOLERANCE: f64 = 30.0;
+const CENTER_DIRECTION_BIAS: f64 = 4.0;
+const DOMINANT_DIRECTION_THRESHOLD: f64 = 3.6;
+const STEEP_DIRECTION_THRESHOLD: f64 = 2.2;
+
+const BLEND_NONE: u8 = 0;
+const BLEND_NORMAL: u8 = 1;
+const BLEND_DOMINANT: u8 = 2;
+
+// YCbCr distance between two colors, using BT.2020 coefficients.
+fn dist(pix1: u32, pix2: u32) -> f64 {
+    let [r1, g1, b1, _] = pix1.to_ne_bytes();
+    let [r2, g2, b2, _] = pix2.to_ne_bytes();
+    let r_diff = r1 as f64 - r2 as f64;
+    let g_diff = g1 as f64 - g2 as f64;
+    let b_diff = b1 as f64 - b2 as f64;
+
+    const K_B: f64 = 0.0593;
+    const K_R: f64 = 0.2627;
+    const K_G: f64 = 1.0 - K_B - K_R;
+    const SCALE_B: f64 = 0.5 / (1.0 - K_B);
+    const SCALE_R: f64 = 0.5 / (1.0 - K_R);
+
+    let y = K_R * r_diff + K_G * g_diff + K_B * b_diff;
+    let c_b = SCALE_B * (b_diff - y);
+    let c_r = SCALE_R * (r_diff - y);
+    ((LUMINANCE_WEIGHT * y).powi(2) + c_b.powi(2) + c_r.powi(2)).sqrt()
+}
+
+fn eq(pix1: u32, pix2: u32) -> bool {
+    dist(pix1, pix2) < EQUAL_COLOR_TOLERANCE
+}
+
+// How to blend each corner of the 2x2 block f g / j k.
+#[derive(Default)]
+struct BlendResult {
+    f: u8,
+    g: u8,
+    j: u8,
+    k: u8,
+}
+
+// Decides which corners of the 2x2 block in the middle of this 4x4 kernel should be blended:
+//
+//     a b c d
+//     e f g h
+//     i j k l
+//     m n o p
+fn preprocess_corners(ker: [u32; 16]) -> BlendResult {
+    let [_, b, c, _, e, f, g, h, i, j, k, l, _, n, o, _] = ker;
+
+    let mut result = BlendResult::default();
+    if (f == g && j == k) || (f == j && g == k) {
+        return result;
+    }
+
+    let jg = dist(i, f) + dist(f, c) + dist(n, k) + dist(k, h) + CENTER_DIRECTION_BIAS * dist(j, g);
+    let fk = dist(e, j) + dist(j, o) + dist(b, g) + dist(g, l) + CENTER_DIRECTION_BIAS * dist(f, k);
+
+    if jg < fk {
+        let blend = if DOMINANT_DIRECTION_THRESHOLD * jg < fk {
+            BLEND_DOMINANT
+        } else {
+            BLEND_NORMAL
+        };
+        if f != g && f != j {
+            result.f = blend;
+        }
+        if k != j && k != g {
+            result.k = blend;
+        }
+    } else if fk < jg {
+        let blend = if DOMINANT_DIRECTION_THRESHOLD * fk < jg {
+            BLEND_DOMINANT
+        } else {
+            BLEND_NORMAL
+        };
+        if j != f && j != k {
+            result.j = blend;
+        }
+        if g != f && g != k {
+            result.g = blend;
+        }
+    }
+    result
+}
+
+// Blend info for a pixel packs how to blend each of its corners, clockwise from the top left.
+fn top_r(b: u8) -> u8 {
+    (b >> 2) & 0x3
+}
+
+fn bottom_r(b: u8) -> u8 {
+    (b >> 4) & 0x3
+}
+
+fn bottom_l(b: u8) -> u8 {
+    (b >> 6) & 0x3
+}
+
+fn rotate_blend_info(b: u8, rot: usize) -> u8 {
+    b.rotate_left(2 * rot as u32)
+}
+
+// Indexes into a 3x3 kernel rotated clockwise by 90 degrees rot times.
+fn rotate_kernel_index(i: usize, rot: usize) -> usize {
+    const ROT_90: [usize; 9] = [6, 3, 0, 7, 4, 1, 8, 5, 2];
+    (0..rot).fold(i, |i, _| ROT_90[i])
+}
+
+// Blends one corner of the output block for the pixel in the middle of this 3x3 kernel, as if it were the bottom right
+// corner:
+//
+//     a b c
+//     d e f
+//     g h i
+fn scale_corner(scaler: &dyn Scaler, ker: [u32; 9], blend_info: u8, out: &mut Out) {
+    let blend = rotate_blend_info(blend_info, out.rot);
+    if bottom_r(blend) < BLEND_NORMAL {
+        return;
+    }
+
+    let [_, b, c, d, e, f, g, h, i] = std::array::from_fn(|i| ker[rotate_kernel_index(i, out.rot)]);
+
+    let do_line_blend = if bottom_r(blend) >= BLEND_DOMINANT {
+        true
+    } else if top_r(blend) != BLEND_NONE && !eq(e, g) {
+        // Make sure there is no second blending in an adjacent rotation for this pixel: this handles insular pixels.
+        false
+    } else if bottom_l(blend) != BLEND_NONE && !eq(e, c) {
+        false
+    } else {
+        // No full blending for L-shapes: blend the corner only.
+        !(!eq(e, i) && eq(g, h) && eq(h, i) && eq(i, f) && eq(f, c))
+    };
+
+    let col = if dist(e, f) <= dist(e, h) { f } else { h };
+
+    if !do_line_blend {
+        scaler.blend_corner(col, out);
+        return;
+    }
+
+    let fg = dist(f, g);
+    let hc = dist(h, c);
+    let have_shallow_line = STEEP_DIRECTION_THRESHOLD * fg <= hc && e != g && d != g;
+    let have_steep_line = STEEP_DIRECTION_THRESHOLD * hc <= fg && e != c && b != c;
+
+    match (have_shallow_line, have_steep_line) {
+        (true, true) => scaler.blend_line_steep_and_shallow(col, out),
+        (true, false) => scaler.blend_line_shallow(col, out),
+        (false, true) => scaler.blend_line_steep(col, out),
+        (false, false) => scaler.blend_line_diagonal(col, out),
+    }
+}
+
+/// Scales `src`, which is `width` by `height`, by `factor` into `dst`.
+///
+/// `factor` must be between 2 and 6.
+pub fn scale(factor: usize, src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    let scaler = scalers::for_factor(factor).expect("unsupported xBRZ scale factor");
+    assert!(src.len() >= width * height);
+    assert!(dst.len() >= width * height * factor * factor);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let pixel = |x: isize, y: isize| -> u32 {
+        let x = x.clamp(0, width as isize - 1) as usize;
+        let y = y.clamp(0, height as isize - 1) as usize;
+        src[y * width + x]
+    };
+
+    // Work out the blend info for each pixel from the 2x2 blocks around it: the block whose top left is (x, y) decides
+    // the bottom right corner of (x, y), the bottom left corner of (x + 1, y), and so on.
+    let mut blend_infos = vec![0u8; width * height];
+    for y in -1..height as isize {
+        for x in -1..width as isize {
+            let ker = std::array::from_fn(|i| pixel(x - 1 + (i % 4) as isize, y - 1 + (i / 4) as isize));
+            let result = preprocess_corners(ker);
+
+            let mut set = |x: isize, y: isize, shift: u8, blend: u8| {
+                if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+                    blend_infos[y as usize * width + x as usize] |= blend << shift;
+                }
+            };
+            set(x, y, 4, result.f);
+            set(x + 1, y, 6, result.g);
+            set(x, y + 1, 2, result.j);
+            set(x + 1, y + 1, 0, result.k);
+        }
+    }
+
+    let dst_width = width * factor;
+    let mut block = vec![0u32; factor * factor];
+    for y in 0..height {
+        for x in 0..width {
+            let (xi, yi) = (x as isize, y as isize);
+            let ker: [u32; 9] = std::array::from_fn(|i| pixel(xi - 1 + (i % 3) as isize, yi - 1 + (i / 3) as isize));
+
+            block.fill(ker[4]);
+            let blend_info = blend_infos[y * width + x];
+            if blend_info != 0 {
+                for rot in 0..4 {
+                    scale_corner(scaler, ker, blend_info, &mut Out::new(&mut block, factor, rot));
+                }
+            }
+
+            for (j, row) in block.chunks_exact(factor).enumerate() {
+                let k = (y * factor + j) * dst_width + x * factor;
+                dst[k..k + factor].copy_from_slice(row);
+            }
+        }
+    }
+}
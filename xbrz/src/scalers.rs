@@ -0,0 +1,285 @@
+// The output block for one source pixel, viewed rotated clockwise by 90 degrees rot times, so the scalers only ever have
+// to deal with the bottom right corner.
+pub struct Out<'a> {
+    block: &'a mut [u32],
+    n: usize,
+    pub rot: usize,
+}
+
+impl<'a> Out<'a> {
+    pub fn new(block: &'a mut [u32], n: usize, rot: usize) -> Self {
+        Self { block, n, rot }
+    }
+
+    fn index(&self, i: usize, j: usize) -> usize {
+        let (i, j) = (0..self.rot).fold((i, j), |(i, j), _| (self.n - 1 - j, i));
+        i * self.n + j
+    }
+
+    fn set(&mut self, i: usize, j: usize, col: u32) {
+        let index = self.index(i, j);
+        self.block[index] = col;
+    }
+
+    // Blends m/n of col into the pixel at (i, j).
+    fn alpha_grad(&mut self, i: usize, j: usize, m: u32, n: u32, col: u32) {
+        let index = self.index(i, j);
+        let back = self.block[index].to_ne_bytes();
+        let front = col.to_ne_bytes();
+        self.block[index] = u32::from_ne_bytes(std::array::from_fn(|c| {
+            ((front[c] as u32 * m + back[c] as u32 * (n - m)) / n) as u8
+        }));
+    }
+}
+
+pub trait Scaler: Sync {
+    fn blend_line_shallow(&self, col: u32, out: &mut Out);
+    fn blend_line_steep(&self, col: u32, out: &mut Out);
+    fn blend_line_steep_and_shallow(&self, col: u32, out: &mut Out);
+    fn blend_line_diagonal(&self, col: u32, out: &mut Out);
+    fn blend_corner(&self, col: u32, out: &mut Out);
+}
+
+pub fn for_factor(factor: usize) -> Option<&'static dyn Scaler> {
+    match factor {
+        2 => Some(&Scaler2x),
+        3 => Some(&Scaler3x),
+        4 => Some(&Scaler4x),
+        5 => Some(&Scaler5x),
+        6 => Some(&Scaler6x),
+        _ => None,
+    }
+}
+
+struct Scaler2x;
+impl Scaler for Scaler2x {
+    fn blend_line_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(1, 0, 1, 4, col);
+        out.alpha_grad(1, 1, 3, 4, col);
+    }
+
+    fn blend_line_steep(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(0, 1, 1, 4, col);
+        out.alpha_grad(1, 1, 3, 4, col);
+    }
+
+    fn blend_line_steep_and_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(1, 0, 1, 4, col);
+        out.alpha_grad(0, 1, 1, 4, col);
+        out.alpha_grad(1, 1, 5, 6, col);
+    }
+
+    fn blend_line_diagonal(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(1, 1, 1, 2, col);
+    }
+
+    fn blend_corner(&self, col: u32, out: &mut Out) {
+        // Model a round corner.
+        out.alpha_grad(1, 1, 21, 100, col);
+    }
+}
+
+struct Scaler3x;
+impl Scaler for Scaler3x {
+    fn blend_line_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(2, 0, 1, 4, col);
+        out.alpha_grad(1, 2, 1, 4, col);
+        out.alpha_grad(2, 1, 3, 4, col);
+        out.set(2, 2, col);
+    }
+
+    fn blend_line_steep(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(0, 2, 1, 4, col);
+        out.alpha_grad(2, 1, 1, 4, col);
+        out.alpha_grad(1, 2, 3, 4, col);
+        out.set(2, 2, col);
+    }
+
+    fn blend_line_steep_and_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(2, 0, 1, 4, col);
+        out.alpha_grad(0, 2, 1, 4, col);
+        out.alpha_grad(2, 1, 3, 4, col);
+        out.alpha_grad(1, 2, 3, 4, col);
+        out.set(2, 2, col);
+    }
+
+    fn blend_line_diagonal(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(1, 2, 1, 8, col);
+        out.alpha_grad(2, 1, 1, 8, col);
+        out.alpha_grad(2, 2, 7, 8, col);
+    }
+
+    fn blend_corner(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(2, 2, 45, 100, col);
+    }
+}
+
+struct Scaler4x;
+impl Scaler for Scaler4x {
+    fn blend_line_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(3, 0, 1, 4, col);
+        out.alpha_grad(2, 2, 1, 4, col);
+        out.alpha_grad(3, 1, 3, 4, col);
+        out.alpha_grad(2, 3, 3, 4, col);
+        out.set(3, 2, col);
+        out.set(3, 3, col);
+    }
+
+    fn blend_line_steep(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(0, 3, 1, 4, col);
+        out.alpha_grad(2, 2, 1, 4, col);
+        out.alpha_grad(1, 3, 3, 4, col);
+        out.alpha_grad(3, 2, 3, 4, col);
+        out.set(2, 3, col);
+        out.set(3, 3, col);
+    }
+
+    fn blend_line_steep_and_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(3, 1, 3, 4, col);
+        out.alpha_grad(1, 3, 3, 4, col);
+        out.alpha_grad(3, 0, 1, 4, col);
+        out.alpha_grad(0, 3, 1, 4, col);
+        out.alpha_grad(2, 2, 1, 3, col);
+        out.set(3, 3, col);
+        out.set(3, 2, col);
+        out.set(2, 3, col);
+    }
+
+    fn blend_line_diagonal(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(3, 2, 1, 2, col);
+        out.alpha_grad(2, 3, 1, 2, col);
+        out.set(3, 3, col);
+    }
+
+    fn blend_corner(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(3, 3, 68, 100, col);
+        out.alpha_grad(3, 2, 9, 100, col);
+        out.alpha_grad(2, 3, 9, 100, col);
+    }
+}
+
+struct Scaler5x;
+impl Scaler for Scaler5x {
+    fn blend_line_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(4, 0, 1, 4, col);
+        out.alpha_grad(3, 2, 1, 4, col);
+        out.alpha_grad(2, 4, 1, 4, col);
+        out.alpha_grad(4, 1, 3, 4, col);
+        out.alpha_grad(3, 3, 3, 4, col);
+        out.set(4, 2, col);
+        out.set(4, 3, col);
+        out.set(4, 4, col);
+        out.set(3, 4, col);
+    }
+
+    fn blend_line_steep(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(0, 4, 1, 4, col);
+        out.alpha_grad(2, 3, 1, 4, col);
+        out.alpha_grad(4, 2, 1, 4, col);
+        out.alpha_grad(1, 4, 3, 4, col);
+        out.alpha_grad(3, 3, 3, 4, col);
+        out.set(2, 4, col);
+        out.set(3, 4, col);
+        out.set(4, 4, col);
+        out.set(4, 3, col);
+    }
+
+    fn blend_line_steep_and_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(0, 4, 1, 4, col);
+        out.alpha_grad(2, 3, 1, 4, col);
+        out.alpha_grad(1, 4, 3, 4, col);
+        out.alpha_grad(4, 0, 1, 4, col);
+        out.alpha_grad(3, 2, 1, 4, col);
+        out.alpha_grad(4, 1, 3, 4, col);
+        out.alpha_grad(3, 3, 2, 3, col);
+        out.set(2, 4, col);
+        out.set(3, 4, col);
+        out.set(4, 4, col);
+        out.set(4, 2, col);
+        out.set(4, 3, col);
+    }
+
+    fn blend_line_diagonal(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(4, 2, 1, 8, col);
+        out.alpha_grad(3, 3, 1, 8, col);
+        out.alpha_grad(2, 4, 1, 8, col);
+        out.alpha_grad(4, 3, 7, 8, col);
+        out.alpha_grad(3, 4, 7, 8, col);
+        out.set(4, 4, col);
+    }
+
+    fn blend_corner(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(4, 4, 86, 100, col);
+        out.alpha_grad(4, 3, 23, 100, col);
+        out.alpha_grad(3, 4, 23, 100, col);
+    }
+}
+
+struct Scaler6x;
+impl Scaler for Scaler6x {
+    fn blend_line_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(5, 0, 1, 4, col);
+        out.alpha_grad(4, 2, 1, 4, col);
+        out.alpha_grad(3, 4, 1, 4, col);
+        out.alpha_grad(5, 1, 3, 4, col);
+        out.alpha_grad(4, 3, 3, 4, col);
+        out.alpha_grad(3, 5, 3, 4, col);
+        out.set(5, 2, col);
+        out.set(5, 3, col);
+        out.set(5, 4, col);
+        out.set(5, 5, col);
+        out.set(4, 4, col);
+        out.set(4, 5, col);
+    }
+
+    fn blend_line_steep(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(0, 5, 1, 4, col);
+        out.alpha_grad(2, 4, 1, 4, col);
+        out.alpha_grad(4, 3, 1, 4, col);
+        out.alpha_grad(1, 5, 3, 4, col);
+        out.alpha_grad(3, 4, 3, 4, col);
+        out.alpha_grad(5, 3, 3, 4, col);
+        out.set(2, 5, col);
+        out.set(3, 5, col);
+        out.set(4, 5, col);
+        out.set(5, 5, col);
+        out.set(4, 4, col);
+        out.set(5, 4, col);
+    }
+
+    fn blend_line_steep_and_shallow(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(0, 5, 1, 4, col);
+        out.alpha_grad(2, 4, 1, 4, col);
+        out.alpha_grad(1, 5, 3, 4, col);
+        out.alpha_grad(3, 4, 3, 4, col);
+        out.alpha_grad(5, 0, 1, 4, col);
+        out.alpha_grad(4, 2, 1, 4, col);
+        out.alpha_grad(5, 1, 3, 4, col);
+        out.alpha_grad(4, 3, 3, 4, col);
+        out.set(2, 5, col);
+        out.set(3, 5, col);
+        out.set(4, 5, col);
+        out.set(5, 5, col);
+        out.set(4, 4, col);
+        out.set(5, 4, col);
+        out.set(5, 2, col);
+        out.set(5, 3, col);
+    }
+
+    fn blend_line_diagonal(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(5, 3, 1, 2, col);
+        out.alpha_grad(4, 4, 1, 2, col);
+        out.alpha_grad(3, 5, 1, 2, col);
+        out.set(4, 5, col);
+        out.set(5, 5, col);
+        out.set(5, 4, col);
+    }
+
+    fn blend_corner(&self, col: u32, out: &mut Out) {
+        out.alpha_grad(5, 5, 97, 100, col);
+        out.alpha_grad(4, 5, 42, 100, col);
+        out.alpha_grad(5, 4, 42, 100, col);
+        out.alpha_grad(5, 3, 6, 100, col);
+        out.alpha_grad(3, 5, 6, 100, col);
+    }
+}